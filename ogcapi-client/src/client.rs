@@ -1,3 +1,6 @@
+#[cfg(feature = "stac")]
+use std::collections::HashMap;
+
 use once_cell::sync::OnceCell;
 use reqwest::{
     blocking::Client as ReqwestClient,
@@ -13,7 +16,7 @@ use ogcapi_types::{
         link_rel::{CHILD, ITEM, SELF},
         Link,
     },
-    stac::{Catalog, Item as Feature, SearchParams, StacEntity},
+    stac::{Asset, Catalog, Item as Feature, SearchParams, StacEntity},
 };
 use ogcapi_types::{
     common::{
@@ -54,6 +57,9 @@ pub struct Client {
 
 impl Client {
     /// Creates a Client for a given `OGCAPI`/`STAC` endpoint.
+    ///
+    /// Static catalogs are accessed by the (`file://`) URL of their root
+    /// `catalog.json` and support [`Client::walk`] only.
     pub fn new(endpoint: &str) -> Result<Self, Error> {
         let mut headers = HeaderMap::new();
         headers.insert(USER_AGENT, HeaderValue::from_static(UA_STRING));
@@ -63,7 +69,7 @@ impl Client {
             .build()
             .expect("Build a client");
 
-        let endpoint = if endpoint.ends_with('/') {
+        let endpoint = if endpoint.ends_with('/') || endpoint.ends_with(".json") {
             endpoint.parse::<Url>()?
         } else {
             format!("{}/", endpoint).parse::<Url>()?
@@ -177,6 +183,15 @@ impl Client {
     {
         println!("Fetching {}", url);
 
+        let parsed = Url::parse(url)?;
+        if parsed.scheme() == "file" {
+            let path = parsed
+                .to_file_path()
+                .map_err(|_| Error::ClientError(format!("Invalid file url `{url}`")))?;
+            let file = std::fs::File::open(path)?;
+            return serde_json::from_reader(file).map_err(Error::DeserializationError);
+        }
+
         self.client
             .get(url)
             .send()
@@ -244,6 +259,7 @@ impl Pagination<StacEntity> for StacEntities {
                         .map_err(Error::DeserializationError)?;
 
                     resolve_relative_links(&mut collection.links, &link.href);
+                    resolve_relative_assets(&mut collection.assets, &link.href);
 
                     let mut children = collection
                         .links
@@ -261,6 +277,7 @@ impl Pagination<StacEntity> for StacEntities {
                         .map_err(Error::DeserializationError)?;
 
                     resolve_relative_links(&mut item.links, &link.href);
+                    resolve_relative_assets(&mut item.assets, &link.href);
 
                     let mut children = item
                         .links
@@ -423,6 +440,19 @@ fn resolve_relative_links(links: &mut Links, base: &str) {
     });
 }
 
+#[cfg(feature = "stac")]
+fn resolve_relative_assets(assets: &mut HashMap<String, Asset>, base: &str) {
+    let base_url = Url::parse(base).expect("Parse base url from string");
+
+    assets.values_mut().for_each(|a| match Url::parse(&a.href) {
+        Ok(_) => (),
+        Err(url::ParseError::RelativeUrlWithoutBase) => {
+            a.href = base_url.join(&a.href).unwrap().to_string();
+        }
+        Err(e) => eprintln!("{}", e),
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use crate::Client;
@@ -467,6 +497,76 @@ mod tests {
         assert!(collections.len() > 0);
     }
 
+    #[test]
+    #[cfg(feature = "stac")]
+    fn walk_static() {
+        use ogcapi_types::stac::StacEntity;
+        use serde_json::json;
+
+        let dir = std::env::temp_dir().join("ogcapi-client-walk-static");
+        std::fs::create_dir_all(dir.join("collection/item")).unwrap();
+
+        let files = [
+            (
+                "catalog.json",
+                json!({
+                    "type": "Catalog",
+                    "id": "catalog",
+                    "description": "Root catalog",
+                    "links": [{ "rel": "child", "href": "./collection/collection.json" }]
+                }),
+            ),
+            (
+                "collection/collection.json",
+                json!({
+                    "type": "Collection",
+                    "id": "collection",
+                    "license": "MIT",
+                    "links": [{ "rel": "item", "href": "./item/item.json" }]
+                }),
+            ),
+            (
+                "collection/item/item.json",
+                json!({
+                    "type": "Feature",
+                    "id": "item",
+                    "geometry": { "type": "Point", "coordinates": [7.4, 46.9] },
+                    "properties": { "datetime": "2022-09-01T00:00:00Z" },
+                    "links": [{ "rel": "parent", "href": "../collection.json" }],
+                    "assets": { "data": { "href": "./data.tif" } }
+                }),
+            ),
+        ];
+        for (path, value) in files {
+            std::fs::write(dir.join(path), value.to_string()).unwrap();
+        }
+
+        let endpoint = url::Url::from_file_path(dir.join("catalog.json")).unwrap();
+        let client = Client::new(endpoint.as_str()).unwrap();
+        let entities = client
+            .walk()
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert!(matches!(&entities[0], StacEntity::Catalog(c) if c.id == "catalog"));
+        assert!(matches!(&entities[1], StacEntity::Collection(c) if c.id == "collection"));
+        match &entities[2] {
+            StacEntity::Item(item) => {
+                let base = url::Url::from_file_path(dir.join("collection/item/item.json")).unwrap();
+                assert_eq!(
+                    item.links[0].href,
+                    base.join("../collection.json").unwrap().to_string()
+                );
+                assert_eq!(
+                    item.assets["data"].href,
+                    base.join("data.tif").unwrap().to_string()
+                );
+            }
+            _ => panic!("Expected an item"),
+        }
+    }
+
     #[test]
     #[cfg(feature = "stac")]
     fn search() {
@@ -9,6 +9,9 @@ pub enum Error {
     #[error("Encountered a conformance error: `{0}`")]
     UnknownConformance(String),
 
+    #[error("Encountered an io error: {0}")]
+    IoError(#[from] std::io::Error),
+
     #[error("Encountered url parse error")]
     UrlError(#[from] url::ParseError),
 
@@ -0,0 +1,7 @@
+-- Out-of-line job results
+ALTER TABLE meta.jobs
+    ADD COLUMN results_media_type text,
+    ADD COLUMN results_artifact text;
+
+CREATE INDEX ON meta.jobs (process_id);
+CREATE INDEX ON meta.jobs (created);
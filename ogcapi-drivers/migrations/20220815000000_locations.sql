@@ -0,0 +1,12 @@
+-- Named locations of EDR collections
+CREATE TABLE meta.locations (
+    collection_id text NOT NULL REFERENCES meta.collections (id) ON DELETE CASCADE,
+    id text NOT NULL,
+    name text,
+    properties jsonb,
+    -- queries transform locations to the storage crs of the collection
+    geom geometry(Geometry, 4326) NOT NULL,
+    PRIMARY KEY (collection_id, id)
+);
+
+CREATE INDEX ON meta.locations USING gist (geom);
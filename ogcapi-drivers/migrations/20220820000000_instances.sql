@@ -0,0 +1,7 @@
+-- Instances of EDR collections, items are stored in `items."{collection_id}@{id}"`
+CREATE TABLE meta.instances (
+    collection_id text NOT NULL REFERENCES meta.collections (id) ON DELETE CASCADE,
+    id text NOT NULL,
+    instance jsonb NOT NULL,
+    PRIMARY KEY (collection_id, id)
+);
@@ -0,0 +1,102 @@
+-- Parses a timestamp, NULL for values which are not a valid timestamp, so
+-- that items with malformed datetime properties do not break aggregations
+CREATE FUNCTION meta.try_timestamptz(value text) RETURNS timestamptz
+LANGUAGE plpgsql STABLE AS $$
+BEGIN
+    RETURN value::timestamptz;
+EXCEPTION WHEN others THEN
+    RETURN NULL;
+END
+$$;
+
+-- Search index over the items of all collections, maintained by the triggers
+-- `search_index` and `search_index_update` on the `items."{collection_id}"`
+-- tables
+CREATE TABLE meta.search (
+    collection text NOT NULL REFERENCES meta.collections (id) ON DELETE CASCADE,
+    id text NOT NULL,
+    start_datetime timestamptz,
+    end_datetime timestamptz,
+    geom geometry NOT NULL,
+    properties jsonb,
+    PRIMARY KEY (collection, id)
+);
+
+CREATE INDEX ON meta.search USING gist (geom);
+CREATE INDEX ON meta.search (start_datetime, end_datetime);
+CREATE INDEX ON meta.search USING gin (properties);
+
+-- Items with malformed datetimes are indexed without start and end instead
+-- of failing their writes, updates only touch the index if an indexed
+-- column changed
+CREATE FUNCTION meta.index_item() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'DELETE' OR (TG_OP = 'UPDATE' AND OLD.id <> NEW.id) THEN
+        DELETE FROM meta.search WHERE collection = TG_TABLE_NAME AND id = OLD.id;
+    END IF;
+
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        INSERT INTO meta.search (collection, id, start_datetime, end_datetime, geom, properties)
+        VALUES (
+            TG_TABLE_NAME,
+            NEW.id,
+            meta.try_timestamptz(COALESCE(NEW.properties->>'start_datetime', NEW.properties->>'datetime')),
+            meta.try_timestamptz(COALESCE(NEW.properties->>'end_datetime', NEW.properties->>'datetime')),
+            ST_Transform(NEW.geom, 4326),
+            NEW.properties
+        )
+        ON CONFLICT (collection, id) DO UPDATE SET
+            start_datetime = EXCLUDED.start_datetime,
+            end_datetime = EXCLUDED.end_datetime,
+            geom = EXCLUDED.geom,
+            properties = EXCLUDED.properties;
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Creates the triggers maintaining the search index of the items of a
+-- collection and (re)builds its index from the existing items
+CREATE FUNCTION meta.search_index(collection_id text) RETURNS void AS $$
+BEGIN
+    EXECUTE format('DROP TRIGGER IF EXISTS search_index ON items.%I', collection_id);
+    EXECUTE format('DROP TRIGGER IF EXISTS search_index_update ON items.%I', collection_id);
+
+    EXECUTE format(
+        'CREATE TRIGGER search_index AFTER INSERT OR DELETE ON items.%I
+        FOR EACH ROW EXECUTE FUNCTION meta.index_item()',
+        collection_id
+    );
+    EXECUTE format(
+        'CREATE TRIGGER search_index_update AFTER UPDATE ON items.%I
+        FOR EACH ROW
+        WHEN (
+            OLD.id IS DISTINCT FROM NEW.id
+            OR OLD.geom IS DISTINCT FROM NEW.geom
+            OR OLD.properties IS DISTINCT FROM NEW.properties
+        )
+        EXECUTE FUNCTION meta.index_item()',
+        collection_id
+    );
+
+    DELETE FROM meta.search WHERE collection = collection_id;
+    EXECUTE format(
+        'INSERT INTO meta.search (collection, id, start_datetime, end_datetime, geom, properties)
+        SELECT
+            %L,
+            id,
+            meta.try_timestamptz(COALESCE(properties->>''start_datetime'', properties->>''datetime'')),
+            meta.try_timestamptz(COALESCE(properties->>''end_datetime'', properties->>''datetime'')),
+            ST_Transform(geom, 4326),
+            properties
+        FROM items.%I',
+        collection_id,
+        collection_id
+    );
+END;
+$$ LANGUAGE plpgsql;
+
+SELECT meta.search_index(id)
+FROM meta.collections
+WHERE to_regclass(format('items.%I', id)) IS NOT NULL;
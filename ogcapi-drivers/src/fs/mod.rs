@@ -0,0 +1,60 @@
+use std::{io::ErrorKind, path::PathBuf};
+
+use crate::ArtifactStore;
+
+/// File system driver
+#[derive(Clone, Debug)]
+pub struct Fs {
+    /// Root directory
+    pub root: PathBuf,
+}
+
+impl Fs {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Fs { root: root.into() }
+    }
+
+    /// Resolves a key to a path below the root directory
+    fn path(&self, key: &str) -> anyhow::Result<PathBuf> {
+        if key
+            .split('/')
+            .any(|s| s.is_empty() || s == "." || s == "..")
+        {
+            anyhow::bail!("Invalid key `{key}`");
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait::async_trait]
+impl ArtifactStore for Fs {
+    async fn put_artifact(
+        &self,
+        key: &str,
+        data: Vec<u8>,
+        _media_type: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let path = self.path(key)?;
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        Ok(tokio::fs::write(path, data).await?)
+    }
+
+    async fn get_artifact(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete_artifact(&self, key: &str) -> anyhow::Result<()> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use ogcapi_types::common::{Bbox, Collection, Collections, Crs, Extent, Query, SpatialExtent};
+
+use crate::CollectionTransactions;
+
+use super::{quote, GeoPackage, WRITE_SCHEMA};
+
+#[async_trait::async_trait]
+impl CollectionTransactions for GeoPackage {
+    async fn create_collection(&self, collection: &Collection) -> anyhow::Result<String> {
+        if self.layer(&collection.id).await?.is_some() || self.has_table(&collection.id).await? {
+            anyhow::bail!("Collection `{}` already exists", collection.id);
+        }
+
+        let crs = collection.storage_crs.clone().unwrap_or_default();
+        let srs_id = crs.as_srid();
+        if sqlx::query_scalar::<_, i32>("SELECT srs_id FROM gpkg_spatial_ref_sys WHERE srs_id = ?")
+            .bind(srs_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .is_none()
+        {
+            anyhow::bail!("Storage crs `{crs}` is not defined in the GeoPackage");
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        for statement in WRITE_SCHEMA {
+            sqlx::query(statement).execute(&mut tx).await?;
+        }
+
+        sqlx::query(&format!(
+            "CREATE TABLE {} (fid INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL, geom GEOMETRY)",
+            quote(&collection.id)
+        ))
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO gpkg_contents (table_name, data_type, identifier, description, srs_id)
+            VALUES (?, 'features', ?, ?, ?)
+            "#,
+        )
+        .bind(&collection.id)
+        .bind(&collection.id)
+        .bind(collection.description.as_deref().unwrap_or_default())
+        .bind(srs_id)
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query("INSERT INTO gpkg_geometry_columns VALUES (?, 'geom', 'GEOMETRY', ?, 2, 2)")
+            .bind(&collection.id)
+            .bind(srs_id)
+            .execute(&mut tx)
+            .await?;
+
+        // spatial index (see `feature.rs`)
+        sqlx::query(&format!(
+            "CREATE VIRTUAL TABLE {} USING rtree(id, minx, maxx, miny, maxy)",
+            quote(&format!("rtree_{}_geom", collection.id))
+        ))
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO gpkg_extensions VALUES (
+                ?, 'geom', 'gpkg_rtree_index',
+                'http://www.geopackage.org/spec120/#extension_rtree', 'write-only'
+            )
+            "#,
+        )
+        .bind(&collection.id)
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query("INSERT INTO ogcapi_collections VALUES (?, ?)")
+            .bind(&collection.id)
+            .bind(serde_json::to_string(collection)?)
+            .execute(&mut tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(collection.id.to_owned())
+    }
+
+    /// Reads a collection, feature tables not created through the API are
+    /// described by their entry in `gpkg_contents`
+    ///
+    /// Features are served in the crs of their table only, which is the sole
+    /// crs of the collection.
+    async fn read_collection(&self, id: &str) -> anyhow::Result<Option<Collection>> {
+        if self.has_table("ogcapi_collections").await? {
+            let collection: Option<String> =
+                sqlx::query_scalar("SELECT collection FROM ogcapi_collections WHERE id = ?")
+                    .bind(id)
+                    .fetch_optional(&self.pool)
+                    .await?;
+
+            if let Some(collection) = collection {
+                let mut collection: Collection = serde_json::from_str(&collection)?;
+                if let Some(layer) = self.layer(id).await? {
+                    collection.crs = vec![layer.crs()];
+                    collection.storage_crs = Some(layer.crs());
+                }
+                return Ok(Some(collection));
+            }
+        }
+
+        let row: Option<ContentsRow> = sqlx::query_as(
+            r#"
+            SELECT table_name, identifier, description, min_x, min_y, max_x, max_y, srs_id
+            FROM gpkg_contents
+            WHERE table_name = ? AND data_type = 'features'
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(collection))
+    }
+
+    async fn update_collection(&self, collection: &Collection) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for statement in WRITE_SCHEMA {
+            sqlx::query(statement).execute(&mut tx).await?;
+        }
+
+        sqlx::query("INSERT OR REPLACE INTO ogcapi_collections VALUES (?, ?)")
+            .bind(&collection.id)
+            .bind(serde_json::to_string(collection)?)
+            .execute(&mut tx)
+            .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE gpkg_contents
+            SET description = ?, last_change = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+            WHERE table_name = ?
+            "#,
+        )
+        .bind(collection.description.as_deref().unwrap_or_default())
+        .bind(&collection.id)
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn delete_collection(&self, id: &str) -> anyhow::Result<()> {
+        let layer = self.layer(id).await?;
+        let extensions = self.has_table("gpkg_extensions").await?;
+        let metadata = self.has_table("ogcapi_collections").await?;
+
+        let mut tx = self.pool.begin().await?;
+
+        if let Some(layer) = layer {
+            if let Some(rtree) = layer.rtree {
+                sqlx::query(&format!("DROP TABLE {}", quote(&rtree)))
+                    .execute(&mut tx)
+                    .await?;
+            }
+            sqlx::query(&format!("DROP TABLE {}", quote(&layer.table)))
+                .execute(&mut tx)
+                .await?;
+        }
+
+        sqlx::query("DELETE FROM gpkg_geometry_columns WHERE table_name = ?")
+            .bind(id)
+            .execute(&mut tx)
+            .await?;
+        sqlx::query("DELETE FROM gpkg_contents WHERE table_name = ?")
+            .bind(id)
+            .execute(&mut tx)
+            .await?;
+        if extensions {
+            sqlx::query("DELETE FROM gpkg_extensions WHERE table_name = ?")
+                .bind(id)
+                .execute(&mut tx)
+                .await?;
+        }
+        if metadata {
+            sqlx::query("DELETE FROM ogcapi_collections WHERE id = ?")
+                .bind(id)
+                .execute(&mut tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn list_collections(&self, _query: &Query) -> anyhow::Result<Collections> {
+        let ids: Vec<String> = sqlx::query_scalar(
+            "SELECT table_name FROM gpkg_contents WHERE data_type = 'features' ORDER BY table_name",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut collections = Vec::new();
+        for id in ids {
+            if let Some(collection) = self.read_collection(&id).await? {
+                collections.push(collection);
+            }
+        }
+
+        let mut collections = Collections::new(collections);
+        collections.number_matched = collections.number_returned;
+
+        Ok(collections)
+    }
+}
+
+type ContentsRow = (
+    String,
+    Option<String>,
+    Option<String>,
+    Option<f64>,
+    Option<f64>,
+    Option<f64>,
+    Option<f64>,
+    Option<i32>,
+);
+
+/// Collection describing a feature table from its `gpkg_contents` entry
+fn collection(row: ContentsRow) -> Collection {
+    let (id, identifier, description, min_x, min_y, max_x, max_y, srs_id) = row;
+
+    let crs = srs_id.map(Crs::from_srid).unwrap_or_default();
+
+    let extent = match (min_x, min_y, max_x, max_y) {
+        (Some(min_x), Some(min_y), Some(max_x), Some(max_y)) => Some(Extent {
+            spatial: Some(SpatialExtent {
+                bbox: vec![Bbox::Bbox2D([min_x, min_y, max_x, max_y])],
+                crs: crs.clone(),
+            }),
+            ..Default::default()
+        }),
+        _ => None,
+    };
+
+    Collection {
+        id,
+        title: identifier,
+        description: description.filter(|description| !description.is_empty()),
+        extent,
+        item_type: Some("Feature".to_string()),
+        crs: vec![crs.clone()],
+        storage_crs: Some(crs),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,352 @@
+use anyhow::Context;
+use geo::BoundingRect;
+use serde_json::Value;
+use sqlx::{Acquire, Sqlite, Transaction};
+
+use ogcapi_types::{
+    common::Crs,
+    features::{Feature, FeatureCollection, Query},
+};
+
+use crate::{index::Entry, FeatureTransactions};
+
+use super::{encode, feature, quote, GeoPackage, Layer};
+
+#[async_trait::async_trait]
+impl FeatureTransactions for GeoPackage {
+    async fn create_feature(&self, feature: &Feature) -> anyhow::Result<String> {
+        self.write_feature(feature, false).await
+    }
+
+    async fn read_feature(
+        &self,
+        collection: &str,
+        id: &str,
+        crs: &Crs,
+    ) -> anyhow::Result<Option<Feature>> {
+        let (layer, fid) = match (self.layer(collection).await?, id.parse::<i64>()) {
+            (Some(layer), Ok(fid)) => (layer, fid),
+            _ => return Ok(None),
+        };
+
+        if *crs != layer.crs() {
+            anyhow::bail!("The GeoPackage driver only serves features in their storage crs");
+        }
+
+        let row = sqlx::query(&format!(
+            "SELECT * FROM {} WHERE {} = ?",
+            quote(&layer.table),
+            quote(&layer.fid)
+        ))
+        .bind(fid)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| feature(&row, &layer)).transpose()
+    }
+
+    async fn update_feature(&self, feature: &Feature) -> anyhow::Result<()> {
+        self.write_feature(feature, true).await?;
+
+        Ok(())
+    }
+
+    async fn delete_feature(&self, collection: &str, id: &str) -> anyhow::Result<()> {
+        let (layer, fid) = match (self.layer(collection).await?, id.parse::<i64>()) {
+            (Some(layer), Ok(fid)) => (layer, fid),
+            _ => return Ok(()),
+        };
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(&format!(
+            "DELETE FROM {} WHERE {} = ?",
+            quote(&layer.table),
+            quote(&layer.fid)
+        ))
+        .bind(fid)
+        .execute(&mut tx)
+        .await?;
+
+        if let Some(rtree) = &layer.rtree {
+            sqlx::query(&format!("DELETE FROM {} WHERE id = ?", quote(rtree)))
+                .bind(fid)
+                .execute(&mut tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Lists the features of a table filtered by `bbox`, `datetime` and
+    /// properties, ordered by id
+    ///
+    /// Properties without a column match no features. The `datetime` is
+    /// matched in process against the datetime properties of the features,
+    /// as are bboxes on tables without R-tree index. Filter expressions are
+    /// not supported.
+    async fn list_items(
+        &self,
+        collection: &str,
+        query: &Query,
+    ) -> anyhow::Result<FeatureCollection> {
+        if query.filter.is_some() {
+            anyhow::bail!("The GeoPackage driver does not support filter expressions");
+        }
+
+        let layer = match self.layer(collection).await? {
+            Some(layer) => layer,
+            None => {
+                let mut fc = FeatureCollection::new(Vec::new());
+                fc.number_matched = Some(0);
+                return Ok(fc);
+            }
+        };
+
+        if query.crs != layer.crs() {
+            anyhow::bail!("The GeoPackage driver only serves features in their storage crs");
+        }
+        if query.bbox.is_some() && query.bbox_crs != layer.crs() {
+            anyhow::bail!("The GeoPackage driver only supports bboxes in the storage crs");
+        }
+
+        let mut conditions = Vec::new();
+        let mut corners = None;
+        let mut values = Vec::new();
+
+        if let (Some(bbox), Some(rtree)) = (&query.bbox, &layer.rtree) {
+            conditions.push(format!(
+                "{} IN (SELECT id FROM {} WHERE minx <= ? AND maxx >= ? AND miny <= ? AND maxy >= ?)",
+                quote(&layer.fid),
+                quote(rtree)
+            ));
+            corners = Some(bbox.corners());
+        }
+
+        for (key, value) in &query.additional_parameters {
+            // values are converted to the affinity of numeric columns
+            let value = match layer.column(key) {
+                Some("BOOLEAN") => match value.as_str() {
+                    "true" => "1",
+                    "false" => "0",
+                    value => value,
+                },
+                Some(_) => value.as_str(),
+                // properties without a column match no features
+                None => {
+                    conditions.push("FALSE".to_string());
+                    continue;
+                }
+            };
+            conditions.push(format!("{} = ?", quote(key)));
+            values.push(value);
+        }
+
+        let condition = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        let in_process =
+            query.datetime.is_some() || (query.bbox.is_some() && layer.rtree.is_none());
+
+        let (limit, offset) = match in_process {
+            true => (-1, 0),
+            false => (
+                query.limit.map_or(-1, |limit| limit as i64),
+                query.offset.unwrap_or(0) as i64,
+            ),
+        };
+
+        let sql = format!(
+            "SELECT * FROM {} {condition} ORDER BY {} LIMIT ? OFFSET ?",
+            quote(&layer.table),
+            quote(&layer.fid)
+        );
+        let mut rows = sqlx::query(&sql);
+        if let Some([xmin, ymin, xmax, ymax]) = corners {
+            rows = rows.bind(xmax).bind(xmin).bind(ymax).bind(ymin);
+        }
+        for value in &values {
+            rows = rows.bind(*value);
+        }
+
+        let mut features = rows
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|row| feature(row, &layer))
+            .collect::<anyhow::Result<Vec<Feature>>>()?;
+
+        let number_matched = if in_process {
+            features.retain(|feature| {
+                let bbox = query.bbox.as_ref().filter(|_| layer.rtree.is_none());
+                Entry::new(feature).matches(bbox, query.datetime.as_ref())
+            });
+            let number_matched = features.len();
+
+            features = features
+                .into_iter()
+                .skip(query.offset.unwrap_or(0))
+                .take(query.limit.unwrap_or(usize::MAX))
+                .collect();
+
+            number_matched as i64
+        } else {
+            let sql = format!("SELECT count(*) FROM {} {condition}", quote(&layer.table));
+            let mut count = sqlx::query_scalar(&sql);
+            if let Some([xmin, ymin, xmax, ymax]) = corners {
+                count = count.bind(xmax).bind(xmin).bind(ymax).bind(ymin);
+            }
+            for value in &values {
+                count = count.bind(*value);
+            }
+            count.fetch_one(&self.pool).await?
+        };
+
+        let mut fc = FeatureCollection::new(features);
+        fc.number_matched = Some(number_matched as u64);
+
+        Ok(fc)
+    }
+}
+
+impl GeoPackage {
+    /// Inserts or replaces a feature, columns are added for new properties
+    ///
+    /// Arrays and objects are stored as JSON text.
+    async fn write_feature(&self, feature: &Feature, replace: bool) -> anyhow::Result<String> {
+        let collection = feature.collection.as_ref().unwrap();
+
+        let mut layer = self
+            .layer(collection)
+            .await?
+            .with_context(|| format!("Collection `{collection}` does not exist"))?;
+
+        let fid = feature
+            .id
+            .as_ref()
+            .map(|id| {
+                id.parse::<i64>()
+                    .with_context(|| format!("GeoPackage feature ids must be integers, not `{id}`"))
+            })
+            .transpose()?;
+
+        let geometry = geo::Geometry::<f64>::try_from(feature.geometry.value.to_owned())?;
+        let properties = feature.properties.to_owned().unwrap_or_default();
+
+        let mut tx = self.pool.begin().await?;
+
+        // columns of the properties
+        let mut columns = Vec::new();
+        for (key, value) in &properties {
+            if key.eq_ignore_ascii_case(&layer.fid) || key.eq_ignore_ascii_case(&layer.geometry) {
+                continue;
+            }
+            if layer.column(key).is_none() {
+                let r#type = match value {
+                    Value::Null => continue,
+                    Value::Bool(_) => "BOOLEAN",
+                    Value::Number(number) if number.is_i64() => "INTEGER",
+                    Value::Number(_) => "REAL",
+                    _ => "TEXT",
+                };
+                sqlx::query(&format!(
+                    "ALTER TABLE {} ADD COLUMN {} {type}",
+                    quote(&layer.table),
+                    quote(key)
+                ))
+                .execute(&mut tx)
+                .await?;
+                layer.columns.push((key.to_owned(), r#type.to_owned()));
+            }
+            columns.push((key, value));
+        }
+
+        let names: Vec<String> = [&layer.fid, &layer.geometry]
+            .into_iter()
+            .chain(columns.iter().map(|(key, _)| *key))
+            .map(|name| quote(name))
+            .collect();
+
+        let sql = format!(
+            "INSERT {} INTO {} ({}) VALUES ({})",
+            if replace { "OR REPLACE" } else { "" },
+            quote(&layer.table),
+            names.join(", "),
+            vec!["?"; names.len()].join(", ")
+        );
+
+        let mut insert = sqlx::query(&sql)
+            .bind(fid)
+            .bind(encode(&geometry, layer.srs_id)?);
+        for (_, value) in columns {
+            insert = match value {
+                Value::Null => insert.bind(None::<String>),
+                Value::Bool(boolean) => insert.bind(*boolean),
+                Value::Number(number) => match number.as_i64() {
+                    Some(integer) => insert.bind(integer),
+                    None => insert.bind(number.as_f64()),
+                },
+                Value::String(string) => insert.bind(string.as_str()),
+                other => insert.bind(other.to_string()),
+            };
+        }
+        let fid = insert.execute(&mut tx).await?.last_insert_rowid();
+
+        update_rtree(&mut tx, &layer, fid, &geometry).await?;
+
+        tx.commit().await?;
+
+        Ok(fid.to_string())
+    }
+}
+
+impl Layer {
+    /// Declared type of an attribute column, column names are case
+    /// insensitive
+    fn column(&self, name: &str) -> Option<&str> {
+        self.columns
+            .iter()
+            .find(|(column, _)| column.eq_ignore_ascii_case(name))
+            .map(|(_, r#type)| r#type.as_str())
+    }
+}
+
+/// Replaces the R-tree entry of a feature
+async fn update_rtree(
+    tx: &mut Transaction<'_, Sqlite>,
+    layer: &Layer,
+    fid: i64,
+    geometry: &geo::Geometry<f64>,
+) -> anyhow::Result<()> {
+    let rtree = match &layer.rtree {
+        Some(rtree) => quote(rtree),
+        None => return Ok(()),
+    };
+
+    let conn = tx.acquire().await?;
+
+    sqlx::query(&format!("DELETE FROM {rtree} WHERE id = ?"))
+        .bind(fid)
+        .execute(&mut *conn)
+        .await?;
+
+    if let Some(rect) = geometry.bounding_rect() {
+        sqlx::query(&format!("INSERT INTO {rtree} VALUES (?, ?, ?, ?, ?)"))
+            .bind(fid)
+            .bind(rect.min().x)
+            .bind(rect.max().x)
+            .bind(rect.min().y)
+            .bind(rect.max().y)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    Ok(())
+}
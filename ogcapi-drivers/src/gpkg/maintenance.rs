@@ -0,0 +1,75 @@
+use ogcapi_types::{common::Collection, features::Query};
+
+use crate::{
+    index::{self, Entry},
+    CollectionMaintenance, CollectionTransactions, FeatureTransactions,
+};
+
+use super::GeoPackage;
+
+#[async_trait::async_trait]
+impl CollectionMaintenance for GeoPackage {
+    /// Recomputes the extent and summaries of a collection from its
+    /// features and updates the bounds in `gpkg_contents`
+    async fn refresh_collection(&self, id: &str) -> anyhow::Result<Option<Collection>> {
+        let mut collection = match self.read_collection(id).await? {
+            Some(collection) => collection,
+            None => return Ok(None),
+        };
+
+        let crs = collection.storage_crs.clone().unwrap_or_default();
+        let query = Query {
+            crs: crs.clone(),
+            ..Default::default()
+        };
+        let features = self.list_items(id, &query).await?.features;
+
+        let entries: Vec<Entry> = features.iter().map(Entry::new).collect();
+        collection.extent = index::extent(&entries);
+
+        // bounds of tables without features are unset
+        let bounds = collection
+            .extent
+            .as_mut()
+            .and_then(|extent| extent.spatial.as_mut())
+            .map(|spatial| {
+                spatial.crs = crs;
+                spatial.bbox[0].corners()
+            });
+        sqlx::query(
+            r#"
+            UPDATE gpkg_contents
+            SET min_x = ?, min_y = ?, max_x = ?, max_y = ?,
+                last_change = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+            WHERE table_name = ?
+            "#,
+        )
+        .bind(bounds.map(|b| b[0]))
+        .bind(bounds.map(|b| b[1]))
+        .bind(bounds.map(|b| b[2]))
+        .bind(bounds.map(|b| b[3]))
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        #[cfg(feature = "stac")]
+        {
+            let properties: Vec<_> = features
+                .iter()
+                .filter_map(|feature| feature.properties.to_owned())
+                .collect();
+            index::summarize(&mut collection.summaries, &properties);
+        }
+
+        // collections created through the API keep their metadata
+        if self.has_table("ogcapi_collections").await? {
+            sqlx::query("UPDATE ogcapi_collections SET collection = ? WHERE id = ?")
+                .bind(serde_json::to_string(&collection)?)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+        }
+
+        Ok(Some(collection))
+    }
+}
@@ -0,0 +1,342 @@
+mod collection;
+mod feature;
+mod maintenance;
+
+use std::path::Path;
+
+use anyhow::Context;
+use geo::BoundingRect;
+use serde_json::{Map, Value};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions, SqliteRow},
+    Column, Row, TypeInfo, ValueRef,
+};
+
+use ogcapi_types::{common::Crs, features::Feature};
+
+/// GeoPackage driver
+///
+/// Serves the feature tables of a GeoPackage as collections. Feature ids are
+/// the integer primary keys of the tables and geometries are served in the
+/// spatial reference system of their table.
+///
+/// Spatial filters use the R-tree index of a table if present. The driver
+/// maintains the index itself on writes, as the triggers of the R-tree
+/// extension require spatial SQL functions missing in plain SQLite. These
+/// triggers are dropped when opening GeoPackages written by other tools.
+#[derive(Debug, Clone)]
+pub struct GeoPackage {
+    pub pool: SqlitePool,
+}
+
+/// Feature table of a GeoPackage
+#[derive(Debug, Clone)]
+pub(crate) struct Layer {
+    pub(crate) table: String,
+    /// Integer primary key column
+    pub(crate) fid: String,
+    pub(crate) geometry: String,
+    pub(crate) srs_id: i32,
+    /// Attribute columns with their declared type
+    pub(crate) columns: Vec<(String, String)>,
+    /// Name of the R-tree index of the geometry column if present
+    pub(crate) rtree: Option<String>,
+}
+
+impl Layer {
+    pub(crate) fn crs(&self) -> Crs {
+        Crs::from_srid(self.srs_id)
+    }
+}
+
+impl GeoPackage {
+    /// Open a GeoPackage, an empty GeoPackage is created if the file does not
+    /// exist
+    pub async fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true);
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(8)
+            .connect_with(options)
+            .await?;
+
+        // initialize new GeoPackages
+        let application_id: i64 = sqlx::query_scalar("PRAGMA application_id")
+            .fetch_one(&pool)
+            .await?;
+        if application_id == 0 {
+            // "GPKG" version 1.2
+            sqlx::query("PRAGMA application_id = 1196444487")
+                .execute(&pool)
+                .await?;
+            sqlx::query("PRAGMA user_version = 10200")
+                .execute(&pool)
+                .await?;
+
+            for statement in SCHEMA {
+                sqlx::query(statement).execute(&pool).await?;
+            }
+        }
+
+        let triggers: Vec<String> = sqlx::query_scalar(
+            r"SELECT name FROM sqlite_master WHERE type = 'trigger' AND name LIKE 'rtree\_%' ESCAPE '\'",
+        )
+        .fetch_all(&pool)
+        .await?;
+        for trigger in triggers {
+            sqlx::query(&format!("DROP TRIGGER {}", quote(&trigger)))
+                .execute(&pool)
+                .await?;
+        }
+
+        Ok(GeoPackage { pool })
+    }
+
+    /// Whether the GeoPackage contains a table
+    pub(crate) async fn has_table(&self, name: &str) -> anyhow::Result<bool> {
+        Ok(sqlx::query_scalar::<_, String>(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?",
+        )
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await?
+        .is_some())
+    }
+
+    /// Feature table of a collection, `None` if the GeoPackage has none
+    pub(crate) async fn layer(&self, collection: &str) -> anyhow::Result<Option<Layer>> {
+        let row: Option<(String, i32)> = sqlx::query_as(
+            r#"
+            SELECT g.column_name, g.srs_id
+            FROM gpkg_geometry_columns g
+            JOIN gpkg_contents c ON c.table_name = g.table_name
+            WHERE g.table_name = ? AND c.data_type = 'features'
+            "#,
+        )
+        .bind(collection)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let (geometry, srs_id) = match row {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+
+        let mut fid = None;
+        let mut columns = Vec::new();
+        for row in sqlx::query(&format!("PRAGMA table_info({})", quote(collection)))
+            .fetch_all(&self.pool)
+            .await?
+        {
+            let name: String = row.try_get("name")?;
+            let r#type: String = row.try_get("type")?;
+            let pk: i64 = row.try_get("pk")?;
+
+            if pk == 1 && fid.is_none() {
+                fid = Some(name);
+            } else if !name.eq_ignore_ascii_case(&geometry) {
+                columns.push((name, r#type.to_uppercase()));
+            }
+        }
+
+        let rtree = format!("rtree_{collection}_{geometry}");
+        let rtree = self.has_table(&rtree).await?.then_some(rtree);
+
+        Ok(Some(Layer {
+            table: collection.to_owned(),
+            fid: fid.context("Feature table without integer primary key")?,
+            geometry,
+            srs_id,
+            columns,
+            rtree,
+        }))
+    }
+}
+
+/// Tables of the GeoPackage core
+const SCHEMA: [&str; 4] = [
+    r#"
+    CREATE TABLE IF NOT EXISTS gpkg_spatial_ref_sys (
+        srs_name TEXT NOT NULL,
+        srs_id INTEGER PRIMARY KEY,
+        organization TEXT NOT NULL,
+        organization_coordsys_id INTEGER NOT NULL,
+        definition TEXT NOT NULL,
+        description TEXT
+    )
+    "#,
+    r#"
+    INSERT OR IGNORE INTO gpkg_spatial_ref_sys VALUES
+        ('Undefined cartesian SRS', -1, 'NONE', -1, 'undefined', 'undefined cartesian coordinate reference system'),
+        ('Undefined geographic SRS', 0, 'NONE', 0, 'undefined', 'undefined geographic coordinate reference system'),
+        ('WGS 84 geodetic', 4326, 'EPSG', 4326, 'GEOGCS["WGS 84",DATUM["WGS_1984",SPHEROID["WGS 84",6378137,298.257223563,AUTHORITY["EPSG","7030"]],AUTHORITY["EPSG","6326"]],PRIMEM["Greenwich",0,AUTHORITY["EPSG","8901"]],UNIT["degree",0.0174532925199433,AUTHORITY["EPSG","9122"]],AXIS["Latitude",NORTH],AXIS["Longitude",EAST],AUTHORITY["EPSG","4326"]]', 'longitude/latitude coordinates in decimal degrees on the WGS 84 spheroid')
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS gpkg_contents (
+        table_name TEXT NOT NULL PRIMARY KEY,
+        data_type TEXT NOT NULL,
+        identifier TEXT UNIQUE,
+        description TEXT DEFAULT '',
+        last_change DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+        min_x DOUBLE,
+        min_y DOUBLE,
+        max_x DOUBLE,
+        max_y DOUBLE,
+        srs_id INTEGER,
+        CONSTRAINT fk_gc_r_srs_id FOREIGN KEY (srs_id) REFERENCES gpkg_spatial_ref_sys(srs_id)
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS gpkg_geometry_columns (
+        table_name TEXT NOT NULL,
+        column_name TEXT NOT NULL,
+        geometry_type_name TEXT NOT NULL,
+        srs_id INTEGER NOT NULL,
+        z TINYINT NOT NULL,
+        m TINYINT NOT NULL,
+        CONSTRAINT pk_geom_cols PRIMARY KEY (table_name, column_name),
+        CONSTRAINT uk_gc_table_name UNIQUE (table_name),
+        CONSTRAINT fk_gc_tn FOREIGN KEY (table_name) REFERENCES gpkg_contents(table_name),
+        CONSTRAINT fk_gc_srs FOREIGN KEY (srs_id) REFERENCES gpkg_spatial_ref_sys (srs_id)
+    )
+    "#,
+];
+
+/// Tables of the extensions and the metadata of collections created through
+/// the API, created on the first write
+pub(crate) const WRITE_SCHEMA: [&str; 2] = [
+    r#"
+    CREATE TABLE IF NOT EXISTS gpkg_extensions (
+        table_name TEXT,
+        column_name TEXT,
+        extension_name TEXT NOT NULL,
+        definition TEXT NOT NULL,
+        scope TEXT NOT NULL,
+        CONSTRAINT ge_tce UNIQUE (table_name, column_name, extension_name)
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS ogcapi_collections (
+        id TEXT NOT NULL PRIMARY KEY,
+        collection TEXT NOT NULL
+    )
+    "#,
+];
+
+/// Quotes an SQL identifier
+pub(crate) fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// Decodes a GeoPackage binary geometry, `None` for empty geometries
+pub(crate) fn decode(blob: &[u8]) -> anyhow::Result<Option<geo::Geometry<f64>>> {
+    if blob.len() < 8 || &blob[..2] != b"GP" {
+        anyhow::bail!("Invalid GeoPackage geometry");
+    }
+
+    let flags = blob[3];
+    if flags & 0b0010_0000 != 0 {
+        anyhow::bail!("Extended GeoPackage geometries are not supported");
+    }
+    if flags & 0b0001_0000 != 0 {
+        return Ok(None);
+    }
+
+    let envelope = match (flags >> 1) & 0b111 {
+        0 => 0,
+        1 => 32,
+        2 | 3 => 48,
+        4 => 64,
+        _ => anyhow::bail!("Invalid GeoPackage geometry envelope"),
+    };
+
+    let mut wkb = blob
+        .get(8 + envelope..)
+        .context("Truncated GeoPackage geometry")?;
+    let geometry =
+        wkb::wkb_to_geom(&mut wkb).map_err(|e| anyhow::anyhow!("Invalid WKB geometry: {e:?}"))?;
+
+    Ok(Some(geometry))
+}
+
+/// Encodes a geometry as little endian GeoPackage binary with an xy
+/// envelope
+pub(crate) fn encode(geometry: &geo::Geometry<f64>, srs_id: i32) -> anyhow::Result<Vec<u8>> {
+    let wkb = wkb::geom_to_wkb(geometry).map_err(|e| anyhow::anyhow!("Invalid geometry: {e:?}"))?;
+
+    let mut blob = vec![b'G', b'P', 0];
+    match geometry.bounding_rect() {
+        Some(rect) => {
+            blob.push(0b0000_0011);
+            blob.extend(srs_id.to_le_bytes());
+            for value in [rect.min().x, rect.max().x, rect.min().y, rect.max().y] {
+                blob.extend(value.to_le_bytes());
+            }
+        }
+        None => {
+            blob.push(0b0001_0001);
+            blob.extend(srs_id.to_le_bytes());
+        }
+    }
+    blob.extend(wkb);
+
+    Ok(blob)
+}
+
+/// Converts a row of a feature table into a feature
+///
+/// `BOOLEAN` columns are converted to booleans, blobs are skipped.
+pub(crate) fn feature(row: &SqliteRow, layer: &Layer) -> anyhow::Result<Feature> {
+    let mut id = None;
+    let mut geometry = None;
+    let mut properties = Map::new();
+
+    for column in row.columns() {
+        let name = column.name();
+        let value = row.try_get_raw(column.ordinal())?;
+
+        if name == layer.fid {
+            id = Some(row.try_get::<i64, _>(column.ordinal())?.to_string());
+            continue;
+        }
+
+        if name.eq_ignore_ascii_case(&layer.geometry) {
+            if !value.is_null() {
+                geometry = decode(&row.try_get_unchecked::<Vec<u8>, _>(column.ordinal())?)?;
+            }
+            continue;
+        }
+
+        let boolean = layer
+            .columns
+            .iter()
+            .any(|(column, r#type)| column == name && r#type == "BOOLEAN");
+
+        let value = match value.type_info().name() {
+            "NULL" => Value::Null,
+            "INTEGER" if boolean => {
+                Value::from(row.try_get_unchecked::<i64, _>(column.ordinal())? != 0)
+            }
+            "INTEGER" => Value::from(row.try_get_unchecked::<i64, _>(column.ordinal())?),
+            "REAL" => Value::from(row.try_get_unchecked::<f64, _>(column.ordinal())?),
+            "TEXT" => Value::from(row.try_get_unchecked::<String, _>(column.ordinal())?),
+            _ => continue,
+        };
+        properties.insert(name.to_owned(), value);
+    }
+
+    let geometry = match geometry {
+        Some(geometry) => geojson::Geometry::new(geojson::Value::from(&geometry)),
+        None => geojson::Geometry::new(geojson::Value::GeometryCollection(Vec::new())),
+    };
+
+    Ok(serde_json::from_value(serde_json::json!({
+        "id": id,
+        "collection": layer.table,
+        "type": "Feature",
+        "properties": properties,
+        "geometry": geometry,
+    }))?)
+}
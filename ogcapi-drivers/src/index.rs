@@ -0,0 +1,396 @@
+//! Item index of drivers filtering items in process
+//!
+//! Used by the S3, memory and GeoPackage drivers, which lack a database with
+//! temporal and aggregation support.
+
+#[cfg(all(feature = "stac", any(feature = "memory", feature = "s3")))]
+use std::collections::BTreeMap;
+#[cfg(any(feature = "memory", feature = "s3"))]
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(all(feature = "stac", any(feature = "memory", feature = "s3")))]
+use serde_json::json;
+#[cfg(any(feature = "stac", feature = "memory", feature = "s3"))]
+use serde_json::{Map, Value};
+
+use ogcapi_types::{
+    common::{Bbox, Datetime, Extent, IntervalDatetime, SpatialExtent, TemporalExtent},
+    features::Feature,
+};
+#[cfg(all(feature = "stac", any(feature = "memory", feature = "s3")))]
+use ogcapi_types::{
+    features::Queryables,
+    stac::{
+        Aggregation, AggregationCollection, AggregationParams, Bucket, DatetimeInterval,
+        AGGREGATIONS,
+    },
+};
+
+/// Format of datetime aggregations
+#[cfg(all(feature = "stac", any(feature = "memory", feature = "s3")))]
+const DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3fZ";
+
+/// Entry of the item index of a collection
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct Entry {
+    pub(crate) id: String,
+    pub(crate) bbox: Option<Bbox>,
+    pub(crate) start: Option<DateTime<Utc>>,
+    pub(crate) end: Option<DateTime<Utc>>,
+}
+
+impl Entry {
+    pub(crate) fn new(feature: &Feature) -> Self {
+        #[cfg(feature = "stac")]
+        let bbox = feature
+            .bbox
+            .clone()
+            .or_else(|| Bbox::from_geometry(&feature.geometry));
+        #[cfg(not(feature = "stac"))]
+        let bbox = Bbox::from_geometry(&feature.geometry);
+
+        let (start, end) = feature.interval();
+
+        Entry {
+            id: feature.id.clone().unwrap_or_default(),
+            bbox,
+            start,
+            end,
+        }
+    }
+
+    /// Whether the entry intersects the `bbox` and `datetime`, entries without
+    /// a datetime match any `datetime`
+    pub(crate) fn matches(&self, bbox: Option<&Bbox>, datetime: Option<&Datetime>) -> bool {
+        if let Some(bbox) = bbox {
+            match &self.bbox {
+                Some(b) if b.intersects(bbox) => {}
+                _ => return false,
+            }
+        }
+
+        let (start, end) = match (self.start.or(self.end), self.end.or(self.start)) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return true,
+        };
+
+        match datetime {
+            None => true,
+            Some(Datetime::Datetime(d)) => start <= *d && *d <= end,
+            Some(Datetime::Interval { from, to }) => {
+                let after = match from {
+                    IntervalDatetime::Datetime(from) => end >= *from,
+                    IntervalDatetime::Open => true,
+                };
+                let before = match to {
+                    IntervalDatetime::Datetime(to) => start <= *to,
+                    IntervalDatetime::Open => true,
+                };
+                after && before
+            }
+        }
+    }
+}
+
+/// Whether the `properties` of an item equal the values of the property
+/// filters, items without a filtered property match
+#[cfg(any(feature = "memory", feature = "s3"))]
+pub(crate) fn matches_properties(
+    properties: Option<&Map<String, Value>>,
+    filters: &HashMap<String, String>,
+) -> bool {
+    filters
+        .iter()
+        .all(|(key, value)| match properties.and_then(|p| p.get(key)) {
+            None => true,
+            Some(Value::Number(number)) => value
+                .parse::<f64>()
+                .is_ok_and(|value| number.as_f64() == Some(value)),
+            Some(Value::String(string)) => string == value,
+            Some(other) => serde_json::from_str::<Value>(value).is_ok_and(|value| value == *other),
+        })
+}
+
+/// Extent covering the entries of an index, `None` for an empty index
+pub(crate) fn extent(index: &[Entry]) -> Option<Extent> {
+    if index.is_empty() {
+        return None;
+    }
+
+    let bbox = index
+        .iter()
+        .filter_map(|entry| entry.bbox.as_ref().map(Bbox::corners))
+        .reduce(|a, b| {
+            [
+                a[0].min(b[0]),
+                a[1].min(b[1]),
+                a[2].max(b[2]),
+                a[3].max(b[3]),
+            ]
+        });
+
+    let start = index.iter().filter_map(|entry| entry.start).min();
+    let end = index.iter().filter_map(|entry| entry.end).max();
+
+    Some(Extent {
+        spatial: bbox.map(|bbox| SpatialExtent {
+            bbox: vec![Bbox::Bbox2D(bbox)],
+            crs: Default::default(),
+        }),
+        temporal: Some(TemporalExtent {
+            interval: vec![vec![start, end]],
+            ..Default::default()
+        }),
+    })
+}
+
+/// Recomputes the value sets and ranges of summaries from item properties
+#[cfg(feature = "stac")]
+pub(crate) fn summarize(summaries: &mut Map<String, Value>, properties: &[Map<String, Value>]) {
+    for (key, summary) in summaries.iter_mut() {
+        let values = properties
+            .iter()
+            .filter_map(|p| p.get(key))
+            .filter(|v| !v.is_null());
+
+        match summary {
+            Value::Array(distinct) => {
+                distinct.clear();
+                for value in values.flat_map(|v| match v {
+                    Value::Array(items) => items.iter().collect(),
+                    v => vec![v],
+                }) {
+                    if !value.is_null() && !distinct.contains(value) {
+                        distinct.push(value.to_owned());
+                    }
+                }
+            }
+            Value::Object(range)
+                if range.contains_key("minimum") && range.contains_key("maximum") =>
+            {
+                let (minimum, maximum) = match range["minimum"] {
+                    Value::Number(_) => {
+                        let numbers: Vec<&Value> = values.filter(|v| v.is_number()).collect();
+                        let number = |v: &&Value| v.as_f64().unwrap_or(f64::NAN);
+                        (
+                            numbers
+                                .iter()
+                                .min_by(|a, b| number(a).total_cmp(&number(b)))
+                                .copied(),
+                            numbers
+                                .iter()
+                                .max_by(|a, b| number(a).total_cmp(&number(b)))
+                                .copied(),
+                        )
+                    }
+                    // RFC 3339 datetimes compare lexicographically when in UTC
+                    Value::String(_) => {
+                        let strings: Vec<&Value> = values.filter(|v| v.is_string()).collect();
+                        (
+                            strings.iter().min_by_key(|v| v.as_str()).copied(),
+                            strings.iter().max_by_key(|v| v.as_str()).copied(),
+                        )
+                    }
+                    _ => continue,
+                };
+
+                // summaries of collections without matching items are kept
+                if let (Some(minimum), Some(maximum)) = (minimum, maximum) {
+                    range.insert("minimum".to_string(), minimum.to_owned());
+                    range.insert("maximum".to_string(), maximum.to_owned());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Aggregates the index entries of the items matching a search
+///
+/// Geohashes are computed from the center of the item bbox.
+#[cfg(all(feature = "stac", any(feature = "memory", feature = "s3")))]
+pub(crate) fn aggregate(
+    matches: &[(String, Entry)],
+    params: &AggregationParams,
+) -> anyhow::Result<AggregationCollection> {
+    let names = params.names().map_err(anyhow::Error::msg)?;
+
+    let mut aggregations = Vec::new();
+    for (name, data_type) in AGGREGATIONS {
+        if !names.contains(&name) {
+            continue;
+        }
+
+        let mut aggregation = Aggregation {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            value: None,
+            buckets: Vec::new(),
+        };
+
+        let format = |d: DateTime<Utc>| json!(d.format(DATETIME_FORMAT).to_string());
+
+        // single valued aggregations
+        match name {
+            "total_count" => aggregation.value = Some(json!(matches.len())),
+            "datetime_min" => {
+                aggregation.value = Some(
+                    matches
+                        .iter()
+                        .filter_map(|(_, entry)| entry.start)
+                        .min()
+                        .map_or(Value::Null, format),
+                )
+            }
+            "datetime_max" => {
+                aggregation.value = Some(
+                    matches
+                        .iter()
+                        .filter_map(|(_, entry)| entry.end)
+                        .max()
+                        .map_or(Value::Null, format),
+                )
+            }
+            _ => {}
+        }
+
+        // frequency distributions
+        let keys: Option<(Vec<String>, &str)> = match name {
+            "collection_frequency" => Some((
+                matches.iter().map(|(c, _)| c.to_owned()).collect(),
+                "string",
+            )),
+            "datetime_frequency" => {
+                let interval = params.datetime_frequency_interval.unwrap_or_default();
+                Some((
+                    matches
+                        .iter()
+                        .filter_map(|(_, entry)| entry.start)
+                        .map(|start| truncate(start, interval))
+                        .collect(),
+                    "datetime",
+                ))
+            }
+            "geohash_frequency" => {
+                let precision = params.geohash_precision().map_err(anyhow::Error::msg)?;
+                Some((
+                    matches
+                        .iter()
+                        .filter_map(|(_, entry)| entry.bbox.as_ref())
+                        .map(|bbox| {
+                            let [xmin, ymin, xmax, ymax] = bbox.corners();
+                            geohash((xmin + xmax) / 2.0, (ymin + ymax) / 2.0, precision)
+                        })
+                        .collect(),
+                    "string",
+                ))
+            }
+            _ => None,
+        };
+        if let Some((keys, data_type)) = keys {
+            let mut frequencies: BTreeMap<String, u64> = BTreeMap::new();
+            for key in keys {
+                *frequencies.entry(key).or_default() += 1;
+            }
+
+            aggregation.buckets = frequencies
+                .into_iter()
+                .map(|(key, frequency)| Bucket {
+                    key,
+                    data_type: data_type.to_string(),
+                    frequency,
+                })
+                .collect();
+        }
+
+        aggregations.push(aggregation);
+    }
+
+    Ok(AggregationCollection::new(aggregations))
+}
+
+/// Queryables inferred from the json types of the properties of items
+#[cfg(all(feature = "stac", any(feature = "memory", feature = "s3")))]
+pub(crate) fn queryables(properties: impl IntoIterator<Item = Map<String, Value>>) -> Queryables {
+    let mut queryables = Queryables::default();
+
+    for properties in properties {
+        for (key, value) in properties {
+            let schema = match value {
+                Value::Null => continue,
+                Value::Bool(_) => json!({ "type": "boolean" }),
+                Value::Number(_) => json!({ "type": "number" }),
+                Value::String(_) if key.ends_with("datetime") => {
+                    json!({ "type": "string", "format": "date-time" })
+                }
+                Value::String(_) => json!({ "type": "string" }),
+                Value::Array(_) => json!({ "type": "array" }),
+                Value::Object(_) => json!({ "type": "object" }),
+            };
+            match queryables.properties.get_mut(&key) {
+                // properties with mixed types
+                Some(Value::Object(existing)) if existing.get("type") != schema.get("type") => {
+                    existing.remove("format");
+                    existing.remove("type");
+                }
+                Some(_) => {}
+                None => {
+                    queryables.properties.insert(key, schema);
+                }
+            }
+        }
+    }
+
+    queryables
+}
+
+/// Start of the datetime bucket of `interval` containing `datetime`
+#[cfg(all(feature = "stac", any(feature = "memory", feature = "s3")))]
+fn truncate(datetime: DateTime<Utc>, interval: DatetimeInterval) -> String {
+    let format = match interval {
+        DatetimeInterval::Year => "%Y-01-01T00:00:00.000Z",
+        DatetimeInterval::Month => "%Y-%m-01T00:00:00.000Z",
+        DatetimeInterval::Day => "%Y-%m-%dT00:00:00.000Z",
+        DatetimeInterval::Hour => "%Y-%m-%dT%H:00:00.000Z",
+        DatetimeInterval::Minute => "%Y-%m-%dT%H:%M:00.000Z",
+    };
+    datetime.format(format).to_string()
+}
+
+/// Geohash of a position with `precision` characters
+#[cfg(all(feature = "stac", any(feature = "memory", feature = "s3")))]
+fn geohash(lon: f64, lat: f64, precision: u8) -> String {
+    const BASE32: &[u8] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+    let mut lon_range = (-180.0, 180.0);
+    let mut lat_range = (-90.0, 90.0);
+
+    let mut hash = String::new();
+    let (mut bits, mut count, mut even) = (0, 0, true);
+    while hash.len() < precision as usize {
+        let (range, value) = if even {
+            (&mut lon_range, lon)
+        } else {
+            (&mut lat_range, lat)
+        };
+        let mid = (range.0 + range.1) / 2.0;
+        if value >= mid {
+            bits = bits << 1 | 1;
+            range.0 = mid;
+        } else {
+            bits <<= 1;
+            range.1 = mid;
+        }
+        even = !even;
+
+        count += 1;
+        if count == 5 {
+            hash.push(BASE32[bits] as char);
+            bits = 0;
+            count = 0;
+        }
+    }
+    hash
+}
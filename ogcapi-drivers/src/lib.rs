@@ -1,15 +1,34 @@
+#[cfg(feature = "fs")]
+pub mod fs;
+#[cfg(feature = "gpkg")]
+pub mod gpkg;
+#[cfg(feature = "memory")]
+pub mod memory;
 #[cfg(feature = "postgres")]
 pub mod postgres;
+#[cfg(feature = "raster")]
+pub mod raster;
 #[cfg(feature = "s3")]
 pub mod s3;
+pub mod unavailable;
+
+#[cfg(any(feature = "gpkg", feature = "memory", feature = "s3"))]
+mod index;
 
 #[cfg(feature = "stac")]
-use ogcapi_types::stac::SearchParams;
+use ogcapi_types::{
+    features::Queryables,
+    stac::{AggregationCollection, AggregationParams, Catalog, SearchParams},
+};
+use std::{collections::HashMap, time::Duration};
+
 use ogcapi_types::{
     common::{Collection, Collections, Crs, Query as CollectionQuery},
-    edr::{Query as EdrQuery, QueryType},
-    features::{Feature, FeatureCollection, Query as FeatureQuery},
-    processes::{Results, StatusInfo},
+    coverage::Coverage,
+    coverages::{DomainSet, Query as CoverageQuery, RangeType},
+    edr::{DataType, Query as EdrQuery, QueryType},
+    features::{Feature, FeatureCollection, Geometry, Query as FeatureQuery},
+    processes::{JobQuery, StatusInfo},
     styles::Styles,
     tiles::TileMatrixSet,
 };
@@ -28,11 +47,40 @@ pub trait CollectionTransactions: Send + Sync {
     async fn list_collections(&self, query: &CollectionQuery) -> anyhow::Result<Collections>;
 }
 
+/// Trait for maintaining the `extent` and `summaries` of collections
+#[async_trait::async_trait]
+pub trait CollectionMaintenance: Send + Sync {
+    /// Recompute the extent and summaries of a collection from its items,
+    /// returns the updated collection
+    async fn refresh_collection(&self, id: &str) -> anyhow::Result<Option<Collection>>;
+
+    /// Update the extent and summaries of a collection after items have been
+    /// `removed` (or replaced) and `added`, defaults to a refresh
+    async fn update_extent(
+        &self,
+        id: &str,
+        _removed: &[Feature],
+        _added: &[Feature],
+    ) -> anyhow::Result<()> {
+        self.refresh_collection(id).await?;
+        Ok(())
+    }
+}
+
 /// Trait for `Feature` transactions
 #[async_trait::async_trait]
 pub trait FeatureTransactions: Send + Sync {
     async fn create_feature(&self, feature: &Feature) -> anyhow::Result<String>;
 
+    /// Create several features, all or none with drivers supporting transactions
+    async fn create_features(&self, features: &[Feature]) -> anyhow::Result<Vec<String>> {
+        let mut ids = Vec::new();
+        for feature in features {
+            ids.push(self.create_feature(feature).await?);
+        }
+        Ok(ids)
+    }
+
     async fn read_feature(
         &self,
         collection: &str,
@@ -50,32 +98,234 @@ pub trait FeatureTransactions: Send + Sync {
     ) -> anyhow::Result<FeatureCollection>;
 }
 
+/// Trait for `STAC` catalog transactions
+///
+/// Catalogs share the namespace of collection ids but have no items.
+#[cfg(feature = "stac")]
+#[async_trait::async_trait]
+pub trait CatalogTransactions: Send + Sync {
+    async fn create_catalog(&self, catalog: &Catalog) -> anyhow::Result<String>;
+
+    async fn read_catalog(&self, id: &str) -> anyhow::Result<Option<Catalog>>;
+
+    async fn update_catalog(&self, catalog: &Catalog) -> anyhow::Result<()>;
+
+    async fn delete_catalog(&self, id: &str) -> anyhow::Result<()>;
+
+    async fn list_catalogs(&self) -> anyhow::Result<Vec<Catalog>>;
+}
+
 /// Trait for `STAC` search
 #[cfg(feature = "stac")]
 #[async_trait::async_trait]
 pub trait StacSeach: Send + Sync {
     async fn search(&self, query: &SearchParams) -> anyhow::Result<FeatureCollection>;
+
+    /// Aggregations of the items matching a search (aggregation extension)
+    async fn aggregate(
+        &self,
+        query: &SearchParams,
+        params: &AggregationParams,
+    ) -> anyhow::Result<AggregationCollection>;
+
+    /// Queryables of a collection inferred from its items
+    async fn queryables(&self, collection_id: &str) -> anyhow::Result<Queryables>;
 }
 
 /// Trait for `EDR` queries
 #[async_trait::async_trait]
 pub trait EdrQuerier: Send + Sync {
+    /// Whether the driver serves requests, see [unavailable::Unavailable]
+    fn is_available(&self) -> bool {
+        true
+    }
+
     async fn query(
         &self,
         collection_id: &str,
         query_type: &QueryType,
         query: &EdrQuery,
     ) -> anyhow::Result<FeatureCollection>;
+
+    /// Named locations of a collection
+    async fn locations(&self, collection_id: &str) -> anyhow::Result<FeatureCollection>;
+
+    /// Named location of a collection
+    async fn location(&self, collection_id: &str, id: &str) -> anyhow::Result<Option<Feature>>;
+
+    /// Create or replace a named location, the `name` property is its label
+    async fn put_location(&self, collection_id: &str, location: &Feature)
+        -> anyhow::Result<String>;
+
+    async fn delete_location(&self, collection_id: &str, id: &str) -> anyhow::Result<()>;
+
+    /// Data types of the item properties of a collection
+    async fn parameters(&self, collection_id: &str) -> anyhow::Result<HashMap<String, DataType>>;
+}
+
+/// Trait for `Coverages` of gridded data
+#[async_trait::async_trait]
+pub trait CoverageQuerier: Send + Sync {
+    /// Whether the driver serves requests, see [unavailable::Unavailable]
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    /// Domain set of the coverage of a collection
+    async fn domain_set(&self, collection_id: &str) -> anyhow::Result<Option<DomainSet>>;
+
+    /// Range type of the coverage of a collection
+    async fn range_type(&self, collection_id: &str) -> anyhow::Result<Option<RangeType>>;
+
+    /// Subset of the coverage of a collection encoded as CoverageJSON
+    async fn coverage(
+        &self,
+        collection_id: &str,
+        query: &CoverageQuery,
+    ) -> anyhow::Result<Option<Coverage>>;
+
+    /// Subset of the coverage of a collection encoded as GeoTIFF
+    async fn geotiff(
+        &self,
+        collection_id: &str,
+        query: &CoverageQuery,
+    ) -> anyhow::Result<Option<Vec<u8>>>;
+}
+
+/// Trait for `EDR` collection instances
+#[async_trait::async_trait]
+pub trait InstanceTransactions: Send + Sync {
+    async fn create_instance(
+        &self,
+        collection_id: &str,
+        instance: &Collection,
+    ) -> anyhow::Result<String>;
+
+    /// Read an instance with its extent derived from its items
+    async fn read_instance(
+        &self,
+        collection_id: &str,
+        id: &str,
+    ) -> anyhow::Result<Option<Collection>>;
+
+    async fn delete_instance(&self, collection_id: &str, id: &str) -> anyhow::Result<()>;
+
+    /// Insert items into an instance, all or none, returns their ids
+    async fn create_instance_items(
+        &self,
+        collection_id: &str,
+        id: &str,
+        features: &[Feature],
+    ) -> anyhow::Result<Vec<String>>;
+
+    /// List instances with their extent derived from their items
+    async fn list_instances(&self, collection_id: &str) -> anyhow::Result<Vec<Collection>>;
 }
 
 /// Trait for `Processes` jobs
 #[async_trait::async_trait]
 pub trait JobHandler: Send + Sync {
+    async fn register(&self, job: &StatusInfo) -> anyhow::Result<String>;
+
+    async fn update(&self, job: &StatusInfo) -> anyhow::Result<()>;
+
     async fn status(&self, id: &str) -> anyhow::Result<Option<StatusInfo>>;
 
+    async fn list_jobs(&self, query: &JobQuery) -> anyhow::Result<Vec<StatusInfo>>;
+
     async fn delete(&self, id: &str) -> anyhow::Result<()>;
 
-    async fn results(&self, id: &str) -> anyhow::Result<Option<Results>>;
+    async fn store_results(&self, id: &str, results: &JobResults) -> anyhow::Result<()>;
+
+    async fn results(&self, id: &str) -> anyhow::Result<Option<JobResults>>;
+
+    /// Delete jobs created longer than `retention` ago, returns the results of
+    /// the deleted jobs for cleaning up artifacts
+    async fn purge(&self, retention: Duration) -> anyhow::Result<Vec<JobResults>>;
+}
+
+/// Stored results of a job
+#[derive(Debug, Clone)]
+pub enum JobResults {
+    /// JSON results stored along with the job
+    Inline {
+        value: serde_json::Value,
+        media_type: Option<String>,
+    },
+    /// Results stored out-of-line as artifact with `key`
+    Artifact {
+        key: String,
+        media_type: Option<String>,
+    },
+}
+
+/// Trait for out-of-line storage of (large) job results
+#[async_trait::async_trait]
+pub trait ArtifactStore: Send + Sync {
+    async fn put_artifact(
+        &self,
+        key: &str,
+        data: Vec<u8>,
+        media_type: Option<&str>,
+    ) -> anyhow::Result<()>;
+
+    async fn get_artifact(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>>;
+
+    async fn delete_artifact(&self, key: &str) -> anyhow::Result<()>;
+}
+
+/// Geoprocessing operations on the items of a collection
+#[derive(Debug, Clone)]
+pub enum Operation {
+    /// Buffer geometries by a distance in meters
+    Buffer { distance: f64 },
+    /// Clip geometries by a geometry in `CRS84`
+    Clip { geometry: Geometry },
+    /// Union geometries, grouped by the value of a property if present
+    Dissolve { property: Option<String> },
+    /// Join the properties of intersecting items of another collection
+    SpatialJoin { collection: String },
+    /// Replace geometries with their centroid
+    Centroid,
+    /// Reproject geometries to another crs
+    Reproject { crs: Crs },
+    /// Simplify geometries with a tolerance in units of the storage crs
+    Simplify { tolerance: f64 },
+}
+
+/// Input items of a geoprocessing operation
+#[derive(Debug, Clone)]
+pub enum Source {
+    /// Items of a stored collection
+    Collection(String),
+    /// Inline features in `CRS84`
+    Features(FeatureCollection),
+}
+
+/// Trait for `Geoprocessing` of collections
+#[async_trait::async_trait]
+pub trait Geoprocessing: Send + Sync {
+    /// Whether the driver serves requests, see [unavailable::Unavailable]
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    /// Apply the operation to the source items and return the result in
+    /// `CRS84`, or in the target crs of a reprojection
+    async fn process(
+        &self,
+        source: &Source,
+        operation: &Operation,
+    ) -> anyhow::Result<FeatureCollection>;
+
+    /// Apply the operation to the source items and insert the result into the
+    /// (existing) `target` collection, returns the number of items inserted
+    async fn process_into(
+        &self,
+        source: &Source,
+        operation: &Operation,
+        target: &str,
+    ) -> anyhow::Result<u64>;
 }
 
 /// Trait for `Style` transactions
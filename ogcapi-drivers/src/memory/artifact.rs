@@ -0,0 +1,27 @@
+use crate::ArtifactStore;
+
+use super::Memory;
+
+#[async_trait::async_trait]
+impl ArtifactStore for Memory {
+    async fn put_artifact(
+        &self,
+        key: &str,
+        data: Vec<u8>,
+        _media_type: Option<&str>,
+    ) -> anyhow::Result<()> {
+        self.write().artifacts.insert(key.to_owned(), data);
+
+        Ok(())
+    }
+
+    async fn get_artifact(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(self.read().artifacts.get(key).cloned())
+    }
+
+    async fn delete_artifact(&self, key: &str) -> anyhow::Result<()> {
+        self.write().artifacts.remove(key);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use ogcapi_types::stac::Catalog;
+
+use crate::CatalogTransactions;
+
+use super::Memory;
+
+#[async_trait::async_trait]
+impl CatalogTransactions for Memory {
+    async fn create_catalog(&self, catalog: &Catalog) -> anyhow::Result<String> {
+        let mut store = self.write();
+
+        if store.catalogs.contains_key(&catalog.id) || store.collections.contains_key(&catalog.id) {
+            anyhow::bail!("Catalog `{}` already exists", catalog.id);
+        }
+
+        store
+            .catalogs
+            .insert(catalog.id.to_owned(), catalog.to_owned());
+
+        Ok(catalog.id.to_owned())
+    }
+
+    async fn read_catalog(&self, id: &str) -> anyhow::Result<Option<Catalog>> {
+        Ok(self.read().catalogs.get(id).cloned())
+    }
+
+    async fn update_catalog(&self, catalog: &Catalog) -> anyhow::Result<()> {
+        self.write()
+            .catalogs
+            .insert(catalog.id.to_owned(), catalog.to_owned());
+
+        Ok(())
+    }
+
+    async fn delete_catalog(&self, id: &str) -> anyhow::Result<()> {
+        self.write().catalogs.remove(id);
+
+        Ok(())
+    }
+
+    async fn list_catalogs(&self) -> anyhow::Result<Vec<Catalog>> {
+        Ok(self.read().catalogs.values().cloned().collect())
+    }
+}
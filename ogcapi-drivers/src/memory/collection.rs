@@ -0,0 +1,61 @@
+use ogcapi_types::common::{Collection, Collections, Query};
+
+use crate::CollectionTransactions;
+
+use super::Memory;
+
+#[async_trait::async_trait]
+impl CollectionTransactions for Memory {
+    async fn create_collection(&self, collection: &Collection) -> anyhow::Result<String> {
+        let mut store = self.write();
+
+        if store.collections.contains_key(&collection.id) {
+            anyhow::bail!("Collection `{}` already exists", collection.id);
+        }
+        #[cfg(feature = "stac")]
+        if store.catalogs.contains_key(&collection.id) {
+            anyhow::bail!("Catalog `{}` already exists", collection.id);
+        }
+
+        store
+            .collections
+            .insert(collection.id.to_owned(), collection.to_owned());
+        store
+            .items
+            .insert(collection.id.to_owned(), Default::default());
+
+        Ok(collection.id.to_owned())
+    }
+
+    async fn read_collection(&self, id: &str) -> anyhow::Result<Option<Collection>> {
+        Ok(self.read().collections.get(id).cloned())
+    }
+
+    async fn update_collection(&self, collection: &Collection) -> anyhow::Result<()> {
+        self.write()
+            .collections
+            .insert(collection.id.to_owned(), collection.to_owned());
+
+        Ok(())
+    }
+
+    async fn delete_collection(&self, id: &str) -> anyhow::Result<()> {
+        let mut store = self.write();
+
+        // collection, items and tiles
+        store.collections.remove(id);
+        store.items.remove(id);
+        store.tiles.retain(|(collection, ..), _| collection != id);
+
+        Ok(())
+    }
+
+    async fn list_collections(&self, _query: &Query) -> anyhow::Result<Collections> {
+        let collections = self.read().collections.values().cloned().collect();
+
+        let mut collections = Collections::new(collections);
+        collections.number_matched = collections.number_returned;
+
+        Ok(collections)
+    }
+}
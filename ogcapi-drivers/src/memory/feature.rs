@@ -0,0 +1,125 @@
+use anyhow::Context;
+
+use ogcapi_types::{
+    common::Crs,
+    features::{Feature, FeatureCollection, Query},
+};
+
+use crate::{unavailable::UnavailableError, FeatureTransactions};
+
+use super::Memory;
+
+const CRS_MESSAGE: &str = "The memory driver only serves features in `CRS84`";
+
+const FILTER_MESSAGE: &str = "The memory driver does not support filter expressions";
+
+const BBOX_CRS_MESSAGE: &str = "The memory driver only supports bboxes in `CRS84`";
+
+#[async_trait::async_trait]
+impl FeatureTransactions for Memory {
+    async fn create_feature(&self, feature: &Feature) -> anyhow::Result<String> {
+        let collection = feature.collection.as_ref().unwrap();
+
+        let mut feature = feature.to_owned();
+        let id = feature
+            .id
+            .get_or_insert_with(|| uuid::Uuid::new_v4().to_string())
+            .to_owned();
+
+        self.write()
+            .items
+            .get_mut(collection)
+            .with_context(|| format!("Collection `{collection}` does not exist"))?
+            .insert(id.to_owned(), feature);
+
+        Ok(id)
+    }
+
+    async fn read_feature(
+        &self,
+        collection: &str,
+        id: &str,
+        crs: &Crs,
+    ) -> anyhow::Result<Option<Feature>> {
+        if *crs != Crs::default() {
+            return Err(UnavailableError(CRS_MESSAGE).into());
+        }
+
+        Ok(self
+            .read()
+            .items
+            .get(collection)
+            .and_then(|items| items.get(id))
+            .cloned())
+    }
+
+    async fn update_feature(&self, feature: &Feature) -> anyhow::Result<()> {
+        let collection = feature.collection.as_ref().unwrap();
+
+        self.write()
+            .items
+            .get_mut(collection)
+            .with_context(|| format!("Collection `{collection}` does not exist"))?
+            .insert(feature.id.to_owned().unwrap(), feature.to_owned());
+
+        Ok(())
+    }
+
+    async fn delete_feature(&self, collection: &str, id: &str) -> anyhow::Result<()> {
+        if let Some(items) = self.write().items.get_mut(collection) {
+            items.remove(id);
+        }
+
+        Ok(())
+    }
+
+    /// Lists the items of a collection filtered by `bbox`, `datetime` and
+    /// properties, ordered by id
+    ///
+    /// Items are stored and served in `CRS84`, filter expressions are not
+    /// supported.
+    async fn list_items(
+        &self,
+        collection: &str,
+        query: &Query,
+    ) -> anyhow::Result<FeatureCollection> {
+        if query.crs != Crs::default() {
+            return Err(UnavailableError(CRS_MESSAGE).into());
+        }
+        if query.filter.is_some() {
+            return Err(UnavailableError(FILTER_MESSAGE).into());
+        }
+        if query.bbox.is_some() && query.bbox_crs != Crs::default() {
+            return Err(UnavailableError(BBOX_CRS_MESSAGE).into());
+        }
+
+        let store = self.read();
+
+        let matched: Vec<&Feature> = store
+            .items
+            .get(collection)
+            .into_iter()
+            .flat_map(|items| items.values())
+            .filter(|feature| {
+                super::matches(
+                    feature,
+                    query.bbox.as_ref(),
+                    query.datetime.as_ref(),
+                    &query.additional_parameters,
+                )
+            })
+            .collect();
+
+        let features = matched
+            .iter()
+            .skip(query.offset.unwrap_or(0))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .map(|&feature| feature.to_owned())
+            .collect();
+
+        let mut fc = FeatureCollection::new(features);
+        fc.number_matched = Some(matched.len() as u64);
+
+        Ok(fc)
+    }
+}
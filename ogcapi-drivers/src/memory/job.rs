@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use chrono::Utc;
+
+use ogcapi_types::processes::{JobQuery, StatusCode, StatusInfo};
+
+use crate::{JobHandler, JobResults};
+
+use super::Memory;
+
+#[async_trait::async_trait]
+impl JobHandler for Memory {
+    async fn register(&self, job: &StatusInfo) -> anyhow::Result<String> {
+        self.write()
+            .jobs
+            .insert(job.job_id.to_owned(), job.to_owned());
+
+        Ok(job.job_id.to_owned())
+    }
+
+    async fn update(&self, job: &StatusInfo) -> anyhow::Result<()> {
+        self.write()
+            .jobs
+            .insert(job.job_id.to_owned(), job.to_owned());
+
+        Ok(())
+    }
+
+    async fn status(&self, id: &str) -> anyhow::Result<Option<StatusInfo>> {
+        Ok(self.read().jobs.get(id).cloned())
+    }
+
+    /// Lists jobs most recently created first
+    async fn list_jobs(&self, query: &JobQuery) -> anyhow::Result<Vec<StatusInfo>> {
+        let mut jobs: Vec<StatusInfo> = self
+            .read()
+            .jobs
+            .values()
+            .filter(|job| query.matches(job))
+            .cloned()
+            .collect();
+
+        jobs.sort_by(|a, b| {
+            b.created
+                .cmp(&a.created)
+                .then_with(|| a.job_id.cmp(&b.job_id))
+        });
+
+        Ok(jobs
+            .into_iter()
+            .skip(query.offset.unwrap_or(0))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    async fn delete(&self, id: &str) -> anyhow::Result<()> {
+        let mut store = self.write();
+
+        store.jobs.remove(id);
+        store.results.remove(id);
+
+        Ok(())
+    }
+
+    async fn store_results(&self, id: &str, results: &JobResults) -> anyhow::Result<()> {
+        self.write()
+            .results
+            .insert(id.to_owned(), results.to_owned());
+
+        Ok(())
+    }
+
+    async fn results(&self, id: &str) -> anyhow::Result<Option<JobResults>> {
+        Ok(self.read().results.get(id).cloned())
+    }
+
+    async fn purge(&self, retention: Duration) -> anyhow::Result<Vec<JobResults>> {
+        let threshold = Utc::now() - chrono::Duration::from_std(retention)?;
+
+        let mut store = self.write();
+
+        let expired: Vec<String> = store
+            .jobs
+            .values()
+            .filter(|job| {
+                !matches!(job.status, StatusCode::Accepted | StatusCode::Running)
+                    && matches!(job.created, Some(created) if created < threshold)
+            })
+            .map(|job| job.job_id.to_owned())
+            .collect();
+
+        let mut deleted = Vec::new();
+        for id in expired {
+            store.jobs.remove(&id);
+            if let Some(results) = store.results.remove(&id) {
+                deleted.push(results);
+            }
+        }
+
+        Ok(deleted)
+    }
+}
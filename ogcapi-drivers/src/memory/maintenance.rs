@@ -0,0 +1,35 @@
+use ogcapi_types::common::Collection;
+
+use crate::{
+    index::{self, Entry},
+    CollectionMaintenance,
+};
+
+use super::Memory;
+
+#[async_trait::async_trait]
+impl CollectionMaintenance for Memory {
+    /// Recomputes the extent and summaries of a collection from its items
+    async fn refresh_collection(&self, id: &str) -> anyhow::Result<Option<Collection>> {
+        let mut store = self.write();
+        let store = &mut *store;
+
+        let collection = match store.collections.get_mut(id) {
+            Some(collection) => collection,
+            None => return Ok(None),
+        };
+        let items = store
+            .items
+            .get(id)
+            .into_iter()
+            .flat_map(|items| items.values());
+
+        let entries: Vec<Entry> = items.clone().map(Entry::new).collect();
+        collection.extent = index::extent(&entries);
+
+        #[cfg(feature = "stac")]
+        index::summarize(&mut collection.summaries, &super::properties(items));
+
+        Ok(Some(collection.to_owned()))
+    }
+}
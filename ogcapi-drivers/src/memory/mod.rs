@@ -0,0 +1,201 @@
+mod artifact;
+#[cfg(feature = "stac")]
+mod catalog;
+mod collection;
+mod feature;
+mod job;
+mod maintenance;
+#[cfg(feature = "stac")]
+mod stac;
+mod style;
+mod tile;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+#[cfg(feature = "stac")]
+use serde_json::Map;
+use serde_json::Value;
+
+#[cfg(feature = "stac")]
+use ogcapi_types::stac::Catalog;
+use ogcapi_types::{
+    common::{Bbox, Collection, Datetime},
+    features::Feature,
+    processes::StatusInfo,
+};
+
+use crate::{
+    index::{self, Entry},
+    CollectionMaintenance, JobResults,
+};
+
+/// In-memory driver
+///
+/// Clones share the same store, all data is lost once the last clone is
+/// dropped. Items are stored in `CRS84`.
+#[derive(Clone, Default)]
+pub struct Memory {
+    store: Arc<RwLock<Store>>,
+}
+
+#[derive(Default)]
+struct Store {
+    collections: BTreeMap<String, Collection>,
+    /// Items by collection and id
+    items: BTreeMap<String, BTreeMap<String, Feature>>,
+    #[cfg(feature = "stac")]
+    catalogs: BTreeMap<String, Catalog>,
+    jobs: BTreeMap<String, StatusInfo>,
+    results: HashMap<String, JobResults>,
+    artifacts: HashMap<String, Vec<u8>>,
+    styles: BTreeMap<String, Value>,
+    /// Vector tiles by collection, tile matrix set, matrix, row and col
+    tiles: HashMap<(String, String, String, u32, u32), Vec<u8>>,
+}
+
+impl Memory {
+    pub fn new() -> Self {
+        Memory::default()
+    }
+
+    /// Loads the GeoJSON feature collections of a directory
+    ///
+    /// Each `.geojson` or `.json` file becomes a collection named after the
+    /// file stem, features without id are numbered from 1.
+    pub async fn from_dir(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let memory = Memory::new();
+
+        for entry in std::fs::read_dir(path)? {
+            let path = entry?.path();
+            if !matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("geojson" | "json")
+            ) {
+                continue;
+            }
+            let id = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(id) => id.to_owned(),
+                None => continue,
+            };
+
+            let fc: Value = serde_json::from_slice(&std::fs::read(&path)?)?;
+            let mut items = BTreeMap::new();
+            for (i, feature) in fc["features"].as_array().into_iter().flatten().enumerate() {
+                let mut feature = feature.to_owned();
+                if let Some(id @ Value::Number(_)) = feature.get_mut("id") {
+                    *id = Value::String(id.to_string());
+                }
+                let mut feature: Feature = serde_json::from_value(feature)?;
+                feature.collection = Some(id.to_owned());
+                let fid = feature
+                    .id
+                    .get_or_insert_with(|| (i + 1).to_string())
+                    .to_owned();
+                items.insert(fid, feature);
+            }
+
+            let collection = Collection {
+                id: id.to_owned(),
+                title: fc["name"].as_str().map(ToOwned::to_owned),
+                item_type: Some("Feature".to_string()),
+                ..Default::default()
+            };
+
+            let mut store = memory.write();
+            store.collections.insert(id.to_owned(), collection);
+            store.items.insert(id.to_owned(), items);
+        }
+
+        let ids: Vec<String> = memory.read().collections.keys().cloned().collect();
+        for id in ids {
+            memory.refresh_collection(&id).await?;
+        }
+
+        Ok(memory)
+    }
+
+    /// Adds or replaces a style
+    pub fn insert_style(&self, id: impl ToString, stylesheet: Value) {
+        self.write().styles.insert(id.to_string(), stylesheet);
+    }
+
+    /// Adds or replaces a pregenerated vector tile of a collection
+    pub fn insert_tile(
+        &self,
+        collection: &str,
+        tms: &str,
+        matrix: &str,
+        row: u32,
+        col: u32,
+        tile: Vec<u8>,
+    ) {
+        self.write().tiles.insert(
+            (
+                collection.to_owned(),
+                tms.to_owned(),
+                matrix.to_owned(),
+                row,
+                col,
+            ),
+            tile,
+        );
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Store> {
+        self.store.read().unwrap()
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Store> {
+        self.store.write().unwrap()
+    }
+}
+
+/// Whether a feature intersects the `bbox` and `datetime` and has the
+/// `properties`
+///
+/// Properties missing on the feature match any value, numbers are compared
+/// numerically.
+fn matches(
+    feature: &Feature,
+    bbox: Option<&Bbox>,
+    datetime: Option<&Datetime>,
+    properties: &HashMap<String, String>,
+) -> bool {
+    if let Some(bbox) = bbox {
+        let [xmin, ymin, xmax, ymax] = bbox.corners();
+        let rect = geo::Rect::new(
+            geo::coord! { x: xmin, y: ymin },
+            geo::coord! { x: xmax, y: ymax },
+        );
+        if !intersects(feature, &rect.into()) {
+            return false;
+        }
+    }
+
+    if !Entry::new(feature).matches(None, datetime) {
+        return false;
+    }
+
+    index::matches_properties(feature.properties.as_ref(), properties)
+}
+
+/// Whether the geometry of a feature intersects a geometry in `CRS84`
+fn intersects(feature: &Feature, geometry: &geo::Geometry<f64>) -> bool {
+    use geo::Intersects;
+
+    geo::Geometry::<f64>::try_from(feature.geometry.value.to_owned())
+        .is_ok_and(|g| g.intersects(geometry))
+}
+
+/// Properties of features for use in summaries and queryables
+#[cfg(feature = "stac")]
+fn properties<'a>(features: impl IntoIterator<Item = &'a Feature>) -> Vec<Map<String, Value>> {
+    features
+        .into_iter()
+        .filter_map(|feature| feature.properties.to_owned())
+        .collect()
+}
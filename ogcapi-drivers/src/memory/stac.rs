@@ -0,0 +1,106 @@
+use ogcapi_types::{
+    features::{Feature, FeatureCollection, Queryables},
+    stac::{AggregationCollection, AggregationParams, SearchParams},
+};
+
+use crate::{
+    index::{self, Entry},
+    StacSeach,
+};
+
+use super::Memory;
+
+impl Memory {
+    /// Items matching a search, ordered by collection and id
+    fn matches(&self, query: &SearchParams) -> anyhow::Result<Vec<Feature>> {
+        if query.filter.is_some() || query.query.is_some() {
+            anyhow::bail!("The memory driver does not support the `filter` and `query` parameters");
+        }
+
+        let intersects = match &query.intersects {
+            Some(geometry) => Some(geo::Geometry::<f64>::try_from(geometry.value.to_owned())?),
+            None => None,
+        };
+
+        let store = self.read();
+
+        let matches = store
+            .items
+            .iter()
+            .filter(|(collection, _)| match &query.collections {
+                Some(collections) => collections.0.contains(collection),
+                None => true,
+            })
+            .flat_map(|(_, items)| items.values())
+            .filter(|item| match &query.ids {
+                Some(ids) => item.id.as_ref().is_some_and(|id| ids.0.contains(id)),
+                None => true,
+            })
+            .filter(|item| {
+                super::matches(
+                    item,
+                    query.bbox.as_ref(),
+                    query.datetime.as_ref(),
+                    &Default::default(),
+                )
+            })
+            .filter(|item| match &intersects {
+                Some(geometry) => super::intersects(item, geometry),
+                None => true,
+            })
+            .cloned()
+            .collect();
+
+        Ok(matches)
+    }
+}
+
+#[async_trait::async_trait]
+impl StacSeach for Memory {
+    async fn search(&self, query: &SearchParams) -> anyhow::Result<FeatureCollection> {
+        let matches = self.matches(query)?;
+        let number_matched = matches.len();
+
+        let features = matches
+            .into_iter()
+            .skip(query.offset.unwrap_or(0) as usize)
+            .take(query.limit.map_or(usize::MAX, |limit| limit as usize))
+            .collect();
+
+        let mut fc = FeatureCollection::new(features);
+        fc.number_matched = Some(number_matched as u64);
+
+        Ok(fc)
+    }
+
+    async fn aggregate(
+        &self,
+        query: &SearchParams,
+        params: &AggregationParams,
+    ) -> anyhow::Result<AggregationCollection> {
+        let matches: Vec<(String, Entry)> = self
+            .matches(query)?
+            .iter()
+            .map(|item| {
+                (
+                    item.collection.to_owned().unwrap_or_default(),
+                    Entry::new(item),
+                )
+            })
+            .collect();
+
+        index::aggregate(&matches, params)
+    }
+
+    /// Infers the queryables of a collection from all its items
+    async fn queryables(&self, collection_id: &str) -> anyhow::Result<Queryables> {
+        let store = self.read();
+        let items = store
+            .items
+            .get(collection_id)
+            .into_iter()
+            .flat_map(|items| items.values());
+
+        Ok(index::queryables(super::properties(items)))
+    }
+}
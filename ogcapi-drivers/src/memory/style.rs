@@ -0,0 +1,27 @@
+use ogcapi_types::styles::{Style, Styles};
+
+use crate::StyleTransactions;
+
+use super::Memory;
+
+#[async_trait::async_trait]
+impl StyleTransactions for Memory {
+    async fn list_styles(&self) -> anyhow::Result<Styles> {
+        let styles = self
+            .read()
+            .styles
+            .keys()
+            .map(|id| Style {
+                id: id.to_owned(),
+                title: None,
+                links: Vec::new(),
+            })
+            .collect();
+
+        Ok(Styles { styles })
+    }
+
+    async fn read_style(&self, id: &str) -> anyhow::Result<Option<serde_json::Value>> {
+        Ok(self.read().styles.get(id).cloned())
+    }
+}
@@ -0,0 +1,37 @@
+use ogcapi_types::tiles::TileMatrixSet;
+
+use crate::TileTransactions;
+
+use super::Memory;
+
+#[async_trait::async_trait]
+impl TileTransactions for Memory {
+    /// Concatenates the inserted vector tiles of the collections, missing
+    /// tiles are empty
+    async fn tile(
+        &self,
+        collections: &str,
+        tms: &TileMatrixSet,
+        matrix: &str,
+        row: u32,
+        col: u32,
+    ) -> anyhow::Result<Vec<u8>> {
+        let store = self.read();
+
+        let tiles: Vec<&[u8]> = collections
+            .split(',')
+            .filter_map(|collection| {
+                store.tiles.get(&(
+                    collection.to_owned(),
+                    tms.id.to_owned(),
+                    matrix.to_owned(),
+                    row,
+                    col,
+                ))
+            })
+            .map(Vec::as_slice)
+            .collect();
+
+        Ok(tiles.concat())
+    }
+}
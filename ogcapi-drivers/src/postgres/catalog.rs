@@ -0,0 +1,72 @@
+use ogcapi_types::stac::Catalog;
+
+use crate::CatalogTransactions;
+
+use super::Db;
+
+#[async_trait::async_trait]
+impl CatalogTransactions for Db {
+    async fn create_catalog(&self, catalog: &Catalog) -> anyhow::Result<String> {
+        sqlx::query("INSERT INTO meta.collections ( id, collection ) VALUES ( $1, $2 )")
+            .bind(&catalog.id)
+            .bind(sqlx::types::Json(catalog))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(catalog.id.to_owned())
+    }
+
+    async fn read_catalog(&self, id: &str) -> anyhow::Result<Option<Catalog>> {
+        let catalog: Option<sqlx::types::Json<Catalog>> = sqlx::query_scalar(
+            r#"
+            SELECT collection FROM meta.collections
+            WHERE id = $1 AND collection ->> 'type' = 'Catalog'
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(catalog.map(|c| c.0))
+    }
+
+    async fn update_catalog(&self, catalog: &Catalog) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE meta.collections SET collection = $2
+            WHERE id = $1 AND collection ->> 'type' = 'Catalog'
+            "#,
+        )
+        .bind(&catalog.id)
+        .bind(sqlx::types::Json(catalog))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_catalog(&self, id: &str) -> anyhow::Result<()> {
+        sqlx::query(
+            "DELETE FROM meta.collections WHERE id = $1 AND collection ->> 'type' = 'Catalog'",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_catalogs(&self) -> anyhow::Result<Vec<Catalog>> {
+        let catalogs: Vec<sqlx::types::Json<Catalog>> = sqlx::query_scalar(
+            r#"
+            SELECT collection FROM meta.collections
+            WHERE collection ->> 'type' = 'Catalog'
+            ORDER BY id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(catalogs.into_iter().map(|c| c.0).collect())
+    }
+}
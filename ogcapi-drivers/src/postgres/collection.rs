@@ -2,7 +2,7 @@ use ogcapi_types::common::{Collection, Collections, Query};
 
 use crate::CollectionTransactions;
 
-use super::Db;
+use super::{instance::instance_table, Db};
 
 #[async_trait::async_trait]
 impl CollectionTransactions for Db {
@@ -53,6 +53,12 @@ impl CollectionTransactions for Db {
             .execute(&mut tx)
             .await?;
 
+        // maintain the search index (see migrations)
+        sqlx::query("SELECT meta.search_index($1)")
+            .bind(&collection.id)
+            .execute(&mut tx)
+            .await?;
+
         tx.commit().await?;
 
         Ok(collection.id.to_owned())
@@ -86,6 +92,21 @@ impl CollectionTransactions for Db {
     async fn delete_collection(&self, id: &str) -> anyhow::Result<()> {
         let mut tx = self.pool.begin().await?;
 
+        let instances: Vec<String> =
+            sqlx::query_scalar("SELECT id FROM meta.instances WHERE collection_id = $1")
+                .bind(id)
+                .fetch_all(&mut tx)
+                .await?;
+
+        for instance in instances {
+            sqlx::query(&format!(
+                r#"DROP TABLE IF EXISTS items."{}""#,
+                instance_table(id, &instance)
+            ))
+            .execute(&mut tx)
+            .await?;
+        }
+
         sqlx::query(&format!(r#"DROP TABLE IF EXISTS items."{}""#, id))
             .execute(&mut tx)
             .await?;
@@ -0,0 +1,257 @@
+use ogcapi_types::features::cql2::{Expr, MAX_DEPTH};
+
+/// Postgres type of a comparison
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Type {
+    Text,
+    Numeric,
+    Boolean,
+    Timestamp,
+}
+
+impl Type {
+    /// Type of the literal arguments of a predicate, text by default
+    fn of(args: &[Expr]) -> Self {
+        args.iter()
+            .find_map(|arg| match arg {
+                Expr::Number(_) => Some(Type::Numeric),
+                Expr::Bool(_) => Some(Type::Boolean),
+                Expr::Timestamp { .. } | Expr::Date { .. } => Some(Type::Timestamp),
+                Expr::Text(_) => Some(Type::Text),
+                Expr::Array(items) => Some(Type::of(items)),
+                _ => None,
+            })
+            .unwrap_or(Type::Text)
+    }
+}
+
+/// Translates a CQL2 expression into a SQL predicate over items
+///
+/// Items have the columns `id`, `collection`, `properties`, `geom` (EPSG:4326),
+/// `start_datetime` and `end_datetime` of the search index.
+pub(crate) fn to_sql(expr: &Expr) -> anyhow::Result<String> {
+    if expr.deeper_than(MAX_DEPTH) {
+        anyhow::bail!("Expressions must not nest more than {MAX_DEPTH} levels");
+    }
+    sql(expr)
+}
+
+fn sql(expr: &Expr) -> anyhow::Result<String> {
+    match expr {
+        Expr::Operation { op, args } => match op.as_str() {
+            "and" | "or" => Ok(format!(
+                "({})",
+                args.iter()
+                    .map(sql)
+                    .collect::<anyhow::Result<Vec<_>>>()?
+                    .join(&format!(" {} ", op.to_uppercase()))
+            )),
+            "not" => Ok(format!("(NOT {})", sql(&args[0])?)),
+            "=" | "<>" | "<" | "<=" | ">" | ">=" => {
+                let t = Type::of(args);
+                Ok(format!(
+                    "({} {op} {})",
+                    value(&args[0], t)?,
+                    value(&args[1], t)?
+                ))
+            }
+            "like" => Ok(format!(
+                r"({} LIKE {} ESCAPE '\')",
+                value(&args[0], Type::Text)?,
+                value(&args[1], Type::Text)?
+            )),
+            "between" => {
+                let t = Type::of(&args[1..]);
+                Ok(format!(
+                    "({} BETWEEN {} AND {})",
+                    value(&args[0], t)?,
+                    value(&args[1], t)?,
+                    value(&args[2], t)?
+                ))
+            }
+            "in" => {
+                let items = match &args[1] {
+                    Expr::Array(items) => items,
+                    _ => anyhow::bail!("Operator `in` requires a list"),
+                };
+                let t = Type::of(items);
+                Ok(format!(
+                    "({} IN ({}))",
+                    value(&args[0], t)?,
+                    items
+                        .iter()
+                        .map(|item| value(item, t))
+                        .collect::<anyhow::Result<Vec<_>>>()?
+                        .join(", ")
+                ))
+            }
+            "isNull" => Ok(format!("({} IS NULL)", value(&args[0], Type::Text)?)),
+            op if op.starts_with("s_") => {
+                let function = match op {
+                    "s_intersects" => "ST_Intersects",
+                    "s_equals" => "ST_Equals",
+                    "s_disjoint" => "ST_Disjoint",
+                    "s_touches" => "ST_Touches",
+                    "s_within" => "ST_Within",
+                    "s_overlaps" => "ST_Overlaps",
+                    "s_crosses" => "ST_Crosses",
+                    "s_contains" => "ST_Contains",
+                    op => anyhow::bail!("Unsupported operator `{op}`"),
+                };
+                Ok(format!(
+                    "{function}({}, {})",
+                    geometry(&args[0])?,
+                    geometry(&args[1])?
+                ))
+            }
+            op if op.starts_with("t_") => {
+                let (a_start, a_end) = interval(&args[0])?;
+                let (b_start, b_end) = interval(&args[1])?;
+                Ok(match op {
+                    "t_after" => format!("({a_start} > {b_end})"),
+                    "t_before" => format!("({a_end} < {b_start})"),
+                    "t_contains" => format!("({a_start} < {b_start} AND {a_end} > {b_end})"),
+                    "t_disjoint" => format!("NOT ({a_start} <= {b_end} AND {a_end} >= {b_start})"),
+                    "t_during" => format!("({a_start} > {b_start} AND {a_end} < {b_end})"),
+                    "t_equals" => format!("({a_start} = {b_start} AND {a_end} = {b_end})"),
+                    "t_intersects" => format!("({a_start} <= {b_end} AND {a_end} >= {b_start})"),
+                    op => anyhow::bail!("Unsupported operator `{op}`"),
+                })
+            }
+            op if op.starts_with("a_") => {
+                let (a, b) = (array(&args[0])?, array(&args[1])?);
+                Ok(match op {
+                    "a_contains" => format!("({a} @> {b})"),
+                    "a_overlaps" => {
+                        format!("({a} ?| ARRAY(SELECT jsonb_array_elements_text({b})))")
+                    }
+                    op => anyhow::bail!("Unsupported operator `{op}`"),
+                })
+            }
+            op => anyhow::bail!("Unsupported operator `{op}`"),
+        },
+        Expr::Bool(b) => Ok(b.to_string().to_uppercase()),
+        Expr::Property { .. } => value(expr, Type::Boolean),
+        _ => anyhow::bail!("Expected a predicate"),
+    }
+}
+
+/// Quoted string literal
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// Scalar value cast to a type
+fn value(expr: &Expr, t: Type) -> anyhow::Result<String> {
+    Ok(match expr {
+        Expr::Property { property } => match property.as_str() {
+            "id" | "collection" => property.to_owned(),
+            _ => {
+                let key = quote(property);
+                match t {
+                    Type::Text => format!("properties->>{key}"),
+                    // values of other json types are null instead of failing casts
+                    Type::Numeric => format!(
+                        "(CASE WHEN jsonb_typeof(properties->{key}) = 'number' THEN CAST(properties->>{key} AS numeric) END)"
+                    ),
+                    Type::Boolean => format!(
+                        "(CASE WHEN jsonb_typeof(properties->{key}) = 'boolean' THEN CAST(properties->>{key} AS boolean) END)"
+                    ),
+                    Type::Timestamp => format!("CAST(properties->>{key} AS timestamptz)"),
+                }
+            }
+        },
+        Expr::Text(text) => quote(text),
+        Expr::Number(number) => number.to_string(),
+        Expr::Bool(b) => b.to_string().to_uppercase(),
+        Expr::Timestamp { timestamp } => format!("CAST({} AS timestamptz)", quote(timestamp)),
+        Expr::Date { date } => format!("CAST({} AS timestamptz)", quote(date)),
+        _ => anyhow::bail!("Expected a scalar value"),
+    })
+}
+
+/// Geometry in EPSG:4326
+fn geometry(expr: &Expr) -> anyhow::Result<String> {
+    Ok(match expr {
+        Expr::Property { property } if property == "geometry" || property == "geom" => {
+            "geom".to_string()
+        }
+        Expr::Geometry(geometry) => format!(
+            "ST_SetSRID(ST_GeomFromGeoJSON({}), 4326)",
+            quote(&geometry.to_string())
+        ),
+        Expr::BBox { bbox } => {
+            let (min, max) = match bbox.len() {
+                4 => ((bbox[0], bbox[1]), (bbox[2], bbox[3])),
+                6 => ((bbox[0], bbox[1]), (bbox[3], bbox[4])),
+                _ => anyhow::bail!("Bounding boxes require 4 or 6 numbers"),
+            };
+            format!(
+                "ST_MakeEnvelope({}, {}, {}, {}, 4326)",
+                min.0, min.1, max.0, max.1
+            )
+        }
+        _ => anyhow::bail!("Expected a geometry"),
+    })
+}
+
+/// Start and end of a temporal value
+fn interval(expr: &Expr) -> anyhow::Result<(String, String)> {
+    let bound = |expr: &Expr, open: &str| match expr {
+        Expr::Text(t) if t == ".." => Ok(format!("CAST('{open}' AS timestamptz)")),
+        Expr::Text(t) => Ok(format!("CAST({} AS timestamptz)", quote(t))),
+        expr => value(expr, Type::Timestamp),
+    };
+
+    Ok(match expr {
+        Expr::Property { property } if property == "datetime" => {
+            ("start_datetime".to_string(), "end_datetime".to_string())
+        }
+        Expr::Interval { interval } if interval.len() == 2 => (
+            bound(&interval[0], "-infinity")?,
+            bound(&interval[1], "infinity")?,
+        ),
+        Expr::Property { .. } | Expr::Timestamp { .. } | Expr::Date { .. } => {
+            let instant = value(expr, Type::Timestamp)?;
+            (instant.clone(), instant)
+        }
+        _ => anyhow::bail!("Expected a temporal value"),
+    })
+}
+
+/// Array as jsonb
+fn array(expr: &Expr) -> anyhow::Result<String> {
+    Ok(match expr {
+        Expr::Property { property } => format!("(properties->{})", quote(property)),
+        Expr::Array(_) => format!("{}::jsonb", quote(&serde_json::to_string(expr)?)),
+        _ => anyhow::bail!("Expected an array"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ogcapi_types::features::cql2::{Expr, MAX_DEPTH};
+
+    use super::to_sql;
+
+    #[test]
+    fn translate() {
+        let expr = "eo:cloud_cover < 10 AND name = 'O''Brien'".parse().unwrap();
+        assert_eq!(
+            to_sql(&expr).unwrap(),
+            "(((CASE WHEN jsonb_typeof(properties->'eo:cloud_cover') = 'number' THEN CAST(properties->>'eo:cloud_cover' AS numeric) END) < 10) AND (properties->>'name' = 'O''Brien'))"
+        );
+
+        let expr = r"name LIKE '50\%%'".parse().unwrap();
+        assert_eq!(
+            to_sql(&expr).unwrap(),
+            r"(properties->>'name' LIKE '50\%%' ESCAPE '\')"
+        );
+
+        let mut expr = "a = 1".parse().unwrap();
+        for _ in 0..MAX_DEPTH {
+            expr = Expr::op("not", vec![expr]);
+        }
+        assert!(to_sql(&expr).is_err());
+    }
+}
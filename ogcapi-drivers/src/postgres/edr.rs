@@ -1,12 +1,25 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
 use ogcapi_types::{
-    edr::{Query, QueryType},
+    common::{Bbox, Datetime, IntervalDatetime},
+    edr::{Aggregation, Coords, DataType, Levels, Query, QueryType},
     features::{Feature, FeatureCollection},
 };
+use serde_json::Value;
 use sqlx::types::Json;
 
 use crate::{CollectionTransactions, EdrQuerier};
 
-use super::Db;
+use super::{instance::instance_table, Db};
+
+/// Named location as GeoJSON feature, in `CRS84`
+const LOCATION: &str = r#"
+    id,
+    jsonb_build_object('name', name) || COALESCE(properties, '{}'::jsonb) as properties,
+    ST_AsGeoJSON(geom)::jsonb as geometry,
+    collection_id as collection
+"#;
 
 #[async_trait::async_trait]
 impl EdrQuerier for Db {
@@ -18,110 +31,273 @@ impl EdrQuerier for Db {
     ) -> anyhow::Result<FeatureCollection> {
         let srid: i32 = query.crs.as_srid();
 
-        let c = self.read_collection(collection_id).await?;
-        let storage_srid = c.unwrap().storage_crs.unwrap_or_default().as_srid();
+        let c = self
+            .read_collection(collection_id)
+            .await?
+            .context("Collection not found")?;
+        let storage_srid = c.storage_crs.to_owned().unwrap_or_default().as_srid();
 
-        let mut geometry_type = query.coords.split('(').next().unwrap().to_uppercase();
-        geometry_type.retain(|c| !c.is_whitespace());
+        let coords = query.geometry(query_type).map_err(anyhow::Error::msg)?;
 
-        let spatial_predicate = match &query_type {
-            QueryType::Position | QueryType::Area | QueryType::Trajectory => {
-                if geometry_type.ends_with('Z') || geometry_type.ends_with('M') {
+        let spatial_predicate = match (&query_type, &coords) {
+            (QueryType::Position | QueryType::Area | QueryType::Trajectory, Coords::Wkt(wkt)) => {
+                if wkt.dimensions.has_z() || wkt.dimensions.has_m() {
                     format!(
                         "ST_3DIntersects(geom, ST_Transform(ST_GeomFromEWKT('SRID={};{}'), {}))",
-                        srid, query.coords, storage_srid
+                        srid, wkt, storage_srid
                     )
                 } else {
                     format!(
                         "ST_Intersects(geom, ST_Transform(ST_GeomFromEWKT('SRID={};{}'), {}))",
-                        srid, query.coords, storage_srid
+                        srid, wkt, storage_srid
                     )
                 }
             }
-            QueryType::Radius => {
-                let mut ctx = rink_core::simple_context().unwrap();
-                let line = format!(
-                    "{} {} -> m",
-                    &query.within.to_owned().unwrap_or_else(|| "0".to_string()),
-                    &query
-                        .within_units
-                        .to_owned()
-                        .unwrap_or_else(|| "m".to_string())
-                );
-
-                let distance = rink_core::one_line(&mut ctx, &line)
-                    .ok()
-                    .and_then(|s| s.split(' ').next().and_then(|s| s.parse::<f64>().ok()))
-                    .expect("Failed to parse & convert distance");
+            (QueryType::Radius, Coords::Wkt(wkt)) => {
+                let distance = to_meters(&query.within, &query.within_units)?;
 
-                if geometry_type.ends_with('Z') || geometry_type.ends_with('M') {
+                if wkt.dimensions.has_z() || wkt.dimensions.has_m() {
+                    // geocentric coordinates to measure 3D distances in meters
                     format!(
-                        "ST_3DDWithin(geom, ST_Transform(ST_GeomFromEWKT('SRID={};{}'), {}))",
-                        srid, query.coords, storage_srid
+                        "ST_3DDWithin(ST_Transform(geom, 4978), ST_Transform(ST_GeomFromEWKT('SRID={};{}'), 4978), {})",
+                        srid, wkt, distance
                     )
                 } else {
                     format!(
                     "ST_DWithin(ST_Transform(geom, 4326)::geography, ST_Transform(ST_GeomFromEWKT('SRID={};{}'), 4326)::geography, {}, false)",
-                    srid, query.coords, distance
+                    srid, wkt, distance
                 )
                 }
             }
-            QueryType::Cube => {
-                let bbox: Vec<&str> = query.coords.split(',').collect();
-                if bbox.len() == 4 {
-                    format!(
-                        "ST_Intersects(geom, ST_Transform(ST_MakeEnvelope({}, {}), {})",
-                        query.coords, srid, storage_srid
-                    )
-                } else {
-                    format!(
-                        "ST_3DIntersects(
-                            geom,
-                            ST_Transform(
-                                ST_SetSRID(
-                                    ST_3DMakeBox(ST_MakePoint({}, {}, {}), ST_MakePoint({} , {}, {})),
-                                    {}
-                                ),
+            (QueryType::Cube | QueryType::Items, Coords::Bbox(bbox)) => match bbox {
+                Bbox::Bbox2D(_) => format!(
+                    "ST_Intersects(geom, ST_Transform(ST_MakeEnvelope({}, {}), {}))",
+                    bbox, srid, storage_srid
+                ),
+                Bbox::Bbox3D(bbox) => format!(
+                    "ST_3DIntersects(
+                        geom,
+                        ST_Transform(
+                            ST_SetSRID(
+                                ST_3DMakeBox(ST_MakePoint({}, {}, {}), ST_MakePoint({} , {}, {})),
                                 {}
-                            )
-                        )",
-                        bbox[0], bbox[1], bbox[2], bbox[3], bbox[4], bbox[5], srid, storage_srid
-                    )
+                            ),
+                            {}
+                        )
+                    )",
+                    bbox[0], bbox[1], bbox[2], bbox[3], bbox[4], bbox[5], srid, storage_srid
+                ),
+            },
+            (QueryType::Corridor, Coords::Wkt(wkt)) => {
+                let width = to_meters(&query.corridor_width, &query.width_units)?;
+
+                let trajectory = format!(
+                    "ST_Transform(ST_GeomFromEWKT('SRID={};{}'), 4326)",
+                    srid, wkt
+                );
+
+                let mut predicate = format!(
+                    "ST_DWithin(ST_Transform(geom, 4326)::geography, {}::geography, {}, false)",
+                    trajectory,
+                    width / 2.0
+                );
+
+                if query.corridor_height.is_some() {
+                    let height = to_meters(&query.corridor_height, &query.height_units)?;
+                    predicate.push_str(&format!(
+                        " AND ST_ZMin(geom) <= ST_ZMax({0}) + {1} AND ST_ZMax(geom) >= ST_ZMin({0}) - {1}",
+                        trajectory,
+                        height / 2.0
+                    ));
                 }
+
+                predicate
+            }
+            (QueryType::Items, _) => "TRUE".to_string(),
+            (QueryType::Locations, _) => {
+                let location_id = query
+                    .location_id
+                    .as_ref()
+                    .context("Locations query requires a location id")?;
+                format!(
+                    r#"
+                    ST_Intersects(geom, (
+                        SELECT ST_Transform(geom, {})
+                        FROM meta.locations
+                        WHERE collection_id = '{}' AND id = '{}'
+                    ))
+                    "#,
+                    storage_srid,
+                    collection_id.replace('\'', "''"),
+                    location_id.replace('\'', "''")
+                )
             }
-            QueryType::Corridor | QueryType::Locations => unimplemented!(),
+            (query_type, _) => anyhow::bail!("Invalid coords for {query_type} query"),
         };
 
-        let properties = if let Some(parameters) = &query.parameter_name {
-            format!(
-                "{0} as properties",
-                parameters
-                    .split(',')
-                    .map(|s| format!(
-                        "('{{\"{0}\":' || (properties -> '{0}')::text || '}}')::jsonb",
-                        s
-                    ))
+        let mut conditions = vec![spatial_predicate];
+
+        // datetime
+        if let Some(datetime) = &query.datetime {
+            let (from, to) = match datetime {
+                Datetime::Datetime(_) => (
+                    format!("CAST('{datetime}' AS timestamptz)"),
+                    format!("CAST('{datetime}' AS timestamptz)"),
+                ),
+                Datetime::Interval { from, to } => (
+                    match from {
+                        IntervalDatetime::Datetime(_) => format!("CAST('{from}' AS timestamptz)"),
+                        IntervalDatetime::Open => "to_timestamp('-infinity')".to_owned(),
+                    },
+                    match to {
+                        IntervalDatetime::Datetime(_) => format!("CAST('{to}' AS timestamptz)"),
+                        IntervalDatetime::Open => "to_timestamp('infinity')".to_owned(),
+                    },
+                ),
+            };
+            conditions.push(format!(
+                "meta.try_timestamptz(properties->>'datetime') BETWEEN {from} AND {to}"
+            ));
+        }
+
+        // vertical levels
+        if let Some(levels) = &query.z {
+            let condition = match levels.values() {
+                Some(values) => values
+                    .iter()
+                    .map(|z| format!("(ST_ZMin(geom) <= {z} AND ST_ZMax(geom) >= {z})"))
                     .collect::<Vec<String>>()
-                    .join("||")
+                    .join(" OR "),
+                None => match levels {
+                    Levels::Interval(min, max) => {
+                        format!("ST_ZMax(geom) >= {min} AND ST_ZMin(geom) <= {max}")
+                    }
+                    _ => unreachable!("Levels other than intervals have values"),
+                },
+            };
+            conditions.push(format!("({condition})"));
+        }
+
+        let table = match &query.instance_id {
+            Some(instance_id) => instance_table(collection_id, instance_id),
+            None => collection_id.to_owned(),
+        };
+
+        let sql = if query.interval.is_some() || query.aggregate.is_some() {
+            let parameters: Vec<String> = match &query.parameter_name {
+                Some(parameters) => parameters.split(',').map(ToOwned::to_owned).collect(),
+                None => {
+                    let mut parameters: Vec<String> =
+                        c.parameter_names.keys().map(ToOwned::to_owned).collect();
+                    parameters.sort();
+                    parameters
+                }
+            };
+            if parameters.is_empty() {
+                anyhow::bail!("Aggregation requires `parameter-name`");
+            }
+
+            let aggregations = query
+                .aggregate
+                .to_owned()
+                .unwrap_or_else(|| vec![Aggregation::Mean]);
+
+            // `{parameter}` for a single aggregation, `{parameter}_{aggregation}` otherwise
+            let mut properties = Vec::new();
+            for parameter in &parameters {
+                let parameter = parameter.replace('\'', "''");
+                let value = format!(
+                    "CASE WHEN jsonb_typeof(properties->'{parameter}') = 'number' \
+                    THEN (properties->>'{parameter}')::float8 END"
+                );
+                for aggregation in &aggregations {
+                    let key = if aggregations.len() == 1 {
+                        parameter.to_owned()
+                    } else {
+                        format!("{parameter}_{aggregation}")
+                    };
+                    let function = match aggregation {
+                        Aggregation::Min => "min",
+                        Aggregation::Max => "max",
+                        Aggregation::Mean => "avg",
+                        Aggregation::Count => "count",
+                    };
+                    properties.push(format!("jsonb_build_object('{key}', {function}({value}))"));
+                }
+            }
+
+            let bucket = match &query.interval {
+                Some(interval) => {
+                    properties.insert(0, "jsonb_build_object('datetime', bucket)".to_string());
+                    // like `date_bin` (Postgres 14+) with origin `epoch`
+                    let seconds = format!(
+                        "extract(epoch FROM '{}'::interval)",
+                        interval.replace('\'', "''")
+                    );
+                    format!(
+                        "to_timestamp(floor(extract(epoch FROM meta.try_timestamptz(properties->>'datetime')) / {seconds}) * {seconds})"
+                    )
+                }
+                None => "NULL::timestamptz".to_string(),
+            };
+
+            format!(
+                r#"
+                SELECT
+                    {1} as properties,
+                    ST_AsGeoJSON(ST_Transform(geom, $1))::jsonb as geometry,
+                    '{0}' as collection
+                FROM (
+                    SELECT geom, properties, {2} AS bucket
+                    FROM items."{4}"
+                    WHERE {3}
+                ) t
+                GROUP BY geom, bucket
+                ORDER BY bucket
+                "#,
+                collection_id,
+                properties.join(" || "),
+                bucket,
+                conditions.join(" AND "),
+                table
             )
         } else {
-            "properties".to_string()
-        };
+            let properties = if let Some(parameters) = &query.parameter_name {
+                format!(
+                    "{0} as properties",
+                    parameters
+                        .split(',')
+                        .map(|parameter| {
+                            let parameter = parameter.replace('\'', "''");
+                            format!(
+                                "jsonb_build_object('{parameter}', properties -> '{parameter}')"
+                            )
+                        })
+                        .collect::<Vec<String>>()
+                        .join(" || ")
+                )
+            } else {
+                "properties".to_string()
+            };
 
-        let sql = format!(
-            r#"
-            SELECT
-                id,
-                {1},
-                ST_AsGeoJSON(ST_Transform(geom, $1))::jsonb as geometry,
-                links,
-                '{0}' as collection,
-                assets
-            FROM items."{0}"
-            WHERE {2}
-            "#,
-            collection_id, properties, spatial_predicate
-        );
+            format!(
+                r#"
+                SELECT
+                    id,
+                    {1},
+                    ST_AsGeoJSON(ST_Transform(geom, $1))::jsonb as geometry,
+                    links,
+                    '{0}' as collection,
+                    assets
+                FROM items."{3}"
+                WHERE {2}
+                "#,
+                collection_id,
+                properties,
+                conditions.join(" AND "),
+                table
+            )
+        };
 
         let number_matched = sqlx::query(&sql)
             .bind(srid)
@@ -146,4 +322,136 @@ impl EdrQuerier for Db {
 
         Ok(fc)
     }
+
+    async fn locations(&self, collection_id: &str) -> anyhow::Result<FeatureCollection> {
+        let features: Option<Json<Vec<Feature>>> = sqlx::query_scalar(&format!(
+            r#"
+            SELECT array_to_json(array_agg(row_to_json(t)))
+            FROM (
+                SELECT {LOCATION}
+                FROM meta.locations
+                WHERE collection_id = $1
+                ORDER BY id
+            ) t
+            "#
+        ))
+        .bind(collection_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let features = features.map(|f| f.0).unwrap_or_default();
+        let mut fc = FeatureCollection::new(features);
+        fc.number_matched = fc.number_returned;
+
+        Ok(fc)
+    }
+
+    async fn location(&self, collection_id: &str, id: &str) -> anyhow::Result<Option<Feature>> {
+        let feature: Option<Json<Feature>> = sqlx::query_scalar(&format!(
+            r#"
+            SELECT row_to_json(t)
+            FROM (
+                SELECT {LOCATION}
+                FROM meta.locations
+                WHERE collection_id = $1 AND id = $2
+            ) t
+            "#
+        ))
+        .bind(collection_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(feature.map(|f| f.0))
+    }
+
+    async fn put_location(
+        &self,
+        collection_id: &str,
+        location: &Feature,
+    ) -> anyhow::Result<String> {
+        let id = location.id.as_ref().context("Location requires an id")?;
+
+        let mut properties = location.properties.to_owned().unwrap_or_default();
+        let name = match properties.remove("name") {
+            Some(Value::String(name)) => Some(name),
+            _ => None,
+        };
+
+        let (id,): (String,) = sqlx::query_as(
+            r#"
+            INSERT INTO meta.locations (collection_id, id, name, properties, geom)
+            VALUES ($1, $2, $3, $4, ST_SetSRID(ST_GeomFromGeoJSON($5), 4326))
+            ON CONFLICT (collection_id, id) DO UPDATE
+            SET name = EXCLUDED.name, properties = EXCLUDED.properties, geom = EXCLUDED.geom
+            RETURNING id
+            "#,
+        )
+        .bind(collection_id)
+        .bind(id)
+        .bind(name)
+        .bind(Json(properties))
+        .bind(location.geometry.to_string())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn delete_location(&self, collection_id: &str, id: &str) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM meta.locations WHERE collection_id = $1 AND id = $2")
+            .bind(collection_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn parameters(&self, collection_id: &str) -> anyhow::Result<HashMap<String, DataType>> {
+        // sample of the items, time properties are not parameters
+        let types: Vec<(String, String, bool)> = sqlx::query_as(&format!(
+            r#"
+            SELECT key, jsonb_typeof(value), bool_and(value::text ~ '^-?[0-9]+$')
+            FROM (SELECT properties FROM items."{}" LIMIT 1000) t,
+                jsonb_each(properties)
+            WHERE jsonb_typeof(value) IN ('number', 'string')
+                AND key NOT IN ('datetime', 'start_datetime', 'end_datetime')
+            GROUP BY key, jsonb_typeof(value)
+            "#,
+            collection_id
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut parameters = HashMap::new();
+        for (key, r#type, integer) in types {
+            let data_type = match r#type.as_str() {
+                "number" if integer => DataType::Integer,
+                "number" => DataType::Float,
+                _ => DataType::String,
+            };
+            // mixed types are strings
+            parameters
+                .entry(key)
+                .and_modify(|t| *t = DataType::String)
+                .or_insert(data_type);
+        }
+
+        Ok(parameters)
+    }
+}
+
+/// Converts a distance with units (default `m`) to meters
+fn to_meters(value: &Option<String>, units: &Option<String>) -> anyhow::Result<f64> {
+    let value = value.as_deref().unwrap_or("0");
+    let units = units.as_deref().unwrap_or("m");
+
+    let mut ctx = rink_core::simple_context().unwrap();
+    let line = format!("{} {} -> m", value, units);
+
+    rink_core::one_line(&mut ctx, &line)
+        .ok()
+        .and_then(|s| s.split(' ').next().and_then(|s| s.parse::<f64>().ok()))
+        .with_context(|| format!("Failed to convert `{value} {units}` to meters"))
 }
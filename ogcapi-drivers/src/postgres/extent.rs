@@ -0,0 +1,204 @@
+use ogcapi_types::{
+    common::{Collection, Extent},
+    features::{self, Feature},
+};
+#[cfg(feature = "stac")]
+use serde_json::{Map, Value};
+use sqlx::{types::Json, PgConnection};
+
+use crate::CollectionMaintenance;
+
+use super::Db;
+
+/// Derives the extent of the items in `items."{table}"`, malformed
+/// datetimes are ignored
+pub(super) async fn items_extent(
+    conn: &mut PgConnection,
+    table: &str,
+) -> anyhow::Result<Option<Extent>> {
+    let extent: Option<Json<Extent>> = sqlx::query_scalar(&format!(
+        r#"
+        SELECT CASE WHEN e IS NULL THEN NULL ELSE jsonb_build_object(
+            'spatial', jsonb_build_object(
+                'bbox', jsonb_build_array(jsonb_build_array(
+                    ST_XMin(e), ST_YMin(e), ST_XMax(e), ST_YMax(e)
+                ))
+            ),
+            'temporal', jsonb_build_object(
+                'interval', jsonb_build_array(jsonb_build_array(t_min, t_max))
+            )
+        ) END
+        FROM (
+            SELECT
+                ST_Extent(ST_Transform(geom, 4326)) AS e,
+                min(meta.try_timestamptz(COALESCE(
+                    properties ->> 'start_datetime', properties ->> 'datetime'
+                ))) AS t_min,
+                max(meta.try_timestamptz(COALESCE(
+                    properties ->> 'end_datetime', properties ->> 'datetime'
+                ))) AS t_max
+            FROM items."{table}"
+        ) t
+        "#
+    ))
+    .fetch_one(conn)
+    .await?;
+
+    Ok(extent.map(|e| e.0))
+}
+
+/// Recomputes the value sets and ranges of summaries from the items in
+/// `items."{table}"`, other summaries are left as they are
+#[cfg(feature = "stac")]
+async fn items_summaries(
+    conn: &mut PgConnection,
+    table: &str,
+    summaries: &mut Map<String, Value>,
+) -> anyhow::Result<()> {
+    for (key, summary) in summaries.iter_mut() {
+        match summary {
+            Value::Array(values) => {
+                let distinct: Option<Json<Vec<Value>>> = sqlx::query_scalar(&format!(
+                    r#"
+                    SELECT jsonb_agg(DISTINCT v ORDER BY v)
+                    FROM items."{table}", jsonb_array_elements(
+                        CASE jsonb_typeof(properties -> $1)
+                            WHEN 'array' THEN properties -> $1
+                            ELSE jsonb_build_array(properties -> $1)
+                        END
+                    ) v
+                    WHERE properties ? $1 AND v <> 'null'::jsonb
+                    "#
+                ))
+                .bind(key)
+                .fetch_one(&mut *conn)
+                .await?;
+
+                *values = distinct.map(|d| d.0).unwrap_or_default();
+            }
+            Value::Object(range)
+                if range.contains_key("minimum") && range.contains_key("maximum") =>
+            {
+                let kind = match range["minimum"] {
+                    Value::Number(_) => "number",
+                    Value::String(_) => "string",
+                    _ => continue,
+                };
+
+                let (minimum, maximum): (Option<Json<Value>>, Option<Json<Value>>) =
+                    sqlx::query_as(&format!(
+                        r#"
+                        SELECT
+                            (
+                                SELECT properties -> $1 FROM items."{table}"
+                                WHERE jsonb_typeof(properties -> $1) = $2
+                                ORDER BY properties -> $1 LIMIT 1
+                            ),
+                            (
+                                SELECT properties -> $1 FROM items."{table}"
+                                WHERE jsonb_typeof(properties -> $1) = $2
+                                ORDER BY properties -> $1 DESC LIMIT 1
+                            )
+                        "#
+                    ))
+                    .bind(key)
+                    .bind(kind)
+                    .fetch_one(&mut *conn)
+                    .await?;
+
+                if let (Some(minimum), Some(maximum)) = (minimum, maximum) {
+                    range.insert("minimum".to_string(), minimum.0);
+                    range.insert("maximum".to_string(), maximum.0);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl CollectionMaintenance for Db {
+    async fn refresh_collection(&self, id: &str) -> anyhow::Result<Option<Collection>> {
+        let mut tx = self.pool.begin().await?;
+
+        let mut collection = match lock_collection(&mut tx, id).await? {
+            Some(collection) => collection,
+            None => return Ok(None),
+        };
+
+        collection.extent = items_extent(&mut tx, id).await?;
+
+        #[cfg(feature = "stac")]
+        items_summaries(&mut tx, id, &mut collection.summaries).await?;
+
+        update_collection(&mut tx, &collection).await?;
+
+        tx.commit().await?;
+
+        Ok(Some(collection))
+    }
+
+    /// Grows the extent and summaries incrementally, they are recomputed if a
+    /// removed item was on their boundary
+    async fn update_extent(
+        &self,
+        id: &str,
+        removed: &[Feature],
+        added: &[Feature],
+    ) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let mut collection = match lock_collection(&mut tx, id).await? {
+            Some(collection) => collection,
+            None => return Ok(()),
+        };
+
+        if removed
+            .iter()
+            .any(|item| features::on_boundary(&collection, item))
+        {
+            collection.extent = items_extent(&mut tx, id).await?;
+            #[cfg(feature = "stac")]
+            items_summaries(&mut tx, id, &mut collection.summaries).await?;
+        } else {
+            for item in added {
+                features::include_item(&mut collection, item);
+            }
+        }
+
+        update_collection(&mut tx, &collection).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+/// Reads a collection with items and locks it until the end of the
+/// transaction, which serializes concurrent updates of its extent
+async fn lock_collection(conn: &mut PgConnection, id: &str) -> anyhow::Result<Option<Collection>> {
+    let collection: Option<Json<Collection>> = sqlx::query_scalar(
+        r#"
+        SELECT collection FROM meta.collections
+        WHERE id = $1 AND to_regclass(format('items.%I', id)) IS NOT NULL
+        FOR UPDATE
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(collection.map(|c| c.0))
+}
+
+async fn update_collection(conn: &mut PgConnection, collection: &Collection) -> anyhow::Result<()> {
+    sqlx::query("UPDATE meta.collections SET collection = $2 WHERE id = $1")
+        .bind(&collection.id)
+        .bind(Json(collection))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
@@ -7,39 +7,66 @@ use crate::{CollectionTransactions, FeatureTransactions};
 
 use super::Db;
 
+/// Statement inserting the feature bound as `$1` into `items."{table}"`,
+/// returns its id
+pub(super) fn insert_feature(table: &str) -> String {
+    format!(
+        r#"
+        INSERT INTO items."{table}" (
+            id,
+            properties,
+            geom,
+            links,
+            stac_version,
+            stac_extensions,
+            assets,
+            bbox
+        ) VALUES (
+            COALESCE($1 ->> 'id', gen_random_uuid()::text),
+            $1 -> 'properties',
+            ST_GeomFromGeoJSON($1 -> 'geometry'),
+            $1 -> 'links',
+            $1 ->> 'stac_version',
+            ARRAY(SELECT jsonb_array_elements_text(COALESCE($1 -> 'stac_extensions', '[]'::jsonb))),
+            COALESCE($1 -> 'assets', '{{}}'::jsonb),
+            $1 -> 'bbox'
+        )
+        RETURNING id
+        "#
+    )
+}
+
 #[async_trait::async_trait]
 impl FeatureTransactions for Db {
     async fn create_feature(&self, feature: &Feature) -> anyhow::Result<String> {
         let collection = feature.collection.as_ref().unwrap();
 
-        let id: (String,) = sqlx::query_as(&format!(
-            r#"
-            INSERT INTO items."{0}" (
-                id,
-                properties,
-                geom,
-                links,
-                assets,
-                bbox
-            ) VALUES (
-                COALESCE($1 ->> 'id', gen_random_uuid()::text),
-                $1 -> 'properties',
-                ST_GeomFromGeoJSON($1 -> 'geometry'),
-                $1 -> 'links',
-                COALESCE($1 -> 'assets', '{{}}'::jsonb),
-                $1 -> 'bbox'
-            )
-            RETURNING id
-            "#,
-            &collection
-        ))
-        .bind(serde_json::to_value(&feature)?)
-        .fetch_one(&self.pool)
-        .await?;
+        let id: (String,) = sqlx::query_as(&insert_feature(collection))
+            .bind(serde_json::to_value(feature)?)
+            .fetch_one(&self.pool)
+            .await?;
 
         Ok(id.0)
     }
 
+    async fn create_features(&self, features: &[Feature]) -> anyhow::Result<Vec<String>> {
+        let mut tx = self.pool.begin().await?;
+
+        let mut ids = Vec::new();
+        for feature in features {
+            let (id,): (String,) =
+                sqlx::query_as(&insert_feature(feature.collection.as_ref().unwrap()))
+                    .bind(serde_json::to_value(feature)?)
+                    .fetch_one(&mut tx)
+                    .await?;
+            ids.push(id);
+        }
+
+        tx.commit().await?;
+
+        Ok(ids)
+    }
+
     async fn read_feature(
         &self,
         collection: &str,
@@ -56,6 +83,8 @@ impl FeatureTransactions for Db {
                     properties,
                     ST_AsGeoJSON(ST_Transform(geom, $2::int))::jsonb as geometry,
                     links,
+                    stac_version,
+                    stac_extensions,
                     assets,
                     bbox
                 FROM items."{0}"
@@ -80,6 +109,8 @@ impl FeatureTransactions for Db {
                 properties = $1 -> 'properties',
                 geom = ST_GeomFromGeoJSON($1 -> 'geometry'),
                 links = $1 -> 'links',
+                stac_version = $1 ->> 'stac_version',
+                stac_extensions = ARRAY(SELECT jsonb_array_elements_text(COALESCE($1 -> 'stac_extensions', '[]'::jsonb))),
                 assets = COALESCE($1 -> 'assets', '{{}}'::jsonb),
                 bbox = $1 -> 'bbox'
             WHERE id = $1 ->> 'id'
@@ -230,10 +261,13 @@ impl FeatureTransactions for Db {
                     properties,
                     ST_AsGeoJSON(ST_Transform(geom, $1))::jsonb as geometry,
                     links,
+                    stac_version,
+                    stac_extensions,
                     assets,
                     bbox
                 FROM items."{collection}"
                 WHERE {conditions}
+                ORDER BY id
                 LIMIT {}
                 OFFSET {}
             ) t
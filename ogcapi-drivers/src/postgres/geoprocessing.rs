@@ -0,0 +1,197 @@
+use anyhow::Context;
+use ogcapi_types::features::{Feature, FeatureCollection};
+use sqlx::types::Json;
+
+use crate::{CollectionTransactions, Geoprocessing, Operation, Source};
+
+use super::Db;
+
+impl Db {
+    async fn storage_srid(&self, collection: &str) -> anyhow::Result<i32> {
+        let c = self
+            .read_collection(collection)
+            .await?
+            .with_context(|| format!("Collection `{collection}` does not exist"))?;
+
+        Ok(c.storage_crs.unwrap_or_default().as_srid())
+    }
+
+    /// Builds a query selecting `id`, `properties` and `geom` of the processed
+    /// items, returns the query with the values of its parameters
+    async fn select(
+        &self,
+        source: &Source,
+        operation: &Operation,
+    ) -> anyhow::Result<(String, Vec<String>)> {
+        let mut params = Vec::new();
+
+        let (source, srid) = match source {
+            Source::Collection(collection) => (
+                format!(
+                    "SELECT id, properties, geom FROM items.{}",
+                    quote(collection)
+                ),
+                self.storage_srid(collection).await?,
+            ),
+            Source::Features(fc) => {
+                params.push(serde_json::to_string(&fc.features)?);
+                (
+                    r#"
+                    SELECT
+                        COALESCE(f ->> 'id', (row_number() OVER ())::text) AS id,
+                        f -> 'properties' AS properties,
+                        ST_SetSRID(ST_GeomFromGeoJSON(f -> 'geometry'), 4326) AS geom
+                    FROM jsonb_array_elements($1::jsonb) f
+                    "#
+                    .to_string(),
+                    4326,
+                )
+            }
+        };
+
+        let sql = match operation {
+            Operation::Buffer { distance } => format!(
+                r#"
+                SELECT
+                    id,
+                    properties,
+                    ST_Transform(ST_Buffer(ST_Transform(geom, 4326)::geography, {distance})::geometry, {srid}) AS geom
+                FROM source
+                "#
+            ),
+            Operation::Clip { geometry } => {
+                params.push(geometry.to_string());
+                format!(
+                    r#"
+                    SELECT id, properties, ST_Intersection(i.geom, c.geom) AS geom
+                    FROM source i, (
+                        SELECT ST_Transform(ST_SetSRID(ST_GeomFromGeoJSON(${}::text), 4326), {srid}) AS geom
+                    ) c
+                    WHERE ST_Intersects(i.geom, c.geom)
+                    "#,
+                    params.len()
+                )
+            }
+            Operation::Dissolve { property } => match property {
+                Some(property) => {
+                    params.push(property.to_owned());
+                    format!(
+                        r#"
+                        SELECT
+                            COALESCE(value #>> '{{}}', 'null') AS id,
+                            jsonb_build_object(${0}::text, value) AS properties,
+                            ST_Union(geom) AS geom
+                        FROM (
+                            SELECT properties -> ${0}::text AS value, geom
+                            FROM source
+                        ) t
+                        GROUP BY value
+                        "#,
+                        params.len()
+                    )
+                }
+                None => r#"
+                    SELECT '0' AS id, '{}'::jsonb AS properties, ST_Union(geom) AS geom
+                    FROM source
+                    "#
+                .to_string(),
+            },
+            Operation::SpatialJoin { collection: other } => format!(
+                r#"
+                SELECT
+                    a.id || '_' || b.id AS id,
+                    COALESCE(a.properties, '{{}}'::jsonb) || COALESCE(b.properties, '{{}}'::jsonb) AS properties,
+                    a.geom
+                FROM source a
+                JOIN items.{} b ON ST_Intersects(a.geom, ST_Transform(b.geom, {srid}))
+                "#,
+                quote(other)
+            ),
+            Operation::Centroid => {
+                "SELECT id, properties, ST_Centroid(geom) AS geom FROM source".to_string()
+            }
+            Operation::Reproject { crs } => format!(
+                r#"SELECT id, properties, ST_Transform(geom, {}) AS geom FROM source"#,
+                crs.as_srid()
+            ),
+            Operation::Simplify { tolerance } => format!(
+                r#"
+                SELECT id, properties, ST_SimplifyPreserveTopology(geom, {tolerance}) AS geom
+                FROM source
+                "#
+            ),
+        };
+
+        Ok((format!("WITH source AS ({source}) {sql}"), params))
+    }
+}
+
+/// Quotes an SQL identifier
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+#[async_trait::async_trait]
+impl Geoprocessing for Db {
+    async fn process(
+        &self,
+        source: &Source,
+        operation: &Operation,
+    ) -> anyhow::Result<FeatureCollection> {
+        let (select, params) = self.select(source, operation).await?;
+        let srid = match operation {
+            Operation::Reproject { crs } => crs.as_srid(),
+            _ => 4326,
+        };
+
+        let sql = format!(
+            r#"
+            SELECT array_to_json(array_agg(row_to_json(t)))
+            FROM (
+                SELECT
+                    id,
+                    properties,
+                    ST_AsGeoJSON(ST_Transform(geom, {srid}))::jsonb as geometry
+                FROM ( {select} ) p
+            ) t
+            "#
+        );
+        let mut query = sqlx::query_scalar(&sql);
+        for param in &params {
+            query = query.bind(param);
+        }
+        let features: Option<Json<Vec<Feature>>> = query.fetch_one(&self.pool).await?;
+
+        let features = features.map(|f| f.0).unwrap_or_default();
+        let mut fc = FeatureCollection::new(features);
+        fc.number_matched = fc.number_returned;
+
+        Ok(fc)
+    }
+
+    async fn process_into(
+        &self,
+        source: &Source,
+        operation: &Operation,
+        target: &str,
+    ) -> anyhow::Result<u64> {
+        let (select, params) = self.select(source, operation).await?;
+        let target_srid = self.storage_srid(target).await?;
+
+        let sql = format!(
+            r#"
+            INSERT INTO items.{} (id, properties, geom)
+            SELECT id, properties, ST_Transform(geom, {target_srid})
+            FROM ( {select} ) p
+            "#,
+            quote(target)
+        );
+        let mut query = sqlx::query(&sql);
+        for param in &params {
+            query = query.bind(param);
+        }
+        let rows = query.execute(&self.pool).await?.rows_affected();
+
+        Ok(rows)
+    }
+}
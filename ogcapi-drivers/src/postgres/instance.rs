@@ -0,0 +1,149 @@
+use ogcapi_types::{common::Collection, features::Feature};
+use sqlx::types::Json;
+
+use crate::InstanceTransactions;
+
+use super::{extent::items_extent, feature::insert_feature, Db};
+
+/// Name of the items table of a collection instance
+pub(super) fn instance_table(collection_id: &str, instance_id: &str) -> String {
+    format!("{collection_id}@{instance_id}")
+}
+
+/// Maximum length of Postgres identifiers, longer table names are truncated
+const MAX_IDENTIFIER_LENGTH: usize = 63;
+
+/// Whether an instance id is safe to use in the name of its items table
+pub fn is_valid_instance_id(collection_id: &str, instance_id: &str) -> bool {
+    !instance_id.is_empty()
+        && instance_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && instance_table(collection_id, instance_id).len() <= MAX_IDENTIFIER_LENGTH
+}
+
+#[async_trait::async_trait]
+impl InstanceTransactions for Db {
+    async fn create_instance(
+        &self,
+        collection_id: &str,
+        instance: &Collection,
+    ) -> anyhow::Result<String> {
+        if !is_valid_instance_id(collection_id, &instance.id) {
+            anyhow::bail!("Invalid instance id `{}`", instance.id);
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(&format!(
+            r#"CREATE TABLE items."{}" (LIKE items."{}" INCLUDING ALL)"#,
+            instance_table(collection_id, &instance.id),
+            collection_id
+        ))
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO meta.instances ( collection_id, id, instance ) VALUES ( $1, $2, $3 )",
+        )
+        .bind(collection_id)
+        .bind(&instance.id)
+        .bind(Json(instance))
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(instance.id.to_owned())
+    }
+
+    async fn read_instance(
+        &self,
+        collection_id: &str,
+        id: &str,
+    ) -> anyhow::Result<Option<Collection>> {
+        let instance: Option<Json<Collection>> = sqlx::query_scalar(
+            "SELECT instance FROM meta.instances WHERE collection_id = $1 AND id = $2",
+        )
+        .bind(collection_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match instance {
+            Some(Json(mut instance)) => {
+                let mut conn = self.pool.acquire().await?;
+                if let Some(extent) =
+                    items_extent(&mut conn, &instance_table(collection_id, id)).await?
+                {
+                    instance.extent = Some(extent);
+                }
+                Ok(Some(instance))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn delete_instance(&self, collection_id: &str, id: &str) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(&format!(
+            r#"DROP TABLE IF EXISTS items."{}""#,
+            instance_table(collection_id, id)
+        ))
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query("DELETE FROM meta.instances WHERE collection_id = $1 AND id = $2")
+            .bind(collection_id)
+            .bind(id)
+            .execute(&mut tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn create_instance_items(
+        &self,
+        collection_id: &str,
+        id: &str,
+        features: &[Feature],
+    ) -> anyhow::Result<Vec<String>> {
+        let sql = insert_feature(&instance_table(collection_id, id));
+
+        let mut tx = self.pool.begin().await?;
+
+        let mut ids = Vec::new();
+        for feature in features {
+            let (id,): (String,) = sqlx::query_as(&sql)
+                .bind(serde_json::to_value(feature)?)
+                .fetch_one(&mut tx)
+                .await?;
+            ids.push(id);
+        }
+
+        tx.commit().await?;
+
+        Ok(ids)
+    }
+
+    async fn list_instances(&self, collection_id: &str) -> anyhow::Result<Vec<Collection>> {
+        let ids: Vec<String> = sqlx::query_scalar(
+            "SELECT id FROM meta.instances WHERE collection_id = $1 ORDER BY id",
+        )
+        .bind(collection_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut instances = Vec::new();
+        for id in ids {
+            if let Some(instance) = self.read_instance(collection_id, &id).await? {
+                instances.push(instance);
+            }
+        }
+
+        Ok(instances)
+    }
+}
@@ -1,47 +1,216 @@
-use ogcapi_types::processes::{Results, StatusInfo};
+use std::time::Duration;
 
-use crate::JobHandler;
+use ogcapi_types::{
+    common::{Datetime, IntervalDatetime},
+    processes::{JobQuery, StatusInfo},
+};
+use serde_json::Value;
+use sqlx::types::Json;
+
+use crate::{JobHandler, JobResults};
 
 use super::Db;
 
+const STATUS_INFO: &str = r#"
+    json_build_object(
+        'processID', process_id,
+        'jobID', job_id,
+        'status', status,
+        'message', message,
+        'created', created,
+        'finished', finished,
+        'updated', updated,
+        'progress', progress,
+        'links', COALESCE(links, '[]'::jsonb)
+    )
+"#;
+
 #[async_trait::async_trait]
 impl JobHandler for Db {
-    async fn status(&self, id: &str) -> anyhow::Result<Option<StatusInfo>> {
-        let status = sqlx::query_scalar!(
+    async fn register(&self, job: &StatusInfo) -> anyhow::Result<String> {
+        let (id,): (String,) = sqlx::query_as(
             r#"
-            SELECT row_to_json(jobs) as "status_info!: sqlx::types::Json<StatusInfo>" 
-            FROM meta.jobs WHERE job_id = $1
+            INSERT INTO meta.jobs (
+                job_id, process_id, status, message, created, finished, updated, progress, links
+            )
+            VALUES ($1, $2, $3, $4, $5::timestamptz, $6::timestamptz, $7::timestamptz, $8, $9)
+            RETURNING job_id
+            "#,
+        )
+        .bind(&job.job_id)
+        .bind(&job.process_id)
+        .bind(Json(&job.status))
+        .bind(&job.message)
+        .bind(job.created.map(|d| d.to_rfc3339()))
+        .bind(job.finished.map(|d| d.to_rfc3339()))
+        .bind(job.updated.map(|d| d.to_rfc3339()))
+        .bind(job.progress.map(i16::from))
+        .bind(Json(&job.links))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn update(&self, job: &StatusInfo) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE meta.jobs
+            SET status = $2, message = $3, finished = $4::timestamptz, updated = $5::timestamptz,
+                progress = $6, links = $7
+            WHERE job_id = $1
             "#,
-            id
         )
+        .bind(&job.job_id)
+        .bind(Json(&job.status))
+        .bind(&job.message)
+        .bind(job.finished.map(|d| d.to_rfc3339()))
+        .bind(job.updated.map(|d| d.to_rfc3339()))
+        .bind(job.progress.map(i16::from))
+        .bind(Json(&job.links))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn status(&self, id: &str) -> anyhow::Result<Option<StatusInfo>> {
+        let status: Option<Json<StatusInfo>> = sqlx::query_scalar(&format!(
+            "SELECT {STATUS_INFO} FROM meta.jobs WHERE job_id = $1"
+        ))
+        .bind(id)
         .fetch_optional(&self.pool)
         .await?;
 
         Ok(status.map(|s| s.0))
     }
 
+    async fn list_jobs(&self, query: &JobQuery) -> anyhow::Result<Vec<StatusInfo>> {
+        // open intervals end `now`
+        let (from, to) = match &query.datetime {
+            Some(Datetime::Datetime(datetime)) => {
+                (Some(datetime.to_rfc3339()), Some(datetime.to_rfc3339()))
+            }
+            Some(Datetime::Interval { from, to }) => (
+                match from {
+                    IntervalDatetime::Datetime(from) => Some(from.to_rfc3339()),
+                    IntervalDatetime::Open => None,
+                },
+                match to {
+                    IntervalDatetime::Datetime(to) => Some(to.to_rfc3339()),
+                    IntervalDatetime::Open => Some("now".to_string()),
+                },
+            ),
+            None => (None, None),
+        };
+
+        let jobs: Option<Json<Vec<StatusInfo>>> = sqlx::query_scalar(&format!(
+            r#"
+            SELECT array_to_json(array_agg(status_info))
+            FROM (
+                SELECT {STATUS_INFO} AS status_info
+                FROM meta.jobs
+                WHERE ($1::text[] IS NULL OR process_id = ANY($1))
+                    AND ($2::text[] IS NULL OR status #>> '{{}}' = ANY($2))
+                    AND ($3::text IS NULL OR created >= $3::timestamptz)
+                    AND ($4::text IS NULL OR created <= $4::timestamptz)
+                ORDER BY created DESC, job_id
+                LIMIT $5
+                OFFSET $6
+            ) t
+            "#
+        ))
+        .bind(query.process_id.as_ref().map(|ids| &ids.0))
+        .bind(query.status.as_ref().map(|status| &status.0))
+        .bind(from)
+        .bind(to)
+        .bind(query.limit.map(|limit| limit as i64))
+        .bind(query.offset.unwrap_or(0) as i64)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(jobs.map(|j| j.0).unwrap_or_default())
+    }
+
     async fn delete(&self, id: &str) -> anyhow::Result<()> {
-        let _ = sqlx::query!("DELETE FROM meta.jobs WHERE job_id = $1", id)
+        sqlx::query("DELETE FROM meta.jobs WHERE job_id = $1")
+            .bind(id)
             .execute(&self.pool)
             .await?;
 
-        // TODO: cancel execution
-
         Ok(())
     }
 
-    async fn results(&self, id: &str) -> anyhow::Result<Option<Results>> {
-        let results = sqlx::query_scalar!(
+    async fn store_results(&self, id: &str, results: &JobResults) -> anyhow::Result<()> {
+        let (value, media_type, artifact) = match results {
+            JobResults::Inline { value, media_type } => (Some(value), media_type, None),
+            JobResults::Artifact { key, media_type } => (None, media_type, Some(key)),
+        };
+
+        sqlx::query(
             r#"
-            SELECT results as "results!: sqlx::types::Json<Results>"
-            FROM meta.jobs
+            UPDATE meta.jobs
+            SET results = $2, results_media_type = $3, results_artifact = $4
             WHERE job_id = $1
             "#,
-            id
         )
-        .fetch_optional(&self.pool)
+        .bind(id)
+        .bind(value.map(Json))
+        .bind(media_type)
+        .bind(artifact)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn results(&self, id: &str) -> anyhow::Result<Option<JobResults>> {
+        let results: Option<(Option<Json<Value>>, Option<String>, Option<String>)> =
+            sqlx::query_as(
+                r#"
+                SELECT results, results_media_type, results_artifact
+                FROM meta.jobs
+                WHERE job_id = $1
+                "#,
+            )
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(results.and_then(|r| to_results(r.0, r.1, r.2)))
+    }
+
+    async fn purge(&self, retention: Duration) -> anyhow::Result<Vec<JobResults>> {
+        let deleted: Vec<(Option<Json<Value>>, Option<String>, Option<String>)> = sqlx::query_as(
+            r#"
+            DELETE FROM meta.jobs
+            WHERE created < NOW() - make_interval(secs => $1)
+                AND status #>> '{}' NOT IN ('accepted', 'running')
+            RETURNING results, results_media_type, results_artifact
+            "#,
+        )
+        .bind(retention.as_secs_f64())
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(results.map(|r| r.0))
+        Ok(deleted
+            .into_iter()
+            .filter_map(|r| to_results(r.0, r.1, r.2))
+            .collect())
+    }
+}
+
+fn to_results(
+    value: Option<Json<Value>>,
+    media_type: Option<String>,
+    artifact: Option<String>,
+) -> Option<JobResults> {
+    match (value, artifact) {
+        (_, Some(key)) => Some(JobResults::Artifact { key, media_type }),
+        (Some(value), None) => Some(JobResults::Inline {
+            value: value.0,
+            media_type,
+        }),
+        (None, None) => None,
     }
 }
@@ -1,12 +1,21 @@
+#[cfg(feature = "stac")]
+mod catalog;
 mod collection;
+#[cfg(feature = "stac")]
+mod cql2;
 mod edr;
+mod extent;
 mod feature;
+mod geoprocessing;
+mod instance;
 mod job;
 #[cfg(feature = "stac")]
 mod stac;
 mod style;
 mod tile;
 
+pub use instance::is_valid_instance_id;
+
 use sqlx::{
     migrate::MigrateDatabase,
     postgres::{PgConnectOptions, PgPool, PgPoolOptions},
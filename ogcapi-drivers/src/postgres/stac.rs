@@ -1,175 +1,346 @@
+use std::collections::HashMap;
+
 use ogcapi_types::{
     common::{Bbox, Datetime, IntervalDatetime},
-    features::{Feature, FeatureCollection},
-    stac::SearchParams,
+    features::{Feature, FeatureCollection, Queryables},
+    stac::{
+        Aggregation, AggregationCollection, AggregationParams, Bucket, SearchParams, AGGREGATIONS,
+    },
 };
+use serde_json::{json, Value};
+use sqlx::PgConnection;
 
 use crate::StacSeach;
 
-use super::Db;
+use super::{cql2::to_sql, Db};
+
+/// Format of datetime aggregations
+const DATETIME_FORMAT: &str = r#"YYYY-MM-DD"T"HH24:MI:SS.MS"Z""#;
 
 #[async_trait::async_trait]
 impl StacSeach for Db {
     async fn search(&self, query: &SearchParams) -> anyhow::Result<FeatureCollection> {
         let mut tx = self.pool.begin().await?;
 
-        // WITH
-        let mut collection_ids = sqlx::query_scalar!(
+        let collection_ids = collection_ids(&mut tx, query).await?;
+
+        let conditions = conditions(query)?;
+
+        // COUNT
+        let number_matched: i64 = sqlx::query_scalar(&format!(
             r#"
-            SELECT id FROM meta.collections 
-            WHERE collection ->> 'type' = 'Collection'
-            "#
-        )
+            SELECT count(*) FROM meta.search
+            WHERE collection = ANY($1) AND {conditions}
+            "#,
+        ))
+        .bind(&collection_ids)
+        .fetch_one(&mut tx)
+        .await?;
+
+        // PAGE
+        let page: Vec<(String, String)> = sqlx::query_as(&format!(
+            r#"
+            SELECT collection, id FROM meta.search
+            WHERE collection = ANY($1) AND {conditions}
+            ORDER BY collection, id
+            LIMIT {}
+            OFFSET {}
+            "#,
+            query
+                .limit
+                .map_or_else(|| String::from("NULL"), |l| l.to_string()),
+            query.offset.unwrap_or(0)
+        ))
+        .bind(&collection_ids)
         .fetch_all(&mut tx)
         .await?;
 
-        if let Some(collections) = &query.collections {
-            collection_ids = collections
-                .0
+        // FETCH items of the page from their collections
+        let mut items: HashMap<(String, String), Feature> = HashMap::new();
+        let mut page_collections: Vec<&String> = page.iter().map(|(c, _)| c).collect();
+        page_collections.dedup();
+        for collection_id in page_collections {
+            let ids: Vec<String> = page
                 .iter()
-                .filter(|c| collection_ids.contains(c))
-                .map(|c| c.to_owned())
+                .filter(|(c, _)| c == collection_id)
+                .map(|(_, id)| id.to_owned())
                 .collect();
+
+            let features: Option<sqlx::types::Json<Vec<Feature>>> = sqlx::query_scalar(&format!(
+                r#"
+                    SELECT array_to_json(array_agg(row_to_json(t)))
+                    FROM (
+                        SELECT
+                            id,
+                            $1 as collection,
+                            properties,
+                            ST_AsGeoJSON(ST_Transform(geom, 4326))::jsonb as geometry,
+                            links,
+                            stac_version,
+                            stac_extensions,
+                            assets,
+                            bbox
+                        FROM items."{collection_id}"
+                        WHERE id = ANY($2)
+                    ) t
+                    "#
+            ))
+            .bind(collection_id)
+            .bind(&ids)
+            .fetch_one(&mut tx)
+            .await?;
+
+            for feature in features.map(|f| f.0).unwrap_or_default() {
+                if let Some(id) = &feature.id {
+                    items.insert((collection_id.to_owned(), id.to_owned()), feature);
+                }
+            }
         }
 
-        let union_all_items = collection_ids
-            .iter()
-            .map(|collection_id| {
-                format!(
-                    r#"
-                    SELECT *, '{0}' as collection
-                    FROM items."{0}"
-                    "#,
-                    collection_id
-                )
-            })
-            .collect::<Vec<String>>()
-            .join(" UNION ALL ");
-
-        // WHERE
-        let mut where_conditions = vec!["TRUE".to_string()];
-
-        // bbox
-        if let Some(bbox) = query.bbox.as_ref() {
-            let envelope = match bbox {
-                Bbox::Bbox2D(bbox) => format!(
-                    "ST_MakeEnvelope({}, {}, {}, {}, 4326)",
-                    bbox[0], bbox[1], bbox[2], bbox[3]
-                ),
-                Bbox::Bbox3D(bbox) => format!(
-                    "ST_MakeEnvelope({}, {}, {}, {}, 4326)",
-                    bbox[0], bbox[1], bbox[3], bbox[4]
-                ),
+        tx.commit().await?;
+
+        let features = page
+            .into_iter()
+            .filter_map(|key| items.remove(&key))
+            .collect();
+        let mut fc = FeatureCollection::new(features);
+        fc.number_matched = Some(number_matched as u64);
+
+        Ok(fc)
+    }
+
+    async fn aggregate(
+        &self,
+        query: &SearchParams,
+        params: &AggregationParams,
+    ) -> anyhow::Result<AggregationCollection> {
+        let mut conn = self.pool.acquire().await?;
+
+        let collection_ids = collection_ids(&mut conn, query).await?;
+        let conditions = conditions(query)?;
+
+        let names = params.names().map_err(anyhow::Error::msg)?;
+
+        let mut aggregations = Vec::new();
+        for (name, data_type) in AGGREGATIONS {
+            if !names.contains(&name) {
+                continue;
+            }
+
+            let mut aggregation = Aggregation {
+                name: name.to_string(),
+                data_type: data_type.to_string(),
+                value: None,
+                buckets: Vec::new(),
             };
-            where_conditions.push(format!("geom && {}", envelope));
-        }
 
-        // datetime
-        if let Some(datetime) = query.datetime.as_ref() {
-            let (from, to) = match datetime {
-                Datetime::Datetime(_) => (
-                    format!("CAST('{datetime}' AS timestamptz)"),
-                    format!("CAST('{datetime}' AS timestamptz)"),
-                ),
-                Datetime::Interval { from, to } => {
-                    let from = match from {
-                        IntervalDatetime::Datetime(_) => {
-                            format!("CAST('{from}' AS timestamptz)")
-                        }
-                        IntervalDatetime::Open => "to_timestamp('-infinity')".to_owned(),
-                    };
-                    let to = match to {
-                        IntervalDatetime::Datetime(_) => {
-                            format!("CAST('{to}' AS timestamptz)")
-                        }
-                        IntervalDatetime::Open => "NOW()".to_owned(),
-                    };
-                    (from, to)
-                }
+            // single valued aggregations
+            let value = match name {
+                "total_count" => Some("count(*)".to_string()),
+                "datetime_min" => Some(format!(
+                    "to_char(min(start_datetime) AT TIME ZONE 'UTC', '{DATETIME_FORMAT}')"
+                )),
+                "datetime_max" => Some(format!(
+                    "to_char(max(end_datetime) AT TIME ZONE 'UTC', '{DATETIME_FORMAT}')"
+                )),
+                _ => None,
             };
+            if let Some(value) = value {
+                let value: sqlx::types::Json<Value> = sqlx::query_scalar(&format!(
+                    r#"
+                    SELECT to_jsonb({value}) FROM meta.search
+                    WHERE collection = ANY($1) AND {conditions}
+                    "#,
+                ))
+                .bind(&collection_ids)
+                .fetch_one(&mut conn)
+                .await?;
 
-            where_conditions.push(format!(
-                r#"
-                (
-                    CASE
-                        WHEN (properties->'datetime') IS NOT NULL THEN (
-                            CAST(properties->>'datetime' AS timestamptz)
-                            BETWEEN {from} AND {to}
-                        )
-                        WHEN (
-                            (properties->'datetime') IS NULL
-                            AND (properties->'start_datetime') IS NOT NULL
-                            AND (properties->'end_datetime') IS NOT NULL
-                        ) THEN (
-                            ({from}, {to}) OVERLAPS (
-                                CAST(properties->>'start_datetime' AS timestamptz),
-                                CAST(properties->>'end_datetime' AS timestamptz)
-                            )
-                        )
-                        ELSE TRUE
-                    END
-                )
-                "#
-            ));
-        }
+                aggregation.value = Some(value.0);
+            }
 
-        // ids
-        if let Some(ids) = query.ids.as_ref() {
-            where_conditions.push(format!("id IN ('{}')", ids.0.join("','")))
-        }
+            // frequency distributions
+            let key = match name {
+                "collection_frequency" => Some(("collection".to_string(), "string")),
+                "datetime_frequency" => Some((
+                    format!(
+                        "to_char(date_trunc('{}', start_datetime AT TIME ZONE 'UTC'), '{DATETIME_FORMAT}')",
+                        params.datetime_frequency_interval.unwrap_or_default()
+                    ),
+                    "datetime",
+                )),
+                "geohash_frequency" => Some((
+                    format!(
+                        "ST_GeoHash(ST_Centroid(geom), {})",
+                        params.geohash_precision().map_err(anyhow::Error::msg)?
+                    ),
+                    "string",
+                )),
+                _ => None,
+            };
+            if let Some((key, data_type)) = key {
+                let frequencies: Vec<(Option<String>, i64)> = sqlx::query_as(&format!(
+                    r#"
+                    SELECT {key}, count(*) FROM meta.search
+                    WHERE collection = ANY($1) AND {conditions}
+                    GROUP BY 1
+                    ORDER BY 1
+                    "#,
+                ))
+                .bind(&collection_ids)
+                .fetch_all(&mut conn)
+                .await?;
+
+                // items without a datetime are not bucketed
+                aggregation.buckets = frequencies
+                    .into_iter()
+                    .filter_map(|(key, frequency)| {
+                        key.map(|key| Bucket {
+                            key,
+                            data_type: data_type.to_string(),
+                            frequency: frequency as u64,
+                        })
+                    })
+                    .collect();
+            }
 
-        // intersects
-        if let Some(intersects) = query.intersects.as_ref() {
-            where_conditions.push(format!("geom && ST_GeomFromGeoJSON('{}')", intersects));
+            aggregations.push(aggregation);
         }
 
-        let conditions = where_conditions.join(" AND ");
+        Ok(AggregationCollection::new(aggregations))
+    }
 
-        // COUNT
-        let number_matched: (i64,) = sqlx::query_as(&format!(
+    async fn queryables(&self, collection_id: &str) -> anyhow::Result<Queryables> {
+        // infer the json types of properties from a sample of items
+        let types: Vec<(String, String)> = sqlx::query_as(
             r#"
-            WITH items AS ({union_all_items})
-            SELECT count(*) FROM items
-            WHERE {conditions}
+            SELECT DISTINCT p.key, jsonb_typeof(p.value)
+            FROM (SELECT properties FROM meta.search WHERE collection = $1 LIMIT 1000) i,
+            jsonb_each(i.properties) p
+            WHERE jsonb_typeof(p.value) <> 'null'
+            ORDER BY p.key
             "#,
-        ))
-        .fetch_one(&mut tx)
+        )
+        .bind(collection_id)
+        .fetch_all(&self.pool)
         .await?;
 
-        // FETCH
-        let features: Option<sqlx::types::Json<Vec<Feature>>> = sqlx::query_scalar(&format!(
-            r#"
-            WITH items AS ({union_all_items})
-            SELECT array_to_json(array_agg(row_to_json(t)))
-            FROM (
-                SELECT
-                    id,
-                    collection,
-                    properties,
-                    ST_AsGeoJSON(ST_Transform(geom, 4326))::jsonb as geometry,
-                    links,
-                    assets,
-                    bbox
-                FROM items
-                WHERE {conditions}
-                LIMIT {}
-                OFFSET {}
-            ) t
-            "#,
-            query
-                .limit
-                .map_or_else(|| String::from("NULL"), |l| l.to_string()),
-            query.offset.unwrap_or(0)
-        ))
-        .fetch_one(&mut tx)
-        .await?;
+        let mut queryables = Queryables::default();
+        for (key, r#type) in types {
+            let schema = match r#type.as_str() {
+                "string" if key.ends_with("datetime") => {
+                    json!({ "type": "string", "format": "date-time" })
+                }
+                t => json!({ "type": t }),
+            };
+            match queryables.properties.get_mut(&key) {
+                // properties with mixed types
+                Some(Value::Object(existing)) if existing.get("type") != schema.get("type") => {
+                    existing.remove("format");
+                    existing.remove("type");
+                }
+                Some(_) => {}
+                None => {
+                    queryables.properties.insert(key, schema);
+                }
+            }
+        }
 
-        tx.commit().await?;
+        Ok(queryables)
+    }
+}
 
-        let features = features.map(|f| f.0).unwrap_or_default();
-        let mut fc = FeatureCollection::new(features);
-        fc.number_matched = Some(number_matched.0 as u64);
+/// Ids of the collections to search
+async fn collection_ids(
+    conn: &mut PgConnection,
+    query: &SearchParams,
+) -> anyhow::Result<Vec<String>> {
+    let mut collection_ids: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT id FROM meta.collections
+        WHERE collection ->> 'type' = 'Collection'
+        "#,
+    )
+    .fetch_all(conn)
+    .await?;
 
-        Ok(fc)
+    if let Some(collections) = &query.collections {
+        collection_ids.retain(|c| collections.0.contains(c));
     }
+
+    Ok(collection_ids)
+}
+
+/// WHERE conditions on `meta.search` of the search parameters
+fn conditions(query: &SearchParams) -> anyhow::Result<String> {
+    let mut where_conditions = vec!["TRUE".to_string()];
+
+    // bbox
+    if let Some(bbox) = query.bbox.as_ref() {
+        let envelope = match bbox {
+            Bbox::Bbox2D(bbox) => format!(
+                "ST_MakeEnvelope({}, {}, {}, {}, 4326)",
+                bbox[0], bbox[1], bbox[2], bbox[3]
+            ),
+            Bbox::Bbox3D(bbox) => format!(
+                "ST_MakeEnvelope({}, {}, {}, {}, 4326)",
+                bbox[0], bbox[1], bbox[3], bbox[4]
+            ),
+        };
+        where_conditions.push(format!("geom && {}", envelope));
+    }
+
+    // datetime
+    if let Some(datetime) = query.datetime.as_ref() {
+        let (from, to) = match datetime {
+            Datetime::Datetime(_) => (
+                format!("CAST('{datetime}' AS timestamptz)"),
+                format!("CAST('{datetime}' AS timestamptz)"),
+            ),
+            Datetime::Interval { from, to } => {
+                let from = match from {
+                    IntervalDatetime::Datetime(_) => {
+                        format!("CAST('{from}' AS timestamptz)")
+                    }
+                    IntervalDatetime::Open => "to_timestamp('-infinity')".to_owned(),
+                };
+                let to = match to {
+                    IntervalDatetime::Datetime(_) => {
+                        format!("CAST('{to}' AS timestamptz)")
+                    }
+                    IntervalDatetime::Open => "NOW()".to_owned(),
+                };
+                (from, to)
+            }
+        };
+
+        // items without a datetime match any interval
+        where_conditions.push(format!(
+            "(start_datetime IS NULL OR (start_datetime <= {to} AND end_datetime >= {from}))"
+        ));
+    }
+
+    // ids
+    if let Some(ids) = query.ids.as_ref() {
+        where_conditions.push(format!(
+            "id IN ({})",
+            ids.0
+                .iter()
+                .map(|id| format!("'{}'", id.replace('\'', "''")))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+    }
+
+    // intersects
+    if let Some(intersects) = query.intersects.as_ref() {
+        where_conditions.push(format!("geom && ST_GeomFromGeoJSON('{}')", intersects));
+    }
+
+    // filter and query extension
+    if let Some(expr) = query.filter_expr().map_err(anyhow::Error::msg)? {
+        where_conditions.push(to_sql(&expr)?);
+    }
+
+    Ok(where_conditions.join(" AND "))
 }
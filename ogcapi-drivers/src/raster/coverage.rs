@@ -0,0 +1,386 @@
+use std::{
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use anyhow::Context;
+use chrono::{DateTime, SecondsFormat, Utc};
+use gdal::{raster::Buffer, Driver, Metadata};
+use serde_json::{json, Map, Value};
+
+use ogcapi_types::{
+    coverage::{Coverage, CoverageType, Domain, DomainType},
+    coverages::{
+        Axis, DomainSet, Field, GeneralGrid, GridLimits, IndexAxis, Interval, Query, RangeType,
+    },
+};
+
+use super::{
+    grid::{Band, Grid},
+    MAX_CELLS,
+};
+
+/// Accepted names of the axes
+const X: [&str; 6] = ["x", "Lon", "Long", "Longitude", "E", "Easting"];
+const Y: [&str; 5] = ["y", "Lat", "Latitude", "N", "Northing"];
+const TIME: [&str; 3] = ["time", "t", "date"];
+
+/// Counter for unique in memory file names
+static FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Subset of a grid selected by a coverage query
+struct Selection<'a> {
+    origin: (usize, usize),
+    window: (usize, usize),
+    size: (usize, usize),
+    bands: Vec<&'a Band>,
+}
+
+/// Labels of the x and y axes of a grid
+fn axis_labels(grid: &Grid) -> anyhow::Result<(&'static str, &'static str)> {
+    Ok(if grid.spatial_ref()?.is_geographic() {
+        ("Lon", "Lat")
+    } else {
+        ("E", "N")
+    })
+}
+
+fn srs_name(grid: &Grid) -> anyhow::Result<String> {
+    let srs = grid.spatial_ref()?;
+    let code = srs.auth_code().context("Grid has no EPSG code")?;
+    Ok(format!("http://www.opengis.net/def/crs/EPSG/0/{code}"))
+}
+
+pub(super) fn domain_set(path: &Path) -> anyhow::Result<DomainSet> {
+    let grid = Grid::open(path)?;
+    let (x, y) = axis_labels(&grid)?;
+    let uom = if x == "Lon" { "deg" } else { "m" };
+
+    let t = grid.geo_transform();
+    let (xmin, ymin, xmax, ymax) = grid.bounds();
+
+    let mut axis = vec![
+        Axis::regular(x, xmin, xmax, t[1], uom),
+        Axis::regular(y, ymin, ymax, t[5], uom),
+    ];
+    let mut limits = vec![
+        IndexAxis::new("i", 0, grid.size.0 as i64 - 1),
+        IndexAxis::new("j", 0, grid.size.1 as i64 - 1),
+    ];
+
+    let times = times(&grid.bands.iter().collect::<Vec<_>>());
+    if !times.is_empty() {
+        axis.push(Axis::irregular(
+            "time",
+            times.iter().map(|t| json!(format_time(t))).collect(),
+            "s",
+        ));
+        limits.push(IndexAxis::new("k", 0, times.len() as i64 - 1));
+    }
+
+    Ok(DomainSet::new(GeneralGrid::new(
+        srs_name(&grid)?,
+        axis,
+        GridLimits::new(limits),
+    )))
+}
+
+pub(super) fn range_type(path: &Path) -> anyhow::Result<RangeType> {
+    let grid = Grid::open(path)?;
+
+    let fields = parameters(&grid.bands.iter().collect::<Vec<_>>())
+        .into_iter()
+        .map(|p| {
+            let mut field = Field::new(p);
+            field.id = Some(p.to_owned());
+            field
+        })
+        .collect();
+
+    Ok(RangeType::new(fields))
+}
+
+/// Selects the window, size and bands of a grid for a query
+fn select<'a>(grid: &'a Grid, query: &Query) -> anyhow::Result<Selection<'a>> {
+    let (mut xmin, mut ymin, mut xmax, mut ymax) = grid.bounds();
+
+    // time interval, open if not bounded
+    let mut start: Option<DateTime<Utc>> = None;
+    let mut end: Option<DateTime<Utc>> = None;
+
+    if let Some(subset) = &query.subset {
+        if let Some(interval) = subset.axis(&X) {
+            let (low, high) = interval.bounds().map_err(anyhow::Error::msg)?;
+            xmin = xmin.max(low);
+            xmax = xmax.min(high);
+        }
+        if let Some(interval) = subset.axis(&Y) {
+            let (low, high) = interval.bounds().map_err(anyhow::Error::msg)?;
+            ymin = ymin.max(low);
+            ymax = ymax.min(high);
+        }
+        if let Some(interval) = subset.axis(&TIME) {
+            let parse = |t: &Option<String>| -> anyhow::Result<Option<DateTime<Utc>>> {
+                t.as_deref()
+                    .map(|t| Ok(DateTime::parse_from_rfc3339(t)?.with_timezone(&Utc)))
+                    .transpose()
+            };
+            (start, end) = match interval {
+                Interval::Slice(t) => {
+                    let t = parse(&Some(t.to_owned()))?;
+                    (t, t)
+                }
+                Interval::Trim(low, high) => (parse(low)?, parse(high)?),
+            };
+        }
+    }
+
+    let (origin, window) = grid
+        .window((xmin, ymin), (xmax, ymax))
+        .context("Subset is outside of the coverage")?;
+
+    // bands
+    let properties = query.properties.as_ref().map(|p| &p.0);
+    let bands: Vec<&Band> = grid
+        .bands
+        .iter()
+        .filter(|band| match properties {
+            Some(properties) => properties.contains(&band.parameter),
+            None => true,
+        })
+        .filter(|band| match band.datetime {
+            Some(t) => start.iter().all(|s| t >= *s) && end.iter().all(|e| t <= *e),
+            None => true,
+        })
+        .collect();
+
+    if bands.is_empty() {
+        anyhow::bail!("Subset selects no fields");
+    }
+
+    // scaling
+    let (x, y) = axis_labels(grid)?;
+    let mut size = (window.0 as f64, window.1 as f64);
+    if let Some(factor) = query.scale_factor {
+        size = (size.0 / factor, size.1 / factor);
+    }
+    if let Some(axes) = &query.scale_axes {
+        if let Some(factor) = axes.axis(&[&X[..], &[x]].concat()) {
+            size.0 = window.0 as f64 / factor;
+        }
+        if let Some(factor) = axes.axis(&[&Y[..], &[y]].concat()) {
+            size.1 = window.1 as f64 / factor;
+        }
+    }
+    if let Some(sizes) = &query.scale_size {
+        if let Some(width) = sizes.axis(&[&X[..], &[x]].concat()) {
+            size.0 = width;
+        }
+        if let Some(height) = sizes.axis(&[&Y[..], &[y]].concat()) {
+            size.1 = height;
+        }
+    }
+    if !(size.0.is_finite() && size.1.is_finite() && size.0 > 0.0 && size.1 > 0.0) {
+        anyhow::bail!("Scaling must result in a positive size");
+    }
+    let size = (
+        (size.0.round() as usize).max(1),
+        (size.1.round() as usize).max(1),
+    );
+
+    if !matches!(size.0.checked_mul(size.1), Some(cells) if cells <= MAX_CELLS) {
+        anyhow::bail!("Coverage subset exceeds {MAX_CELLS} cells");
+    }
+
+    Ok(Selection {
+        origin,
+        window,
+        size,
+        bands,
+    })
+}
+
+/// Geo transform of a selection
+fn geo_transform(grid: &Grid, selection: &Selection) -> [f64; 6] {
+    let t = grid.geo_transform();
+    let (col, row) = selection.origin;
+    [
+        t[0] + col as f64 * t[1],
+        t[1] * selection.window.0 as f64 / selection.size.0 as f64,
+        0.0,
+        t[3] + row as f64 * t[5],
+        0.0,
+        t[5] * selection.window.1 as f64 / selection.size.1 as f64,
+    ]
+}
+
+pub(super) fn coverage(path: &Path, query: &Query) -> anyhow::Result<Coverage> {
+    let grid = Grid::open(path)?;
+    let selection = select(&grid, query)?;
+    let (x, _) = axis_labels(&grid)?;
+
+    let t = geo_transform(&grid, &selection);
+    let (width, height) = selection.size;
+    let xs: Vec<f64> = (0..width).map(|i| t[0] + (i as f64 + 0.5) * t[1]).collect();
+    let ys: Vec<f64> = (0..height)
+        .map(|j| t[3] + (j as f64 + 0.5) * t[5])
+        .collect();
+
+    let times = times(&selection.bands);
+    let parameters = parameters(&selection.bands);
+
+    let mut axes = Map::new();
+    axes.insert("x".to_string(), json!({ "values": xs }));
+    axes.insert("y".to_string(), json!({ "values": ys }));
+
+    let mut referencing = vec![json!({
+        "coordinates": ["x", "y"],
+        "system": {
+            "type": if x == "Lon" { "GeographicCRS" } else { "ProjectedCRS" },
+            "id": srs_name(&grid)?
+        }
+    })];
+
+    let (axis_names, shape) = if times.is_empty() {
+        (vec!["y", "x"], vec![height, width])
+    } else {
+        axes.insert(
+            "t".to_string(),
+            json!({ "values": times.iter().map(format_time).collect::<Vec<_>>() }),
+        );
+        referencing.push(json!({
+            "coordinates": ["t"],
+            "system": { "type": "TemporalRS", "calendar": "Gregorian" }
+        }));
+        (vec!["t", "y", "x"], vec![times.len(), height, width])
+    };
+
+    let mut ranges = Map::new();
+    for parameter in &parameters {
+        let mut values = vec![Value::Null; shape.iter().product()];
+        let mut integer = true;
+
+        for band in selection
+            .bands
+            .iter()
+            .filter(|b| &b.parameter.as_str() == parameter)
+        {
+            integer &= band.integer;
+            let offset = band
+                .datetime
+                .and_then(|d| times.iter().position(|t| *t == d))
+                .unwrap_or(0)
+                * width
+                * height;
+            let data =
+                grid.read_scaled(band, selection.origin, selection.window, selection.size)?;
+            for (i, value) in data.into_iter().enumerate() {
+                if let Some(value) = value {
+                    values[offset + i] = json!(value);
+                }
+            }
+        }
+
+        ranges.insert(
+            parameter.to_string(),
+            json!({
+                "type": "NdArray",
+                "dataType": if integer { "integer" } else { "float" },
+                "axisNames": axis_names,
+                "shape": shape,
+                "values": values
+            }),
+        );
+    }
+
+    let parameters = parameters
+        .iter()
+        .map(|p| {
+            (
+                p.to_string(),
+                json!({
+                    "type": "Parameter",
+                    "observedProperty": { "label": { "en": p } }
+                }),
+            )
+        })
+        .collect();
+
+    Ok(Coverage {
+        r#type: CoverageType::Coverage,
+        domain: Domain {
+            r#type: "Domain".to_string(),
+            domain_type: Some(DomainType::Grid),
+            axes,
+            referencing,
+        },
+        parameters,
+        ranges,
+    })
+}
+
+pub(super) fn geotiff(path: &Path, query: &Query) -> anyhow::Result<Vec<u8>> {
+    let grid = Grid::open(path)?;
+    let selection = select(&grid, query)?;
+    let (width, height) = selection.size;
+
+    let file = format!(
+        "/vsimem/ogcapi-coverage-{}.tif",
+        FILES.fetch_add(1, Ordering::Relaxed)
+    );
+
+    {
+        let driver = Driver::get("GTiff")?;
+        let mut dataset = driver.create_with_band_type::<f64, _>(
+            &file,
+            width as isize,
+            height as isize,
+            selection.bands.len() as isize,
+        )?;
+        dataset.set_geo_transform(&geo_transform(&grid, &selection))?;
+        dataset.set_spatial_ref(&grid.spatial_ref()?)?;
+
+        for (i, band) in selection.bands.iter().enumerate() {
+            let data: Vec<f64> = grid
+                .read_scaled(band, selection.origin, selection.window, selection.size)?
+                .into_iter()
+                .map(|v| v.unwrap_or(f64::NAN))
+                .collect();
+
+            let mut target = dataset.rasterband(i as isize + 1)?;
+            target.set_no_data_value(f64::NAN)?;
+            target.set_description(&band.parameter)?;
+            if let Some(datetime) = band.datetime {
+                target.set_metadata_item("datetime", &format_time(&datetime), "")?;
+            }
+            target.write((0, 0), (width, height), &Buffer::new((width, height), data))?;
+        }
+    }
+
+    let bytes = gdal::vsi::get_vsi_mem_file_bytes_owned(&file)?;
+
+    Ok(bytes)
+}
+
+/// Distinct parameters of bands in order of appearance
+fn parameters<'a>(bands: &[&'a Band]) -> Vec<&'a str> {
+    let mut parameters: Vec<&str> = Vec::new();
+    for band in bands {
+        if !parameters.contains(&band.parameter.as_str()) {
+            parameters.push(&band.parameter);
+        }
+    }
+    parameters
+}
+
+/// Distinct sorted times of bands
+fn times(bands: &[&Band]) -> Vec<DateTime<Utc>> {
+    let mut times: Vec<DateTime<Utc>> = bands.iter().filter_map(|b| b.datetime).collect();
+    times.sort();
+    times.dedup();
+    times
+}
+
+fn format_time(datetime: &DateTime<Utc>) -> String {
+    datetime.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
@@ -0,0 +1,294 @@
+use std::path::Path;
+
+use anyhow::Context;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use gdal::{
+    raster::GDALDataType,
+    spatial_ref::{CoordTransform, SpatialRef},
+    Dataset, GeoTransform, Metadata,
+};
+use gdal_sys::OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER;
+
+/// Band of a grid holding the values of a parameter at a point in time
+#[derive(Debug, Clone)]
+pub(crate) struct Band {
+    /// GDAL band index (starting at 1)
+    pub index: isize,
+    pub parameter: String,
+    pub datetime: Option<DateTime<Utc>>,
+    pub no_data: Option<f64>,
+    pub integer: bool,
+}
+
+/// Gridded dataset with an affine (north up) geo transform
+pub(crate) struct Grid {
+    dataset: Dataset,
+    transform: GeoTransform,
+    pub size: (usize, usize),
+    pub bands: Vec<Band>,
+}
+
+impl Grid {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let dataset =
+            Dataset::open(path).with_context(|| format!("Failed to open `{}`", path.display()))?;
+
+        let transform = dataset.geo_transform()?;
+        if transform[2] != 0.0 || transform[4] != 0.0 {
+            anyhow::bail!("Rotated grids are not supported");
+        }
+
+        // NetCDF time dimension, e.g. `hours since 1970-01-01 00:00:00`
+        let time_units = dataset.metadata_item("time#units", "");
+
+        let mut bands = Vec::new();
+        for index in 1..=dataset.raster_count() {
+            let band = dataset.rasterband(index)?;
+
+            let parameter = band
+                .metadata_item("NETCDF_VARNAME", "")
+                .or_else(|| band.description().ok().filter(|d| !d.is_empty()))
+                .unwrap_or_else(|| format!("band{index}"));
+
+            let datetime = match (&time_units, band.metadata_item("NETCDF_DIM_time", "")) {
+                (Some(units), Some(value)) => to_datetime(units, &value),
+                _ => None,
+            };
+
+            bands.push(Band {
+                index,
+                parameter,
+                datetime,
+                no_data: band.no_data_value(),
+                integer: matches!(
+                    band.band_type(),
+                    GDALDataType::GDT_Byte
+                        | GDALDataType::GDT_UInt16
+                        | GDALDataType::GDT_Int16
+                        | GDALDataType::GDT_UInt32
+                        | GDALDataType::GDT_Int32
+                ),
+            });
+        }
+
+        Ok(Grid {
+            size: dataset.raster_size(),
+            dataset,
+            transform,
+            bands,
+        })
+    }
+
+    /// Spatial reference system of the grid in `x/y` axis order
+    pub fn spatial_ref(&self) -> anyhow::Result<SpatialRef> {
+        let srs = self.dataset.spatial_ref()?;
+        srs.set_axis_mapping_strategy(OAMS_TRADITIONAL_GIS_ORDER);
+        Ok(srs)
+    }
+
+    /// Transformations from and to the grid for an EPSG code
+    pub fn transforms(&self, srid: i32) -> anyhow::Result<(CoordTransform, CoordTransform)> {
+        let srs = SpatialRef::from_epsg(srid as u32)?;
+        srs.set_axis_mapping_strategy(OAMS_TRADITIONAL_GIS_ORDER);
+
+        let grid = self.spatial_ref()?;
+
+        Ok((
+            CoordTransform::new(&srs, &grid)?,
+            CoordTransform::new(&grid, &srs)?,
+        ))
+    }
+
+    /// Affine transformation from pixel to grid coordinates
+    pub fn geo_transform(&self) -> GeoTransform {
+        self.transform
+    }
+
+    /// Bounding box `(xmin, ymin, xmax, ymax)` of the grid
+    pub fn bounds(&self) -> (f64, f64, f64, f64) {
+        let t = &self.transform;
+        let (x0, x1) = (t[0], t[0] + self.size.0 as f64 * t[1]);
+        let (y0, y1) = (t[3], t[3] + self.size.1 as f64 * t[5]);
+        (x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1))
+    }
+
+    /// Fractional pixel position of a coordinate
+    pub fn to_pixel(&self, x: f64, y: f64) -> (f64, f64) {
+        let t = &self.transform;
+        ((x - t[0]) / t[1], (y - t[3]) / t[5])
+    }
+
+    /// Coordinate of the center of a pixel
+    pub fn to_coord(&self, col: usize, row: usize) -> (f64, f64) {
+        let t = &self.transform;
+        (
+            t[0] + (col as f64 + 0.5) * t[1],
+            t[3] + (row as f64 + 0.5) * t[5],
+        )
+    }
+
+    /// Pixel containing a coordinate
+    pub fn cell(&self, x: f64, y: f64) -> Option<(usize, usize)> {
+        let (col, row) = self.to_pixel(x, y);
+        if col < 0.0 || row < 0.0 || col >= self.size.0 as f64 || row >= self.size.1 as f64 {
+            None
+        } else {
+            Some((col as usize, row as usize))
+        }
+    }
+
+    /// Pixel window `(col, row), (width, height)` covering a bounding box
+    pub fn window(
+        &self,
+        min: (f64, f64),
+        max: (f64, f64),
+    ) -> Option<((usize, usize), (usize, usize))> {
+        window(&self.transform, self.size, min, max)
+    }
+
+    /// Values of a band in a pixel window (row major), `None` for no data
+    pub fn read(
+        &self,
+        band: &Band,
+        origin: (usize, usize),
+        window: (usize, usize),
+    ) -> anyhow::Result<Vec<Option<f64>>> {
+        self.read_scaled(band, origin, window, window)
+    }
+
+    /// Values of a band in a pixel window resampled to a size (nearest neighbour)
+    pub fn read_scaled(
+        &self,
+        band: &Band,
+        (col, row): (usize, usize),
+        window: (usize, usize),
+        size: (usize, usize),
+    ) -> anyhow::Result<Vec<Option<f64>>> {
+        let buffer = self.dataset.rasterband(band.index)?.read_as::<f64>(
+            (col as isize, row as isize),
+            window,
+            size,
+            None,
+        )?;
+
+        Ok(buffer
+            .data
+            .into_iter()
+            .map(|v| (!v.is_nan() && Some(v) != band.no_data).then_some(v))
+            .collect())
+    }
+}
+
+/// Pixel window of a bounding box in a grid of a size
+fn window(
+    t: &GeoTransform,
+    size: (usize, usize),
+    (xmin, ymin): (f64, f64),
+    (xmax, ymax): (f64, f64),
+) -> Option<((usize, usize), (usize, usize))> {
+    let to_pixel = |x: f64, y: f64| ((x - t[0]) / t[1], (y - t[3]) / t[5]);
+    let (c0, r0) = to_pixel(xmin, ymax);
+    let (c1, r1) = to_pixel(xmax, ymin);
+
+    let clamp = |v: f64, max: usize| v.max(0.0).min(max as f64);
+    let (c0, c1) = (clamp(c0.min(c1), size.0), clamp(c0.max(c1), size.0));
+    let (r0, r1) = (clamp(r0.min(r1), size.1), clamp(r0.max(r1), size.1));
+
+    // degenerate windows (slices) cover at least one cell
+    let (col, row) = (c0.floor() as usize, r0.floor() as usize);
+    let width = (c1.ceil() as usize)
+        .max(col + 1)
+        .min(size.0)
+        .saturating_sub(col);
+    let height = (r1.ceil() as usize)
+        .max(row + 1)
+        .min(size.1)
+        .saturating_sub(row);
+
+    (width > 0 && height > 0).then_some(((col, row), (width, height)))
+}
+
+/// Converts a CF time value (e.g. `6` with units `hours since 2022-01-01`) to a datetime
+fn to_datetime(units: &str, value: &str) -> Option<DateTime<Utc>> {
+    let (unit, reference) = units.split_once(" since ")?;
+    let value: f64 = value.trim().parse().ok()?;
+
+    let reference = reference.trim().trim_end_matches('Z').replace('T', " ");
+    let reference = NaiveDateTime::parse_from_str(&reference, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(&format!("{reference} 00:00:00"), "%Y-%m-%d %H:%M:%S")
+        })
+        .ok()?;
+
+    let seconds = match unit.trim() {
+        "seconds" | "second" | "s" => value,
+        "minutes" | "minute" | "min" => value * 60.0,
+        "hours" | "hour" | "h" => value * 3600.0,
+        "days" | "day" | "d" => value * 86400.0,
+        _ => return None,
+    };
+
+    Some(DateTime::from_utc(
+        reference + Duration::milliseconds((seconds * 1000.0) as i64),
+        Utc,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{to_datetime, window};
+
+    #[test]
+    fn cf_time() {
+        assert_eq!(
+            to_datetime("hours since 2022-01-01 00:00:00", "6"),
+            Some(Utc.ymd(2022, 1, 1).and_hms(6, 0, 0))
+        );
+        assert_eq!(
+            to_datetime("days since 2022-01-01", "1.5"),
+            Some(Utc.ymd(2022, 1, 2).and_hms(12, 0, 0))
+        );
+        assert_eq!(
+            to_datetime("seconds since 1970-01-01T00:00:00Z", "60"),
+            Some(Utc.ymd(1970, 1, 1).and_hms(0, 1, 0))
+        );
+
+        assert_eq!(to_datetime("months since 2022-01-01", "1"), None);
+        assert_eq!(to_datetime("hours since yesterday", "1"), None);
+        assert_eq!(to_datetime("hours", "1"), None);
+        assert_eq!(to_datetime("hours since 2022-01-01", "x"), None);
+    }
+
+    #[test]
+    fn pixel_window() {
+        // 10 x 10 cells of 1 degree from (0, 10) to (10, 0)
+        let t = [0.0, 1.0, 0.0, 10.0, 0.0, -1.0];
+        let size = (10, 10);
+
+        assert_eq!(
+            window(&t, size, (2.0, 3.0), (4.0, 5.0)),
+            Some(((2, 5), (2, 2)))
+        );
+        assert_eq!(
+            window(&t, size, (2.5, 3.5), (3.5, 4.5)),
+            Some(((2, 5), (2, 2)))
+        );
+
+        // clamped to the grid
+        assert_eq!(
+            window(&t, size, (-5.0, -5.0), (20.0, 20.0)),
+            Some(((0, 0), (10, 10)))
+        );
+
+        // slices cover one cell
+        assert_eq!(
+            window(&t, size, (2.5, 3.0), (2.5, 5.0)),
+            Some(((2, 5), (1, 2)))
+        );
+
+        // outside of the grid
+        assert_eq!(window(&t, size, (20.0, 20.0), (30.0, 30.0)), None);
+    }
+}
@@ -0,0 +1,548 @@
+mod coverage;
+mod grid;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{json, Map, Value};
+
+use ogcapi_types::{
+    common::{Bbox, Datetime, IntervalDatetime},
+    coverage::Coverage,
+    coverages::{DomainSet, Query as CoverageQuery, RangeType},
+    edr::{Coords, DataType, Query, QueryType},
+    features::{Feature, FeatureCollection},
+};
+
+use crate::{CoverageQuerier, EdrQuerier};
+
+use grid::{Band, Grid};
+
+/// File extensions of supported gridded data
+const EXTENSIONS: [&str; 6] = ["tif", "tiff", "nc", "zarr", "vrt", "grib2"];
+
+/// Maximum number of cells returned by a query
+const MAX_CELLS: usize = 250_000;
+
+/// Gridded data (GeoTIFF, NetCDF, Zarr, ...) read with GDAL
+///
+/// The data of a collection is stored in the directory as `{collection_id}.{ext}`.
+/// Bands are parameters named by their NetCDF variable name or description, the
+/// time of NetCDF bands is derived from the `time` dimension.
+pub struct Raster {
+    dir: PathBuf,
+    fallback: Option<Box<dyn EdrQuerier>>,
+}
+
+impl Raster {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Raster {
+            dir: dir.as_ref().to_path_buf(),
+            fallback: None,
+        }
+    }
+
+    /// Querier for collections without gridded data
+    pub fn fallback(mut self, querier: Box<dyn EdrQuerier>) -> Self {
+        self.fallback = Some(querier);
+        self
+    }
+
+    /// Path of the gridded data of a collection
+    ///
+    /// Collection ids which could resolve outside of the directory have none.
+    pub fn path(&self, collection_id: &str) -> Option<PathBuf> {
+        if collection_id.is_empty()
+            || collection_id.contains(['/', '\\'])
+            || collection_id.contains("..")
+        {
+            return None;
+        }
+
+        EXTENSIONS
+            .iter()
+            .map(|ext| self.dir.join(format!("{collection_id}.{ext}")))
+            .find(|path| path.exists())
+    }
+
+    fn fallback_for(&self, collection_id: &str) -> anyhow::Result<&dyn EdrQuerier> {
+        self.fallback
+            .as_deref()
+            .with_context(|| format!("No gridded data for collection `{collection_id}`"))
+    }
+}
+
+#[async_trait::async_trait]
+impl EdrQuerier for Raster {
+    async fn query(
+        &self,
+        collection_id: &str,
+        query_type: &QueryType,
+        query: &Query,
+    ) -> anyhow::Result<FeatureCollection> {
+        match self.path(collection_id) {
+            Some(path) => {
+                let query_type = query_type.to_owned();
+                let query = query.to_owned();
+                tokio::task::spawn_blocking(move || sample(&path, &query_type, &query)).await?
+            }
+            None => {
+                self.fallback_for(collection_id)?
+                    .query(collection_id, query_type, query)
+                    .await
+            }
+        }
+    }
+
+    async fn locations(&self, collection_id: &str) -> anyhow::Result<FeatureCollection> {
+        match self.path(collection_id) {
+            Some(_) => Ok(FeatureCollection::new(Vec::new())),
+            None => {
+                self.fallback_for(collection_id)?
+                    .locations(collection_id)
+                    .await
+            }
+        }
+    }
+
+    async fn location(&self, collection_id: &str, id: &str) -> anyhow::Result<Option<Feature>> {
+        match self.path(collection_id) {
+            Some(_) => Ok(None),
+            None => {
+                self.fallback_for(collection_id)?
+                    .location(collection_id, id)
+                    .await
+            }
+        }
+    }
+
+    async fn put_location(
+        &self,
+        collection_id: &str,
+        location: &Feature,
+    ) -> anyhow::Result<String> {
+        match self.path(collection_id) {
+            Some(_) => anyhow::bail!("Raster collections have no named locations"),
+            None => {
+                self.fallback_for(collection_id)?
+                    .put_location(collection_id, location)
+                    .await
+            }
+        }
+    }
+
+    async fn delete_location(&self, collection_id: &str, id: &str) -> anyhow::Result<()> {
+        match self.path(collection_id) {
+            Some(_) => Ok(()),
+            None => {
+                self.fallback_for(collection_id)?
+                    .delete_location(collection_id, id)
+                    .await
+            }
+        }
+    }
+
+    async fn parameters(&self, collection_id: &str) -> anyhow::Result<HashMap<String, DataType>> {
+        match self.path(collection_id) {
+            Some(path) => {
+                let grid = tokio::task::spawn_blocking(move || Grid::open(&path)).await??;
+                Ok(grid
+                    .bands
+                    .iter()
+                    .map(|band| {
+                        let data_type = if band.integer {
+                            DataType::Integer
+                        } else {
+                            DataType::Float
+                        };
+                        (band.parameter.to_owned(), data_type)
+                    })
+                    .collect())
+            }
+            None => {
+                self.fallback_for(collection_id)?
+                    .parameters(collection_id)
+                    .await
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl CoverageQuerier for Raster {
+    async fn domain_set(&self, collection_id: &str) -> anyhow::Result<Option<DomainSet>> {
+        match self.path(collection_id) {
+            Some(path) => Ok(Some(
+                tokio::task::spawn_blocking(move || coverage::domain_set(&path)).await??,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    async fn range_type(&self, collection_id: &str) -> anyhow::Result<Option<RangeType>> {
+        match self.path(collection_id) {
+            Some(path) => Ok(Some(
+                tokio::task::spawn_blocking(move || coverage::range_type(&path)).await??,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    async fn coverage(
+        &self,
+        collection_id: &str,
+        query: &CoverageQuery,
+    ) -> anyhow::Result<Option<Coverage>> {
+        match self.path(collection_id) {
+            Some(path) => {
+                let query = query.to_owned();
+                Ok(Some(
+                    tokio::task::spawn_blocking(move || coverage::coverage(&path, &query))
+                        .await??,
+                ))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn geotiff(
+        &self,
+        collection_id: &str,
+        query: &CoverageQuery,
+    ) -> anyhow::Result<Option<Vec<u8>>> {
+        match self.path(collection_id) {
+            Some(path) => {
+                let query = query.to_owned();
+                Ok(Some(
+                    tokio::task::spawn_blocking(move || coverage::geotiff(&path, &query)).await??,
+                ))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Samples the grid at the cells selected by a query
+fn sample(path: &Path, query_type: &QueryType, query: &Query) -> anyhow::Result<FeatureCollection> {
+    let grid = Grid::open(path)?;
+    let (to_grid, from_grid) = grid.transforms(query.crs.as_srid())?;
+
+    let coords = query.geometry(query_type).map_err(anyhow::Error::msg)?;
+
+    // selected cells
+    let (cells, window) = match (query_type, &coords) {
+        (QueryType::Position | QueryType::Trajectory, Coords::Wkt(wkt)) => {
+            let (mut xs, mut ys): (Vec<f64>, Vec<f64>) =
+                wkt.positions().iter().map(|p| (p[0], p[1])).unzip();
+            to_grid.transform_coords(&mut xs, &mut ys, &mut [])?;
+
+            let mut cells: Vec<(usize, usize)> = Vec::new();
+            for cell in xs
+                .iter()
+                .zip(ys.iter())
+                .filter_map(|(x, y)| grid.cell(*x, *y))
+            {
+                if query_type == &QueryType::Trajectory || !cells.contains(&cell) {
+                    cells.push(cell);
+                }
+            }
+            (cells, None)
+        }
+        (QueryType::Area | QueryType::Cube, coords) => {
+            // polygons of rings in grid coordinates
+            let mut polygons = polygons(coords);
+            for ring in polygons.iter_mut().flatten() {
+                let (mut xs, mut ys): (Vec<f64>, Vec<f64>) = ring.iter().cloned().unzip();
+                to_grid.transform_coords(&mut xs, &mut ys, &mut [])?;
+                *ring = xs.into_iter().zip(ys).collect();
+            }
+
+            let exteriors = polygons.iter().filter_map(|rings| rings.first()).flatten();
+            let (min, max) = exteriors.fold(
+                (
+                    (f64::INFINITY, f64::INFINITY),
+                    (f64::NEG_INFINITY, f64::NEG_INFINITY),
+                ),
+                |(min, max), (x, y)| {
+                    (
+                        (min.0.min(*x), min.1.min(*y)),
+                        (max.0.max(*x), max.1.max(*y)),
+                    )
+                },
+            );
+
+            match grid.window(min, max) {
+                Some(((col, row), (width, height))) => {
+                    if !matches!(width.checked_mul(height), Some(cells) if cells <= MAX_CELLS) {
+                        anyhow::bail!("Query selects more than {MAX_CELLS} cells");
+                    }
+                    let cells = (row..row + height)
+                        .flat_map(|r| (col..col + width).map(move |c| (c, r)))
+                        .filter(|(c, r)| {
+                            query_type == &QueryType::Cube
+                                || polygons.iter().any(|rings| {
+                                    let coord = grid.to_coord(*c, *r);
+                                    match rings.split_first() {
+                                        Some((exterior, holes)) => {
+                                            contains(exterior, coord)
+                                                && !holes.iter().any(|hole| contains(hole, coord))
+                                        }
+                                        None => false,
+                                    }
+                                })
+                        })
+                        .collect();
+                    (cells, Some(((col, row), (width, height))))
+                }
+                None => (Vec::new(), None),
+            }
+        }
+        (query_type, _) => {
+            anyhow::bail!("{query_type} queries are not supported for gridded data")
+        }
+    };
+
+    // selected bands grouped by time
+    let parameters: Option<Vec<&str>> = query
+        .parameter_name
+        .as_deref()
+        .map(|p| p.split(',').map(str::trim).collect());
+
+    let selected = |band: &Band| match &parameters {
+        Some(parameters) => parameters.contains(&band.parameter.as_str()),
+        None => true,
+    };
+
+    let mut times: BTreeMap<Option<DateTime<Utc>>, Vec<&Band>> = BTreeMap::new();
+    for band in grid.bands.iter() {
+        if selected(band) && within(&band.datetime, &query.datetime) {
+            times.entry(band.datetime).or_default().push(band);
+        }
+    }
+
+    // values of the cells by band
+    let mut values: HashMap<isize, Vec<Option<f64>>> = HashMap::new();
+    for band in times.values().flatten() {
+        let band_values = match window {
+            Some((origin, size)) => {
+                let data = grid.read(band, origin, size)?;
+                cells
+                    .iter()
+                    .map(|(c, r)| data[(r - origin.1) * size.0 + (c - origin.0)])
+                    .collect()
+            }
+            None => cells
+                .iter()
+                .map(|cell| Ok(grid.read(band, *cell, (1, 1))?[0]))
+                .collect::<anyhow::Result<_>>()?,
+        };
+        values.insert(band.index, band_values);
+    }
+
+    // coordinates of the cells in the requested crs
+    let (mut xs, mut ys): (Vec<f64>, Vec<f64>) =
+        cells.iter().map(|(c, r)| grid.to_coord(*c, *r)).unzip();
+    from_grid.transform_coords(&mut xs, &mut ys, &mut [])?;
+
+    let mut features = Vec::new();
+    for (i, (x, y)) in xs.iter().zip(ys.iter()).enumerate() {
+        for (datetime, bands) in &times {
+            let mut properties = Map::new();
+            for band in bands {
+                if let Some(value) = values[&band.index][i] {
+                    let value = if band.integer {
+                        json!(value as i64)
+                    } else {
+                        json!(value)
+                    };
+                    properties.insert(band.parameter.to_owned(), value);
+                }
+            }
+            if properties.is_empty() {
+                continue;
+            }
+            if let Some(datetime) = datetime {
+                properties.insert(
+                    "datetime".to_string(),
+                    Value::String(datetime.to_rfc3339_opts(SecondsFormat::Secs, true)),
+                );
+            }
+
+            let feature: Feature = serde_json::from_value(json!({
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [x, y] },
+                "properties": properties
+            }))?;
+            features.push(feature);
+        }
+    }
+
+    let mut fc = FeatureCollection::new(features);
+    fc.number_matched = fc.number_returned;
+
+    Ok(fc)
+}
+
+/// Polygons (rings of `(x, y)`) of area coordinates
+fn polygons(coords: &Coords) -> Vec<Vec<Vec<(f64, f64)>>> {
+    match coords {
+        Coords::Wkt(wkt) => wkt
+            .polygons()
+            .iter()
+            .map(|rings| {
+                rings
+                    .iter()
+                    .map(|ring| ring.iter().map(|p| (p[0], p[1])).collect())
+                    .collect()
+            })
+            .collect(),
+        Coords::Bbox(bbox) => {
+            let (min, max) = match bbox {
+                Bbox::Bbox2D(b) => ((b[0], b[1]), (b[2], b[3])),
+                Bbox::Bbox3D(b) => ((b[0], b[1]), (b[3], b[4])),
+            };
+            vec![vec![vec![
+                (min.0, min.1),
+                (max.0, min.1),
+                (max.0, max.1),
+                (min.0, max.1),
+            ]]]
+        }
+        Coords::None => Vec::new(),
+    }
+}
+
+/// Point in polygon test (ray casting)
+fn contains(ring: &[(f64, f64)], (x, y): (f64, f64)) -> bool {
+    let mut inside = false;
+    let mut j = ring.len().wrapping_sub(1);
+    for i in 0..ring.len() {
+        let (xi, yi) = ring[i];
+        let (xj, yj) = ring[j];
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// Whether a (band) datetime is within the datetime of a query
+fn within(datetime: &Option<DateTime<Utc>>, filter: &Option<Datetime>) -> bool {
+    match (datetime, filter) {
+        (Some(datetime), Some(Datetime::Datetime(d))) => datetime == d,
+        (Some(datetime), Some(Datetime::Interval { from, to })) => {
+            let after = match from {
+                IntervalDatetime::Datetime(from) => datetime >= from,
+                IntervalDatetime::Open => true,
+            };
+            let before = match to {
+                IntervalDatetime::Datetime(to) => datetime <= to,
+                IntervalDatetime::Open => true,
+            };
+            after && before
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use ogcapi_types::{
+        common::Datetime,
+        edr::{Coords, Query, QueryType},
+    };
+
+    use super::{contains, polygons, within, Raster};
+
+    #[test]
+    fn path() {
+        let dir = std::env::temp_dir().join("ogcapi-raster-path");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("grid.tif"), b"").unwrap();
+        std::fs::write(std::env::temp_dir().join("ogcapi-raster-outside.tif"), b"").unwrap();
+
+        let raster = Raster::new(&dir);
+        assert_eq!(raster.path("grid"), Some(dir.join("grid.tif")));
+        assert_eq!(raster.path("missing"), None);
+
+        for id in ["", "../ogcapi-raster-outside", "a/b", "a\\b", ".."] {
+            assert_eq!(raster.path(id), None, "{id}");
+        }
+    }
+
+    #[test]
+    fn parse_wkt() {
+        let query = Query {
+            coords: "POLYGON((0 0, 4 0, 4 4, 0 4, 0 0), (1 1, 2 1, 2 2, 1 2, 1 1))".to_string(),
+            ..Default::default()
+        };
+        let coords = query.geometry(&QueryType::Area).unwrap();
+        assert!(matches!(coords, Coords::Wkt(_)));
+
+        let polygons = polygons(&coords);
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0].len(), 2);
+        assert_eq!(polygons[0][1][2], (2.0, 2.0));
+    }
+
+    #[test]
+    fn parse_bbox() {
+        let query = Query {
+            coords: "1,2,3,4".to_string(),
+            ..Default::default()
+        };
+        let coords = query.geometry(&QueryType::Cube).unwrap();
+        assert_eq!(
+            polygons(&coords),
+            vec![vec![vec![(1.0, 2.0), (3.0, 2.0), (3.0, 4.0), (1.0, 4.0)]]]
+        );
+
+        let query = Query {
+            coords: "1,2,0,3,4,10".to_string(),
+            ..Default::default()
+        };
+        let coords = query.geometry(&QueryType::Cube).unwrap();
+        assert_eq!(polygons(&coords)[0][0][2], (3.0, 4.0));
+
+        assert!(polygons(&Coords::None).is_empty());
+    }
+
+    #[test]
+    fn point_in_polygon() {
+        let ring = [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0), (0.0, 0.0)];
+        assert!(contains(&ring, (2.0, 2.0)));
+        assert!(!contains(&ring, (5.0, 2.0)));
+        assert!(!contains(&ring, (-1.0, -1.0)));
+
+        // concave
+        let ring = [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (2.0, 1.0), (0.0, 4.0)];
+        assert!(contains(&ring, (1.0, 0.5)));
+        assert!(!contains(&ring, (2.0, 3.0)));
+
+        assert!(!contains(&[], (0.0, 0.0)));
+    }
+
+    #[test]
+    fn datetime_filter() {
+        let datetime = Some(Utc.ymd(2022, 1, 1).and_hms(6, 0, 0));
+
+        let filter = |s: &str| Some(s.parse::<Datetime>().unwrap());
+
+        assert!(within(&datetime, &None));
+        assert!(within(&None, &filter("2020-01-01T00:00:00Z")));
+        assert!(within(&datetime, &filter("2022-01-01T06:00:00Z")));
+        assert!(!within(&datetime, &filter("2022-01-01T00:00:00Z")));
+        assert!(within(&datetime, &filter("2022-01-01T00:00:00Z/..")));
+        assert!(within(&datetime, &filter("../2022-01-01T06:00:00Z")));
+        assert!(!within(
+            &datetime,
+            &filter("2021-01-01T00:00:00Z/2021-12-31T00:00:00Z")
+        ));
+    }
+}
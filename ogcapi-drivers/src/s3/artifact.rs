@@ -0,0 +1,48 @@
+use aws_sdk_s3::{error::GetObjectErrorKind, types::SdkError};
+
+use crate::ArtifactStore;
+
+use super::S3;
+
+#[async_trait::async_trait]
+impl ArtifactStore for S3 {
+    async fn put_artifact(
+        &self,
+        key: &str,
+        data: Vec<u8>,
+        media_type: Option<&str>,
+    ) -> anyhow::Result<()> {
+        self.put_object(
+            self.bucket.clone().unwrap_or_default(),
+            key,
+            data,
+            media_type.map(ToString::to_string),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_artifact(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        match self
+            .get_object(self.bucket.clone().unwrap_or_default(), key)
+            .await
+        {
+            Ok(r) => Ok(Some(r.body.collect().await?.into_bytes().to_vec())),
+            Err(e) => match e {
+                SdkError::ServiceError { err, raw: _ } => match err.kind {
+                    GetObjectErrorKind::NoSuchKey(_) => Ok(None),
+                    _ => Err(anyhow::Error::new(err)),
+                },
+                _ => Err(anyhow::Error::new(e)),
+            },
+        }
+    }
+
+    async fn delete_artifact(&self, key: &str) -> anyhow::Result<()> {
+        self.delete_object(self.bucket.clone().unwrap_or_default(), key)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use ogcapi_types::{common::media_type::JSON, stac::Catalog};
+
+use crate::CatalogTransactions;
+
+use super::S3;
+
+#[async_trait::async_trait]
+impl CatalogTransactions for S3 {
+    async fn create_catalog(&self, catalog: &Catalog) -> anyhow::Result<String> {
+        let key = format!("catalogs/{}/catalog.json", catalog.id);
+
+        self.write_json(&key, catalog, JSON).await?;
+
+        Ok(catalog.id.to_owned())
+    }
+
+    async fn read_catalog(&self, id: &str) -> anyhow::Result<Option<Catalog>> {
+        let key = format!("catalogs/{}/catalog.json", id);
+
+        self.read_json(&key).await
+    }
+
+    async fn update_catalog(&self, catalog: &Catalog) -> anyhow::Result<()> {
+        let key = format!("catalogs/{}/catalog.json", catalog.id);
+
+        self.write_json(&key, catalog, JSON).await
+    }
+
+    async fn delete_catalog(&self, id: &str) -> anyhow::Result<()> {
+        self.delete_prefix(&format!("catalogs/{}/", id)).await
+    }
+
+    async fn list_catalogs(&self) -> anyhow::Result<Vec<Catalog>> {
+        let mut catalogs = Vec::new();
+
+        for key in self.keys("catalogs/").await? {
+            if key.ends_with("/catalog.json") {
+                if let Some(catalog) = self.read_json(&key).await? {
+                    catalogs.push(catalog);
+                }
+            }
+        }
+
+        Ok(catalogs)
+    }
+}
@@ -1,5 +1,3 @@
-use aws_sdk_s3::{error::GetObjectErrorKind, types::SdkError};
-
 use ogcapi_types::common::{media_type::JSON, Collection, Collections, Query};
 
 use crate::CollectionTransactions;
@@ -10,15 +8,8 @@ use super::S3;
 impl CollectionTransactions for S3 {
     async fn create_collection(&self, collection: &Collection) -> Result<String, anyhow::Error> {
         let key = format!("collections/{}/collection.json", collection.id);
-        let data = serde_json::to_vec(&collection)?;
 
-        self.put_object(
-            self.bucket.clone().unwrap_or_default(),
-            &key,
-            data,
-            Some(JSON.to_string()),
-        )
-        .await?;
+        self.write_json(&key, collection, JSON).await?;
 
         Ok(collection.id.to_owned())
     }
@@ -27,73 +18,34 @@ impl CollectionTransactions for S3 {
         // TODO: cache
         let key = format!("collections/{}/collection.json", id);
 
-        match self
-            .get_object(self.bucket.clone().unwrap_or_default(), &key)
-            .await
-        {
-            Ok(r) => Ok(Some(serde_json::from_slice(
-                &r.body.collect().await?.into_bytes(),
-            )?)),
-            Err(e) => match e {
-                SdkError::ServiceError { err, raw: _ } => match err.kind {
-                    GetObjectErrorKind::NoSuchKey(_) => Ok(None),
-                    _ => Err(anyhow::Error::new(err)),
-                },
-                _ => Err(anyhow::Error::new(e)),
-            },
-        }
+        self.read_json(&key).await
     }
 
     async fn update_collection(&self, collection: &Collection) -> Result<(), anyhow::Error> {
         let key = format!("collections/{}/collection.json", collection.id);
-        let data = serde_json::to_vec(&collection)?;
 
-        self.put_object(
-            self.bucket.clone().unwrap_or_default(),
-            &key,
-            data,
-            Some(JSON.to_string()),
-        )
-        .await?;
-
-        Ok(())
+        self.write_json(&key, collection, JSON).await
     }
 
     async fn delete_collection(&self, id: &str) -> Result<(), anyhow::Error> {
-        let key = format!("collections/{}", id);
-
-        self.delete_object(self.bucket.clone().unwrap_or_default(), &key)
-            .await?;
-
-        Ok(())
+        // collection, index and items
+        self.delete_prefix(&format!("collections/{}/", id)).await
     }
 
     async fn list_collections(&self, _query: &Query) -> Result<Collections, anyhow::Error> {
         let mut collections = Vec::new();
 
-        let resp = self
-            .client
-            .list_objects()
-            .bucket(self.bucket.clone().unwrap_or_default())
-            .send()
-            .await?;
-
-        for object in resp.contents.unwrap() {
-            if let Some(key) = object.key() {
-                if key.ends_with("collection.json") {
-                    let r = self
-                        .get_object(self.bucket.clone().unwrap_or_default(), key)
-                        .await?;
-
-                    let c = serde_json::from_slice(&r.body.collect().await?.into_bytes()[..])?;
-
-                    collections.push(c);
+        for key in self.keys("collections/").await? {
+            // skip items with an id of `collection`
+            if key.ends_with("/collection.json") && key.matches('/').count() == 2 {
+                if let Some(collection) = self.read_json(&key).await? {
+                    collections.push(collection);
                 }
             }
         }
 
         let mut collections = Collections::new(collections);
-        collections.number_matched = collections.number_matched.to_owned();
+        collections.number_matched = collections.number_returned;
 
         Ok(collections)
     }
@@ -1,13 +1,17 @@
-use aws_sdk_s3::{error::GetObjectErrorKind, types::SdkError};
-
 use ogcapi_types::{
     common::{media_type::GEO_JSON, Crs},
     features::{Feature, FeatureCollection, Query},
 };
 
-use crate::FeatureTransactions;
+use crate::{index, unavailable::UnavailableError, FeatureTransactions};
+
+use super::{index::Indexed, S3};
+
+const CRS_MESSAGE: &str = "The S3 driver only serves features in `CRS84`";
+
+const FILTER_MESSAGE: &str = "The S3 driver does not support filter expressions";
 
-use super::S3;
+const BBOX_CRS_MESSAGE: &str = "The S3 driver only supports bboxes in `CRS84`";
 
 #[async_trait::async_trait]
 impl FeatureTransactions for S3 {
@@ -17,15 +21,9 @@ impl FeatureTransactions for S3 {
             feature.collection.as_ref().unwrap(),
             feature.id.as_ref().unwrap()
         );
-        let data = serde_json::to_vec(&feature)?;
 
-        self.put_object(
-            self.bucket.clone().unwrap_or_default(),
-            &key,
-            data,
-            Some(GEO_JSON.to_string()),
-        )
-        .await?;
+        self.write_json(&key, feature, GEO_JSON).await?;
+        self.index_feature(feature).await?;
 
         Ok(key)
     }
@@ -34,25 +32,15 @@ impl FeatureTransactions for S3 {
         &self,
         collection: &str,
         id: &str,
-        _crs: &Crs,
+        crs: &Crs,
     ) -> anyhow::Result<Option<Feature>> {
+        if *crs != Crs::default() {
+            return Err(UnavailableError(CRS_MESSAGE).into());
+        }
+
         let key = format!("collections/{}/items/{}.json", collection, id);
 
-        match self
-            .get_object(self.bucket.clone().unwrap_or_default(), &key)
-            .await
-        {
-            Ok(r) => Ok(Some(serde_json::from_slice(
-                &r.body.collect().await?.into_bytes(),
-            )?)),
-            Err(e) => match e {
-                SdkError::ServiceError { err, raw: _ } => match err.kind {
-                    GetObjectErrorKind::NoSuchKey(_) => Ok(None),
-                    _ => Err(anyhow::Error::new(err)),
-                },
-                _ => Err(anyhow::Error::new(e)),
-            },
-        }
+        self.read_json(&key).await
     }
     async fn update_feature(&self, feature: &Feature) -> anyhow::Result<()> {
         let key = format!(
@@ -60,15 +48,9 @@ impl FeatureTransactions for S3 {
             feature.collection.as_ref().unwrap(),
             feature.id.as_ref().unwrap()
         );
-        let data = serde_json::to_vec(&feature)?;
 
-        self.put_object(
-            self.bucket.clone().unwrap_or_default(),
-            &key,
-            data,
-            Some(GEO_JSON.to_string()),
-        )
-        .await?;
+        self.write_json(&key, feature, GEO_JSON).await?;
+        self.index_feature(feature).await?;
 
         Ok(())
     }
@@ -78,15 +60,56 @@ impl FeatureTransactions for S3 {
 
         self.delete_object(self.bucket.clone().unwrap_or_default(), &key)
             .await?;
+        self.unindex_feature(collection, id).await?;
 
         Ok(())
     }
 
+    /// Lists the items of a collection filtered by `bbox`, `datetime` and
+    /// properties on the collection index
+    ///
+    /// Items are stored and served in `CRS84`, filter expressions are not
+    /// supported.
     async fn list_items(
         &self,
-        _collection: &str,
-        _query: &Query,
+        collection: &str,
+        query: &Query,
     ) -> anyhow::Result<FeatureCollection> {
-        unimplemented!()
+        if query.crs != Crs::default() {
+            return Err(UnavailableError(CRS_MESSAGE).into());
+        }
+        if query.filter.is_some() {
+            return Err(UnavailableError(FILTER_MESSAGE).into());
+        }
+        if query.bbox.is_some() && query.bbox_crs != Crs::default() {
+            return Err(UnavailableError(BBOX_CRS_MESSAGE).into());
+        }
+
+        let matched: Vec<Indexed> = self
+            .read_index(collection)
+            .await?
+            .into_iter()
+            .filter(|indexed| {
+                indexed
+                    .entry
+                    .matches(query.bbox.as_ref(), query.datetime.as_ref())
+                    && index::matches_properties(
+                        indexed.properties.as_ref(),
+                        &query.additional_parameters,
+                    )
+            })
+            .collect();
+
+        let keys = matched
+            .iter()
+            .skip(query.offset.unwrap_or(0))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .map(|indexed| format!("collections/{}/items/{}.json", collection, indexed.entry.id));
+        let features = self.read_jsons(keys).await?.into_iter().flatten().collect();
+
+        let mut fc = FeatureCollection::new(features);
+        fc.number_matched = Some(matched.len() as u64);
+
+        Ok(fc)
     }
 }
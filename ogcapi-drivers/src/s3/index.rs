@@ -0,0 +1,182 @@
+use std::{
+    collections::BTreeMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use ogcapi_types::{common::media_type::JSON, features::Feature};
+
+use crate::index::Entry;
+
+use super::S3;
+
+/// Log records younger than this are not compacted, so that records of
+/// writers with slightly skewed clocks are not missed
+const SETTLE: Duration = Duration::from_secs(60);
+
+/// Number of log records after which reads compact the index
+const COMPACT_AFTER: usize = 64;
+
+/// Entry of the item index with the properties of the item
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(super) struct Indexed {
+    #[serde(flatten)]
+    pub(super) entry: Entry,
+    #[serde(default)]
+    pub(super) properties: Option<Map<String, Value>>,
+}
+
+impl Indexed {
+    pub(super) fn new(feature: &Feature) -> Self {
+        Indexed {
+            entry: Entry::new(feature),
+            properties: feature.properties.clone(),
+        }
+    }
+}
+
+/// Compacted item index of a collection up to the log record `watermark`
+#[derive(Serialize, Deserialize, Default)]
+struct Manifest {
+    watermark: String,
+    /// Log records compacted into the manifest, deleted by the next compaction
+    compacted: Vec<String>,
+    entries: Vec<Indexed>,
+}
+
+/// Change of the index entry of an item, `None` for removed items
+#[derive(Serialize, Deserialize)]
+struct Record {
+    id: String,
+    entry: Option<Indexed>,
+}
+
+impl S3 {
+    fn manifest_key(collection: &str) -> String {
+        format!("collections/{collection}/index/manifest.json")
+    }
+
+    fn log_prefix(collection: &str) -> String {
+        format!("collections/{collection}/index/log/")
+    }
+
+    /// Key of a log record, ordered by the time of the change
+    fn record_key(collection: &str, id: &str, time: SystemTime) -> String {
+        let micros = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros();
+        format!("{}{micros:020}-{id}.json", S3::log_prefix(collection))
+    }
+
+    /// Item index of a collection ordered by id, empty if the collection has
+    /// none
+    ///
+    /// The index is a manifest object with the compacted entries and a log of
+    /// later changes, one object per change, so concurrent writers do not
+    /// interfere. Replaying changes is idempotent, so that manifests written
+    /// by concurrent compactions are harmless.
+    pub(super) async fn read_index(&self, collection: &str) -> anyhow::Result<Vec<Indexed>> {
+        let manifest: Manifest = self
+            .read_json(&S3::manifest_key(collection))
+            .await?
+            .unwrap_or_default();
+
+        let marker = Some(manifest.watermark.to_owned()).filter(|m| !m.is_empty());
+        let keys = self.keys_after(&S3::log_prefix(collection), marker).await?;
+        let records = self.read_jsons::<Record>(keys.clone()).await?;
+
+        let mut index: BTreeMap<String, Indexed> = manifest
+            .entries
+            .into_iter()
+            .map(|indexed| (indexed.entry.id.to_owned(), indexed))
+            .collect();
+
+        if keys.len() <= COMPACT_AFTER {
+            apply(&mut index, records.into_iter().flatten());
+            return Ok(index.into_values().collect());
+        }
+
+        // compact the settled records
+        let cutoff = S3::record_key(collection, "", SystemTime::now() - SETTLE);
+        let settled = keys.iter().take_while(|key| **key < cutoff).count();
+        let mut records = records.into_iter();
+
+        apply(&mut index, records.by_ref().take(settled).flatten());
+        if settled > 0 {
+            let compacted = Manifest {
+                watermark: keys[settled - 1].to_owned(),
+                compacted: keys[..settled].to_vec(),
+                entries: index.values().cloned().collect(),
+            };
+            self.write_json(&S3::manifest_key(collection), &compacted, JSON)
+                .await?;
+
+            for key in manifest.compacted {
+                self.delete_object(self.bucket.clone().unwrap_or_default(), key)
+                    .await?;
+            }
+        }
+
+        apply(&mut index, records.flatten());
+        Ok(index.into_values().collect())
+    }
+
+    /// Replaces the item index of a collection, changes logged meanwhile are
+    /// applied on top
+    pub(super) async fn write_index(
+        &self,
+        collection: &str,
+        index: Vec<Indexed>,
+    ) -> anyhow::Result<()> {
+        let manifest: Manifest = self
+            .read_json(&S3::manifest_key(collection))
+            .await?
+            .unwrap_or_default();
+
+        let manifest = Manifest {
+            entries: index,
+            ..manifest
+        };
+
+        self.write_json(&S3::manifest_key(collection), &manifest, JSON)
+            .await
+    }
+
+    /// Adds or replaces the entry of a feature in the index of its collection
+    pub(super) async fn index_feature(&self, feature: &Feature) -> anyhow::Result<()> {
+        let collection = feature.collection.as_deref().unwrap_or_default();
+        let indexed = Indexed::new(feature);
+
+        let record = Record {
+            id: indexed.entry.id.to_owned(),
+            entry: Some(indexed),
+        };
+        let key = S3::record_key(collection, &record.id, SystemTime::now());
+
+        self.write_json(&key, &record, JSON).await
+    }
+
+    /// Removes the entry of a feature from the index of a collection
+    pub(super) async fn unindex_feature(&self, collection: &str, id: &str) -> anyhow::Result<()> {
+        let record = Record {
+            id: id.to_owned(),
+            entry: None,
+        };
+        let key = S3::record_key(collection, id, SystemTime::now());
+
+        self.write_json(&key, &record, JSON).await
+    }
+}
+
+/// Applies log records in order
+fn apply(index: &mut BTreeMap<String, Indexed>, records: impl Iterator<Item = Record>) {
+    for record in records {
+        match record.entry {
+            Some(indexed) => index.insert(record.id, indexed),
+            None => index.remove(&record.id),
+        };
+    }
+}
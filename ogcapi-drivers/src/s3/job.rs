@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use ogcapi_types::{
+    common::media_type::JSON,
+    processes::{JobQuery, StatusCode, StatusInfo},
+};
+
+use crate::{JobHandler, JobResults};
+
+use super::S3;
+
+/// Stored form of `JobResults`
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum StoredResults {
+    Inline {
+        value: Value,
+        media_type: Option<String>,
+    },
+    Artifact {
+        key: String,
+        media_type: Option<String>,
+    },
+}
+
+impl From<&JobResults> for StoredResults {
+    fn from(results: &JobResults) -> Self {
+        match results.to_owned() {
+            JobResults::Inline { value, media_type } => StoredResults::Inline { value, media_type },
+            JobResults::Artifact { key, media_type } => StoredResults::Artifact { key, media_type },
+        }
+    }
+}
+
+impl From<StoredResults> for JobResults {
+    fn from(results: StoredResults) -> Self {
+        match results {
+            StoredResults::Inline { value, media_type } => JobResults::Inline { value, media_type },
+            StoredResults::Artifact { key, media_type } => JobResults::Artifact { key, media_type },
+        }
+    }
+}
+
+impl S3 {
+    /// Status of all jobs, most recently created first
+    async fn jobs(&self) -> anyhow::Result<Vec<StatusInfo>> {
+        let mut jobs = Vec::new();
+
+        for key in self.keys("jobs/").await? {
+            if key.ends_with("/status.json") {
+                if let Some(job) = self.read_json::<StatusInfo>(&key).await? {
+                    jobs.push(job);
+                }
+            }
+        }
+
+        jobs.sort_by(|a, b| {
+            b.created
+                .cmp(&a.created)
+                .then_with(|| a.job_id.cmp(&b.job_id))
+        });
+
+        Ok(jobs)
+    }
+}
+
+#[async_trait::async_trait]
+impl JobHandler for S3 {
+    async fn register(&self, job: &StatusInfo) -> anyhow::Result<String> {
+        self.write_json(&format!("jobs/{}/status.json", job.job_id), job, JSON)
+            .await?;
+
+        Ok(job.job_id.to_owned())
+    }
+
+    async fn update(&self, job: &StatusInfo) -> anyhow::Result<()> {
+        self.write_json(&format!("jobs/{}/status.json", job.job_id), job, JSON)
+            .await
+    }
+
+    async fn status(&self, id: &str) -> anyhow::Result<Option<StatusInfo>> {
+        self.read_json(&format!("jobs/{id}/status.json")).await
+    }
+
+    async fn list_jobs(&self, query: &JobQuery) -> anyhow::Result<Vec<StatusInfo>> {
+        let jobs = self
+            .jobs()
+            .await?
+            .into_iter()
+            .filter(|job| query.matches(job));
+
+        Ok(jobs
+            .skip(query.offset.unwrap_or(0))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    async fn delete(&self, id: &str) -> anyhow::Result<()> {
+        self.delete_prefix(&format!("jobs/{id}/")).await
+    }
+
+    async fn store_results(&self, id: &str, results: &JobResults) -> anyhow::Result<()> {
+        self.write_json(
+            &format!("jobs/{id}/results.json"),
+            &StoredResults::from(results),
+            JSON,
+        )
+        .await
+    }
+
+    async fn results(&self, id: &str) -> anyhow::Result<Option<JobResults>> {
+        Ok(self
+            .read_json::<StoredResults>(&format!("jobs/{id}/results.json"))
+            .await?
+            .map(JobResults::from))
+    }
+
+    async fn purge(&self, retention: Duration) -> anyhow::Result<Vec<JobResults>> {
+        let threshold = Utc::now() - chrono::Duration::from_std(retention)?;
+
+        let mut deleted = Vec::new();
+        for job in self.jobs().await? {
+            if matches!(job.status, StatusCode::Accepted | StatusCode::Running)
+                || !matches!(job.created, Some(created) if created < threshold)
+            {
+                continue;
+            }
+
+            if let Some(results) = self.results(&job.job_id).await? {
+                deleted.push(results);
+            }
+            self.delete(&job.job_id).await?;
+        }
+
+        Ok(deleted)
+    }
+}
@@ -0,0 +1,54 @@
+use ogcapi_types::{common::Collection, features::Feature};
+
+use crate::{index, CollectionMaintenance, CollectionTransactions};
+
+use super::{index::Indexed, S3};
+
+#[async_trait::async_trait]
+impl CollectionMaintenance for S3 {
+    /// Rebuilds the item index of the collection from its items along with
+    /// the extent and summaries
+    async fn refresh_collection(&self, id: &str) -> anyhow::Result<Option<Collection>> {
+        let mut collection = match self.read_collection(id).await? {
+            Some(collection) => collection,
+            None => return Ok(None),
+        };
+
+        let prefix = format!("collections/{id}/items/");
+
+        let mut index = Vec::new();
+        #[cfg(feature = "stac")]
+        let mut properties = Vec::new();
+
+        let keys = self
+            .keys(&prefix)
+            .await?
+            .into_iter()
+            .filter(|key| key.ends_with(".json"));
+        for item in self
+            .read_jsons::<Feature>(keys)
+            .await?
+            .into_iter()
+            .flatten()
+        {
+            index.push(Indexed::new(&item));
+            #[cfg(feature = "stac")]
+            properties.extend(item.properties);
+        }
+
+        let entries: Vec<_> = index
+            .iter()
+            .map(|indexed| indexed.entry.to_owned())
+            .collect();
+        self.write_index(id, index).await?;
+
+        collection.extent = index::extent(&entries);
+
+        #[cfg(feature = "stac")]
+        index::summarize(&mut collection.summaries, &properties);
+
+        self.update_collection(&collection).await?;
+
+        Ok(Some(collection))
+    }
+}
@@ -1,17 +1,48 @@
+mod artifact;
+#[cfg(feature = "stac")]
+mod catalog;
 mod collection;
 mod feature;
+mod index;
+mod job;
+mod maintenance;
+#[cfg(feature = "stac")]
+mod stac;
+mod style;
+mod tile;
+
+use std::time::Duration;
 
 use aws_sdk_s3::{
-    error::{DeleteObjectError, GetObjectError, ListObjectsError, PutObjectError},
+    error::{
+        DeleteObjectError, GetObjectError, GetObjectErrorKind, ListObjectsError, PutObjectError,
+    },
     output::{DeleteObjectOutput, GetObjectOutput, ListObjectsOutput, PutObjectOutput},
-    types::SdkError,
+    presigning::config::PresigningConfig,
     Client, Endpoint,
 };
+use futures::{stream, StreamExt, TryStreamExt};
 use http::Uri;
+use serde::{de::DeserializeOwned, Serialize};
+
+pub use aws_sdk_s3::types::{ByteStream, SdkError};
 
-pub use aws_sdk_s3::types::ByteStream;
+/// Requests in flight when reading several objects
+const CONCURRENCY: usize = 16;
 
 /// S3 driver
+///
+/// Objects are stored in the default bucket with the following layout:
+///
+/// - `collections/{collection}/collection.json`
+/// - `collections/{collection}/index/manifest.json` and
+///   `collections/{collection}/index/log/{time}-{item}.json`, the item index
+///   used for filtering
+/// - `collections/{collection}/items/{item}.json`
+/// - `catalogs/{catalog}/catalog.json`
+/// - `styles/{style}.json`
+/// - `tiles/{collection}/{tms}/{matrix}/{row}/{col}.mvt`
+/// - `jobs/{job}/status.json` and `jobs/{job}/results.json`
 #[derive(Clone)]
 pub struct S3 {
     /// S3 client
@@ -78,6 +109,40 @@ impl S3 {
             .await
     }
 
+    /// Get an object or the byte `range` of it, e.g. `bytes=0-1023`
+    pub async fn get_object_range(
+        &self,
+        bucket: impl Into<String>,
+        key: impl Into<String>,
+        range: Option<String>,
+    ) -> Result<GetObjectOutput, SdkError<GetObjectError>> {
+        self.client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .set_range(range)
+            .send()
+            .await
+    }
+
+    /// Presigned URL to get an object without credentials, valid for `expires_in`
+    pub async fn presign_get_object(
+        &self,
+        bucket: impl Into<String>,
+        key: impl Into<String>,
+        expires_in: Duration,
+    ) -> anyhow::Result<String> {
+        let request = self
+            .client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .presigned(PresigningConfig::expires_in(expires_in)?)
+            .await?;
+
+        Ok(request.uri().to_string())
+    }
+
     pub async fn delete_object(
         &self,
         bucket: impl Into<String>,
@@ -104,4 +169,114 @@ impl S3 {
 
         list_objects.send().await
     }
+
+    /// Reads and deserializes a JSON object of the default bucket, `None` if
+    /// the key does not exist
+    pub(crate) async fn read_json<T: DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> anyhow::Result<Option<T>> {
+        match self
+            .get_object(self.bucket.clone().unwrap_or_default(), key)
+            .await
+        {
+            Ok(r) => Ok(Some(serde_json::from_slice(
+                &r.body.collect().await?.into_bytes(),
+            )?)),
+            Err(e) => match e {
+                SdkError::ServiceError { err, raw: _ } => match err.kind {
+                    GetObjectErrorKind::NoSuchKey(_) => Ok(None),
+                    _ => Err(anyhow::Error::new(err)),
+                },
+                _ => Err(anyhow::Error::new(e)),
+            },
+        }
+    }
+
+    /// Reads JSON objects of the default bucket concurrently, in the order of
+    /// their keys
+    pub(crate) async fn read_jsons<T: DeserializeOwned>(
+        &self,
+        keys: impl IntoIterator<Item = String>,
+    ) -> anyhow::Result<Vec<Option<T>>> {
+        // collected up front, a closure within the stream fails the `Send`
+        // bound of the `async_trait` methods calling this
+        let reads: Vec<_> = keys
+            .into_iter()
+            .map(|key| async move { self.read_json(&key).await })
+            .collect();
+
+        stream::iter(reads)
+            .buffered(CONCURRENCY)
+            .try_collect()
+            .await
+    }
+
+    /// Serializes and writes a JSON object to the default bucket
+    pub(crate) async fn write_json(
+        &self,
+        key: &str,
+        value: &impl Serialize,
+        content_type: &str,
+    ) -> anyhow::Result<()> {
+        self.put_object(
+            self.bucket.clone().unwrap_or_default(),
+            key,
+            serde_json::to_vec(value)?,
+            Some(content_type.to_string()),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Keys of all objects of the default bucket starting with `prefix`
+    pub(crate) async fn keys(&self, prefix: &str) -> anyhow::Result<Vec<String>> {
+        self.keys_after(prefix, None).await
+    }
+
+    /// Keys of the objects of the default bucket starting with `prefix`,
+    /// ordered and following the key `marker` if given
+    pub(crate) async fn keys_after(
+        &self,
+        prefix: &str,
+        mut marker: Option<String>,
+    ) -> anyhow::Result<Vec<String>> {
+        let mut keys = Vec::new();
+
+        loop {
+            let resp = self
+                .client
+                .list_objects()
+                .bucket(self.bucket.clone().unwrap_or_default())
+                .prefix(prefix)
+                .set_marker(marker)
+                .send()
+                .await?;
+
+            keys.extend(
+                resp.contents()
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|object| object.key().map(ToString::to_string)),
+            );
+
+            if !resp.is_truncated() || keys.is_empty() {
+                break;
+            }
+            marker = keys.last().cloned();
+        }
+
+        Ok(keys)
+    }
+
+    /// Deletes all objects of the default bucket starting with `prefix`
+    pub(crate) async fn delete_prefix(&self, prefix: &str) -> anyhow::Result<()> {
+        for key in self.keys(prefix).await? {
+            self.delete_object(self.bucket.clone().unwrap_or_default(), key)
+                .await?;
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,104 @@
+use ogcapi_types::{
+    common::{Crs, Query as CollectionQuery},
+    features::{FeatureCollection, Queryables},
+    stac::{AggregationCollection, AggregationParams, SearchParams},
+};
+
+use crate::{
+    index::{self, Entry},
+    CollectionTransactions, FeatureTransactions, StacSeach,
+};
+
+use super::S3;
+
+/// Items sampled to infer the queryables of a collection
+const QUERYABLES_SAMPLE: usize = 100;
+
+impl S3 {
+    /// Index entries of the items matching a search, ordered by collection
+    /// and id
+    async fn matches(&self, query: &SearchParams) -> anyhow::Result<Vec<(String, Entry)>> {
+        if query.intersects.is_some() || query.filter.is_some() || query.query.is_some() {
+            anyhow::bail!(
+                "The S3 driver does not support the `intersects`, `filter` and `query` parameters"
+            );
+        }
+
+        let mut collections = match &query.collections {
+            Some(collections) => collections.0.to_owned(),
+            None => self
+                .list_collections(&CollectionQuery::default())
+                .await?
+                .collections
+                .into_iter()
+                .map(|collection| collection.id)
+                .collect(),
+        };
+        collections.sort();
+        collections.dedup();
+
+        let mut matches = Vec::new();
+        for collection in collections {
+            matches.extend(
+                self.read_index(&collection)
+                    .await?
+                    .into_iter()
+                    .map(|indexed| indexed.entry)
+                    .filter(|entry| match &query.ids {
+                        Some(ids) => ids.0.contains(&entry.id),
+                        None => true,
+                    })
+                    .filter(|entry| entry.matches(query.bbox.as_ref(), query.datetime.as_ref()))
+                    .map(|entry| (collection.to_owned(), entry)),
+            );
+        }
+
+        Ok(matches)
+    }
+}
+
+#[async_trait::async_trait]
+impl StacSeach for S3 {
+    async fn search(&self, query: &SearchParams) -> anyhow::Result<FeatureCollection> {
+        let matches = self.matches(query).await?;
+
+        let mut features = Vec::new();
+        for (collection, entry) in matches
+            .iter()
+            .skip(query.offset.unwrap_or(0) as usize)
+            .take(query.limit.map_or(usize::MAX, |limit| limit as usize))
+        {
+            if let Some(feature) = self
+                .read_feature(collection, &entry.id, &Crs::default())
+                .await?
+            {
+                features.push(feature);
+            }
+        }
+
+        let mut fc = FeatureCollection::new(features);
+        fc.number_matched = Some(matches.len() as u64);
+
+        Ok(fc)
+    }
+
+    async fn aggregate(
+        &self,
+        query: &SearchParams,
+        params: &AggregationParams,
+    ) -> anyhow::Result<AggregationCollection> {
+        index::aggregate(&self.matches(query).await?, params)
+    }
+
+    /// Infers the queryables of a collection from a sample of its items
+    async fn queryables(&self, collection_id: &str) -> anyhow::Result<Queryables> {
+        let properties = self
+            .read_index(collection_id)
+            .await?
+            .into_iter()
+            .take(QUERYABLES_SAMPLE)
+            .filter_map(|indexed| indexed.properties);
+
+        Ok(index::queryables(properties))
+    }
+}
@@ -0,0 +1,28 @@
+use ogcapi_types::styles::{Style, Styles};
+
+use crate::StyleTransactions;
+
+use super::S3;
+
+#[async_trait::async_trait]
+impl StyleTransactions for S3 {
+    async fn list_styles(&self) -> anyhow::Result<Styles> {
+        let styles = self
+            .keys("styles/")
+            .await?
+            .iter()
+            .filter_map(|key| key.strip_prefix("styles/")?.strip_suffix(".json"))
+            .map(|id| Style {
+                id: id.to_owned(),
+                title: None,
+                links: Vec::new(),
+            })
+            .collect();
+
+        Ok(Styles { styles })
+    }
+
+    async fn read_style(&self, id: &str) -> anyhow::Result<Option<serde_json::Value>> {
+        self.read_json(&format!("styles/{id}.json")).await
+    }
+}
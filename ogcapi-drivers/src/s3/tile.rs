@@ -0,0 +1,43 @@
+use aws_sdk_s3::{error::GetObjectErrorKind, types::SdkError};
+
+use ogcapi_types::tiles::TileMatrixSet;
+
+use crate::TileTransactions;
+
+use super::S3;
+
+#[async_trait::async_trait]
+impl TileTransactions for S3 {
+    /// Concatenates the pregenerated vector tiles of the collections, missing
+    /// tiles are empty
+    async fn tile(
+        &self,
+        collections: &str,
+        tms: &TileMatrixSet,
+        matrix: &str,
+        row: u32,
+        col: u32,
+    ) -> anyhow::Result<Vec<u8>> {
+        let mut tiles = Vec::new();
+
+        for collection in collections.split(',') {
+            let key = format!("tiles/{collection}/{}/{matrix}/{row}/{col}.mvt", tms.id);
+
+            match self
+                .get_object(self.bucket.clone().unwrap_or_default(), &key)
+                .await
+            {
+                Ok(r) => tiles.push(r.body.collect().await?.into_bytes().to_vec()),
+                Err(e) => match e {
+                    SdkError::ServiceError { err, raw: _ } => match err.kind {
+                        GetObjectErrorKind::NoSuchKey(_) => continue,
+                        _ => return Err(anyhow::Error::new(err)),
+                    },
+                    _ => return Err(anyhow::Error::new(e)),
+                },
+            }
+        }
+
+        Ok(tiles.concat())
+    }
+}
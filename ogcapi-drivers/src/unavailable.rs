@@ -0,0 +1,192 @@
+use std::{collections::HashMap, fmt};
+
+use ogcapi_types::{
+    common::Collection,
+    coverage::Coverage,
+    coverages::{DomainSet, Query as CoverageQuery, RangeType},
+    edr::{DataType, Query as EdrQuery, QueryType},
+    features::{Feature, FeatureCollection},
+};
+
+use crate::{
+    ArtifactStore, CoverageQuerier, EdrQuerier, Geoprocessing, InstanceTransactions, Operation,
+    Source,
+};
+
+/// Driver of subsystems without configured backend
+///
+/// Listings are empty and every other operation fails, so that a service
+/// without database still serves the subsystems backed by other drivers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Unavailable;
+
+/// Error of operations of the [Unavailable] driver, or of operations other
+/// drivers do not support
+#[derive(Debug, Clone, Copy)]
+pub struct UnavailableError(pub(crate) &'static str);
+
+impl fmt::Display for UnavailableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl std::error::Error for UnavailableError {}
+
+const MESSAGE: &str = "This operation requires the postgres backend";
+
+const COVERAGES_MESSAGE: &str = "Coverages require a configured raster directory";
+
+const ARTIFACTS_MESSAGE: &str = "Storing large job results requires a configured job storage";
+
+#[async_trait::async_trait]
+impl EdrQuerier for Unavailable {
+    fn is_available(&self) -> bool {
+        false
+    }
+
+    async fn query(
+        &self,
+        _collection_id: &str,
+        _query_type: &QueryType,
+        _query: &EdrQuery,
+    ) -> anyhow::Result<FeatureCollection> {
+        Err(UnavailableError(MESSAGE).into())
+    }
+
+    async fn locations(&self, _collection_id: &str) -> anyhow::Result<FeatureCollection> {
+        Ok(FeatureCollection::new(Vec::new()))
+    }
+
+    async fn location(&self, _collection_id: &str, _id: &str) -> anyhow::Result<Option<Feature>> {
+        Ok(None)
+    }
+
+    async fn put_location(
+        &self,
+        _collection_id: &str,
+        _location: &Feature,
+    ) -> anyhow::Result<String> {
+        Err(UnavailableError(MESSAGE).into())
+    }
+
+    async fn delete_location(&self, _collection_id: &str, _id: &str) -> anyhow::Result<()> {
+        Err(UnavailableError(MESSAGE).into())
+    }
+
+    async fn parameters(&self, _collection_id: &str) -> anyhow::Result<HashMap<String, DataType>> {
+        Ok(HashMap::new())
+    }
+}
+
+#[async_trait::async_trait]
+impl CoverageQuerier for Unavailable {
+    fn is_available(&self) -> bool {
+        false
+    }
+
+    async fn domain_set(&self, _collection_id: &str) -> anyhow::Result<Option<DomainSet>> {
+        Err(UnavailableError(COVERAGES_MESSAGE).into())
+    }
+
+    async fn range_type(&self, _collection_id: &str) -> anyhow::Result<Option<RangeType>> {
+        Err(UnavailableError(COVERAGES_MESSAGE).into())
+    }
+
+    async fn coverage(
+        &self,
+        _collection_id: &str,
+        _query: &CoverageQuery,
+    ) -> anyhow::Result<Option<Coverage>> {
+        Err(UnavailableError(COVERAGES_MESSAGE).into())
+    }
+
+    async fn geotiff(
+        &self,
+        _collection_id: &str,
+        _query: &CoverageQuery,
+    ) -> anyhow::Result<Option<Vec<u8>>> {
+        Err(UnavailableError(COVERAGES_MESSAGE).into())
+    }
+}
+
+#[async_trait::async_trait]
+impl InstanceTransactions for Unavailable {
+    async fn create_instance(
+        &self,
+        _collection_id: &str,
+        _instance: &Collection,
+    ) -> anyhow::Result<String> {
+        Err(UnavailableError(MESSAGE).into())
+    }
+
+    async fn read_instance(
+        &self,
+        _collection_id: &str,
+        _id: &str,
+    ) -> anyhow::Result<Option<Collection>> {
+        Ok(None)
+    }
+
+    async fn delete_instance(&self, _collection_id: &str, _id: &str) -> anyhow::Result<()> {
+        Err(UnavailableError(MESSAGE).into())
+    }
+
+    async fn create_instance_items(
+        &self,
+        _collection_id: &str,
+        _id: &str,
+        _features: &[Feature],
+    ) -> anyhow::Result<Vec<String>> {
+        Err(UnavailableError(MESSAGE).into())
+    }
+
+    async fn list_instances(&self, _collection_id: &str) -> anyhow::Result<Vec<Collection>> {
+        Ok(Vec::new())
+    }
+}
+
+#[async_trait::async_trait]
+impl Geoprocessing for Unavailable {
+    fn is_available(&self) -> bool {
+        false
+    }
+
+    async fn process(
+        &self,
+        _source: &Source,
+        _operation: &Operation,
+    ) -> anyhow::Result<FeatureCollection> {
+        Err(UnavailableError(MESSAGE).into())
+    }
+
+    async fn process_into(
+        &self,
+        _source: &Source,
+        _operation: &Operation,
+        _target: &str,
+    ) -> anyhow::Result<u64> {
+        Err(UnavailableError(MESSAGE).into())
+    }
+}
+
+/// Without artifact store nothing is stored, so there is nothing to read or delete
+#[async_trait::async_trait]
+impl ArtifactStore for Unavailable {
+    async fn put_artifact(
+        &self,
+        _key: &str,
+        _data: Vec<u8>,
+        _media_type: Option<&str>,
+    ) -> anyhow::Result<()> {
+        Err(UnavailableError(ARTIFACTS_MESSAGE).into())
+    }
+
+    async fn get_artifact(&self, _key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
+    async fn delete_artifact(&self, _key: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+//! Conformance tests shared by the backends
+
+use ogcapi_drivers::{CollectionMaintenance, CollectionTransactions, FeatureTransactions};
+use ogcapi_types::{
+    common::{Bbox, Collection, Crs, Datetime},
+    features::{Feature, Query},
+};
+
+pub fn item(id: &str, coordinates: [f64; 2], datetime: &str, kind: &str) -> Feature {
+    serde_json::from_value(serde_json::json!({
+        "id": id,
+        "collection": "test",
+        "type": "Feature",
+        "geometry": { "type": "Point", "coordinates": coordinates },
+        "properties": { "datetime": datetime, "kind": kind, "height": 10, "public": true }
+    }))
+    .unwrap()
+}
+
+/// Creates, queries and deletes the collection `test` with three items
+pub async fn collections_and_features<D>(driver: &D) -> anyhow::Result<()>
+where
+    D: CollectionTransactions + FeatureTransactions + CollectionMaintenance,
+{
+    let collection = Collection {
+        id: "test".to_string(),
+        title: Some("Test".to_string()),
+        ..Default::default()
+    };
+    driver.create_collection(&collection).await?;
+    assert_eq!(
+        driver.read_collection("test").await?.unwrap().title,
+        collection.title
+    );
+
+    driver
+        .create_feature(&item("1", [0.0, 0.0], "2022-01-01T00:00:00Z", "tree"))
+        .await?;
+    driver
+        .create_feature(&item("2", [10.0, 10.0], "2022-02-01T00:00:00Z", "tree"))
+        .await?;
+    driver
+        .create_feature(&item("3", [20.0, 20.0], "2022-03-01T00:00:00Z", "bush"))
+        .await?;
+
+    let crs = Crs::default();
+    let feature = driver.read_feature("test", "1", &crs).await?.unwrap();
+    assert_eq!(feature.properties.unwrap()["kind"], "tree");
+    assert!(driver.read_feature("test", "4", &crs).await?.is_none());
+
+    // bbox
+    let query = Query {
+        bbox: Some(Bbox::from([5.0, 5.0, 25.0, 25.0])),
+        ..Default::default()
+    };
+    let fc = driver.list_items("test", &query).await?;
+    assert_eq!(fc.number_matched, Some(2));
+
+    // datetime with paging
+    let query = Query {
+        datetime: Some("2022-01-15T00:00:00Z/..".parse::<Datetime>().unwrap()),
+        limit: Some(1),
+        offset: Some(1),
+        ..Default::default()
+    };
+    let fc = driver.list_items("test", &query).await?;
+    assert_eq!(fc.number_matched, Some(2));
+    assert_eq!(fc.features[0].id.as_deref(), Some("3"));
+
+    // deleted items leave the queries and the extent
+    driver.delete_feature("test", "3").await?;
+    let fc = driver.list_items("test", &Query::default()).await?;
+    assert_eq!(fc.number_matched, Some(2));
+    let collection = driver.refresh_collection("test").await?.unwrap();
+    let spatial = collection.extent.unwrap().spatial.unwrap();
+    assert_eq!(spatial.bbox[0], Bbox::from([0.0, 0.0, 10.0, 10.0]));
+
+    assert_eq!(
+        driver
+            .list_collections(&Default::default())
+            .await?
+            .collections
+            .len(),
+        1
+    );
+
+    driver.delete_collection("test").await?;
+    assert!(driver.read_collection("test").await?.is_none());
+    assert!(driver.read_feature("test", "1", &crs).await?.is_none());
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "fs")]
+
+use ogcapi_drivers::{fs::Fs, ArtifactStore};
+
+#[tokio::test]
+async fn fs_artifacts() -> anyhow::Result<()> {
+    let root = std::env::temp_dir().join(format!("ogcapi-fs-{}", std::process::id()));
+    let fs = Fs::new(&root);
+
+    fs.put_artifact("jobs/a/results", b"data".to_vec(), None)
+        .await?;
+    assert_eq!(
+        fs.get_artifact("jobs/a/results").await?.as_deref(),
+        Some(&b"data"[..])
+    );
+    assert!(root.join("jobs/a/results").is_file());
+
+    fs.delete_artifact("jobs/a/results").await?;
+    assert!(fs.get_artifact("jobs/a/results").await?.is_none());
+    // deleting missing artifacts is not an error
+    fs.delete_artifact("jobs/a/results").await?;
+
+    // keys must stay below the root
+    for key in [
+        "../escape",
+        "jobs/../../escape",
+        "/absolute",
+        "jobs//results",
+    ] {
+        assert!(fs.put_artifact(key, Vec::new(), None).await.is_err());
+        assert!(fs.get_artifact(key).await.is_err());
+    }
+
+    std::fs::remove_dir_all(&root)?;
+
+    Ok(())
+}
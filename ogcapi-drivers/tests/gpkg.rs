@@ -0,0 +1,136 @@
+#![cfg(feature = "gpkg")]
+
+mod common;
+
+use std::collections::HashMap;
+
+use ogcapi_drivers::{gpkg::GeoPackage, CollectionTransactions, FeatureTransactions};
+use ogcapi_types::{
+    common::{Bbox, Collection, Crs},
+    features::{Feature, Query},
+};
+
+use common::item;
+
+#[tokio::test]
+async fn gpkg_backend() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("ogcapi-{}.gpkg", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let gpkg = GeoPackage::open(&path).await?;
+
+    common::collections_and_features(&gpkg).await?;
+
+    let collection = Collection {
+        id: "test".to_string(),
+        crs: vec![Crs::default(), Crs::from_epsg(3857)],
+        ..Default::default()
+    };
+    gpkg.create_collection(&collection).await?;
+    assert!(gpkg.create_collection(&collection).await.is_err());
+
+    // features are served in the storage crs only
+    let collection = gpkg.read_collection("test").await?.unwrap();
+    assert_eq!(collection.crs, vec![Crs::default()]);
+
+    gpkg.create_feature(&item("1", [0.0, 0.0], "2022-01-01T00:00:00Z", "tree"))
+        .await?;
+    gpkg.create_feature(&item("2", [10.0, 10.0], "2022-02-01T00:00:00Z", "tree"))
+        .await?;
+    gpkg.create_feature(&item("3", [20.0, 20.0], "2022-03-01T00:00:00Z", "bush"))
+        .await?;
+
+    let feature = gpkg
+        .read_feature("test", "1", &Crs::default())
+        .await?
+        .unwrap();
+    assert_eq!(feature.properties.unwrap()["public"], true);
+    assert!(gpkg
+        .read_feature("test", "1", &Crs::from_epsg(3857))
+        .await
+        .is_err());
+
+    // properties
+    let query = Query {
+        additional_parameters: HashMap::from([
+            ("kind".to_string(), "tree".to_string()),
+            ("height".to_string(), "10.0".to_string()),
+            ("public".to_string(), "true".to_string()),
+        ]),
+        ..Default::default()
+    };
+    let fc = gpkg.list_items("test", &query).await?;
+    assert_eq!(fc.number_matched, Some(2));
+
+    // properties without a column match no features
+    let query = Query {
+        additional_parameters: HashMap::from([("missing".to_string(), "value".to_string())]),
+        ..Default::default()
+    };
+    let fc = gpkg.list_items("test", &query).await?;
+    assert_eq!(fc.number_matched, Some(0));
+
+    // reopened GeoPackages keep their content
+    drop(gpkg);
+    let gpkg = GeoPackage::open(&path).await?;
+    assert_eq!(
+        gpkg.list_items("test", &Query::default())
+            .await?
+            .number_matched,
+        Some(3)
+    );
+
+    std::fs::remove_file(&path)?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gdal_geopackage() -> anyhow::Result<()> {
+    // the R-tree triggers of GDAL call spatial SQL functions
+    let path = std::env::temp_dir().join(format!("ogcapi-gdal-{}.gpkg", std::process::id()));
+    std::fs::copy(
+        concat!(env!("CARGO_MANIFEST_DIR"), "/../data/places.gpkg"),
+        &path,
+    )?;
+
+    let gpkg = GeoPackage::open(&path).await?;
+
+    let fc = gpkg.list_items("places", &Query::default()).await?;
+    assert_eq!(fc.number_matched, Some(3));
+
+    let mut feature: Feature = serde_json::from_value(serde_json::json!({
+        "collection": "places",
+        "type": "Feature",
+        "geometry": { "type": "Point", "coordinates": [6.14, 46.2] },
+        "properties": { "NAME": "Geneva", "POP_MAX": 1240000 }
+    }))?;
+    let id = gpkg.create_feature(&feature).await?;
+
+    feature.id = Some(id.clone());
+    feature.geometry = serde_json::from_value(
+        serde_json::json!({ "type": "Point", "coordinates": [6.15, 46.21] }),
+    )?;
+    gpkg.update_feature(&feature).await?;
+
+    // the index is maintained by the driver
+    let query = Query {
+        bbox: Some(Bbox::Bbox2D([6.0, 46.0, 6.5, 46.5])),
+        ..Default::default()
+    };
+    let fc = gpkg.list_items("places", &query).await?;
+    assert_eq!(fc.number_matched, Some(1));
+    assert_eq!(
+        fc.features[0].properties.as_ref().unwrap()["NAME"],
+        "Geneva"
+    );
+
+    gpkg.delete_feature("places", &id).await?;
+    gpkg.delete_feature("places", "1").await?;
+    let fc = gpkg.list_items("places", &Query::default()).await?;
+    assert_eq!(fc.number_matched, Some(2));
+
+    std::fs::remove_file(&path)?;
+
+    Ok(())
+}
@@ -0,0 +1,188 @@
+#![cfg(feature = "memory")]
+
+mod common;
+
+use std::{collections::HashMap, time::Duration};
+
+use ogcapi_drivers::{
+    memory::Memory, unavailable::UnavailableError, CollectionMaintenance, CollectionTransactions,
+    FeatureTransactions, JobHandler, JobResults, StyleTransactions,
+};
+use ogcapi_types::{
+    common::{Bbox, Collection, Crs, Datetime},
+    features::Query,
+    processes::{JobQuery, StatusCode, StatusInfo},
+};
+
+use common::item;
+
+#[tokio::test]
+async fn memory_backend() -> anyhow::Result<()> {
+    let memory = Memory::new();
+
+    common::collections_and_features(&memory).await?;
+
+    let collection = Collection {
+        id: "test".to_string(),
+        ..Default::default()
+    };
+    memory.create_collection(&collection).await?;
+    assert!(memory.create_collection(&collection).await.is_err());
+
+    memory
+        .create_feature(&item("1", [0.0, 0.0], "2022-01-01T00:00:00Z", "tree"))
+        .await?;
+    memory
+        .create_feature(&item("2", [10.0, 10.0], "2022-02-01T00:00:00Z", "tree"))
+        .await?;
+    memory
+        .create_feature(&item("3", [20.0, 20.0], "2022-03-01T00:00:00Z", "bush"))
+        .await?;
+
+    // properties
+    let query = Query {
+        additional_parameters: HashMap::from([
+            ("kind".to_string(), "tree".to_string()),
+            ("height".to_string(), "10.0".to_string()),
+        ]),
+        ..Default::default()
+    };
+    let fc = memory.list_items("test", &query).await?;
+    assert_eq!(fc.number_matched, Some(2));
+
+    // unsupported crs and filters are unavailable
+    let crs = Crs::from_epsg(3857);
+    let err = memory.read_feature("test", "1", &crs).await.unwrap_err();
+    assert!(err.is::<UnavailableError>());
+    let query = Query {
+        bbox: Some(Bbox::from([0.0, 0.0, 1.0, 1.0])),
+        bbox_crs: crs,
+        ..Default::default()
+    };
+    let err = memory.list_items("test", &query).await.unwrap_err();
+    assert!(err.is::<UnavailableError>());
+
+    // extent, cleared with the last item
+    let collection = memory.refresh_collection("test").await?.unwrap();
+    let spatial = collection.extent.unwrap().spatial.unwrap();
+    assert_eq!(spatial.bbox[0], Bbox::from([0.0, 0.0, 20.0, 20.0]));
+
+    for id in ["1", "2", "3"] {
+        memory.delete_feature("test", id).await?;
+    }
+    let collection = memory.refresh_collection("test").await?.unwrap();
+    assert!(collection.extent.is_none());
+
+    // jobs
+    let job = StatusInfo {
+        job_id: "job".to_string(),
+        process_id: Some("greet".to_string()),
+        ..Default::default()
+    };
+    memory.register(&job).await?;
+    let query = JobQuery {
+        process_id: Some("greet".parse().unwrap()),
+        ..Default::default()
+    };
+    assert_eq!(memory.list_jobs(&query).await?.len(), 1);
+    JobHandler::delete(&memory, "job").await?;
+    assert!(memory.status("job").await?.is_none());
+
+    // styles
+    memory.insert_style("night", serde_json::json!({ "version": 8 }));
+    assert_eq!(memory.list_styles().await?.styles.len(), 1);
+    assert!(memory.read_style("night").await?.is_some());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_from_dir() -> anyhow::Result<()> {
+    let dir = std::env::temp_dir().join(format!("ogcapi-memory-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(
+        dir.join("places.geojson"),
+        serde_json::to_vec(&serde_json::json!({
+            "type": "FeatureCollection",
+            "name": "Places",
+            "features": [
+                { "type": "Feature", "id": 7, "geometry": { "type": "Point", "coordinates": [1.0, 2.0] }, "properties": {} },
+                { "type": "Feature", "geometry": { "type": "Point", "coordinates": [3.0, 4.0] }, "properties": {} }
+            ]
+        }))?,
+    )?;
+
+    let memory = Memory::from_dir(&dir).await?;
+    std::fs::remove_dir_all(&dir)?;
+
+    let collection = memory.read_collection("places").await?.unwrap();
+    assert_eq!(collection.title.as_deref(), Some("Places"));
+    let spatial = collection.extent.unwrap().spatial.unwrap();
+    assert_eq!(spatial.bbox[0], Bbox::from([1.0, 2.0, 3.0, 4.0]));
+
+    let crs = Default::default();
+    assert!(memory.read_feature("places", "7", &crs).await?.is_some());
+    assert!(memory.read_feature("places", "2", &crs).await?.is_some());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn memory_jobs() -> anyhow::Result<()> {
+    let memory = Memory::new();
+
+    let jobs = [
+        ("a", "greet", StatusCode::Successful, "2022-01-01T00:00:00Z"),
+        ("b", "greet", StatusCode::Running, "2022-02-01T00:00:00Z"),
+        ("c", "buffer", StatusCode::Failed, "2022-03-01T00:00:00Z"),
+    ];
+    for (id, process, status, created) in jobs {
+        let job = StatusInfo {
+            job_id: id.to_string(),
+            process_id: Some(process.to_string()),
+            status,
+            created: Some(created.parse()?),
+            ..Default::default()
+        };
+        memory.register(&job).await?;
+    }
+    let results = JobResults::Artifact {
+        key: "jobs/a/results".to_string(),
+        media_type: None,
+    };
+    memory.store_results("a", &results).await?;
+
+    // filters, most recently created first
+    let ids = |jobs: Vec<StatusInfo>| jobs.into_iter().map(|j| j.job_id).collect::<Vec<_>>();
+    let query = JobQuery {
+        process_id: Some("greet".parse().unwrap()),
+        ..Default::default()
+    };
+    assert_eq!(ids(memory.list_jobs(&query).await?), ["b", "a"]);
+    let query = JobQuery {
+        status: Some("successful,failed".parse().unwrap()),
+        ..Default::default()
+    };
+    assert_eq!(ids(memory.list_jobs(&query).await?), ["c", "a"]);
+    let query = JobQuery {
+        datetime: Some("2022-01-15T00:00:00Z/..".parse::<Datetime>().unwrap()),
+        ..Default::default()
+    };
+    assert_eq!(ids(memory.list_jobs(&query).await?), ["c", "b"]);
+    let query = JobQuery {
+        limit: Some(1),
+        offset: Some(1),
+        ..Default::default()
+    };
+    assert_eq!(ids(memory.list_jobs(&query).await?), ["b"]);
+
+    // retention, running jobs are kept
+    let purged = memory.purge(Duration::from_secs(3600)).await?;
+    assert!(matches!(&purged[..], [JobResults::Artifact { key, .. }] if key == "jobs/a/results"));
+    assert!(memory.status("a").await?.is_none());
+    assert!(memory.status("b").await?.is_some());
+    assert!(memory.status("c").await?.is_none());
+    assert!(memory.purge(Duration::from_secs(3600)).await?.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,183 @@
+#![cfg(feature = "s3")]
+
+mod common;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use aws_sdk_s3::{Client, Config, Credentials, Endpoint, Region};
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+
+use ogcapi_drivers::{
+    s3::S3, unavailable::UnavailableError, CollectionMaintenance, CollectionTransactions,
+    FeatureTransactions,
+};
+use ogcapi_types::{
+    common::{Bbox, Collection, Crs},
+    features::Query,
+};
+
+use common::item;
+
+type Objects = Arc<Mutex<BTreeMap<String, Vec<u8>>>>;
+
+/// Minimal S3 compatible stand-in serving path-style object requests and
+/// object listings from memory
+async fn handle(req: Request<Body>, objects: Objects) -> Result<Response<Body>, Infallible> {
+    let path = req.uri().path().trim_start_matches('/').to_owned();
+    let query = req.uri().query().unwrap_or_default().to_owned();
+    let method = req.method().to_owned();
+
+    let response = match path.split_once('/').filter(|(_, key)| !key.is_empty()) {
+        Some((_, key)) => match method {
+            Method::PUT => {
+                let data = hyper::body::to_bytes(req.into_body()).await.unwrap();
+                objects
+                    .lock()
+                    .unwrap()
+                    .insert(key.to_owned(), data.to_vec());
+                Response::new(Body::empty())
+            }
+            Method::GET => match objects.lock().unwrap().get(key) {
+                Some(data) => Response::new(Body::from(data.to_owned())),
+                None => Response::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .body(Body::from(
+                        "<Error><Code>NoSuchKey</Code><Message>Not found</Message></Error>",
+                    ))
+                    .unwrap(),
+            },
+            Method::DELETE => {
+                objects.lock().unwrap().remove(key);
+                Response::builder()
+                    .status(StatusCode::NO_CONTENT)
+                    .body(Body::empty())
+                    .unwrap()
+            }
+            _ => Response::builder()
+                .status(StatusCode::METHOD_NOT_ALLOWED)
+                .body(Body::empty())
+                .unwrap(),
+        },
+        None => {
+            let param = |name: &str| {
+                query
+                    .split('&')
+                    .find_map(|p| p.strip_prefix(name))
+                    .unwrap_or_default()
+                    .replace("%2F", "/")
+            };
+            let (prefix, marker) = (param("prefix="), param("marker="));
+            let contents: String = objects
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(key, _)| key.starts_with(&prefix) && **key > marker)
+                .map(|(key, data)| {
+                    format!(
+                        "<Contents><Key>{key}</Key><Size>{}</Size></Contents>",
+                        data.len()
+                    )
+                })
+                .collect();
+            Response::new(Body::from(format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?><ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/"><Name>ogcapi</Name><Prefix>{prefix}</Prefix><MaxKeys>1000</MaxKeys><IsTruncated>false</IsTruncated>{contents}</ListBucketResult>"#
+            )))
+        }
+    };
+
+    Ok(response)
+}
+
+async fn spawn_s3() -> (S3, Objects) {
+    let objects: Objects = Default::default();
+
+    let service_objects = objects.clone();
+    let server =
+        Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service_fn(move |_| {
+            let objects = service_objects.clone();
+            async move { Ok::<_, Infallible>(service_fn(move |req| handle(req, objects.clone()))) }
+        }));
+    let addr = server.local_addr();
+    tokio::spawn(server);
+
+    let config = Config::builder()
+        .region(Region::new("us-east-1"))
+        .credentials_provider(Credentials::new("ogcapi", "ogcapi", None, None, "test"))
+        .endpoint_resolver(Endpoint::immutable(
+            format!("http://{addr}").parse().unwrap(),
+        ))
+        .build();
+
+    let mut s3 = S3::new_with(Client::from_conf(config)).await;
+    s3.set_default_bucket("ogcapi");
+
+    (s3, objects)
+}
+
+#[tokio::test]
+async fn s3_backend() -> anyhow::Result<()> {
+    let (s3, objects) = spawn_s3().await;
+
+    common::collections_and_features(&s3).await?;
+    assert!(objects.lock().unwrap().is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn s3_index() -> anyhow::Result<()> {
+    let (s3, objects) = spawn_s3().await;
+
+    let collection = Collection {
+        id: "test".to_string(),
+        ..Default::default()
+    };
+    s3.create_collection(&collection).await?;
+
+    s3.create_feature(&item("1", [0.0, 0.0], "2022-01-01T00:00:00Z", "tree"))
+        .await?;
+    s3.create_feature(&item("2", [10.0, 10.0], "2022-02-01T00:00:00Z", "tree"))
+        .await?;
+
+    // the index and extent are rebuilt from the items
+    objects
+        .lock()
+        .unwrap()
+        .retain(|key, _| !key.starts_with("collections/test/index/"));
+    assert_eq!(
+        s3.list_items("test", &Query::default())
+            .await?
+            .number_matched,
+        Some(0)
+    );
+    let collection = s3.refresh_collection("test").await?.unwrap();
+    let spatial = collection.extent.unwrap().spatial.unwrap();
+    assert_eq!(spatial.bbox[0], Bbox::from([0.0, 0.0, 10.0, 10.0]));
+    let fc = s3.list_items("test", &Query::default()).await?;
+    assert_eq!(fc.number_matched, Some(2));
+
+    // properties are filtered on the index
+    s3.create_feature(&item("3", [20.0, 20.0], "2022-03-01T00:00:00Z", "bush"))
+        .await?;
+    let query = Query {
+        additional_parameters: HashMap::from([("kind".to_string(), "tree".to_string())]),
+        ..Default::default()
+    };
+    let fc = s3.list_items("test", &query).await?;
+    assert_eq!(fc.number_matched, Some(2));
+
+    // unsupported crs are unavailable
+    let crs = Crs::from_epsg(3857);
+    let err = s3.read_feature("test", "1", &crs).await.unwrap_err();
+    assert!(err.is::<UnavailableError>());
+
+    Ok(())
+}
@@ -1,3 +1,5 @@
+use std::{fmt, str::FromStr};
+
 use clap::Parser;
 
 /// Application configuration
@@ -9,10 +11,216 @@ pub struct Config {
     /// istening host address of the server
     #[clap(long, env("APP_HOST"), default_value = "0.0.0.0")]
     pub host: String,
-    /// Postgres database url
+    /// Postgres database url, required by the `postgres` backend
     #[clap(long, env, hide_env_values = true, parse(try_from_str))]
-    pub database_url: url::Url,
+    pub database_url: Option<url::Url>,
+    /// GeoPackage or directory of GeoJSON files served as collections
+    #[clap(long, env, parse(from_os_str))]
+    pub source: Option<std::path::PathBuf>,
+    /// Backend of all subsystems unless overridden, `memory` if a `source` is
+    /// served and `postgres` otherwise
+    #[clap(long, env, parse(try_from_str))]
+    pub backend: Option<Backend>,
+    /// Backend of collections
+    #[clap(long, env, parse(try_from_str))]
+    pub collections_backend: Option<Backend>,
+    /// Backend of features
+    #[cfg(feature = "features")]
+    #[clap(long, env, parse(try_from_str))]
+    pub features_backend: Option<Backend>,
+    /// Backend of STAC search
+    #[cfg(feature = "stac")]
+    #[clap(long, env, parse(try_from_str))]
+    pub search_backend: Option<Backend>,
+    /// Backend of jobs
+    #[cfg(feature = "processes")]
+    #[clap(long, env, parse(try_from_str))]
+    pub jobs_backend: Option<Backend>,
+    /// Backend of styles
+    #[cfg(feature = "styles")]
+    #[clap(long, env, parse(try_from_str))]
+    pub styles_backend: Option<Backend>,
+    /// Backend of tiles
+    #[cfg(feature = "tiles")]
+    #[clap(long, env, parse(try_from_str))]
+    pub tiles_backend: Option<Backend>,
+    /// Bucket of the `s3` backend
+    #[cfg(feature = "stac")]
+    #[clap(long, env)]
+    pub s3_bucket: Option<String>,
     /// OpenAPI definition
     #[clap(long, env, parse(from_os_str))]
     pub openapi: Option<std::path::PathBuf>,
+    /// Directory with gridded data of EDR and coverage collections (`{collection_id}.{ext}`),
+    /// coverages are unavailable without
+    #[cfg(any(feature = "raster", feature = "coverages"))]
+    #[clap(long, env, parse(from_os_str))]
+    pub raster_dir: Option<std::path::PathBuf>,
+    /// Directory with declarative command process definitions (YAML/JSON)
+    #[cfg(feature = "processes")]
+    #[clap(long, env, parse(from_os_str))]
+    pub processes_dir: Option<std::path::PathBuf>,
+    /// Storage for large job results, a directory or `s3://{bucket}` url,
+    /// required for large results unless jobs are kept in memory
+    #[cfg(feature = "processes")]
+    #[clap(long, env)]
+    pub job_storage: Option<String>,
+    /// Retention period of finished jobs and their results in hours
+    #[cfg(feature = "processes")]
+    #[clap(long, env)]
+    pub job_retention: Option<u64>,
+    /// Rewrite S3 asset hrefs into presigned URLs (`presign`) or links to the asset proxy (`proxy`)
+    #[cfg(feature = "stac")]
+    #[clap(long, env, value_enum)]
+    pub asset_hrefs: Option<AssetHrefs>,
+    /// Bucket of assets referenced by a bare key as `href`
+    #[cfg(feature = "stac")]
+    #[clap(long, env = "AWS_S3_BUCKET_NAME")]
+    pub asset_bucket: Option<String>,
+    /// Further buckets whose assets may be served, besides the asset bucket
+    #[cfg(feature = "stac")]
+    #[clap(long, env, value_delimiter = ',')]
+    pub asset_buckets: Vec<String>,
+}
+
+impl Config {
+    /// Configured backend of a subsystem or the default backend, `memory` if
+    /// a `source` is served and `postgres` otherwise
+    pub(crate) fn backend_of(&self, backend: Option<Backend>) -> Backend {
+        backend.or(self.backend).unwrap_or(match self.source {
+            Some(_) => Backend::Memory,
+            None => Backend::Postgres,
+        })
+    }
+
+    /// Configured backend of a subsystem serving the `source`, which is the
+    /// `gpkg` backend for a GeoPackage and the preloaded `memory` backend for
+    /// a directory
+    pub(crate) fn source_backend_of(&self, backend: Option<Backend>) -> Backend {
+        self.backend_of(backend.or(match &self.source {
+            Some(path) if path.is_dir() => Some(Backend::Memory),
+            Some(_) => Some(Backend::GeoPackage),
+            None => None,
+        }))
+    }
+
+    /// Backend of items, i.e. of the features
+    fn items_backend(&self) -> Backend {
+        #[cfg(feature = "features")]
+        return self.source_backend_of(self.features_backend);
+        #[cfg(not(feature = "features"))]
+        return self.source_backend_of(self.collections_backend);
+    }
+
+    /// Configured backend of STAC search or the backend of the items it
+    /// searches, GeoPackages are not searchable
+    #[cfg(feature = "stac")]
+    pub(crate) fn search_backend_of(&self) -> Backend {
+        match (self.search_backend, self.items_backend()) {
+            (Some(backend), _) => backend,
+            (None, Backend::GeoPackage) => self.backend_of(None),
+            (None, items) => items,
+        }
+    }
+
+    /// Checks that the backends of the subsystems support them and that
+    /// subsystems sharing data share their backend
+    pub fn check_backends(&self) -> anyhow::Result<()> {
+        let collections = self.source_backend_of(self.collections_backend);
+        let items = self.items_backend();
+        if items != collections {
+            anyhow::bail!(
+                "Features are stored with their collections, the features backend `{items}` \
+                differs from the collections backend `{collections}`"
+            );
+        }
+
+        let mut backends = vec![("collections", collections)];
+
+        #[cfg(feature = "stac")]
+        {
+            let search = self.search_backend_of();
+            if items != Backend::GeoPackage && search != items {
+                anyhow::bail!(
+                    "STAC search runs on the features, the search backend `{search}` \
+                    differs from the features backend `{items}`"
+                );
+            }
+            backends.push(("search", search));
+        }
+        #[cfg(feature = "processes")]
+        backends.push(("jobs", self.backend_of(self.jobs_backend)));
+        #[cfg(feature = "styles")]
+        backends.push(("styles", self.backend_of(self.styles_backend)));
+        #[cfg(feature = "tiles")]
+        backends.push(("tiles", self.backend_of(self.tiles_backend)));
+
+        for (subsystem, backend) in backends {
+            match backend {
+                Backend::Postgres if self.database_url.is_none() => {
+                    anyhow::bail!("The postgres backend of {subsystem} requires a database url")
+                }
+                Backend::GeoPackage if subsystem != "collections" => anyhow::bail!(
+                    "The gpkg backend only supports collections and features, not {subsystem}"
+                ),
+                Backend::GeoPackage if self.source.is_none() => {
+                    anyhow::bail!("The gpkg backend of {subsystem} requires a source")
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Storage backend of a subsystem
+///
+/// EDR, instances and geoprocessing are only supported by `postgres`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    Postgres,
+    /// Volatile in-memory storage
+    Memory,
+    /// GeoPackage of the `source`, only supported by collections and features
+    GeoPackage,
+    #[cfg(feature = "stac")]
+    S3,
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "postgres" => Ok(Backend::Postgres),
+            "memory" => Ok(Backend::Memory),
+            "gpkg" => Ok(Backend::GeoPackage),
+            #[cfg(feature = "stac")]
+            "s3" => Ok(Backend::S3),
+            _ => Err(format!("Unknown backend `{s}`")),
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backend::Postgres => write!(f, "postgres"),
+            Backend::Memory => write!(f, "memory"),
+            Backend::GeoPackage => write!(f, "gpkg"),
+            #[cfg(feature = "stac")]
+            Backend::S3 => write!(f, "s3"),
+        }
+    }
+}
+
+/// Rewriting of S3 asset hrefs
+#[cfg(feature = "stac")]
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssetHrefs {
+    /// Presigned URLs to the objects
+    Presign,
+    /// Links to the asset routes of the service, which stream the objects
+    Proxy,
 }
@@ -5,6 +5,7 @@ use axum::{
     Json,
 };
 
+use ogcapi_drivers::unavailable::UnavailableError;
 use ogcapi_types::common::{media_type::PROBLEM_JSON, Exception};
 
 /// A common error type that can be used throughout the API.
@@ -20,7 +21,8 @@ pub enum Error {
     #[error("not found")]
     NotFound,
 
-    /// Return `500 Internal Server Error` on a `anyhow::Error`.
+    /// Return `500 Internal Server Error` on a `anyhow::Error`, `501 Not
+    /// Implemented` for operations of unavailable drivers.
     #[error("an internal server error occurred")]
     Anyhow(#[from] anyhow::Error),
 
@@ -42,6 +44,7 @@ impl Error {
         match self {
             Self::NotFound => StatusCode::NOT_FOUND,
             Self::Exception(status, _) => *status,
+            Self::Anyhow(e) if e.is::<UnavailableError>() => StatusCode::NOT_IMPLEMENTED,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -60,6 +63,10 @@ impl IntoResponse for Error {
             //     (self.status_code(), self.to_string())
             // }
             Self::NotFound => (self.status_code(), self.to_string()),
+            Self::Anyhow(ref e) if e.is::<UnavailableError>() => {
+                tracing::debug!("Unavailable: {}", e);
+                (self.status_code(), e.to_string())
+            }
             Self::Anyhow(ref e) => {
                 tracing::error!("Generic error: {:?}", e);
                 (self.status_code(), self.to_string())
@@ -9,14 +9,19 @@ mod service;
 mod state;
 pub mod telemetry;
 
-pub use config::Config;
+#[cfg(feature = "stac")]
+pub use config::AssetHrefs;
+pub use config::{Backend, Config};
 pub use error::Error;
 pub use openapi::OpenAPI;
 pub use service::Service;
-pub use state::State;
+pub use state::{Drivers, State};
 
 #[cfg(feature = "processes")]
-pub use processor::{Greeter, Processor};
+pub use processor::{
+    Buffer, Centroid, Clip, CommandDefinition, CommandInput, CommandOutput, CommandProcessor,
+    Dissolve, Greeter, InputMode, Processor, Reproject, Simplify, SpatialJoin,
+};
 
 #[doc(hidden)]
 pub use clap::Parser as ConfigParser;
@@ -7,7 +7,7 @@ async fn main() -> anyhow::Result<()> {
     ogcapi_services::telemetry::init();
 
     // build & run our application with hyper
-    ogcapi_services::Service::new().await.serve().await;
+    ogcapi_services::Service::new().await?.serve().await;
 
     Ok(())
 }
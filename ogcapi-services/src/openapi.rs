@@ -1,5 +1,10 @@
 use std::{fs, path::Path, str::FromStr};
 
+#[cfg(feature = "processes")]
+use ogcapi_types::processes::Process;
+#[cfg(feature = "processes")]
+use serde_json::{json, Map, Value};
+
 #[doc(hidden)]
 pub static OPENAPI: &[u8; 29696] = include_bytes!("../openapi.yaml");
 
@@ -26,3 +31,226 @@ impl OpenAPI {
         OpenAPI::from_str(&api)
     }
 }
+
+#[cfg(feature = "processes")]
+impl OpenAPI {
+    /// Adds an execution path for each process, documenting its inputs and
+    /// outputs with the schemas of the process description.
+    pub fn processes(&mut self, processes: &[Process]) {
+        for process in processes {
+            let id = &process.summary.id;
+
+            let mut definitions = Map::new();
+            let mut inputs = process.inputs.schema.to_owned();
+            let mut outputs = process.outputs.schema.to_owned();
+            to_oas30(&mut inputs, id, &mut definitions);
+            to_oas30(&mut outputs, id, &mut definitions);
+            input_forms(&mut inputs, &mut definitions);
+
+            let summary = process
+                .summary
+                .description_type
+                .title
+                .to_owned()
+                .unwrap_or_else(|| format!("Execute process `{id}`"));
+
+            let path = json!({
+                "post": {
+                    "tags": ["Processes"],
+                    "summary": summary,
+                    "operationId": format!("execute_{}", id.replace(|c: char| !c.is_alphanumeric(), "_")),
+                    "parameters": [
+                        {
+                            "name": "response",
+                            "in": "query",
+                            "description": "Set to `collection` to expose the result as virtual collection",
+                            "required": false,
+                            "schema": { "type": "string", "enum": ["collection"] }
+                        },
+                        {
+                            "name": "Prefer",
+                            "in": "header",
+                            "description": "Set to `respond-async` to execute asynchronously as job",
+                            "required": false,
+                            "schema": { "type": "string" }
+                        }
+                    ],
+                    "requestBody": {
+                        "required": true,
+                        "content": {
+                            "application/json": {
+                                "schema": {
+                                    "type": "object",
+                                    "required": ["inputs"],
+                                    "properties": {
+                                        "inputs": inputs,
+                                        "outputs": { "type": "object" },
+                                        "response": { "type": "string", "enum": ["raw", "document"] },
+                                        "subscriber": { "type": "object" }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    "responses": {
+                        "200": {
+                            "description": "Result of the synchronous execution",
+                            "content": {
+                                "application/json": { "schema": outputs }
+                            }
+                        },
+                        "201": {
+                            "description": "Job or collection output created, see `Location` header"
+                        },
+                        "400": { "description": "Invalid inputs" },
+                        "404": { "description": "Process not found" },
+                        "500": { "description": "Execution failed" }
+                    }
+                }
+            });
+
+            let components = self.0.components.get_or_insert_with(Default::default);
+
+            let schemas = definitions
+                .into_iter()
+                .map(|(name, schema)| Ok((name, serde_json::from_value(schema)?)))
+                .collect::<Result<Vec<_>, serde_json::Error>>();
+
+            match (schemas, serde_json::from_value(path)) {
+                (Ok(schemas), Ok(path)) => {
+                    components.schemas.extend(schemas);
+                    self.0
+                        .paths
+                        .paths
+                        .insert(format!("/processes/{id}/execution"), path);
+                }
+                (Err(e), _) | (_, Err(e)) => {
+                    tracing::warn!("Unable to document process `{}`: {}", id, e)
+                }
+            }
+        }
+    }
+}
+
+/// Documents the alternative forms of each input besides its plain value, a
+/// qualified value, the output of a nested process and a collection reference.
+#[cfg(feature = "processes")]
+fn input_forms(inputs: &mut Value, definitions_out: &mut Map<String, Value>) {
+    let properties = match inputs.get_mut("properties") {
+        Some(Value::Object(properties)) => properties,
+        _ => return,
+    };
+
+    for schema in properties.values_mut() {
+        let value = schema.take();
+        *schema = json!({
+            "oneOf": [
+                value.clone(),
+                {
+                    "type": "object",
+                    "required": ["value"],
+                    "properties": {
+                        "value": value,
+                        "mediaType": { "type": "string" },
+                        "encoding": { "type": "string" },
+                        "schema": {}
+                    }
+                },
+                { "$ref": "#/components/schemas/processInput" },
+                { "$ref": "#/components/schemas/collectionInput" }
+            ]
+        });
+    }
+
+    definitions_out.insert(
+        "processInput".to_string(),
+        json!({
+            "description": "Output of a nested process execution",
+            "type": "object",
+            "required": ["process"],
+            "properties": {
+                "process": { "type": "string", "format": "uri" },
+                "inputs": { "type": "object" },
+                "outputs": { "type": "object" }
+            }
+        }),
+    );
+    definitions_out.insert(
+        "collectionInput".to_string(),
+        json!({
+            "description": "Items of a collection",
+            "type": "object",
+            "required": ["collection"],
+            "properties": {
+                "collection": { "type": "string", "format": "uri" }
+            }
+        }),
+    );
+}
+
+/// Converts a JSON schema (as generated by `schemars`) to an OpenAPI 3.0 schema,
+/// moving `definitions` of process `id` to `definitions_out`.
+#[cfg(feature = "processes")]
+fn to_oas30(schema: &mut Value, id: &str, definitions_out: &mut Map<String, Value>) {
+    match schema {
+        Value::Bool(true) => *schema = json!({}),
+        Value::Bool(false) => *schema = json!({ "not": {} }),
+        Value::Object(object) => {
+            object.remove("$schema");
+
+            if let Some(Value::Object(definitions)) = object.remove("definitions") {
+                for (name, mut definition) in definitions {
+                    to_oas30(&mut definition, id, definitions_out);
+                    definitions_out.insert(format!("{id}.{name}"), definition);
+                }
+            }
+
+            if let Some(Value::String(reference)) = object.get_mut("$ref") {
+                if let Some(name) = reference.strip_prefix("#/definitions/") {
+                    *reference = format!("#/components/schemas/{id}.{name}");
+                }
+            }
+
+            // `"type": ["string", "null"]` -> `"type": "string", "nullable": true`
+            if let Some(Value::Array(types)) = object.get("type") {
+                let mut types = types.to_owned();
+                if types.contains(&json!("null")) {
+                    types.retain(|t| t != "null");
+                    object.insert("nullable".to_string(), Value::Bool(true));
+                }
+                match types.len() {
+                    1 => {
+                        object.insert("type".to_string(), types.remove(0));
+                    }
+                    _ => {
+                        object.remove("type");
+                    }
+                }
+            }
+
+            for (key, value) in object.iter_mut() {
+                match key.as_str() {
+                    "properties" | "patternProperties" => {
+                        if let Value::Object(properties) = value {
+                            properties
+                                .values_mut()
+                                .for_each(|p| to_oas30(p, id, definitions_out));
+                        }
+                    }
+                    "items" | "additionalProperties" | "not" => {
+                        to_oas30(value, id, definitions_out)
+                    }
+                    "allOf" | "anyOf" | "oneOf" => {
+                        if let Value::Array(schemas) = value {
+                            schemas
+                                .iter_mut()
+                                .for_each(|s| to_oas30(s, id, definitions_out));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,426 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::Duration,
+};
+
+use anyhow::Context;
+use axum::{
+    async_trait,
+    http::{header::CONTENT_TYPE, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use url::Url;
+
+use ogcapi_types::{
+    common::media_type::JSON,
+    processes::{Execute, Process},
+};
+
+use crate::{Error, Result, State};
+
+use super::{inputs, Processor};
+
+/// Declarative definition of a process backed by a command line tool
+///
+/// ```yaml
+/// id: word-count
+/// command: wc
+/// args: ["-w", "{text}"]
+/// inputs:
+///   text:
+///     mode: file
+///     schema:
+///       type: string
+/// outputs:
+///   count:
+///     media_type: text/plain
+/// timeout: 10
+/// ```
+#[derive(Deserialize, Debug, Clone)]
+pub struct CommandDefinition {
+    /// Process id
+    pub id: String,
+    #[serde(default = "version")]
+    pub version: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Program to execute, resolved with the `PATH` of the server
+    pub command: String,
+    /// Arguments, `{input}` placeholders are replaced with the input value
+    /// or, in `file` mode, with the path of the file holding it
+    ///
+    /// Values must not start an argument with `-` unless they follow a `--`
+    /// argument, so that they are not mistaken for options of the command.
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub inputs: BTreeMap<String, CommandInput>,
+    #[serde(default)]
+    pub outputs: BTreeMap<String, CommandOutput>,
+    /// Timeout in seconds
+    #[serde(default = "timeout")]
+    pub timeout: u64,
+}
+
+/// Input of a command process
+#[derive(Deserialize, Debug, Clone)]
+pub struct CommandInput {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// JSON schema of the input value
+    #[serde(default)]
+    pub schema: Value,
+    /// How the value is passed to the command
+    #[serde(default)]
+    pub mode: InputMode,
+    /// Value used if the input is omitted, inputs without default are required
+    pub default: Option<Value>,
+}
+
+/// Passing of input values to the command
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum InputMode {
+    /// Value as argument
+    #[default]
+    Argument,
+    /// Value written to a file in the working directory
+    File,
+}
+
+/// Output of a command process
+#[derive(Deserialize, Debug, Clone)]
+pub struct CommandOutput {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// JSON schema of the output value
+    #[serde(default)]
+    pub schema: Value,
+    /// File in the working directory holding the output, `stdout` if omitted
+    pub file: Option<String>,
+    #[serde(default = "media_type")]
+    pub media_type: String,
+}
+
+fn version() -> String {
+    "0.1.0".to_string()
+}
+
+fn timeout() -> u64 {
+    60
+}
+
+fn media_type() -> String {
+    "text/plain".to_string()
+}
+
+/// Processor executing a command line tool as local subprocess
+///
+/// Each execution runs in a fresh working directory with a cleared environment
+/// (except for `PATH`) and is killed when exceeding the timeout.
+#[derive(Debug, Clone)]
+pub struct CommandProcessor {
+    definition: CommandDefinition,
+}
+
+impl CommandProcessor {
+    /// Creates a processor, fails for definitions with invalid media types
+    pub fn new(definition: CommandDefinition) -> anyhow::Result<Self> {
+        for (id, output) in &definition.outputs {
+            HeaderValue::from_str(&output.media_type).with_context(|| {
+                format!(
+                    "Invalid media type `{}` of output `{id}`",
+                    output.media_type
+                )
+            })?;
+        }
+
+        Ok(CommandProcessor { definition })
+    }
+
+    /// Reads a definition from a YAML or JSON file
+    pub fn from_path(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read(path)?;
+
+        let definition = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_slice(&content)?,
+            _ => serde_yaml::from_slice(&content)?,
+        };
+
+        CommandProcessor::new(definition)
+    }
+
+    /// Reads all definitions (`*.yaml`, `*.yml`, `*.json`) in a directory,
+    /// invalid definitions are logged and skipped
+    pub fn from_dir(dir: impl AsRef<Path>) -> anyhow::Result<Vec<Self>> {
+        let mut processors = Vec::new();
+
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if let Some("yaml" | "yml" | "json") = path.extension().and_then(|e| e.to_str()) {
+                match CommandProcessor::from_path(&path) {
+                    Ok(processor) => processors.push(processor),
+                    Err(e) => tracing::error!(
+                        "Skipping invalid process definition `{}`: {:?}",
+                        path.display(),
+                        e
+                    ),
+                }
+            }
+        }
+
+        Ok(processors)
+    }
+
+    /// Replaces `{input}` placeholders in an argument in a single pass, so
+    /// that placeholders in substituted values are kept as is
+    fn arg(&self, arg: &str, values: &HashMap<String, String>) -> String {
+        let mut result = String::with_capacity(arg.len());
+        let mut rest = arg;
+
+        while let Some(start) = rest.find('{') {
+            result.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let value = rest
+                .find('}')
+                .and_then(|end| values.get(&rest[1..end]).map(|value| (end, value)));
+
+            match value {
+                Some((end, value)) => {
+                    result.push_str(value);
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    result.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        result.push_str(rest);
+
+        result
+    }
+
+    async fn run(&self, dir: &Path, inputs: Map<String, Value>) -> Result<Response> {
+        let definition = &self.definition;
+
+        // prepare input values
+        let mut values = HashMap::new();
+        for (id, input) in &definition.inputs {
+            let value = match inputs.get(id).or(input.default.as_ref()) {
+                Some(value) => value,
+                None => {
+                    return Err(Error::Exception(
+                        StatusCode::BAD_REQUEST,
+                        format!("Missing input `{id}`"),
+                    ))
+                }
+            };
+
+            let value = match value {
+                Value::String(s) => s.to_owned(),
+                value => value.to_string(),
+            };
+
+            let value = match input.mode {
+                InputMode::Argument => value,
+                InputMode::File => {
+                    let path = dir.join(id);
+                    tokio::fs::write(&path, value)
+                        .await
+                        .map_err(anyhow::Error::new)?;
+                    path.to_string_lossy().into_owned()
+                }
+            };
+
+            values.insert(id.to_owned(), value);
+        }
+
+        let mut args = Vec::with_capacity(definition.args.len());
+        let mut options = true;
+        for arg in &definition.args {
+            let value = self.arg(arg, &values);
+
+            // values must not be mistaken for options of the command
+            if options && value.starts_with('-') && !arg.starts_with('-') {
+                return Err(Error::Exception(
+                    StatusCode::BAD_REQUEST,
+                    format!("Argument `{value}` must not start with `-`"),
+                ));
+            }
+            if arg == "--" {
+                options = false;
+            }
+
+            args.push(value);
+        }
+
+        // execute
+        let mut command = tokio::process::Command::new(&definition.command);
+        command
+            .args(&args)
+            .current_dir(dir)
+            .env_clear()
+            .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+            .env("HOME", dir)
+            .env("TMPDIR", dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let output =
+            tokio::time::timeout(Duration::from_secs(definition.timeout), command.output())
+                .await
+                .map_err(|_| {
+                    Error::Exception(
+                        StatusCode::GATEWAY_TIMEOUT,
+                        format!(
+                            "Execution of `{}` timed out after {} seconds",
+                            definition.id, definition.timeout
+                        ),
+                    )
+                })?
+                .map_err(|e| anyhow::anyhow!("Failed to execute `{}`: {e}", definition.command))?;
+
+        if !output.status.success() {
+            return Err(Error::Exception(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!(
+                    "Execution of `{}` failed ({}): {}",
+                    definition.id,
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            ));
+        }
+
+        // collect outputs
+        let mut results = Vec::new();
+        for (id, out) in &definition.outputs {
+            let data = match &out.file {
+                Some(file) => tokio::fs::read(dir.join(file)).await.map_err(|e| {
+                    anyhow::anyhow!("Failed to read output `{id}` from `{file}`: {e}")
+                })?,
+                None => output.stdout.to_owned(),
+            };
+            results.push((id, out, data));
+        }
+
+        match results.len() {
+            0 => Ok(output.stdout.into_response()),
+            1 => {
+                let (_, out, data) = results.remove(0);
+                let mut headers = HeaderMap::new();
+                headers.insert(CONTENT_TYPE, out.media_type.parse().unwrap());
+                Ok((headers, data).into_response())
+            }
+            _ => {
+                let results: Map<String, Value> = results
+                    .into_iter()
+                    .map(|(id, out, data)| {
+                        let value = match out.media_type.as_str() {
+                            JSON => serde_json::from_slice(&data).ok(),
+                            _ => None,
+                        }
+                        .unwrap_or_else(|| {
+                            Value::String(String::from_utf8_lossy(&data).into_owned())
+                        });
+                        (id.to_owned(), value)
+                    })
+                    .collect();
+                Ok(Json(results).into_response())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Processor for CommandProcessor {
+    fn id(&self) -> String {
+        self.definition.id.to_owned()
+    }
+
+    fn process(&self) -> Process {
+        let definition = &self.definition;
+
+        let required: Vec<&String> = definition
+            .inputs
+            .iter()
+            .filter(|(_, input)| input.default.is_none())
+            .map(|(id, _)| id)
+            .collect();
+
+        let inputs = json!({
+            "type": "object",
+            "properties": definition.inputs.iter().map(|(id, input)| {
+                (id.to_owned(), describe(&input.schema, &input.title, &input.description))
+            }).collect::<Map<String, Value>>(),
+            "required": required,
+        });
+
+        let outputs = json!({
+            "type": "object",
+            "properties": definition.outputs.iter().map(|(id, output)| {
+                let mut schema = describe(&output.schema, &output.title, &output.description);
+                schema["contentMediaType"] = Value::String(output.media_type.to_owned());
+                (id.to_owned(), schema)
+            }).collect::<Map<String, Value>>(),
+        });
+
+        let mut process = Process::new(&definition.id, &definition.version, &inputs, &outputs);
+        process.summary.description_type.title = definition.title.to_owned();
+        process.summary.description_type.description = definition.description.to_owned();
+
+        process
+    }
+
+    async fn execute(&self, execute: Execute, _state: &State, _url: &Url) -> Result<Response> {
+        let inputs: Map<String, Value> = inputs(&execute)?;
+
+        let dir = working_dir(&self.definition.id);
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(anyhow::Error::new)?;
+
+        let response = self.run(&dir, inputs).await;
+
+        if let Err(e) = tokio::fs::remove_dir_all(&dir).await {
+            tracing::warn!("Failed to remove `{}`: {:?}", dir.display(), e);
+        }
+
+        response
+    }
+}
+
+/// Adds title and description to a schema
+fn describe(schema: &Value, title: &Option<String>, description: &Option<String>) -> Value {
+    let mut schema = match schema {
+        Value::Object(schema) => schema.to_owned(),
+        _ => Map::new(),
+    };
+    if let Some(title) = title {
+        schema.insert("title".to_string(), Value::String(title.to_owned()));
+    }
+    if let Some(description) = description {
+        schema.insert(
+            "description".to_string(),
+            Value::String(description.to_owned()),
+        );
+    }
+    Value::Object(schema)
+}
+
+/// Fresh working directory for an execution
+fn working_dir(id: &str) -> PathBuf {
+    std::env::temp_dir()
+        .join("ogcapi-processes")
+        .join(format!("{id}-{}", uuid::Uuid::new_v4().simple()))
+}
@@ -0,0 +1,420 @@
+use axum::{
+    async_trait,
+    http::{
+        header::{CONTENT_TYPE, LOCATION},
+        HeaderMap, StatusCode,
+    },
+    response::{IntoResponse, Response},
+    Json,
+};
+use schemars::{schema::RootSchema, schema_for, JsonSchema};
+use serde::Deserialize;
+use serde_json::json;
+use url::Url;
+
+use ogcapi_drivers::Operation;
+use ogcapi_types::{
+    common::{media_type::GEO_JSON, Collection, Crs},
+    features::{FeatureCollection, Geometry},
+    processes::{Execute, Process},
+};
+
+use crate::{Error, Result, State};
+
+use super::{inputs, Processor};
+
+/// Inputs shared by all geoprocessing processes
+#[derive(Deserialize, Debug, JsonSchema)]
+struct Source {
+    /// Id of the collection to process or inline GeoJSON features
+    #[schemars(with = "serde_json::Value")]
+    collection: Items,
+    /// Id of a new collection to store the result in, returns GeoJSON if omitted
+    output: Option<String>,
+}
+
+/// Items to process
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum Items {
+    Collection(String),
+    Features(FeatureCollection),
+}
+
+/// Inputs for the `buffer` process
+#[derive(Deserialize, Debug, JsonSchema)]
+struct BufferInputs {
+    #[serde(flatten)]
+    source: Source,
+    /// Buffer distance in meters
+    distance: f64,
+}
+
+/// Inputs for the `clip` process
+#[derive(Deserialize, Debug, JsonSchema)]
+struct ClipInputs {
+    #[serde(flatten)]
+    source: Source,
+    /// GeoJSON geometry in `CRS84` to clip by
+    #[schemars(with = "serde_json::Value")]
+    geometry: Geometry,
+}
+
+/// Inputs for the `dissolve` process
+#[derive(Deserialize, Debug, JsonSchema)]
+struct DissolveInputs {
+    #[serde(flatten)]
+    source: Source,
+    /// Property to group by, dissolves all items if omitted
+    property: Option<String>,
+}
+
+/// Inputs for the `spatial-join` process
+#[derive(Deserialize, Debug, JsonSchema)]
+struct SpatialJoinInputs {
+    #[serde(flatten)]
+    source: Source,
+    /// Id of the collection to join
+    join: String,
+}
+
+/// Inputs for the `centroid` process
+#[derive(Deserialize, Debug, JsonSchema)]
+struct CentroidInputs {
+    #[serde(flatten)]
+    source: Source,
+}
+
+/// Inputs for the `reproject` process
+#[derive(Deserialize, Debug, JsonSchema)]
+struct ReprojectInputs {
+    #[serde(flatten)]
+    source: Source,
+    /// Target crs uri, e.g. `http://www.opengis.net/def/crs/EPSG/0/2056`
+    crs: String,
+}
+
+/// Inputs for the `simplify` process
+#[derive(Deserialize, Debug, JsonSchema)]
+struct SimplifyInputs {
+    #[serde(flatten)]
+    source: Source,
+    /// Distance tolerance in units of the storage crs
+    tolerance: f64,
+}
+
+/// Buffer process
+///
+/// ```bash
+/// curl http://localhost:8484/processes/buffer/execution \
+///         -H 'Content-Type: application/json' \
+///         -d '{"inputs": { "collection": "places", "distance": 1000 } }'
+/// ```
+pub struct Buffer;
+
+/// Clip by geometry process
+pub struct Clip;
+
+/// Dissolve (union by attribute) process
+pub struct Dissolve;
+
+/// Spatial join process
+pub struct SpatialJoin;
+
+/// Centroid process
+pub struct Centroid;
+
+/// Reprojection process
+pub struct Reproject;
+
+/// Simplification process
+pub struct Simplify;
+
+#[async_trait]
+impl Processor for Buffer {
+    fn id(&self) -> String {
+        "buffer".to_string()
+    }
+
+    fn process(&self) -> Process {
+        describe(
+            self.id(),
+            "Buffer the items of a collection by a distance",
+            schema_for!(BufferInputs),
+        )
+    }
+
+    async fn execute(&self, execute: Execute, state: &State, url: &Url) -> Result<Response> {
+        let inputs: BufferInputs = inputs(&execute)?;
+        let operation = Operation::Buffer {
+            distance: inputs.distance,
+        };
+        run(inputs.source, operation, state, url).await
+    }
+}
+
+#[async_trait]
+impl Processor for Clip {
+    fn id(&self) -> String {
+        "clip".to_string()
+    }
+
+    fn process(&self) -> Process {
+        describe(
+            self.id(),
+            "Clip the items of a collection by a geometry",
+            schema_for!(ClipInputs),
+        )
+    }
+
+    async fn execute(&self, execute: Execute, state: &State, url: &Url) -> Result<Response> {
+        let inputs: ClipInputs = inputs(&execute)?;
+        let operation = Operation::Clip {
+            geometry: inputs.geometry,
+        };
+        run(inputs.source, operation, state, url).await
+    }
+}
+
+#[async_trait]
+impl Processor for Dissolve {
+    fn id(&self) -> String {
+        "dissolve".to_string()
+    }
+
+    fn process(&self) -> Process {
+        describe(
+            self.id(),
+            "Union the items of a collection, grouped by a property",
+            schema_for!(DissolveInputs),
+        )
+    }
+
+    async fn execute(&self, execute: Execute, state: &State, url: &Url) -> Result<Response> {
+        let inputs: DissolveInputs = inputs(&execute)?;
+        let operation = Operation::Dissolve {
+            property: inputs.property,
+        };
+        run(inputs.source, operation, state, url).await
+    }
+}
+
+#[async_trait]
+impl Processor for SpatialJoin {
+    fn id(&self) -> String {
+        "spatial-join".to_string()
+    }
+
+    fn process(&self) -> Process {
+        describe(
+            self.id(),
+            "Join the properties of intersecting items of another collection",
+            schema_for!(SpatialJoinInputs),
+        )
+    }
+
+    async fn execute(&self, execute: Execute, state: &State, url: &Url) -> Result<Response> {
+        let inputs: SpatialJoinInputs = inputs(&execute)?;
+
+        if state
+            .drivers
+            .collections
+            .read_collection(&inputs.join)
+            .await?
+            .is_none()
+        {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("No collection with id `{}`", inputs.join),
+            ));
+        }
+
+        let operation = Operation::SpatialJoin {
+            collection: inputs.join,
+        };
+        run(inputs.source, operation, state, url).await
+    }
+}
+
+#[async_trait]
+impl Processor for Centroid {
+    fn id(&self) -> String {
+        "centroid".to_string()
+    }
+
+    fn process(&self) -> Process {
+        describe(
+            self.id(),
+            "Compute the centroid of the items of a collection",
+            schema_for!(CentroidInputs),
+        )
+    }
+
+    async fn execute(&self, execute: Execute, state: &State, url: &Url) -> Result<Response> {
+        let inputs: CentroidInputs = inputs(&execute)?;
+        run(inputs.source, Operation::Centroid, state, url).await
+    }
+}
+
+#[async_trait]
+impl Processor for Reproject {
+    fn id(&self) -> String {
+        "reproject".to_string()
+    }
+
+    fn process(&self) -> Process {
+        describe(
+            self.id(),
+            "Reproject the items of a collection to another crs",
+            schema_for!(ReprojectInputs),
+        )
+    }
+
+    async fn execute(&self, execute: Execute, state: &State, url: &Url) -> Result<Response> {
+        let inputs: ReprojectInputs = inputs(&execute)?;
+        let crs: Crs = inputs
+            .crs
+            .parse()
+            .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e))?;
+        run(inputs.source, Operation::Reproject { crs }, state, url).await
+    }
+}
+
+#[async_trait]
+impl Processor for Simplify {
+    fn id(&self) -> String {
+        "simplify".to_string()
+    }
+
+    fn process(&self) -> Process {
+        describe(
+            self.id(),
+            "Simplify the geometries of the items of a collection",
+            schema_for!(SimplifyInputs),
+        )
+    }
+
+    async fn execute(&self, execute: Execute, state: &State, url: &Url) -> Result<Response> {
+        let inputs: SimplifyInputs = inputs(&execute)?;
+        let operation = Operation::Simplify {
+            tolerance: inputs.tolerance,
+        };
+        run(inputs.source, operation, state, url).await
+    }
+}
+
+fn describe(id: String, title: &str, inputs: RootSchema) -> Process {
+    let outputs = json!({
+        "description": "GeoJSON FeatureCollection, unless stored in the `output` collection",
+        "type": "object",
+    });
+
+    let mut process = Process::new(
+        id,
+        "0.1.0",
+        &serde_json::to_value(&inputs.schema).unwrap(),
+        &outputs,
+    );
+    process.summary.description_type.title = Some(title.to_string());
+
+    process
+}
+
+/// Executes the operation, either returning the result as GeoJSON or storing
+/// it in a new collection.
+async fn run(source: Source, operation: Operation, state: &State, url: &Url) -> Result<Response> {
+    let (items, crs, storage_crs) = match source.collection {
+        Items::Collection(id) => {
+            let collection = state
+                .drivers
+                .collections
+                .read_collection(&id)
+                .await?
+                .ok_or_else(|| {
+                    Error::Exception(
+                        StatusCode::BAD_REQUEST,
+                        format!("No collection with id `{id}`"),
+                    )
+                })?;
+            (
+                ogcapi_drivers::Source::Collection(id),
+                collection.crs,
+                collection.storage_crs,
+            )
+        }
+        Items::Features(fc) => (
+            ogcapi_drivers::Source::Features(fc),
+            vec![Crs::default()],
+            None,
+        ),
+    };
+
+    match source.output {
+        Some(id) => {
+            if state
+                .drivers
+                .collections
+                .read_collection(&id)
+                .await?
+                .is_some()
+            {
+                return Err(Error::Exception(
+                    StatusCode::CONFLICT,
+                    format!("Collection with id `{id}` already exists."),
+                ));
+            }
+
+            let mut output = Collection {
+                id,
+                crs,
+                storage_crs,
+                ..Default::default()
+            };
+
+            if let Operation::Reproject { crs } = &operation {
+                if !output.crs.contains(crs) {
+                    output.crs.push(crs.to_owned());
+                }
+                output.storage_crs = Some(crs.to_owned());
+            }
+
+            let id = state.drivers.collections.create_collection(&output).await?;
+
+            // remove the output collection of failed processes
+            if let Err(e) = state
+                .drivers
+                .geoprocessing
+                .process_into(&items, &operation, &id)
+                .await
+            {
+                state.drivers.collections.delete_collection(&id).await?;
+                return Err(e.into());
+            }
+
+            let location = url.join(&format!("../../collections/{id}"))?;
+
+            let mut headers = HeaderMap::new();
+            headers.insert(LOCATION, location.as_str().parse().unwrap());
+
+            Ok((StatusCode::CREATED, headers).into_response())
+        }
+        None => {
+            let fc = state
+                .drivers
+                .geoprocessing
+                .process(&items, &operation)
+                .await?;
+
+            let crs = match operation {
+                Operation::Reproject { crs } => crs,
+                _ => Crs::default(),
+            };
+
+            let mut headers = HeaderMap::new();
+            headers.insert(CONTENT_TYPE, GEO_JSON.parse().unwrap());
+            headers.insert("Content-Crs", crs.to_string().parse().unwrap());
+
+            Ok((headers, Json(fc)).into_response())
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    http::{
+        header::{CONTENT_TYPE, LOCATION},
+        StatusCode,
+    },
+    response::Response,
+};
+use chrono::Utc;
+use serde_json::{json, Value};
+use url::Url;
+
+use ogcapi_drivers::JobResults;
+use ogcapi_types::{
+    common::media_type::JSON,
+    processes::{Execute, StatusCode as JobStatus, StatusInfo},
+};
+
+use crate::{Error, Result, State};
+
+use super::workflow;
+
+/// Results up to this size (in bytes) are stored inline with the job
+const INLINE_LIMIT: usize = 64 * 1024;
+
+/// Registers a job and executes the process in the background
+pub(crate) async fn submit(
+    id: &str,
+    execute: Execute,
+    state: Arc<State>,
+    url: Url,
+) -> Result<StatusInfo> {
+    if !state.processors.contains_key(id) {
+        return Err(Error::Exception(
+            StatusCode::NOT_FOUND,
+            format!("No process with id `{}`", id),
+        ));
+    }
+
+    let now = Utc::now();
+    let job = StatusInfo {
+        process_id: Some(id.to_owned()),
+        job_id: uuid::Uuid::new_v4().to_string(),
+        status: JobStatus::Accepted,
+        created: Some(now),
+        updated: Some(now),
+        ..Default::default()
+    };
+
+    state.drivers.jobs.register(&job).await?;
+
+    // hold the lock until the handle is registered, the job removes it when done
+    let mut executions = state.executions.lock().unwrap();
+    let handle = tokio::spawn(run(job.clone(), execute, state.clone(), url));
+    executions.insert(job.job_id.to_owned(), handle);
+
+    Ok(job)
+}
+
+async fn run(mut job: StatusInfo, execute: Execute, state: Arc<State>, url: Url) {
+    job.status = JobStatus::Running;
+    job.updated = Some(Utc::now());
+    if let Err(e) = state.drivers.jobs.update(&job).await {
+        tracing::error!("Failed to update job `{}`: {:?}", job.job_id, e);
+    }
+
+    let process_id = job.process_id.clone().unwrap_or_default();
+    let result = match workflow::run(&process_id, execute, &state, &url).await {
+        Ok(response) => store(&job.job_id, response, &state).await,
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(()) => {
+            job.status = JobStatus::Successful;
+            job.progress = Some(100);
+        }
+        Err(e) => {
+            job.status = JobStatus::Failed;
+            job.message = Some(match e {
+                Error::Exception(_, message) => message,
+                Error::Anyhow(e) => e.to_string(),
+                e => e.to_string(),
+            });
+        }
+    }
+
+    let now = Utc::now();
+    job.finished = Some(now);
+    job.updated = Some(now);
+    if let Err(e) = state.drivers.jobs.update(&job).await {
+        tracing::error!("Failed to update job `{}`: {:?}", job.job_id, e);
+    }
+
+    state.executions.lock().unwrap().remove(&job.job_id);
+}
+
+/// Stores the response of an execution as job results, inline if it is small
+/// JSON, otherwise out-of-line as artifact
+async fn store(id: &str, response: Response, state: &State) -> Result<()> {
+    if !response.status().is_success() {
+        return Err(Error::Exception(
+            response.status(),
+            format!("Execution failed with status `{}`", response.status()),
+        ));
+    }
+
+    let headers = response.headers().to_owned();
+    let media_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|h| h.to_str().ok())
+        .map(ToString::to_string);
+
+    let body = hyper::body::to_bytes(response.into_body())
+        .await
+        .map_err(anyhow::Error::new)?;
+
+    // results stored in a collection are referenced by its location
+    if body.is_empty() {
+        if let Some(location) = headers.get(LOCATION).and_then(|h| h.to_str().ok()) {
+            let results = JobResults::Inline {
+                value: json!({ "href": location }),
+                media_type: Some(JSON.to_string()),
+            };
+            return Ok(state.drivers.jobs.store_results(id, &results).await?);
+        }
+    }
+
+    let results = match serde_json::from_slice::<Value>(&body) {
+        Ok(value) if body.len() <= INLINE_LIMIT => JobResults::Inline { value, media_type },
+        _ => {
+            let key = format!("jobs/{id}/results");
+            state
+                .drivers
+                .artifacts
+                .put_artifact(&key, body.to_vec(), media_type.as_deref())
+                .await?;
+            JobResults::Artifact { key, media_type }
+        }
+    };
+
+    Ok(state.drivers.jobs.store_results(id, &results).await?)
+}
+
+/// Cancels the execution of a job and deletes it along with its results
+pub(crate) async fn dismiss(id: &str, state: &State) -> Result<StatusInfo> {
+    let mut job = state.drivers.jobs.status(id).await?.ok_or_else(|| {
+        Error::Exception(StatusCode::NOT_FOUND, format!("No job with id `{}`", id))
+    })?;
+
+    if let Some(handle) = state.executions.lock().unwrap().remove(id) {
+        handle.abort();
+    }
+
+    if let Some(JobResults::Artifact { key, .. }) = state.drivers.jobs.results(id).await? {
+        state.drivers.artifacts.delete_artifact(&key).await?;
+    }
+
+    state.drivers.jobs.delete(id).await?;
+
+    job.status = JobStatus::Dismissed;
+    job.message = Some("Job dismissed".to_string());
+    job.updated = Some(Utc::now());
+
+    Ok(job)
+}
+
+/// Periodically deletes finished jobs older than the retention period along
+/// with their artifacts
+pub(crate) async fn purge(state: Arc<State>, retention: Duration) {
+    let mut interval =
+        tokio::time::interval(retention.clamp(Duration::from_secs(60), Duration::from_secs(3600)));
+
+    loop {
+        interval.tick().await;
+
+        let results = match state.drivers.jobs.purge(retention).await {
+            Ok(results) => results,
+            Err(e) => {
+                tracing::error!("Failed to purge jobs: {:?}", e);
+                continue;
+            }
+        };
+
+        for results in results {
+            if let JobResults::Artifact { key, .. } = results {
+                if let Err(e) = state.drivers.artifacts.delete_artifact(&key).await {
+                    tracing::warn!("Failed to delete artifact `{}`: {:?}", key, e);
+                }
+            }
+        }
+    }
+}
@@ -1,14 +1,24 @@
+mod command;
+mod geoprocessing;
+pub(crate) mod job;
+pub(crate) mod workflow;
+
 use axum::{
     async_trait,
+    http::StatusCode,
     response::{IntoResponse, Response},
 };
 use schemars::{schema_for, JsonSchema};
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
 use url::Url;
 
-use ogcapi_types::processes::{Execute, Process};
+use ogcapi_types::processes::{Execute, InlineOrRefData, Input, Process};
+
+use crate::{Error, Result, State};
 
-use crate::{Result, State};
+pub use command::{CommandDefinition, CommandInput, CommandOutput, CommandProcessor, InputMode};
+pub use geoprocessing::{Buffer, Centroid, Clip, Dissolve, Reproject, Simplify, SpatialJoin};
 
 #[async_trait]
 /// Trait for defining and executing a [Process]
@@ -64,3 +74,32 @@ impl Processor for Greeter {
         Ok(format!("Hello, {}!\n", inputs.name).into_response())
     }
 }
+
+/// Deserializes the inputs of an [Execute] request, unwrapping qualified input values
+pub(crate) fn inputs<T: DeserializeOwned>(execute: &Execute) -> Result<T> {
+    let inputs = execute
+        .inputs
+        .iter()
+        .map(|(id, input)| {
+            let value = match input {
+                Input::InlineOrRefData(data) => input_value(data),
+                Input::InlineOrRefDataArray(data) => {
+                    Value::Array(data.iter().map(input_value).collect())
+                }
+            };
+            (id.to_owned(), value)
+        })
+        .collect();
+
+    serde_json::from_value(Value::Object(inputs))
+        .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, format!("Invalid inputs: {e}")))
+}
+
+fn input_value(data: &InlineOrRefData) -> Value {
+    match data {
+        InlineOrRefData::QualifiedInputValue(qualified) => {
+            serde_json::to_value(&qualified.value).unwrap()
+        }
+        data => serde_json::to_value(data).unwrap(),
+    }
+}
@@ -0,0 +1,344 @@
+use std::{collections::HashMap, future::Future, pin::Pin};
+
+use axum::{
+    http::{header::CONTENT_TYPE, StatusCode},
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use url::Url;
+
+use ogcapi_types::{
+    common::{Bbox, Collection, Crs},
+    features::{FeatureCollection, Query},
+    processes::{
+        Execute, Format, InlineOrRefData, Input, InputValue, QualifiedInputValue,
+        Response as ResponseType,
+    },
+};
+
+use crate::{Error, Result, State};
+
+/// Maximum number of items of a stored collection passed as process input
+const MAX_INPUT_ITEMS: usize = 10000;
+
+/// Member of the collection metadata holding the workflow
+const WORKFLOW: &str = "workflow";
+
+/// Maximum nesting depth of processes and virtual collections
+const MAX_DEPTH: usize = 16;
+
+/// Process execution backing a virtual collection
+///
+/// Stored in the metadata of the collection, so virtual collections are
+/// listed and persisted like any other collection. The member is owned by the
+/// server, clients cannot attach or change workflows (see [Workflow::retain]).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct Workflow {
+    /// Id of the process to execute
+    pub(crate) process: String,
+    pub(crate) execute: Execute,
+}
+
+impl Workflow {
+    /// Workflow backing a collection, if any
+    pub(crate) fn of(collection: &Collection) -> Option<Workflow> {
+        collection
+            .additional_properties
+            .get(WORKFLOW)
+            .and_then(|workflow| serde_json::from_value(workflow.to_owned()).ok())
+    }
+
+    /// Stores the workflow in the metadata of a collection
+    pub(crate) fn attach(&self, collection: &mut Collection) {
+        collection
+            .additional_properties
+            .insert(WORKFLOW.to_string(), serde_json::to_value(self).unwrap());
+    }
+
+    /// Replaces the workflow of a collection written by a client with the
+    /// workflow of the `stored` collection, if any
+    pub(crate) fn retain(collection: &mut Collection, stored: Option<&Collection>) {
+        collection.additional_properties.remove(WORKFLOW);
+        if let Some(workflow) = stored.and_then(|c| c.additional_properties.get(WORKFLOW)) {
+            collection
+                .additional_properties
+                .insert(WORKFLOW.to_string(), workflow.to_owned());
+        }
+    }
+}
+
+/// Virtual collections and nesting depth of the executions leading to an input
+#[derive(Default, Clone)]
+struct Trace {
+    collections: Vec<String>,
+    depth: usize,
+}
+
+impl Trace {
+    /// Trace of a nested execution, optionally resolving a virtual collection
+    fn enter(&self, collection: Option<&str>) -> Result<Trace> {
+        if self.depth >= MAX_DEPTH {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("Workflows must not be nested deeper than {MAX_DEPTH} levels"),
+            ));
+        }
+
+        let mut trace = self.clone();
+        trace.depth += 1;
+
+        if let Some(collection) = collection {
+            if trace.collections.iter().any(|c| c == collection) {
+                return Err(Error::Exception(
+                    StatusCode::BAD_REQUEST,
+                    format!("The workflow of collection `{collection}` depends on itself"),
+                ));
+            }
+            trace.collections.push(collection.to_owned());
+        }
+
+        Ok(trace)
+    }
+}
+
+/// Resolves nested processes and collection inputs and executes the process
+pub(crate) async fn run(id: &str, execute: Execute, state: &State, url: &Url) -> Result<Response> {
+    run_traced(id, execute, state, url, &Trace::default()).await
+}
+
+async fn run_traced(
+    id: &str,
+    mut execute: Execute,
+    state: &State,
+    url: &Url,
+    trace: &Trace,
+) -> Result<Response> {
+    let processor = state.processors.get(id).ok_or_else(|| {
+        Error::Exception(
+            StatusCode::NOT_FOUND,
+            format!("No process with id `{}`", id),
+        )
+    })?;
+
+    resolve(&mut execute.inputs, state, url, trace).await?;
+
+    processor.execute(execute, state, url).await
+}
+
+/// Executes the workflow of a virtual collection and pages the resulting
+/// features by `bbox`, `offset` and `limit`
+///
+/// Other parameters are rejected, features are served in `CRS84`.
+pub(crate) async fn items(
+    collection: &str,
+    workflow: Workflow,
+    query: &Query,
+    state: &State,
+    url: &Url,
+) -> Result<FeatureCollection> {
+    let unsupported = |message: &str| {
+        Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            message.to_string(),
+        ))
+    };
+    if query.bbox.is_some() && query.bbox_crs != Crs::default() {
+        return unsupported("Virtual collections only support bboxes in `CRS84`");
+    }
+    if query.crs != Crs::default() {
+        return unsupported("Virtual collections are only served in `CRS84`");
+    }
+    if query.datetime.is_some() {
+        return unsupported("Virtual collections do not support the `datetime` parameter");
+    }
+    if query.filter.is_some() || !query.additional_parameters.is_empty() {
+        return unsupported("Virtual collections do not support filters");
+    }
+
+    let trace = Trace::default().enter(Some(collection))?;
+    let response = run_traced(&workflow.process, workflow.execute, state, url, &trace).await?;
+    let (value, _) = read_response(&workflow.process, response).await?;
+    let fc: FeatureCollection = serde_json::from_value(value).map_err(|e| {
+        Error::Exception(
+            StatusCode::BAD_GATEWAY,
+            format!(
+                "Process `{}` did not return features: {e}",
+                workflow.process
+            ),
+        )
+    })?;
+
+    let features: Vec<_> = fc
+        .features
+        .into_iter()
+        .filter(|feature| match &query.bbox {
+            Some(bbox) => {
+                Bbox::from_geometry(&feature.geometry).is_some_and(|b| b.intersects(bbox))
+            }
+            None => true,
+        })
+        .collect();
+    let number_matched = features.len();
+
+    let mut fc = FeatureCollection::new(
+        features
+            .into_iter()
+            .skip(query.offset.unwrap_or(0))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect(),
+    );
+    fc.number_matched = Some(number_matched as u64);
+
+    Ok(fc)
+}
+
+fn resolve<'a>(
+    inputs: &'a mut HashMap<String, Input>,
+    state: &'a State,
+    url: &'a Url,
+    trace: &'a Trace,
+) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+    Box::pin(async move {
+        for input in inputs.values_mut() {
+            match input {
+                Input::InlineOrRefData(data) => resolve_data(data, state, url, trace).await?,
+                Input::InlineOrRefDataArray(data) => {
+                    for data in data.iter_mut() {
+                        resolve_data(data, state, url, trace).await?
+                    }
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+async fn resolve_data(
+    data: &mut InlineOrRefData,
+    state: &State,
+    url: &Url,
+    trace: &Trace,
+) -> Result<()> {
+    let (value, media_type) = match data {
+        InlineOrRefData::ProcessInput(nested) => {
+            let id = segment_after(&nested.process, "processes")?;
+
+            let execute = Execute {
+                inputs: std::mem::take(&mut nested.inputs),
+                outputs: std::mem::take(&mut nested.outputs),
+                response: ResponseType::Raw,
+                subscriber: None,
+            };
+
+            let response = run_traced(&id, execute, state, url, &trace.enter(None)?).await?;
+
+            read_response(&id, response).await?
+        }
+        InlineOrRefData::CollectionInput(input) => {
+            let id = segment_after(&input.collection, "collections")?;
+
+            let collection = state
+                .drivers
+                .collections
+                .read_collection(&id)
+                .await?
+                .ok_or_else(|| {
+                    Error::Exception(
+                        StatusCode::BAD_REQUEST,
+                        format!("No collection with id `{id}`"),
+                    )
+                })?;
+
+            if let Some(workflow) = Workflow::of(&collection) {
+                let trace = trace.enter(Some(&id))?;
+                let response =
+                    run_traced(&workflow.process, workflow.execute, state, url, &trace).await?;
+                read_response(&workflow.process, response).await?
+            } else {
+                let query = Query {
+                    limit: Some(MAX_INPUT_ITEMS),
+                    ..Default::default()
+                };
+                let fc = state.drivers.features.list_items(&id, &query).await?;
+
+                if fc.number_matched.unwrap_or_default() > MAX_INPUT_ITEMS as u64 {
+                    return Err(Error::Exception(
+                        StatusCode::PAYLOAD_TOO_LARGE,
+                        format!(
+                            "Collection `{id}` exceeds the limit of {MAX_INPUT_ITEMS} items of collection inputs"
+                        ),
+                    ));
+                }
+
+                (serde_json::to_value(fc).unwrap(), None)
+            }
+        }
+        _ => return Ok(()),
+    };
+
+    *data = match value {
+        Value::Object(object) => InlineOrRefData::QualifiedInputValue(QualifiedInputValue {
+            value: InputValue::Object(object),
+            format: Format {
+                media_type,
+                encoding: None,
+                schema: None,
+            },
+        }),
+        value => InlineOrRefData::InputValueNoObject(
+            serde_json::from_value(value).map_err(anyhow::Error::new)?,
+        ),
+    };
+
+    Ok(())
+}
+
+/// Reads the response body of a nested execution as JSON value (or string)
+async fn read_response(id: &str, response: Response) -> Result<(Value, Option<String>)> {
+    if !response.status().is_success() {
+        return Err(Error::Exception(
+            response.status(),
+            format!("Execution of nested process `{id}` failed"),
+        ));
+    }
+
+    let media_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|h| h.to_str().ok())
+        .map(ToString::to_string);
+
+    let body = hyper::body::to_bytes(response.into_body())
+        .await
+        .map_err(anyhow::Error::new)?;
+
+    let value = serde_json::from_slice(&body)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&body).into_owned()));
+
+    Ok((value, media_type))
+}
+
+/// Extracts the path segment following `parent` from an uri (or returns the input
+/// if it is not an uri), e.g. the process id from `http://host/processes/{id}`.
+fn segment_after(uri: &str, parent: &str) -> Result<String> {
+    let url = match Url::parse(uri) {
+        Ok(url) => url,
+        Err(_) => return Ok(uri.to_string()),
+    };
+
+    url.path_segments()
+        .and_then(|mut segments| {
+            segments
+                .by_ref()
+                .find(|s| *s == parent)
+                .and_then(|_| segments.next())
+        })
+        .map(ToString::to_string)
+        .ok_or_else(|| {
+            Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("Unable to resolve `{uri}`, expected `../{parent}/{{id}}`"),
+            )
+        })
+}
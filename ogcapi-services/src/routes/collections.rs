@@ -1,11 +1,15 @@
 use std::sync::Arc;
 
+use anyhow::Context;
 use axum::{
     extract::{Extension, Path},
     headers::HeaderMap,
     http::{header::LOCATION, StatusCode},
     Json,
-    {routing::get, Router},
+    {
+        routing::{get, post},
+        Router,
+    },
 };
 
 use ogcapi_types::common::{
@@ -13,12 +17,17 @@ use ogcapi_types::common::{
     media_type::{GEO_JSON, JSON},
     Collection, Collections, Crs, Link, Linked, Query,
 };
+use serde::Deserialize;
+use serde_json::Value;
 
 use crate::{
     extractors::{Qs, RemoteUrl},
     Error, Result, State,
 };
 
+#[cfg(feature = "processes")]
+use crate::processor::workflow::Workflow;
+
 const CONFORMANCE: [&str; 3] = [
     "http://www.opengis.net/spec/ogcapi-common-1/1.0/conf/core",
     "http://www.opengis.net/spec/ogcapi-common-2/1.0/conf/collections",
@@ -27,10 +36,22 @@ const CONFORMANCE: [&str; 3] = [
 
 /// Create new collection metadata
 async fn create(
-    Json(collection): Json<Collection>,
+    Json(body): Json<Value>,
     RemoteUrl(url): RemoteUrl,
     Extension(state): Extension<Arc<State>>,
 ) -> Result<(StatusCode, HeaderMap)> {
+    #[allow(unused_mut)]
+    let mut collection = parse(&body)?;
+
+    // validate collections declaring a `stac_version` as STAC Collections
+    #[cfg(feature = "stac")]
+    if collection.stac_version.is_some() {
+        crate::routes::stac::validate_collection(&body, &collection)?;
+    }
+
+    #[cfg(feature = "processes")]
+    Workflow::retain(&mut collection, None);
+
     if state
         .drivers
         .collections
@@ -71,11 +92,22 @@ async fn read(
         .await?
         .ok_or(Error::NotFound)?;
 
+    #[cfg(feature = "edr")]
+    crate::routes::edr::describe(&mut collection, &url, &state).await;
+
     collection.links.insert_or_update(&[
         Link::new(&url, SELF),
         Link::new(&url.join("..")?, ROOT).mediatype(JSON),
     ]);
 
+    #[cfg(feature = "stac")]
+    crate::routes::stac::resolve_assets(
+        &mut collection.assets,
+        &url.join(&format!("{}/", collection.id))?,
+        &state,
+    )
+    .await?;
+
     if collection.r#type == "Collection" {
         collection.links.insert_or_update(&[
             Link::new(&url.join(&format!("{}/items", collection.id))?, ITEMS).mediatype(GEO_JSON),
@@ -90,11 +122,67 @@ async fn read(
 /// Update collection metadata
 async fn update(
     Path(collection_id): Path<String>,
-    Json(mut collection): Json<Collection>,
+    Json(body): Json<Value>,
     Extension(state): Extension<Arc<State>>,
 ) -> Result<StatusCode> {
+    let mut collection = parse(&body)?;
     collection.id = collection_id;
 
+    #[cfg(feature = "stac")]
+    if collection.stac_version.is_some() {
+        crate::routes::stac::validate_collection(&body, &collection)?;
+    }
+
+    #[cfg(feature = "processes")]
+    {
+        let stored = state
+            .drivers
+            .collections
+            .read_collection(&collection.id)
+            .await?;
+        Workflow::retain(&mut collection, stored.as_ref());
+    }
+
+    state
+        .drivers
+        .collections
+        .update_collection(&collection)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Update collection metadata with a JSON merge patch
+async fn patch(
+    Path(collection_id): Path<String>,
+    Json(patch): Json<Value>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<StatusCode> {
+    let collection = state
+        .drivers
+        .collections
+        .read_collection(&collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let mut value = serde_json::to_value(&collection).context("Unable to serialize collection")?;
+    crate::routes::merge_patch(&mut value, patch);
+
+    #[cfg(feature = "processes")]
+    let stored = collection;
+
+    let mut collection = parse(&value)?;
+    collection.id = collection_id;
+
+    // STAC Collections have to remain valid
+    #[cfg(feature = "stac")]
+    if collection.stac_version.is_some() {
+        crate::routes::stac::validate_collection(&value, &collection)?;
+    }
+
+    #[cfg(feature = "processes")]
+    Workflow::retain(&mut collection, Some(&stored));
+
     state
         .drivers
         .collections
@@ -126,6 +214,14 @@ async fn collections(
     let mut collections = state.drivers.collections.list_collections(&query).await?;
 
     for collection in collections.collections.iter_mut() {
+        #[cfg(feature = "stac")]
+        crate::routes::stac::resolve_assets(
+            &mut collection.assets,
+            &url.join(&format!("collections/{}/", collection.id))?,
+            &state,
+        )
+        .await?;
+
         collection.links.insert_or_update(&[
             Link::new(&url.join(&format!("collections/{}", collection.id))?, SELF).mediatype(JSON),
             Link::new(&url.join(".")?, ROOT).mediatype(JSON),
@@ -151,6 +247,26 @@ async fn collections(
     Ok(Json(collections))
 }
 
+/// Recompute the extent and summaries of a collection from its items
+async fn refresh(
+    Path(collection_id): Path<String>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Collection>> {
+    let collection = state
+        .drivers
+        .maintenance
+        .refresh_collection(&collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(Json(collection))
+}
+
+fn parse(value: &Value) -> Result<Collection> {
+    Collection::deserialize(value)
+        .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, format!("Invalid collection: {e}")))
+}
+
 pub(crate) fn router(state: &State) -> Router {
     let mut root = state.root.write().unwrap();
     root.links.push(
@@ -165,6 +281,7 @@ pub(crate) fn router(state: &State) -> Router {
         .route("/collections", get(collections).post(create))
         .route(
             "/collections/:collection_id",
-            get(read).put(update).delete(remove),
+            get(read).put(update).patch(patch).delete(remove),
         )
+        .route("/collections/:collection_id/refresh", post(refresh))
 }
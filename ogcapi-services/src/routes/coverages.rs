@@ -0,0 +1,214 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Extension, Path},
+    http::{header::CONTENT_TYPE, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::DateTime;
+
+use ogcapi_types::{
+    common::media_type::{COVERAGE_JSON, GEO_TIFF},
+    coverages::{DomainSet, Interval, Query, RangeType},
+};
+
+use crate::{extractors::Qs, Error, Result, State};
+
+const CONFORMANCE: [&str; 6] = [
+    "http://www.opengis.net/spec/ogcapi-coverages-1/1.0/conf/core",
+    "http://www.opengis.net/spec/ogcapi-coverages-1/1.0/conf/subsetting",
+    "http://www.opengis.net/spec/ogcapi-coverages-1/1.0/conf/scaling",
+    "http://www.opengis.net/spec/ogcapi-coverages-1/1.0/conf/fieldselection",
+    "http://www.opengis.net/spec/ogcapi-coverages-1/1.0/conf/coveragejson",
+    "http://www.opengis.net/spec/ogcapi-coverages-1/1.0/conf/geotiff",
+];
+
+async fn coverage(
+    Path(collection_id): Path<String>,
+    Qs(query): Qs<Query>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Response> {
+    let domain_set = state
+        .drivers
+        .coverages
+        .domain_set(&collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    validate(&query, &domain_set)?;
+
+    if let Some(properties) = &query.properties {
+        let range_type = state
+            .drivers
+            .coverages
+            .range_type(&collection_id)
+            .await?
+            .ok_or(Error::NotFound)?;
+
+        for property in &properties.0 {
+            if !range_type.field.iter().any(|field| &field.name == property) {
+                return Err(Error::Exception(
+                    StatusCode::BAD_REQUEST,
+                    format!("Unknown field `{property}`"),
+                ));
+            }
+        }
+    }
+
+    match query.f.as_deref().map(str::to_lowercase).as_deref() {
+        None | Some("geotiff" | "tif" | "tiff") => {
+            let bytes = state
+                .drivers
+                .coverages
+                .geotiff(&collection_id, &query)
+                .await?
+                .ok_or(Error::NotFound)?;
+
+            Ok(([(CONTENT_TYPE, GEO_TIFF)], bytes).into_response())
+        }
+        Some("coveragejson" | "covjson" | "json") => {
+            let coverage = state
+                .drivers
+                .coverages
+                .coverage(&collection_id, &query)
+                .await?
+                .ok_or(Error::NotFound)?;
+
+            Ok(([(CONTENT_TYPE, COVERAGE_JSON)], Json(coverage)).into_response())
+        }
+        Some(f) => Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            format!("Unsupported format `{f}`"),
+        )),
+    }
+}
+
+async fn domain_set(
+    Path(collection_id): Path<String>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<DomainSet>> {
+    state
+        .drivers
+        .coverages
+        .domain_set(&collection_id)
+        .await?
+        .map(Json)
+        .ok_or(Error::NotFound)
+}
+
+async fn range_type(
+    Path(collection_id): Path<String>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<RangeType>> {
+    state
+        .drivers
+        .coverages
+        .range_type(&collection_id)
+        .await?
+        .map(Json)
+        .ok_or(Error::NotFound)
+}
+
+/// Validates the axes of subsetting and scaling parameters against the domain set
+fn validate(query: &Query, domain_set: &DomainSet) -> Result<()> {
+    let grid = &domain_set.general_grid;
+
+    let axis = |name: &str| {
+        grid.axis
+            .iter()
+            .find(|axis| axis.axis_label.eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                Error::Exception(
+                    StatusCode::BAD_REQUEST,
+                    format!(
+                        "Unknown axis `{name}`, expected one of `{}`",
+                        grid.axis_labels.join("`, `")
+                    ),
+                )
+            })
+    };
+
+    if let Some(subset) = &query.subset {
+        for axis_subset in &subset.0 {
+            let axis = axis(&axis_subset.axis)?;
+            let invalid = |message: String| {
+                Error::Exception(
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid subset of axis `{}`: {message}", axis.axis_label),
+                )
+            };
+
+            match (&axis.lower_bound, &axis.upper_bound) {
+                // regular (spatial) axis
+                (Some(lower), Some(upper)) => {
+                    let (low, high) = axis_subset.interval.bounds().map_err(invalid)?;
+                    if low > high {
+                        return Err(invalid("lower bound exceeds upper bound".to_string()));
+                    }
+                    let (lower, upper) = (
+                        lower.as_f64().unwrap_or(f64::NEG_INFINITY),
+                        upper.as_f64().unwrap_or(f64::INFINITY),
+                    );
+                    if high < lower || low > upper {
+                        return Err(invalid(format!("outside of the extent `{lower}:{upper}`")));
+                    }
+                }
+                // irregular (temporal) axis
+                _ => {
+                    let values = match &axis_subset.interval {
+                        Interval::Slice(v) => vec![v],
+                        Interval::Trim(low, high) => low.iter().chain(high.iter()).collect(),
+                    };
+                    for value in values {
+                        if DateTime::parse_from_rfc3339(value).is_err() {
+                            return Err(invalid(format!("`{value}` is not a RFC 3339 datetime")));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let positive = |name: &str, value: f64| {
+        if value.is_finite() && value > 0.0 {
+            Ok(())
+        } else {
+            Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("{name} must be a positive number"),
+            ))
+        }
+    };
+
+    for values in [&query.scale_axes, &query.scale_size].into_iter().flatten() {
+        for (name, value) in &values.0 {
+            axis(name)?;
+            positive(&format!("Scale of axis `{name}`"), *value)?;
+        }
+    }
+
+    if let Some(factor) = query.scale_factor {
+        positive("Scale factor", factor)?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn router(state: &State) -> Router {
+    if state.drivers.coverages.is_available() {
+        state.conformance.write().unwrap().extend(&CONFORMANCE);
+    }
+
+    Router::new()
+        .route("/collections/:collection_id/coverage", get(coverage))
+        .route(
+            "/collections/:collection_id/coverage/domainset",
+            get(domain_set),
+        )
+        .route(
+            "/collections/:collection_id/coverage/rangetype",
+            get(range_type),
+        )
+}
@@ -3,20 +3,36 @@ use std::sync::Arc;
 use axum::{
     extract::{Extension, Path},
     headers::HeaderMap,
-    http::header::CONTENT_TYPE,
+    http::{
+        header::{CONTENT_TYPE, LOCATION},
+        StatusCode,
+    },
+    response::{IntoResponse, Response},
     routing::get,
     Json, Router,
 };
+use serde_json::Value;
+use url::{Position, Url};
+
+use ogcapi_drivers::postgres::is_valid_instance_id;
 
 use ogcapi_types::{
-    common::{link_rel::SELF, media_type::GEO_JSON, Link},
-    edr::{Query, QueryType},
-    features::FeatureCollection,
+    common::{
+        link_rel::{COLLECTION, DATA, SELF},
+        media_type::{COVERAGE_JSON, GEO_JSON, JSON},
+        Collection, Link,
+    },
+    coverage::Coverage,
+    edr::{
+        DataQuery, DataQueryLink, Instances, Label, ObservedPropertyCollection, ParameterNames,
+        Query, QueryType, Variables,
+    },
+    features::{Feature, FeatureCollection},
 };
 
 use crate::{
     extractors::{Qs, RemoteUrl},
-    Result, State,
+    Error, Result, State,
 };
 
 const CONFORMANCE: [&str; 8] = [
@@ -36,42 +52,544 @@ async fn query(
     Qs(query): Qs<Query>,
     RemoteUrl(url): RemoteUrl,
     Extension(state): Extension<Arc<State>>,
+) -> Result<Response> {
+    let items = url.join("items/")?;
+
+    execute(&collection_id, &query_type, query, &items, &state).await
+}
+
+async fn locations(
+    Path(collection_id): Path<String>,
+    RemoteUrl(url): RemoteUrl,
+    Extension(state): Extension<Arc<State>>,
 ) -> Result<(HeaderMap, Json<FeatureCollection>)> {
+    let mut fc = state.drivers.edr.locations(&collection_id).await?;
+
+    for feature in fc.features.iter_mut() {
+        let id = feature.id.as_ref().expect("Location should have id");
+        feature.links = vec![
+            Link::new(format!("{}/{}", &url[..Position::AfterPath], id), SELF)
+                .mediatype(GEO_JSON)
+                .title("location query"),
+        ]
+    }
+
+    fc.links = vec![Link::new(&url, SELF).mediatype(GEO_JSON)];
+
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, GEO_JSON.parse().unwrap());
+
+    Ok((headers, Json(fc)))
+}
+
+async fn location(
+    Path((collection_id, location_id)): Path<(String, String)>,
+    Qs(mut query): Qs<Query>,
+    RemoteUrl(url): RemoteUrl,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Response> {
+    query.location_id = Some(location_id);
+
+    let items = url.join("../items/")?;
+
+    execute(&collection_id, &QueryType::Locations, query, &items, &state).await
+}
+
+/// Create or replace a named location of a collection
+async fn put_location(
+    Path((collection_id, location_id)): Path<(String, String)>,
+    Extension(state): Extension<Arc<State>>,
+    Json(mut location): Json<Feature>,
+) -> Result<StatusCode> {
+    collection_exists(&collection_id, &state).await?;
+
+    let created = state
+        .drivers
+        .edr
+        .location(&collection_id, &location_id)
+        .await?
+        .is_none();
+
+    location.id = Some(location_id);
+    state
+        .drivers
+        .edr
+        .put_location(&collection_id, &location)
+        .await?;
+
+    Ok(if created {
+        StatusCode::CREATED
+    } else {
+        StatusCode::NO_CONTENT
+    })
+}
+
+async fn delete_location(
+    Path((collection_id, location_id)): Path<(String, String)>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<StatusCode> {
+    state
+        .drivers
+        .edr
+        .location(&collection_id, &location_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    state
+        .drivers
+        .edr
+        .delete_location(&collection_id, &location_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Create a new instance of a collection
+async fn create_instance(
+    Path(collection_id): Path<String>,
+    RemoteUrl(url): RemoteUrl,
+    Extension(state): Extension<Arc<State>>,
+    Json(instance): Json<Collection>,
+) -> Result<(StatusCode, HeaderMap)> {
+    collection_exists(&collection_id, &state).await?;
+
+    if !is_valid_instance_id(&collection_id, &instance.id) {
+        return Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Invalid instance id `{}`, expected letters, digits, `-`, `_` or `.`",
+                instance.id
+            ),
+        ));
+    }
+
+    if state
+        .drivers
+        .instances
+        .read_instance(&collection_id, &instance.id)
+        .await?
+        .is_some()
+    {
+        return Err(Error::Exception(
+            StatusCode::CONFLICT,
+            format!("Instance with id `{}` already exists.", instance.id),
+        ));
+    }
+
+    let id = state
+        .drivers
+        .instances
+        .create_instance(&collection_id, &instance)
+        .await?;
+
+    let location = format!("{}/{}", &url[..Position::AfterPath], id);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(LOCATION, location.parse().unwrap());
+
+    Ok((StatusCode::CREATED, headers))
+}
+
+/// Add a feature or the features of a feature collection to an instance
+async fn create_instance_items(
+    Path((collection_id, instance_id)): Path<(String, String)>,
+    Extension(state): Extension<Arc<State>>,
+    Json(body): Json<Value>,
+) -> Result<StatusCode> {
+    instance_exists(&collection_id, &instance_id, &state).await?;
+
+    let values = match body.get("features") {
+        Some(Value::Array(features)) if body["type"] == "FeatureCollection" => features.to_owned(),
+        _ => vec![body],
+    };
+
+    let features = values
+        .into_iter()
+        .map(serde_json::from_value)
+        .collect::<std::result::Result<Vec<Feature>, _>>()
+        .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, format!("Invalid feature: {e}")))?;
+
+    state
+        .drivers
+        .instances
+        .create_instance_items(&collection_id, &instance_id, &features)
+        .await?;
+
+    Ok(StatusCode::CREATED)
+}
+
+/// List the instances of a collection
+async fn instances(
+    Path(collection_id): Path<String>,
+    RemoteUrl(url): RemoteUrl,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Instances>> {
+    collection_exists(&collection_id, &state).await?;
+
+    let mut instances = state
+        .drivers
+        .instances
+        .list_instances(&collection_id)
+        .await?;
+
+    for instance in instances.iter_mut() {
+        instance.links = vec![Link::new(
+            format!("{}/{}", &url[..Position::AfterPath], instance.id),
+            SELF,
+        )
+        .mediatype(JSON)];
+    }
+
+    Ok(Json(Instances {
+        links: vec![Link::new(&url, SELF).mediatype(JSON)],
+        instances,
+    }))
+}
+
+/// Get instance metadata
+async fn instance(
+    Path((collection_id, instance_id)): Path<(String, String)>,
+    RemoteUrl(url): RemoteUrl,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Collection>> {
+    let mut instance = state
+        .drivers
+        .instances
+        .read_instance(&collection_id, &instance_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    instance.links = vec![
+        Link::new(&url, SELF).mediatype(JSON),
+        Link::new(url.join("..")?, COLLECTION).mediatype(JSON),
+    ];
+
+    Ok(Json(instance))
+}
+
+/// Delete an instance with its items
+async fn delete_instance(
+    Path((collection_id, instance_id)): Path<(String, String)>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<StatusCode> {
+    state
+        .drivers
+        .instances
+        .read_instance(&collection_id, &instance_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    state
+        .drivers
+        .instances
+        .delete_instance(&collection_id, &instance_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn instance_query(
+    Path((collection_id, instance_id, query_type)): Path<(String, String, QueryType)>,
+    Qs(mut query): Qs<Query>,
+    RemoteUrl(url): RemoteUrl,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Response> {
+    instance_exists(&collection_id, &instance_id, &state).await?;
+
+    query.instance_id = Some(instance_id);
+
+    let items = url.join("../../items/")?;
+
+    execute(&collection_id, &query_type, query, &items, &state).await
+}
+
+/// Items query of an instance, the path is shared with adding items
+async fn instance_items(
+    Path((collection_id, instance_id)): Path<(String, String)>,
+    query: Qs<Query>,
+    url: RemoteUrl,
+    state: Extension<Arc<State>>,
+) -> Result<Response> {
+    instance_query(
+        Path((collection_id, instance_id, QueryType::Items)),
+        query,
+        url,
+        state,
+    )
+    .await
+}
+
+async fn instance_location(
+    Path((collection_id, instance_id, location_id)): Path<(String, String, String)>,
+    Qs(mut query): Qs<Query>,
+    RemoteUrl(url): RemoteUrl,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Response> {
+    instance_exists(&collection_id, &instance_id, &state).await?;
+
+    query.instance_id = Some(instance_id);
+    query.location_id = Some(location_id);
+
+    let items = url.join("../../../items/")?;
+
+    execute(&collection_id, &QueryType::Locations, query, &items, &state).await
+}
+
+/// Parameters only EDR items queries accept
+#[cfg(feature = "features")]
+const EDR_ITEMS_PARAMETERS: [&str; 5] = ["coords", "parameter-name", "z", "interval", "aggregate"];
+
+/// Parses the query string of an EDR items query, which shares its path with
+/// the features items, recognized by EDR parameters or `f=CoverageJSON`
+#[cfg(feature = "features")]
+pub(crate) fn items_query(qs: Option<&str>) -> Result<Option<Query>> {
+    let qs = qs.unwrap_or_default();
+
+    let edr = url::form_urlencoded::parse(qs.as_bytes()).any(|(key, value)| {
+        EDR_ITEMS_PARAMETERS.contains(&key.as_ref())
+            || key == "f" && matches!(value.to_lowercase().as_str(), "coveragejson" | "covjson")
+    });
+
+    if !edr {
+        return Ok(None);
+    }
+
+    serde_qs::from_str(qs)
+        .map(Some)
+        .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))
+}
+
+/// Executes a query and links the resulting features to the `items` of the collection
+pub(crate) async fn execute(
+    collection_id: &str,
+    query_type: &QueryType,
+    query: Query,
+    items: &Url,
+    state: &State,
+) -> Result<Response> {
     tracing::debug!("{:#?}", query);
 
+    if let Err(e) = query.geometry(query_type) {
+        return Err(Error::Exception(StatusCode::BAD_REQUEST, e));
+    }
+
+    if let Some(location_id) = &query.location_id {
+        if state
+            .drivers
+            .edr
+            .location(collection_id, location_id)
+            .await?
+            .is_none()
+        {
+            return Err(Error::Exception(
+                StatusCode::NOT_FOUND,
+                format!("No location with id `{location_id}`"),
+            ));
+        }
+    }
+
     let mut fc = state
         .drivers
         .edr
-        .query(&collection_id, &query_type, &query)
+        .query(collection_id, query_type, &query)
         .await?;
 
+    // aggregated features are not linked to an item
     for feature in fc.features.iter_mut() {
-        feature.links = vec![Link::new(
-            &url.join(&format!(
-                "items/{}",
-                feature.id.as_ref().expect("Feature should have id")
-            ))?,
-            SELF,
-        )
-        .mediatype(GEO_JSON)]
+        if let Some(id) = &feature.id {
+            feature.links = vec![Link::new(&items.join(id)?, SELF).mediatype(GEO_JSON)]
+        }
     }
 
+    respond(collection_id, query_type, &query, fc, state).await
+}
+
+/// Encodes the query result as GeoJSON or, with `f=CoverageJSON`, as CoverageJSON
+async fn respond(
+    collection_id: &str,
+    query_type: &QueryType,
+    query: &Query,
+    fc: FeatureCollection,
+    state: &State,
+) -> Result<Response> {
     let mut headers = HeaderMap::new();
     headers.insert("Content-Crs", query.crs.to_string().parse().unwrap());
-    headers.insert(CONTENT_TYPE, GEO_JSON.parse().unwrap());
 
-    Ok((headers, Json(fc)))
+    match query.f.as_deref().map(str::to_lowercase).as_deref() {
+        Some("coveragejson" | "covjson") => {
+            let collection = state
+                .drivers
+                .collections
+                .read_collection(collection_id)
+                .await?
+                .ok_or(Error::NotFound)?;
+
+            let coverage =
+                Coverage::from_features(&fc, query_type, &collection.parameter_names, &query.crs);
+
+            headers.insert(CONTENT_TYPE, COVERAGE_JSON.parse().unwrap());
+
+            Ok((headers, Json(coverage)).into_response())
+        }
+        _ => {
+            headers.insert(CONTENT_TYPE, GEO_JSON.parse().unwrap());
+
+            Ok((headers, Json(fc)).into_response())
+        }
+    }
+}
+
+/// Advertises the supported data queries, output formats and parameters of a collection
+///
+/// Parameters are inferred from the item properties, configured parameters
+/// (e.g. with units or observed properties) take precedence. Collections
+/// without parameters, or for which the EDR backend fails to list them,
+/// are not EDR capable and left as they are.
+///
+/// Only used for single collections, listings would scan the items of all
+/// collections.
+pub(crate) async fn describe(collection: &mut Collection, url: &Url, state: &State) {
+    let parameters = match state.drivers.edr.parameters(&collection.id).await {
+        Ok(parameters) if !parameters.is_empty() => parameters,
+        Ok(_) => return,
+        Err(e) => {
+            tracing::debug!("No EDR metadata for `{}`: {e}", collection.id);
+            return;
+        }
+    };
+
+    let base = &url[..Position::AfterPath];
+
+    let output_formats = vec!["CoverageJSON".to_string(), "GeoJSON".to_string()];
+    let units: Vec<String> = ["m", "km", "mi", "nmi", "ft"]
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+
+    let mut query_types = vec![
+        QueryType::Position,
+        QueryType::Radius,
+        QueryType::Area,
+        QueryType::Cube,
+        QueryType::Trajectory,
+        QueryType::Corridor,
+        QueryType::Items,
+    ];
+
+    let locations = state.drivers.edr.locations(&collection.id).await;
+    if locations.is_ok_and(|locations| !locations.features.is_empty()) {
+        query_types.push(QueryType::Locations);
+    }
+
+    let mut data_queries = collection.data_queries.take().unwrap_or_default();
+    for query_type in query_types {
+        let name = query_type.to_string();
+
+        let mut variables = Variables::new(query_type.clone());
+        variables.title = Some(format!("{name} query"));
+        variables.output_formats = output_formats.clone();
+        variables.default_output_format = Some("GeoJSON".to_string());
+        match query_type {
+            QueryType::Radius => variables.within_units = Some(units.clone()),
+            QueryType::Corridor => {
+                variables.width_units = Some(units.clone());
+                variables.height_units = Some(units.clone());
+            }
+            _ => {}
+        }
+
+        let query = DataQuery {
+            link: DataQueryLink {
+                link: Link::new(format!("{base}/{name}"), DATA).title(format!("{name} query")),
+                variables,
+            },
+        };
+        data_queries.insert(&query_type, query);
+    }
+    collection.data_queries = Some(data_queries);
+
+    for format in output_formats {
+        if !collection.output_formats.contains(&format) {
+            collection.output_formats.push(format);
+        }
+    }
+
+    for (id, data_type) in parameters {
+        let parameter = collection
+            .parameter_names
+            .entry(id.to_owned())
+            .or_insert_with(|| ParameterNames {
+                r#type: Default::default(),
+                id: Some(id.to_owned()),
+                description: None,
+                label: Some(id.to_owned()),
+                data_type: None,
+                unit: None,
+                observed_property: ObservedPropertyCollection {
+                    id: None,
+                    label: Label::String(id.to_owned()),
+                    description: None,
+                    categories: Vec::new(),
+                },
+                category_encoding: None,
+                extent: None,
+                measurement_type: None,
+            });
+        parameter.data_type.get_or_insert(data_type);
+    }
 }
 
-// async fn instances() {}
+async fn collection_exists(collection_id: &str, state: &State) -> Result<()> {
+    state
+        .drivers
+        .collections
+        .read_collection(collection_id)
+        .await?
+        .map(|_| ())
+        .ok_or(Error::NotFound)
+}
 
-// async fn instance() {}
+async fn instance_exists(collection_id: &str, instance_id: &str, state: &State) -> Result<()> {
+    state
+        .drivers
+        .instances
+        .read_instance(collection_id, instance_id)
+        .await?
+        .map(|_| ())
+        .ok_or(Error::NotFound)
+}
 
 pub(crate) fn router(state: &State) -> Router {
-    state.conformance.write().unwrap().extend(&CONFORMANCE);
+    // queries answer `501 Not Implemented` without EDR backend
+    if state.drivers.edr.is_available() {
+        state.conformance.write().unwrap().extend(&CONFORMANCE);
+    }
 
-    Router::new().route("/collections/:collection_id/:query_type", get(query))
-    // .route("/collections/:collection_id/instances", get(instances))
-    // .route("/collections/:collection_id/instances/:instance_id", get(instance))
-    // .route("/collections/:collection_id/instances/:instance_id/:query_type", get(instance))
+    Router::new()
+        .route("/collections/:collection_id/:query_type", get(query))
+        .route("/collections/:collection_id/locations", get(locations))
+        .route(
+            "/collections/:collection_id/locations/:location_id",
+            get(location).put(put_location).delete(delete_location),
+        )
+        .route(
+            "/collections/:collection_id/instances",
+            get(instances).post(create_instance),
+        )
+        .route(
+            "/collections/:collection_id/instances/:instance_id",
+            get(instance).delete(delete_instance),
+        )
+        .route(
+            "/collections/:collection_id/instances/:instance_id/items",
+            get(instance_items).post(create_instance_items),
+        )
+        .route(
+            "/collections/:collection_id/instances/:instance_id/:query_type",
+            get(instance_query),
+        )
+        .route(
+            "/collections/:collection_id/instances/:instance_id/locations/:location_id",
+            get(instance_location),
+        )
 }
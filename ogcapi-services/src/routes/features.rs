@@ -7,6 +7,7 @@ use axum::{
         header::{CONTENT_TYPE, LOCATION},
         HeaderMap, StatusCode,
     },
+    response::{IntoResponse, Response},
     routing::get,
     Json, Router,
 };
@@ -17,8 +18,10 @@ use ogcapi_types::{
         media_type::{GEO_JSON, JSON},
         Collection, Crs, Link, Linked,
     },
-    features::{Feature, FeatureCollection, Query},
+    features::{Feature, Query},
 };
+use serde::Deserialize;
+use serde_json::Value;
 
 use crate::{
     extractors::{Qs, RemoteUrl},
@@ -32,20 +35,41 @@ const CONFORMANCE: [&str; 4] = [
     "http://www.opengis.net/spec/ogcapi-features-2/1.0/conf/crs",
 ];
 
+/// Create a feature or the features of a feature collection
 async fn create(
     Path(collection_id): Path<String>,
-    Json(mut feature): Json<Feature>,
+    Json(body): Json<Value>,
     RemoteUrl(url): RemoteUrl,
     Extension(state): Extension<Arc<State>>,
 ) -> Result<(StatusCode, HeaderMap)> {
-    feature.collection = Some(collection_id);
+    let values = match body.get("features") {
+        Some(Value::Array(features)) if body["type"] == "FeatureCollection" => features.to_owned(),
+        _ => vec![body],
+    };
+
+    let mut features = Vec::new();
+    for value in values {
+        let mut feature = parse(&value)?;
+        feature.collection = Some(collection_id.to_owned());
+
+        // validate bodies declaring a `stac_version` as STAC Items
+        #[cfg(feature = "stac")]
+        if value.get("stac_version").is_some() {
+            crate::routes::stac::validate_item(&value, &feature)?;
+        }
+
+        features.push(feature);
+    }
 
-    let id = state.drivers.features.create_feature(&feature).await?;
+    let ids = state.drivers.features.create_features(&features).await?;
 
-    let location = url.join(&format!("items/{}", id))?;
+    update_extent(&collection_id, &[], &features, &state).await?;
 
     let mut headers = HeaderMap::new();
-    headers.insert(LOCATION, location.as_str().parse().unwrap());
+    if let [id] = &ids[..] {
+        let location = url.join(&format!("items/{}", id))?;
+        headers.insert(LOCATION, location.as_str().parse().unwrap());
+    }
 
     Ok((StatusCode::CREATED, headers))
 }
@@ -71,6 +95,10 @@ async fn read(
         .await?
         .ok_or(Error::NotFound)?;
 
+    #[cfg(feature = "stac")]
+    crate::routes::stac::resolve_assets(&mut feature.assets, &url.join(&format!("{id}/"))?, &state)
+        .await?;
+
     feature.links.insert_or_update(&[
         Link::new(&url, SELF).mediatype(GEO_JSON),
         Link::new(&url.join("../../..")?, ROOT).mediatype(JSON),
@@ -93,14 +121,111 @@ async fn read(
 
 async fn update(
     Path((collection_id, id)): Path<(String, String)>,
-    Json(mut feature): Json<Feature>,
+    Json(body): Json<Value>,
     Extension(state): Extension<Arc<State>>,
 ) -> Result<StatusCode> {
+    #[cfg(feature = "stac")]
+    let stac = body.get("stac_version").is_some();
+
+    let mut feature = parse(&body)?;
     feature.id = Some(id);
-    feature.collection = Some(collection_id);
+    feature.collection = Some(collection_id.to_owned());
+
+    #[cfg(feature = "stac")]
+    if stac {
+        crate::routes::stac::validate_item(&body, &feature)?;
+    }
+
+    let previous = state
+        .drivers
+        .features
+        .read_feature(
+            &collection_id,
+            feature.id.as_ref().unwrap(),
+            &Crs::default(),
+        )
+        .await?;
 
     state.drivers.features.update_feature(&feature).await?;
 
+    update_extent(&collection_id, previous.as_slice(), &[feature], &state).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Update a feature with a JSON merge patch
+async fn patch(
+    Path((collection_id, id)): Path<(String, String)>,
+    Json(patch): Json<Value>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<StatusCode> {
+    let collection = state
+        .drivers
+        .collections
+        .read_collection(&collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    // features are written in the storage crs
+    let storage_crs = collection.storage_crs.unwrap_or_default();
+
+    let feature = state
+        .drivers
+        .features
+        .read_feature(&collection_id, &id, &storage_crs)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    // valid STAC Items have to remain valid
+    #[cfg(feature = "stac")]
+    let stac =
+        patch.get("stac_version").is_some() || ogcapi_types::stac::validate_item(&feature).is_ok();
+
+    let previous = if storage_crs == Crs::default() {
+        Some(feature.clone())
+    } else {
+        state
+            .drivers
+            .features
+            .read_feature(&collection_id, &id, &Crs::default())
+            .await?
+    };
+
+    let mut value = serde_json::to_value(feature).context("Unable to serialize feature")?;
+    crate::routes::merge_patch(&mut value, patch);
+
+    let mut feature = parse(&value)?;
+    feature.id = Some(id);
+    feature.collection = Some(collection_id.to_owned());
+
+    #[cfg(feature = "stac")]
+    if stac {
+        crate::routes::stac::validate_item(&value, &feature)?;
+    }
+
+    state.drivers.features.update_feature(&feature).await?;
+
+    let feature = if storage_crs == Crs::default() {
+        Some(feature)
+    } else {
+        state
+            .drivers
+            .features
+            .read_feature(
+                &collection_id,
+                feature.id.as_ref().unwrap(),
+                &Crs::default(),
+            )
+            .await?
+    };
+    update_extent(
+        &collection_id,
+        previous.as_slice(),
+        feature.as_slice(),
+        &state,
+    )
+    .await?;
+
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -108,12 +233,20 @@ async fn remove(
     Path((collection_id, id)): Path<(String, String)>,
     Extension(state): Extension<Arc<State>>,
 ) -> Result<StatusCode> {
+    let previous = state
+        .drivers
+        .features
+        .read_feature(&collection_id, &id, &Crs::default())
+        .await?;
+
     state
         .drivers
         .features
         .delete_feature(&collection_id, &id)
         .await?;
 
+    update_extent(&collection_id, previous.as_slice(), &[], &state).await?;
+
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -122,9 +255,23 @@ async fn items(
     Qs(mut query): Qs<Query>,
     RemoteUrl(mut url): RemoteUrl,
     Extension(state): Extension<Arc<State>>,
-) -> Result<(HeaderMap, Json<FeatureCollection>)> {
+) -> Result<Response> {
     tracing::debug!("{:#?}", query);
 
+    // EDR items query
+    #[cfg(feature = "edr")]
+    if let Some(query) = crate::routes::edr::items_query(url.query())? {
+        let items = url.join("items/")?;
+        return crate::routes::edr::execute(
+            &collection_id,
+            &ogcapi_types::edr::QueryType::Items,
+            query,
+            &items,
+            &state,
+        )
+        .await;
+    }
+
     // Limit
     if let Some(limit) = query.limit {
         if limit > 10000 {
@@ -144,11 +291,26 @@ async fn items(
 
     // TODO: validate additional parameters
 
-    let mut fc = state
-        .drivers
-        .features
-        .list_items(&collection_id, &query)
-        .await?;
+    // virtual collection backed by a workflow
+    #[cfg(feature = "processes")]
+    let workflow = crate::processor::workflow::Workflow::of(&collection);
+    #[cfg(not(feature = "processes"))]
+    let workflow: Option<()> = None;
+
+    let mut fc = match workflow {
+        #[cfg(feature = "processes")]
+        Some(workflow) => {
+            crate::processor::workflow::items(&collection_id, workflow, &query, &state, &url)
+                .await?
+        }
+        _ => {
+            state
+                .drivers
+                .features
+                .list_items(&collection_id, &query)
+                .await?
+        }
+    };
 
     fc.links.insert_or_update(&[
         Link::new(&url, SELF).mediatype(GEO_JSON),
@@ -182,6 +344,14 @@ async fn items(
     }
 
     for feature in fc.features.iter_mut() {
+        #[cfg(feature = "stac")]
+        crate::routes::stac::resolve_assets(
+            &mut feature.assets,
+            &url.join(&format!("items/{}/", feature.id.as_ref().unwrap()))?,
+            &state,
+        )
+        .await?;
+
         feature.links.insert_or_update(&[
             Link::new(
                 &url.join(&format!("items/{}", feature.id.as_ref().unwrap()))?,
@@ -197,7 +367,33 @@ async fn items(
     headers.insert("Content-Crs", query.crs.to_string().parse().unwrap());
     headers.insert(CONTENT_TYPE, GEO_JSON.parse().unwrap());
 
-    Ok((headers, Json(fc)))
+    Ok((headers, Json(fc)).into_response())
+}
+
+fn parse(value: &Value) -> Result<Feature> {
+    Feature::deserialize(value)
+        .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, format!("Invalid feature: {e}")))
+}
+
+/// Updates the extent (and summaries) of a collection after items have been
+/// `removed` (or replaced) and `added`
+async fn update_extent(
+    collection_id: &str,
+    removed: &[Feature],
+    added: &[Feature],
+    state: &State,
+) -> Result<()> {
+    if removed.is_empty() && added.is_empty() {
+        return Ok(());
+    }
+
+    state
+        .drivers
+        .maintenance
+        .update_extent(collection_id, removed, added)
+        .await?;
+
+    Ok(())
 }
 
 async fn is_supported_crs(collection: &Collection, crs: &Crs) -> Result<(), Error> {
@@ -218,6 +414,6 @@ pub(crate) fn router(state: &State) -> Router {
         .route("/collections/:collection_id/items", get(items).post(create))
         .route(
             "/collections/:collection_id/items/:id",
-            get(read).put(update).delete(remove),
+            get(read).put(update).patch(patch).delete(remove),
         )
 }
@@ -1,5 +1,7 @@
 pub(crate) mod api;
 pub(crate) mod collections;
+#[cfg(feature = "coverages")]
+pub(crate) mod coverages;
 #[cfg(feature = "edr")]
 pub(crate) mod edr;
 #[cfg(feature = "features")]
@@ -16,7 +18,13 @@ pub(crate) mod tiles;
 use std::sync::Arc;
 
 use axum::{extract::Extension, Json};
+use serde_json::Value;
 
+#[cfg(feature = "stac")]
+use ogcapi_types::common::{
+    link_rel::{AGGREGATE, AGGREGATIONS, QUERYABLES},
+    media_type::SCHEMA_JSON,
+};
 use ogcapi_types::common::{
     link_rel::{CONFORMANCE, ROOT, SEARCH, SELF, SERVICE_DESC, SERVICE_DOC},
     media_type::{HTML, JSON, OPEN_API_JSON},
@@ -50,6 +58,18 @@ pub(crate) async fn root(
         Link::new("search", SEARCH)
             .title("URI for the STAC API - Item Search endpoint")
             .mediatype(JSON),
+        #[cfg(feature = "stac")]
+        Link::new("queryables", QUERYABLES)
+            .title("Queryables available for the filter extension")
+            .mediatype(SCHEMA_JSON),
+        #[cfg(feature = "stac")]
+        Link::new("aggregate", AGGREGATE)
+            .title("Aggregations of the items matching a search")
+            .mediatype(JSON),
+        #[cfg(feature = "stac")]
+        Link::new("aggregations", AGGREGATIONS)
+            .title("Aggregations available for the aggregation extension")
+            .mediatype(JSON),
     ]);
     root.links.resolve_relative_links();
 
@@ -62,3 +82,23 @@ pub(crate) async fn root(
 pub(crate) async fn conformance(Extension(state): Extension<Arc<State>>) -> Json<Conformance> {
     Json(state.conformance.read().unwrap().to_owned())
 }
+
+/// Applies a JSON merge patch (RFC 7396) to a document
+pub(crate) fn merge_patch(target: &mut Value, patch: Value) {
+    match patch {
+        Value::Object(patch) => {
+            if !target.is_object() {
+                *target = Value::Object(Default::default());
+            }
+            let target = target.as_object_mut().unwrap();
+            for (key, value) in patch {
+                if value.is_null() {
+                    target.remove(&key);
+                } else {
+                    merge_patch(target.entry(key).or_insert(Value::Null), value);
+                }
+            }
+        }
+        patch => *target = patch,
+    }
+}
@@ -2,35 +2,61 @@ use std::sync::Arc;
 
 use axum::{
     extract::{Extension, Multipart, Path, Query},
-    http::StatusCode,
-    response::Response,
+    http::{
+        header::{CONTENT_TYPE, LOCATION},
+        HeaderMap, StatusCode,
+    },
+    response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
-use url::Position;
+use serde::Deserialize;
+use url::{Position, Url};
 
 use ogcapi_types::{
     common::{
-        link_rel::{NEXT, PREV, PROCESSES, SELF},
-        media_type::JSON,
-        Link,
+        link_rel::{ITEMS, JOB_LIST, NEXT, PREV, PROCESSES, RESULTS, SELF, STATUS},
+        media_type::{GEO_JSON, JSON},
+        Collection, Link,
+    },
+    processes::{
+        Execute as ProcessExecute, JobList, JobQuery, Process, ProcessList, ProcessQuery,
+        ProcessSummary, StatusCode as JobStatus, StatusInfo,
     },
-    processes::{Execute as ProcessExecute, Process, ProcessList, ProcessQuery, ProcessSummary},
 };
 
-use crate::{extractors::RemoteUrl, Error, Result, State};
+use ogcapi_drivers::JobResults;
+
+use crate::{
+    extractors::RemoteUrl,
+    processor::{
+        job,
+        workflow::{self, Workflow},
+    },
+    Error, Result, State,
+};
 
-const CONFORMANCE: [&str; 4] = [
+const CONFORMANCE: [&str; 9] = [
     "http://www.opengis.net/spec/ogcapi-processes-1/1.0/conf/core",
     "http://www.opengis.net/spec/ogcapi-processes-1/1.0/conf/ogc-process-description",
     "http://www.opengis.net/spec/ogcapi-processes-1/1.0/conf/json",
     // "http://www.opengis.net/spec/ogcapi-processes-1/1.0/conf/html",
-    // "http://www.opengis.net/spec/ogcapi-processes-1/1.0/conf/oas30",
-    // "http://www.opengis.net/spec/ogcapi-processes-1/1.0/conf/job-list",
+    "http://www.opengis.net/spec/ogcapi-processes-1/1.0/conf/oas30",
+    "http://www.opengis.net/spec/ogcapi-processes-1/1.0/conf/job-list",
     // "http://www.opengis.net/spec/ogcapi-processes-1/1.0/conf/callback",
     "http://www.opengis.net/spec/ogcapi-processes-1/1.0/conf/dismiss",
+    "http://www.opengis.net/spec/ogcapi-processes-3/0.0/conf/nested-processes",
+    "http://www.opengis.net/spec/ogcapi-processes-3/0.0/conf/collection-input",
+    "http://www.opengis.net/spec/ogcapi-processes-3/0.0/conf/collection-output",
 ];
 
+/// Query parameters of the execution endpoint
+#[derive(Deserialize, Debug)]
+struct ExecutionQuery {
+    /// Set to `collection` to expose the result as virtual collection
+    response: Option<String>,
+}
+
 async fn processes(
     Query(mut query): Query<ProcessQuery>,
     RemoteUrl(mut url): RemoteUrl,
@@ -106,6 +132,8 @@ async fn process(
 
 async fn execution(
     Path(id): Path<String>,
+    Query(query): Query<ExecutionQuery>,
+    headers: HeaderMap,
     json: Option<Json<ProcessExecute>>,
     multipart: Option<Multipart>,
     RemoteUrl(url): RemoteUrl,
@@ -128,57 +156,207 @@ async fn execution(
         }
     }
 
-    if execute.is_none() {
-        return Err(Error::Exception(
+    let execute = execute.ok_or_else(|| {
+        Error::Exception(
             StatusCode::BAD_REQUEST,
             "Unable to extract `ProcessExecute` from body".to_string(),
-        ));
+        )
+    })?;
+
+    if query.response.as_deref() == Some("collection") {
+        return collection_output(id, execute, url, state).await;
     }
 
-    match state.processors.get(&id) {
-        Some(processor) => processor.execute(execute.unwrap(), &state, &url).await,
-        None => Err(Error::Exception(
+    let respond_async = headers
+        .get_all("Prefer")
+        .iter()
+        .filter_map(|h| h.to_str().ok())
+        .any(|h| h.contains("respond-async"));
+
+    if respond_async {
+        let mut job = job::submit(&id, execute, state, url.clone()).await?;
+
+        let location = url.join(&format!("../../jobs/{}", job.job_id))?;
+        job.links = vec![Link::new(&location, STATUS).mediatype(JSON)];
+
+        let mut headers = HeaderMap::new();
+        headers.insert(LOCATION, location.as_str().parse().unwrap());
+        headers.insert("Preference-Applied", "respond-async".parse().unwrap());
+
+        return Ok((StatusCode::CREATED, headers, Json(job)).into_response());
+    }
+
+    workflow::run(&id, execute, &state, &url).await
+}
+
+/// Registers the execution as virtual collection, evaluated on access
+async fn collection_output(
+    process: String,
+    execute: ProcessExecute,
+    url: Url,
+    state: Arc<State>,
+) -> Result<Response> {
+    if !state.processors.contains_key(&process) {
+        return Err(Error::Exception(
             StatusCode::NOT_FOUND,
-            format!("No process with id `{}`", id),
-        )),
+            format!("No process with id `{}`", process),
+        ));
+    }
+
+    let id = format!("{process}-{}", uuid::Uuid::new_v4().simple());
+    let location = url.join(&format!("../../collections/{id}"))?;
+
+    let mut collection = Collection {
+        id: id.to_owned(),
+        title: Some(format!("Output of process `{process}`")),
+        links: vec![
+            Link::new(&location, SELF).mediatype(JSON),
+            Link::new(format!("{location}/items"), ITEMS).mediatype(GEO_JSON),
+        ],
+        ..Default::default()
+    };
+    Workflow { process, execute }.attach(&mut collection);
+
+    state
+        .drivers
+        .collections
+        .create_collection(&collection)
+        .await?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(LOCATION, location.as_str().parse().unwrap());
+
+    Ok((StatusCode::CREATED, headers, Json(collection)).into_response())
+}
+
+async fn jobs(
+    Query(mut query): Query<JobQuery>,
+    RemoteUrl(mut url): RemoteUrl,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<JobList>> {
+    let mut jobs = state.drivers.jobs.list_jobs(&query).await?;
+
+    jobs.iter_mut().for_each(|job| {
+        let href = format!("{}/{}", &url[..Position::AfterPath], job.job_id);
+        job.links = job_links(&href, job);
+    });
+
+    let mut links = vec![Link::new(&url, SELF).mediatype(JSON)];
+
+    if let Some(limit) = query.limit {
+        let offset = query.offset.unwrap_or(0);
+
+        if offset != 0 && offset >= limit {
+            query.offset = Some(offset - limit);
+            let query_string = serde_qs::to_string(&query)?;
+            url.set_query(Some(&query_string));
+            let previous = Link::new(&url, PREV).mediatype(JSON);
+            links.push(previous);
+        }
+
+        if jobs.len() == limit {
+            query.offset = Some(offset + limit);
+            let query_string = serde_qs::to_string(&query)?;
+            url.set_query(Some(&query_string));
+            let next = Link::new(&url, NEXT).mediatype(JSON);
+            links.push(next);
+        }
     }
+
+    Ok(Json(JobList { jobs, links }))
 }
 
-// async fn jobs() {
-//     todo!()
-// }
+async fn status(
+    Path(id): Path<String>,
+    RemoteUrl(url): RemoteUrl,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<StatusInfo>> {
+    let mut status = state.drivers.jobs.status(&id).await?.ok_or_else(|| {
+        Error::Exception(StatusCode::NOT_FOUND, format!("No job with id `{}`", id))
+    })?;
 
-// async fn status(
-//     RemoteUrl(url): RemoteUrl,
-//     Path(id): Path<String>,
-//     Extension(state): Extension<Arc<State>>,
-// ) -> Result<Json<StatusInfo>> {
-//     let mut status = state.drivers.jobs.status(&id).await?;
+    status.links = job_links(&url[..Position::AfterPath], &status);
 
-//     status.links = vec![Link::new(url, SELF).mediatype(JSON)];
+    Ok(Json(status))
+}
 
-//     Ok(Json(status))
-// }
+async fn dismiss(
+    Path(id): Path<String>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<StatusInfo>> {
+    let status = job::dismiss(&id, &state).await?;
 
-// async fn delete(
-//     Path(id): Path<String>,
-//     Extension(state): Extension<Arc<State>>,
-// ) -> Result<StatusCode> {
-//     state.drivers.jobs.delete(&id).await?;
+    Ok(Json(status))
+}
+
+async fn results(
+    Path(id): Path<String>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Response> {
+    let status = state.drivers.jobs.status(&id).await?.ok_or_else(|| {
+        Error::Exception(StatusCode::NOT_FOUND, format!("No job with id `{}`", id))
+    })?;
+
+    match status.status {
+        JobStatus::Successful => {}
+        JobStatus::Failed => {
+            return Err(Error::Exception(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                status
+                    .message
+                    .unwrap_or_else(|| format!("Job `{}` failed", id)),
+            ))
+        }
+        _ => {
+            return Err(Error::Exception(
+                StatusCode::NOT_FOUND,
+                format!("Results of job `{}` are not ready", id),
+            ))
+        }
+    }
+
+    let not_found = || {
+        Error::Exception(
+            StatusCode::NOT_FOUND,
+            format!("No results for job with id `{}`", id),
+        )
+    };
+
+    let (data, media_type) = match state.drivers.jobs.results(&id).await? {
+        Some(JobResults::Inline { value, media_type }) => {
+            (serde_json::to_vec(&value).unwrap(), media_type)
+        }
+        Some(JobResults::Artifact { key, media_type }) => (
+            state
+                .drivers
+                .artifacts
+                .get_artifact(&key)
+                .await?
+                .ok_or_else(not_found)?,
+            media_type,
+        ),
+        None => return Err(not_found()),
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        CONTENT_TYPE,
+        media_type.as_deref().unwrap_or(JSON).parse().unwrap(),
+    );
 
-//     // TODO: cancel execution
+    Ok((headers, data).into_response())
+}
 
-//     Ok(StatusCode::NO_CONTENT)
-// }
+/// Links of a job with status url `href`
+fn job_links(href: &str, job: &StatusInfo) -> Vec<Link> {
+    let mut links = vec![Link::new(href, SELF).mediatype(JSON)];
 
-// async fn results(
-//     Path(id): Path<String>,
-//     Extension(state): Extension<Arc<State>>,
-// ) -> Result<Json<Results>> {
-//     let results = state.drivers.jobs.results(&id).await?;
+    if job.status == JobStatus::Successful {
+        links.push(Link::new(format!("{href}/results"), RESULTS).title("job results"));
+    }
 
-//     Ok(Json(results))
-// }
+    links
+}
 
 pub(crate) fn router(state: &State) -> Router {
     let mut root = state.root.write().unwrap();
@@ -186,18 +364,20 @@ pub(crate) fn router(state: &State) -> Router {
         Link::new("processes", PROCESSES)
             .mediatype(JSON)
             .title("Metadata about the processes"),
-        // Link::new("jobs", JOB_LIST)
-        //     .mediatype(JSON)
-        //     .title("The endpoint for job monitoring"),
+        Link::new("jobs", JOB_LIST)
+            .mediatype(JSON)
+            .title("The endpoint for job monitoring"),
     ]);
 
-    state.conformance.write().unwrap().extend(&CONFORMANCE);
+    if !state.processors.is_empty() {
+        state.conformance.write().unwrap().extend(&CONFORMANCE);
+    }
 
     Router::new()
         .route("/processes", get(processes))
         .route("/processes/:id", get(process))
         .route("/processes/:id/execution", post(execution))
-    // .route("/jobs", get(jobs))
-    // .route("/jobs/:id", get(status).delete(delete))
-    // .route("/jobs/:id/results", get(results))
+        .route("/jobs", get(jobs))
+        .route("/jobs/:id", get(status).delete(dismiss))
+        .route("/jobs/:id/results", get(results))
 }
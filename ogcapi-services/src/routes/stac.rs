@@ -1,27 +1,46 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
+use anyhow::Context;
 use axum::{
-    http::{HeaderMap, StatusCode},
+    body,
+    extract::Path,
+    http::{
+        header::{ACCEPT_RANGES, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, ETAG, RANGE},
+        HeaderMap, HeaderValue, StatusCode,
+    },
+    response::{IntoResponse, Response},
     Extension, Json,
 };
-use hyper::header::CONTENT_TYPE;
-use ogcapi_drivers::StacSeach;
+use ogcapi_drivers::s3::SdkError;
+#[cfg(feature = "features")]
+use ogcapi_types::common::Crs;
 use ogcapi_types::{
     common::{
         link_rel::{COLLECTION, NEXT, PREV, ROOT, SELF},
-        media_type::{GEO_JSON, JSON},
-        Bbox, Link, Linked,
+        media_type::{GEO_JSON, JSON, SCHEMA_JSON},
+        Bbox, Collection, Link, Linked, ListParam,
+    },
+    features::{FeatureCollection, Queryables},
+    stac::{
+        self, Aggregation, AggregationBody, AggregationCollection, AggregationParams, Asset, Item,
+        SearchBody, SearchParams, Validator, AGGREGATIONS,
     },
-    features::FeatureCollection,
-    stac::{SearchBody, SearchParams},
 };
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use serde_json::{json, Value};
 use url::Url;
 
 use crate::{
     extractors::{Qs, RemoteUrl},
-    Error, Result, State,
+    AssetHrefs, Error, Result, State,
 };
 
+/// Validity of presigned asset URLs
+const PRESIGNED_EXPIRY: Duration = Duration::from_secs(3600);
+
+static VALIDATOR: OnceCell<Validator> = OnceCell::new();
+
 pub(crate) async fn search_get(
     Qs(params): Qs<SearchParams>,
     RemoteUrl(url): RemoteUrl,
@@ -58,29 +77,9 @@ pub(crate) async fn search(
         params.limit = Some(100);
     }
 
-    // Bbox
-    if let Some(bbox) = params.bbox.as_ref() {
-        match bbox {
-            Bbox::Bbox2D(bbox) => {
-                if bbox[0] > bbox[2] || bbox[1] > bbox[3] {
-                    return Err(Error::Exception(
-                        StatusCode::BAD_REQUEST,
-                        "query parameter `bbox` not valid".to_string(),
-                    ));
-                }
-            }
-            Bbox::Bbox3D(bbox) => {
-                if bbox[0] > bbox[3] || bbox[1] > bbox[4] || bbox[2] > bbox[5] {
-                    return Err(Error::Exception(
-                        StatusCode::BAD_REQUEST,
-                        "query parameter `bbox` not valid".to_string(),
-                    ));
-                }
-            }
-        }
-    }
+    check_params(&params)?;
 
-    let mut fc = state.db.search(&params).await?;
+    let mut fc = state.drivers.search.search(&params).await?;
 
     fc.links.insert_or_update(&[
         Link::new(&url, SELF).mediatype(GEO_JSON),
@@ -114,6 +113,18 @@ pub(crate) async fn search(
 
     for feature in fc.features.iter_mut() {
         let collection = feature.collection.as_ref().unwrap();
+
+        resolve_assets(
+            &mut feature.assets,
+            &url.join(&format!(
+                "collections/{}/items/{}/",
+                collection,
+                feature.id.as_ref().unwrap()
+            ))?,
+            &state,
+        )
+        .await?;
+
         feature.links.insert_or_update(&[
             Link::new(
                 &url.join(&format!(
@@ -138,3 +149,399 @@ pub(crate) async fn search(
 
     Ok((headers, Json(fc)))
 }
+
+/// Checks the bbox and filter of search parameters
+fn check_params(params: &SearchParams) -> Result<()> {
+    // Bbox
+    if let Some(bbox) = params.bbox.as_ref() {
+        match bbox {
+            Bbox::Bbox2D(bbox) => {
+                if bbox[0] > bbox[2] || bbox[1] > bbox[3] {
+                    return Err(Error::Exception(
+                        StatusCode::BAD_REQUEST,
+                        "query parameter `bbox` not valid".to_string(),
+                    ));
+                }
+            }
+            Bbox::Bbox3D(bbox) => {
+                if bbox[0] > bbox[3] || bbox[1] > bbox[4] || bbox[2] > bbox[5] {
+                    return Err(Error::Exception(
+                        StatusCode::BAD_REQUEST,
+                        "query parameter `bbox` not valid".to_string(),
+                    ));
+                }
+            }
+        }
+    }
+
+    // Filter
+    if let Err(e) = params.filter_expr() {
+        return Err(Error::Exception(StatusCode::BAD_REQUEST, e));
+    }
+
+    Ok(())
+}
+
+/// Queryables shared by all items
+fn core_queryables() -> Queryables {
+    let mut queryables = Queryables::default();
+    queryables.properties.extend([
+        (
+            "id".to_string(),
+            json!({
+                "description": "ID",
+                "$ref": "https://schemas.stacspec.org/v1.0.0/item-spec/json-schema/item.json#/definitions/core/allOf/2/properties/id"
+            }),
+        ),
+        (
+            "collection".to_string(),
+            json!({
+                "description": "Collection",
+                "$ref": "https://schemas.stacspec.org/v1.0.0/item-spec/json-schema/item.json#/collection"
+            }),
+        ),
+        (
+            "geometry".to_string(),
+            json!({
+                "description": "Geometry",
+                "$ref": "https://schemas.stacspec.org/v1.0.0/item-spec/json-schema/item.json#/definitions/core/allOf/1/oneOf/0/properties/geometry"
+            }),
+        ),
+        (
+            "datetime".to_string(),
+            json!({
+                "description": "Datetime",
+                "$ref": "https://schemas.stacspec.org/v1.0.0/item-spec/json-schema/datetime.json#/properties/datetime"
+            }),
+        ),
+    ]);
+    queryables
+}
+
+pub(crate) async fn queryables(RemoteUrl(url): RemoteUrl) -> Result<(HeaderMap, Json<Queryables>)> {
+    let mut queryables = core_queryables();
+    queryables.id = Some(url.to_string());
+    queryables.title = Some("STAC Queryables".to_string());
+
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, SCHEMA_JSON.parse().unwrap());
+
+    Ok((headers, Json(queryables)))
+}
+
+pub(crate) async fn collection_queryables(
+    Path(collection_id): Path<String>,
+    RemoteUrl(url): RemoteUrl,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<(HeaderMap, Json<Queryables>)> {
+    if state
+        .drivers
+        .collections
+        .read_collection(&collection_id)
+        .await?
+        .is_none()
+    {
+        return Err(Error::NotFound);
+    }
+
+    let mut queryables = core_queryables();
+    for (key, schema) in state
+        .drivers
+        .search
+        .queryables(&collection_id)
+        .await?
+        .properties
+    {
+        queryables.properties.entry(key).or_insert(schema);
+    }
+    queryables.id = Some(url.to_string());
+    queryables.title = Some(format!("Queryables of `{collection_id}`"));
+
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, SCHEMA_JSON.parse().unwrap());
+
+    Ok((headers, Json(queryables)))
+}
+
+pub(crate) async fn aggregate_get(
+    Qs(params): Qs<SearchParams>,
+    Qs(aggregation): Qs<AggregationParams>,
+    RemoteUrl(url): RemoteUrl,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<AggregationCollection>> {
+    aggregate(params, aggregation, url, state).await
+}
+
+pub(crate) async fn aggregate_post(
+    Json(body): Json<Value>,
+    RemoteUrl(url): RemoteUrl,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<AggregationCollection>> {
+    let (params, aggregation) = parse_aggregate(&body)?;
+    aggregate(params, aggregation, url, state).await
+}
+
+pub(crate) async fn collection_aggregate_get(
+    Path(collection_id): Path<String>,
+    Qs(params): Qs<SearchParams>,
+    Qs(aggregation): Qs<AggregationParams>,
+    RemoteUrl(url): RemoteUrl,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<AggregationCollection>> {
+    let params = scope(&collection_id, params, &state).await?;
+    aggregate(params, aggregation, url, state).await
+}
+
+pub(crate) async fn collection_aggregate_post(
+    Path(collection_id): Path<String>,
+    Json(body): Json<Value>,
+    RemoteUrl(url): RemoteUrl,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<AggregationCollection>> {
+    let (params, aggregation) = parse_aggregate(&body)?;
+    let params = scope(&collection_id, params, &state).await?;
+    aggregate(params, aggregation, url, state).await
+}
+
+async fn aggregate(
+    params: SearchParams,
+    aggregation: AggregationParams,
+    url: Url,
+    state: Arc<State>,
+) -> Result<Json<AggregationCollection>> {
+    tracing::debug!("{:#?} {:#?}", params, aggregation);
+
+    check_params(&params)?;
+    if let Err(e) = aggregation
+        .names()
+        .and_then(|_| aggregation.geohash_precision())
+    {
+        return Err(Error::Exception(StatusCode::BAD_REQUEST, e));
+    }
+
+    let mut aggregations = state
+        .drivers
+        .search
+        .aggregate(&params, &aggregation)
+        .await?;
+    aggregations
+        .links
+        .insert_or_update(&[Link::new(&url, SELF).mediatype(JSON)]);
+
+    Ok(Json(aggregations))
+}
+
+/// Parses the search and aggregation parameters of an aggregation body
+fn parse_aggregate(body: &Value) -> Result<(SearchParams, AggregationParams)> {
+    let params = SearchBody::deserialize(body)
+        .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?;
+    let aggregation = AggregationBody::deserialize(body)
+        .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?;
+    Ok((params.into(), aggregation.into()))
+}
+
+/// Restricts search parameters to an existing collection
+async fn scope(
+    collection_id: &str,
+    mut params: SearchParams,
+    state: &State,
+) -> Result<SearchParams> {
+    state
+        .drivers
+        .collections
+        .read_collection(collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    params.collections = Some(ListParam(vec![collection_id.to_owned()]));
+    Ok(params)
+}
+
+/// Aggregations supported by `/aggregate`
+pub(crate) async fn aggregations(RemoteUrl(url): RemoteUrl) -> Json<AggregationCollection> {
+    let mut aggregations = AggregationCollection::new(
+        AGGREGATIONS
+            .iter()
+            .map(|(name, data_type)| Aggregation {
+                name: name.to_string(),
+                data_type: data_type.to_string(),
+                value: None,
+                buckets: Vec::new(),
+            })
+            .collect(),
+    );
+    aggregations
+        .links
+        .insert_or_update(&[Link::new(&url, SELF).mediatype(JSON)]);
+
+    Json(aggregations)
+}
+
+/// Checks the structure of a STAC Item and validates its JSON against the
+/// bundled schemas
+pub(crate) fn validate_item(value: &Value, item: &Item) -> Result<()> {
+    stac::validate_item(item).map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e))?;
+    validate_schemas(value)
+}
+
+/// Checks the structure of a STAC Collection and validates its JSON against
+/// the bundled schemas
+pub(crate) fn validate_collection(value: &Value, collection: &Collection) -> Result<()> {
+    stac::validate_collection(collection)
+        .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e))?;
+    validate_schemas(value)
+}
+
+fn validate_schemas(value: &Value) -> Result<()> {
+    VALIDATOR
+        .get_or_init(Validator::new)
+        .validate(value)
+        .map_err(|errors| Error::Exception(StatusCode::BAD_REQUEST, errors.join("\n")))
+}
+
+/// Rewrites the S3 hrefs of `assets` into presigned URLs or links to the
+/// asset proxy at `{base}assets/{key}`, depending on the configuration
+pub(crate) async fn resolve_assets(
+    assets: &mut HashMap<String, Asset>,
+    base: &Url,
+    state: &State,
+) -> Result<()> {
+    let mode = match state.asset_hrefs {
+        Some(mode) => mode,
+        None => return Ok(()),
+    };
+
+    for (key, asset) in assets.iter_mut() {
+        if let Some((bucket, object)) = s3_object(&asset.href, state) {
+            asset.href = match mode {
+                AssetHrefs::Presign => {
+                    state
+                        .s3
+                        .presign_get_object(bucket, object, PRESIGNED_EXPIRY)
+                        .await?
+                }
+                AssetHrefs::Proxy => {
+                    // the asset key is a single, percent-encoded path segment
+                    let mut url = base.join("assets/")?;
+                    url.path_segments_mut()
+                        .map_err(|_| anyhow::anyhow!("Cannot resolve assets of `{base}`"))?
+                        .pop_if_empty()
+                        .push(key);
+                    url.to_string()
+                }
+            };
+        }
+    }
+
+    Ok(())
+}
+
+/// Bucket and key of an asset stored in an allowed S3 bucket, either
+/// referenced by an `s3://{bucket}/{key}` url or a bare key in the default
+/// bucket
+///
+/// Relative hrefs (e.g. `./data.tif`) are not keys.
+fn s3_object<'a>(href: &'a str, state: &'a State) -> Option<(&'a str, &'a str)> {
+    let (bucket, key) = match href.strip_prefix("s3://") {
+        Some(path) => path.split_once('/')?,
+        None if href.starts_with('.') || Url::parse(href).is_ok() => return None,
+        None => (state.s3.bucket.as_deref()?, href.trim_start_matches('/')),
+    };
+
+    let allowed = state.s3.bucket.as_deref() == Some(bucket)
+        || state.asset_buckets.iter().any(|b| b == bucket);
+
+    (allowed && !key.is_empty()).then_some((bucket, key))
+}
+
+/// Stream an asset of a collection
+pub(crate) async fn collection_asset(
+    Path((collection_id, key)): Path<(String, String)>,
+    headers: HeaderMap,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Response> {
+    let collection = state
+        .drivers
+        .collections
+        .read_collection(&collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let asset = collection.assets.get(&key).ok_or(Error::NotFound)?;
+
+    proxy(&asset.href, &headers, &state).await
+}
+
+/// Stream an asset of an item
+#[cfg(feature = "features")]
+pub(crate) async fn item_asset(
+    Path((collection_id, id, key)): Path<(String, String, String)>,
+    headers: HeaderMap,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Response> {
+    let item = state
+        .drivers
+        .features
+        .read_feature(&collection_id, &id, &Crs::default())
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let asset = item.assets.get(&key).ok_or(Error::NotFound)?;
+
+    proxy(&asset.href, &headers, &state).await
+}
+
+/// Streams an asset from S3, forwarding the `Range` header of the request
+///
+/// Only assets in allowed S3 buckets are served, so the route can not be used
+/// to redirect to arbitrary urls.
+async fn proxy(href: &str, headers: &HeaderMap, state: &State) -> Result<Response> {
+    let (bucket, key) = s3_object(href, state).ok_or(Error::NotFound)?;
+
+    let range = headers
+        .get(RANGE)
+        .and_then(|range| range.to_str().ok())
+        .map(ToString::to_string);
+
+    let object = match state.s3.get_object_range(bucket, key, range).await {
+        Ok(object) => object,
+        Err(SdkError::ServiceError { err, .. }) if err.is_no_such_key() => {
+            return Err(Error::NotFound)
+        }
+        Err(SdkError::ServiceError { raw, .. })
+            if raw.http().status().as_u16() == StatusCode::RANGE_NOT_SATISFIABLE.as_u16() =>
+        {
+            return Err(Error::Exception(
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                "Range not satisfiable".to_string(),
+            ))
+        }
+        Err(e) => return Err(anyhow::Error::new(e).into()),
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    headers.insert(CONTENT_LENGTH, object.content_length().into());
+    for (name, value) in [
+        (CONTENT_TYPE, object.content_type()),
+        (CONTENT_RANGE, object.content_range()),
+        (ETAG, object.e_tag()),
+    ] {
+        if let Some(value) = value {
+            headers.insert(
+                name,
+                value
+                    .parse()
+                    .context("Unable to parse asset header value")?,
+            );
+        }
+    }
+
+    let status = if object.content_range().is_some() {
+        StatusCode::PARTIAL_CONTENT
+    } else {
+        StatusCode::OK
+    };
+
+    Ok((status, headers, body::boxed(object.body.into_inner())).into_response())
+}
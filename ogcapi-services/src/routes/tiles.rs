@@ -136,23 +136,27 @@ pub(crate) fn router(state: &State) -> Router {
 
     state.conformance.write().unwrap().extend(&CONFORMANCE);
 
-    // Setup tile matrix sets
-    let mut tms_map = HashMap::new();
-    let web_mercartor_quad: TileMatrixSet =
-        serde_json::from_slice(WEB_MERCARTOR_QUAD).expect("parse tms");
-    tms_map.insert(web_mercartor_quad.id.to_owned(), web_mercartor_quad);
-
-    let mut tm = HashMap::new();
-    for tms in tms_map.values() {
-        tm.insert(tms.id.to_owned(), HashMap::new());
-        for tile_matrix in &tms.tile_matrices {
-            tm.get_mut(&tms.id).and_then(|tm_map| {
-                tm_map.insert(tile_matrix.id.to_owned(), tile_matrix.to_owned())
-            });
+    // Setup tile matrix sets, shared by the services of the process
+    let tms_map = TMS.get_or_init(|| {
+        let mut tms_map = HashMap::new();
+        let web_mercartor_quad: TileMatrixSet =
+            serde_json::from_slice(WEB_MERCARTOR_QUAD).expect("parse tms");
+        tms_map.insert(web_mercartor_quad.id.to_owned(), web_mercartor_quad);
+        tms_map
+    });
+
+    TM.get_or_init(|| {
+        let mut tm = HashMap::new();
+        for tms in tms_map.values() {
+            tm.insert(tms.id.to_owned(), HashMap::new());
+            for tile_matrix in &tms.tile_matrices {
+                tm.get_mut(&tms.id).and_then(|tm_map| {
+                    tm_map.insert(tile_matrix.id.to_owned(), tile_matrix.to_owned())
+                });
+            }
         }
-    }
-    TMS.set(tms_map).expect("set `TMS` once cell content");
-    TM.set(tm).expect("set `TM` once cell content");
+        tm
+    });
 
     Router::new()
         .route("/tileMatrixSets", get(tile_matrix_sets))
@@ -26,6 +26,8 @@ use tower_http::{
 
 use ogcapi_types::common::Exception;
 
+#[cfg(feature = "stac")]
+use crate::AssetHrefs;
 use crate::{routes, Config, ConfigParser, State};
 
 /// OGC API Services
@@ -36,17 +38,26 @@ pub struct Service {
 }
 
 impl Service {
-    pub async fn new() -> Self {
+    pub async fn new() -> anyhow::Result<Self> {
         // config
         let config = Config::parse();
 
         // state
-        let state = State::new_from(&config).await;
+        let state = State::new_from(&config).await?;
 
-        Service::new_with(&config, state).await
+        Ok(Service::new_with(&config, state).await)
     }
 
     pub async fn new_with(config: &Config, state: State) -> Self {
+        // document registered processes
+        #[cfg(feature = "processes")]
+        let state = {
+            let mut state = state;
+            let processes: Vec<_> = state.processors.values().map(|p| p.process()).collect();
+            state.openapi.processes(&processes);
+            state
+        };
+
         // router
         let router = Router::new()
             .route("/", get(routes::root))
@@ -63,12 +74,56 @@ impl Service {
             get(routes::stac::search_get).post(routes::stac::search_post),
         );
 
+        #[cfg(feature = "stac")]
+        let router = router
+            .route("/queryables", get(routes::stac::queryables))
+            .route(
+                "/collections/:collection_id/queryables",
+                get(routes::stac::collection_queryables),
+            )
+            .route(
+                "/aggregate",
+                get(routes::stac::aggregate_get).post(routes::stac::aggregate_post),
+            )
+            .route("/aggregations", get(routes::stac::aggregations))
+            .route(
+                "/collections/:collection_id/aggregate",
+                get(routes::stac::collection_aggregate_get)
+                    .post(routes::stac::collection_aggregate_post),
+            )
+            .route(
+                "/collections/:collection_id/aggregations",
+                get(routes::stac::aggregations),
+            );
+
+        // the asset proxy is only served when hrefs are rewritten to it
+        #[cfg(feature = "stac")]
+        let router = if state.asset_hrefs == Some(AssetHrefs::Proxy) {
+            let router = router.route(
+                "/collections/:collection_id/assets/:key",
+                get(routes::stac::collection_asset),
+            );
+
+            #[cfg(feature = "features")]
+            let router = router.route(
+                "/collections/:collection_id/items/:id/assets/:key",
+                get(routes::stac::item_asset),
+            );
+
+            router
+        } else {
+            router
+        };
+
         #[cfg(feature = "features")]
         let router = router.merge(routes::features::router(&state));
 
         #[cfg(feature = "edr")]
         let router = router.merge(routes::edr::router(&state));
 
+        #[cfg(feature = "coverages")]
+        let router = router.merge(routes::coverages::router(&state));
+
         #[cfg(feature = "styles")]
         let router = router.merge(routes::styles::router(&state));
 
@@ -111,8 +166,16 @@ impl Service {
 
     /// Serve application
     pub async fn serve(self) {
+        let state = Arc::new(self.state);
+
+        // purge expired jobs
+        #[cfg(feature = "processes")]
+        if let Some(retention) = state.job_retention {
+            tokio::spawn(crate::processor::job::purge(state.clone(), retention));
+        }
+
         // add state
-        let router = self.router.layer(Extension(Arc::new(self.state)));
+        let router = self.router.layer(Extension(state));
 
         // serve
         tracing::info!(
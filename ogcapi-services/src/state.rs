@@ -1,20 +1,37 @@
 use std::{collections::BTreeMap, sync::RwLock};
+#[cfg(feature = "processes")]
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use anyhow::Context;
 
-#[cfg(feature = "edr")]
-use ogcapi_drivers::EdrQuerier;
 #[cfg(feature = "features")]
 use ogcapi_drivers::FeatureTransactions;
-#[cfg(feature = "processes")]
-use ogcapi_drivers::JobHandler;
 #[cfg(feature = "styles")]
 use ogcapi_drivers::StyleTransactions;
 #[cfg(feature = "tiles")]
 use ogcapi_drivers::TileTransactions;
+#[cfg(feature = "processes")]
+use ogcapi_drivers::{fs::Fs, ArtifactStore, Geoprocessing, JobHandler};
+#[cfg(feature = "coverages")]
+use ogcapi_drivers::{raster::Raster, CoverageQuerier};
+#[cfg(feature = "edr")]
+use ogcapi_drivers::{EdrQuerier, InstanceTransactions};
+
+#[cfg(feature = "stac")]
+use ogcapi_drivers::{s3::S3, StacSeach};
 
-use ogcapi_drivers::{postgres::Db, CollectionTransactions};
+#[cfg(any(feature = "coverages", feature = "edr", feature = "processes"))]
+use ogcapi_drivers::unavailable::Unavailable;
+use ogcapi_drivers::{
+    gpkg::GeoPackage, memory::Memory, postgres::Db, CollectionMaintenance, CollectionTransactions,
+};
 use ogcapi_types::common::{Conformance, LandingPage};
 
-use crate::{openapi::OPENAPI, Config, ConfigParser, OpenAPI, Processor};
+#[cfg(feature = "stac")]
+use crate::AssetHrefs;
+#[cfg(feature = "processes")]
+use crate::CommandProcessor;
+use crate::{openapi::OPENAPI, Backend, Config, ConfigParser, OpenAPI, Processor};
 
 /// Application state
 pub struct State {
@@ -22,47 +39,325 @@ pub struct State {
     pub conformance: RwLock<Conformance>,
     pub openapi: OpenAPI,
     pub drivers: Drivers,
-    pub db: Db,
     #[cfg(feature = "stac")]
-    pub s3: ogcapi_drivers::s3::S3,
+    pub s3: S3,
+    /// Rewriting of S3 asset hrefs
+    #[cfg(feature = "stac")]
+    pub(crate) asset_hrefs: Option<AssetHrefs>,
+    /// Buckets whose assets may be served besides the default bucket
+    #[cfg(feature = "stac")]
+    pub(crate) asset_buckets: Vec<String>,
     #[cfg(feature = "processes")]
     pub processors: BTreeMap<String, Box<dyn Processor>>,
+    /// Background executions of jobs
+    #[cfg(feature = "processes")]
+    pub(crate) executions: Mutex<HashMap<String, tokio::task::JoinHandle<()>>>,
+    /// Retention period of finished jobs
+    #[cfg(feature = "processes")]
+    pub(crate) job_retention: Option<Duration>,
 }
 
-// TODO: Introduce service trait
+/// Drivers of the subsystems
 pub struct Drivers {
     pub collections: Box<dyn CollectionTransactions>,
+    pub maintenance: Box<dyn CollectionMaintenance>,
     #[cfg(feature = "features")]
     pub features: Box<dyn FeatureTransactions>,
+    #[cfg(feature = "stac")]
+    pub search: Box<dyn StacSeach>,
     #[cfg(feature = "edr")]
     pub edr: Box<dyn EdrQuerier>,
+    #[cfg(feature = "edr")]
+    pub instances: Box<dyn InstanceTransactions>,
+    #[cfg(feature = "coverages")]
+    pub coverages: Box<dyn CoverageQuerier>,
     #[cfg(feature = "processes")]
     pub jobs: Box<dyn JobHandler>,
+    #[cfg(feature = "processes")]
+    pub geoprocessing: Box<dyn Geoprocessing>,
+    #[cfg(feature = "processes")]
+    pub artifacts: Box<dyn ArtifactStore>,
     #[cfg(feature = "styles")]
     pub styles: Box<dyn StyleTransactions>,
     #[cfg(feature = "tiles")]
     pub tiles: Box<dyn TileTransactions>,
 }
 
+impl Drivers {
+    /// Drivers of all subsystems backed by Postgres
+    pub fn postgres(db: Db) -> Self {
+        Drivers {
+            collections: Box::new(db.clone()),
+            maintenance: Box::new(db.clone()),
+            #[cfg(feature = "features")]
+            features: Box::new(db.clone()),
+            #[cfg(feature = "stac")]
+            search: Box::new(db.clone()),
+            #[cfg(feature = "edr")]
+            edr: Box::new(db.clone()),
+            #[cfg(feature = "edr")]
+            instances: Box::new(db.clone()),
+            #[cfg(feature = "coverages")]
+            coverages: Box::new(Unavailable),
+            #[cfg(feature = "processes")]
+            jobs: Box::new(db.clone()),
+            #[cfg(feature = "processes")]
+            geoprocessing: Box::new(db.clone()),
+            #[cfg(feature = "processes")]
+            artifacts: Box::new(Unavailable),
+            #[cfg(feature = "styles")]
+            styles: Box::new(db.clone()),
+            #[cfg(feature = "tiles")]
+            tiles: Box::new(db),
+        }
+    }
+}
+
+/// Backends opened on first use by a subsystem
+struct Backends<'a> {
+    config: &'a Config,
+    db: Option<Db>,
+    memory: Memory,
+    gpkg: Option<GeoPackage>,
+    #[cfg(feature = "stac")]
+    s3: Option<S3>,
+}
+
+impl Backends<'_> {
+    async fn db(&mut self) -> anyhow::Result<Db> {
+        if let Some(db) = &self.db {
+            return Ok(db.clone());
+        }
+
+        let url = self
+            .config
+            .database_url
+            .as_ref()
+            .context("`database_url` is required by the postgres backend")?;
+        let db = Db::setup(url)
+            .await
+            .context("Unable to setup the postgres backend")?;
+        self.db = Some(db.clone());
+
+        Ok(db)
+    }
+
+    async fn gpkg(&mut self) -> anyhow::Result<GeoPackage> {
+        if let Some(gpkg) = &self.gpkg {
+            return Ok(gpkg.clone());
+        }
+
+        let path = self
+            .config
+            .source
+            .as_ref()
+            .context("`source` is required by the gpkg backend")?;
+        let gpkg = GeoPackage::open(path)
+            .await
+            .with_context(|| format!("Unable to open GeoPackage `{}`", path.display()))?;
+        self.gpkg = Some(gpkg.clone());
+
+        Ok(gpkg)
+    }
+
+    #[cfg(feature = "stac")]
+    async fn s3(&mut self) -> S3 {
+        if self.s3.is_none() {
+            let mut s3 = S3::new().await;
+            if let Some(bucket) = &self.config.s3_bucket {
+                s3.set_default_bucket(bucket);
+            }
+            self.s3 = Some(s3);
+        }
+        self.s3.clone().unwrap()
+    }
+}
+
+/// Driver of a subsystem from its backend, `$gpkg` is the driver of
+/// subsystems supported by the `gpkg` backend
+macro_rules! driver {
+    ($backends:ident, $backend:expr, $trait:ident) => {
+        driver!(
+            $backends,
+            $backend,
+            $trait,
+            anyhow::bail!("The gpkg backend only supports collections and features")
+        )
+    };
+    ($backends:ident, $backend:expr, $trait:ident, $gpkg:expr) => {
+        match $backend {
+            Backend::Postgres => Box::new($backends.db().await?) as Box<dyn $trait>,
+            Backend::Memory => Box::new($backends.memory.clone()) as Box<dyn $trait>,
+            Backend::GeoPackage => $gpkg,
+            #[cfg(feature = "stac")]
+            Backend::S3 => Box::new($backends.s3().await) as Box<dyn $trait>,
+        }
+    };
+}
+
+/// Postgres driver of a subsystem without other backends, unavailable
+/// without database url
+#[cfg(any(feature = "edr", feature = "processes"))]
+macro_rules! postgres {
+    ($backends:ident, $trait:ident) => {
+        match $backends.config.database_url {
+            Some(_) => Box::new($backends.db().await?) as Box<dyn $trait>,
+            None => Box::new(Unavailable) as Box<dyn $trait>,
+        }
+    };
+}
+
 impl State {
-    pub async fn new() -> Self {
+    pub async fn new() -> anyhow::Result<Self> {
         let config = Config::parse();
         State::new_from(&config).await
     }
 
-    pub async fn new_from(config: &Config) -> Self {
+    pub async fn new_from(config: &Config) -> anyhow::Result<Self> {
+        config.check_backends()?;
+
         let openapi = if let Some(path) = &config.openapi {
-            OpenAPI::from_path(path).unwrap()
+            OpenAPI::from_path(path)
+                .with_context(|| format!("Unable to read OpenAPI `{}`", path.display()))?
         } else {
             OpenAPI::from_slice(OPENAPI)
         };
 
-        let db = Db::setup(&config.database_url).await.unwrap();
+        let memory = match &config.source {
+            Some(dir) if dir.is_dir() => Memory::from_dir(dir)
+                .await
+                .with_context(|| format!("Unable to load collections from `{}`", dir.display()))?,
+            _ => Memory::new(),
+        };
 
-        State::new_with(db, openapi).await
+        let mut backends = Backends {
+            config,
+            db: None,
+            memory,
+            gpkg: None,
+            #[cfg(feature = "stac")]
+            s3: None,
+        };
+
+        let drivers = Drivers {
+            collections: driver!(
+                backends,
+                config.source_backend_of(config.collections_backend),
+                CollectionTransactions,
+                Box::new(backends.gpkg().await?)
+            ),
+            maintenance: driver!(
+                backends,
+                config.source_backend_of(config.collections_backend),
+                CollectionMaintenance,
+                Box::new(backends.gpkg().await?)
+            ),
+            #[cfg(feature = "features")]
+            features: driver!(
+                backends,
+                config.source_backend_of(config.features_backend),
+                FeatureTransactions,
+                Box::new(backends.gpkg().await?)
+            ),
+            #[cfg(feature = "stac")]
+            search: driver!(backends, config.search_backend_of(), StacSeach),
+            #[cfg(feature = "edr")]
+            edr: postgres!(backends, EdrQuerier),
+            #[cfg(feature = "edr")]
+            instances: postgres!(backends, InstanceTransactions),
+            #[cfg(feature = "coverages")]
+            coverages: Box::new(Unavailable),
+            #[cfg(feature = "processes")]
+            jobs: driver!(backends, config.backend_of(config.jobs_backend), JobHandler),
+            #[cfg(feature = "processes")]
+            geoprocessing: postgres!(backends, Geoprocessing),
+            #[cfg(feature = "processes")]
+            artifacts: match &config.job_storage {
+                Some(storage) => artifact_store(storage).await?,
+                // artifacts of in-memory jobs live as long as the jobs
+                None => match config.backend_of(config.jobs_backend) {
+                    Backend::Memory => Box::new(backends.memory.clone()),
+                    _ => Box::new(Unavailable),
+                },
+            },
+            #[cfg(feature = "styles")]
+            styles: driver!(
+                backends,
+                config.backend_of(config.styles_backend),
+                StyleTransactions
+            ),
+            #[cfg(feature = "tiles")]
+            tiles: driver!(
+                backends,
+                config.backend_of(config.tiles_backend),
+                TileTransactions
+            ),
+        };
+
+        let state = State::new_with(drivers, openapi).await;
+
+        #[cfg(feature = "raster")]
+        let state = {
+            let mut state = state;
+            if let Some(dir) = &config.raster_dir {
+                state.drivers.edr = Box::new(
+                    ogcapi_drivers::raster::Raster::new(dir)
+                        .fallback(postgres!(backends, EdrQuerier)),
+                );
+            }
+            state
+        };
+
+        #[cfg(feature = "coverages")]
+        let state = {
+            let mut state = state;
+            if let Some(dir) = &config.raster_dir {
+                state.drivers.coverages = Box::new(Raster::new(dir));
+            }
+            state
+        };
+
+        #[cfg(feature = "processes")]
+        let state = {
+            let mut state = state;
+            if let Some(dir) = &config.processes_dir {
+                match CommandProcessor::from_dir(dir) {
+                    Ok(processors) => {
+                        state = state.processors(
+                            processors
+                                .into_iter()
+                                .map(|p| Box::new(p) as Box<dyn Processor>)
+                                .collect(),
+                        )
+                    }
+                    Err(e) => tracing::error!(
+                        "Failed to read process definitions from `{}`: {:?}",
+                        dir.display(),
+                        e
+                    ),
+                }
+            }
+            state.job_retention = config
+                .job_retention
+                .map(|hours| Duration::from_secs(hours * 3600));
+            state
+        };
+
+        #[cfg(feature = "stac")]
+        let state = {
+            let mut state = state;
+            if let Some(bucket) = &config.asset_bucket {
+                state.s3.set_default_bucket(bucket);
+            }
+            state.asset_hrefs = config.asset_hrefs;
+            state.asset_buckets = config.asset_buckets.to_owned();
+            state
+        };
+
+        Ok(state)
     }
 
-    pub async fn new_with(db: Db, openapi: OpenAPI) -> Self {
+    pub async fn new_with(drivers: Drivers, openapi: OpenAPI) -> Self {
         // conformance
         let mut conformace = Conformance::default();
         #[cfg(feature = "stac")]
@@ -71,33 +366,39 @@ impl State {
             "https://api.stacspec.org/v1.0.0-rc.1/item-search",
             "https://api.stacspec.org/v1.0.0-rc.1/collections",
             "https://api.stacspec.org/v1.0.0-rc.1/ogcapi-features",
+            "https://api.stacspec.org/v1.0.0-rc.1/ogcapi-features/extensions/transaction",
+            "https://api.stacspec.org/v1.0.0-rc.1/collections/extensions/transaction",
+            "https://api.stacspec.org/v1.0.0-rc.1/item-search#filter",
+            "https://api.stacspec.org/v1.0.0-rc.1/item-search#query",
+            "https://api.stacspec.org/v0.3.0/aggregation",
+            "http://www.opengis.net/spec/ogcapi-features-3/1.0/conf/filter",
+            "http://www.opengis.net/spec/ogcapi-features-3/1.0/conf/features-filter",
+            "http://www.opengis.net/spec/cql2/1.0/conf/cql2-text",
+            "http://www.opengis.net/spec/cql2/1.0/conf/cql2-json",
+            "http://www.opengis.net/spec/cql2/1.0/conf/basic-cql2",
+            "http://www.opengis.net/spec/cql2/1.0/conf/basic-spatial-operators",
+            "http://www.opengis.net/spec/cql2/1.0/conf/spatial-operators",
+            "http://www.opengis.net/spec/cql2/1.0/conf/temporal-operators",
+            "http://www.opengis.net/spec/cql2/1.0/conf/array-operators",
         ]);
 
-        // drivers
-        let drivers = Drivers {
-            collections: Box::new(db.clone()),
-            #[cfg(feature = "features")]
-            features: Box::new(db.clone()),
-            #[cfg(feature = "edr")]
-            edr: Box::new(db.clone()),
-            #[cfg(feature = "processes")]
-            jobs: Box::new(db.clone()),
-            #[cfg(feature = "styles")]
-            styles: Box::new(db.clone()),
-            #[cfg(feature = "tiles")]
-            tiles: Box::new(db.clone()),
-        };
-
         State {
             root: RwLock::new(LandingPage::new("root").description("root")),
             conformance: RwLock::new(conformace),
             openapi,
             drivers,
-            db,
             #[cfg(feature = "stac")]
-            s3: ogcapi_drivers::s3::S3::new().await,
+            s3: S3::new().await,
+            #[cfg(feature = "stac")]
+            asset_hrefs: None,
+            #[cfg(feature = "stac")]
+            asset_buckets: Vec::new(),
             #[cfg(feature = "processes")]
             processors: Default::default(),
+            #[cfg(feature = "processes")]
+            executions: Default::default(),
+            #[cfg(feature = "processes")]
+            job_retention: None,
         }
     }
 
@@ -112,16 +413,135 @@ impl State {
     }
 
     #[cfg(feature = "stac")]
-    pub async fn s3_client(mut self, client: ogcapi_drivers::s3::S3) -> Self {
+    pub async fn s3_client(mut self, client: S3) -> Self {
         self.s3 = client;
         self
     }
 
+    /// Use S3 as primary backend for the collections, features, search,
+    /// styles, tiles and jobs
+    #[cfg(feature = "stac")]
+    pub fn s3_backend(mut self, s3: S3) -> Self {
+        self.drivers.collections = Box::new(s3.clone());
+        self.drivers.maintenance = Box::new(s3.clone());
+        self.drivers.search = Box::new(s3.clone());
+        #[cfg(feature = "features")]
+        {
+            self.drivers.features = Box::new(s3.clone());
+        }
+        #[cfg(feature = "processes")]
+        {
+            self.drivers.jobs = Box::new(s3.clone());
+        }
+        #[cfg(feature = "styles")]
+        {
+            self.drivers.styles = Box::new(s3.clone());
+        }
+        #[cfg(feature = "tiles")]
+        {
+            self.drivers.tiles = Box::new(s3.clone());
+        }
+        self.s3 = s3;
+        self
+    }
+
+    /// Use an in-memory store as primary backend for the collections,
+    /// features, search, styles, tiles and jobs
+    pub fn memory_backend(mut self, memory: Memory) -> Self {
+        self.drivers.collections = Box::new(memory.clone());
+        self.drivers.maintenance = Box::new(memory.clone());
+        #[cfg(feature = "stac")]
+        {
+            self.drivers.search = Box::new(memory.clone());
+        }
+        #[cfg(feature = "features")]
+        {
+            self.drivers.features = Box::new(memory.clone());
+        }
+        #[cfg(feature = "processes")]
+        {
+            self.drivers.jobs = Box::new(memory.clone());
+        }
+        #[cfg(feature = "styles")]
+        {
+            self.drivers.styles = Box::new(memory.clone());
+        }
+        #[cfg(feature = "tiles")]
+        {
+            self.drivers.tiles = Box::new(memory.clone());
+        }
+        self
+    }
+
+    /// Serve the collections and features of a GeoPackage
+    pub fn gpkg_backend(mut self, gpkg: GeoPackage) -> Self {
+        self.drivers.collections = Box::new(gpkg.clone());
+        self.drivers.maintenance = Box::new(gpkg.clone());
+        #[cfg(feature = "features")]
+        {
+            self.drivers.features = Box::new(gpkg);
+        }
+        self
+    }
+
+    /// Rewrite S3 asset hrefs into presigned URLs or links to the asset proxy
+    #[cfg(feature = "stac")]
+    pub fn asset_hrefs(mut self, mode: AssetHrefs) -> Self {
+        self.asset_hrefs = Some(mode);
+        self
+    }
+
+    /// Allow serving the assets of further buckets besides the default bucket
+    #[cfg(feature = "stac")]
+    pub fn asset_buckets(mut self, buckets: Vec<String>) -> Self {
+        self.asset_buckets = buckets;
+        self
+    }
+
+    /// Register processes, processes with the id of an already registered
+    /// process are logged and skipped
     #[cfg(feature = "processes")]
     pub fn processors(mut self, processors: Vec<Box<dyn Processor>>) -> Self {
         for p in processors {
-            self.processors.insert(p.id(), p);
+            let id = p.id();
+            if self.processors.contains_key(&id) {
+                tracing::error!("Skipping process `{id}`, the id is already taken");
+                continue;
+            }
+            self.processors.insert(id, p);
         }
         self
     }
+
+    /// Set the store for large job results
+    #[cfg(feature = "processes")]
+    pub fn artifacts(mut self, artifacts: Box<dyn ArtifactStore>) -> Self {
+        self.drivers.artifacts = artifacts;
+        self
+    }
+
+    /// Set the retention period of finished jobs
+    #[cfg(feature = "processes")]
+    pub fn job_retention(mut self, retention: Duration) -> Self {
+        self.job_retention = Some(retention);
+        self
+    }
+}
+
+/// Opens the job result storage from a directory path or `s3://{bucket}` url,
+/// the latter requires the `stac` feature
+#[cfg(feature = "processes")]
+async fn artifact_store(storage: &str) -> anyhow::Result<Box<dyn ArtifactStore>> {
+    if let Some(bucket) = storage.strip_prefix("s3://") {
+        #[cfg(feature = "stac")]
+        {
+            let mut s3 = S3::new().await;
+            s3.set_default_bucket(bucket);
+            return Ok(Box::new(s3));
+        }
+        #[cfg(not(feature = "stac"))]
+        anyhow::bail!("Storing job results in S3 (`{bucket}`) requires the `stac` feature");
+    }
+
+    Ok(Box::new(Fs::new(storage)))
 }
@@ -0,0 +1,216 @@
+mod setup;
+
+#[cfg(feature = "stac")]
+#[tokio::test]
+async fn asset_proxy() -> anyhow::Result<()> {
+    use std::{convert::Infallible, net::SocketAddr};
+
+    use aws_sdk_s3::{Client, Config, Credentials, Endpoint, Region};
+    use axum::http::{header, Method, Request, Response, StatusCode};
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body, Server,
+    };
+    use serde_json::json;
+
+    use ogcapi_drivers::{memory::Memory, s3::S3};
+    use ogcapi_services::AssetHrefs;
+
+    const DATA: &[u8] = b"0123456789";
+
+    /// S3 stand-in serving `ogcapi/data.bin` with range requests
+    async fn handle(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+        let not_found = || {
+            Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Body::from(
+                    "<Error><Code>NoSuchKey</Code><Message>Not found</Message></Error>",
+                ))
+                .unwrap()
+        };
+        if req.method() != Method::GET || req.uri().path() != "/ogcapi/data.bin" {
+            return Ok(not_found());
+        }
+
+        let range = req
+            .headers()
+            .get(header::RANGE)
+            .and_then(|r| r.to_str().ok())
+            .and_then(|r| r.strip_prefix("bytes="))
+            .and_then(|r| r.split_once('-'))
+            .map(|(start, end)| {
+                let start: usize = start.parse().unwrap();
+                let end = end.parse().unwrap_or(DATA.len() - 1).min(DATA.len() - 1);
+                (start, end)
+            });
+
+        let response = match range {
+            None => Response::builder()
+                .header(header::CONTENT_LENGTH, DATA.len())
+                .body(Body::from(DATA)),
+            Some((start, _)) if start >= DATA.len() => Response::builder()
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .body(Body::from(
+                    "<Error><Code>InvalidRange</Code><Message>Invalid range</Message></Error>",
+                )),
+            Some((start, end)) => Response::builder()
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_LENGTH, end - start + 1)
+                .header(
+                    header::CONTENT_RANGE,
+                    format!("bytes {start}-{end}/{}", DATA.len()),
+                )
+                .body(Body::from(&DATA[start..=end])),
+        };
+
+        Ok(response.unwrap())
+    }
+
+    let server =
+        Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service_fn(|_| async {
+            Ok::<_, Infallible>(service_fn(handle))
+        }));
+    let s3_addr = server.local_addr();
+    tokio::spawn(server);
+
+    let config = Config::builder()
+        .region(Region::new("us-east-1"))
+        .credentials_provider(Credentials::new("ogcapi", "ogcapi", None, None, "test"))
+        .endpoint_resolver(Endpoint::immutable(
+            format!("http://{s3_addr}").parse().unwrap(),
+        ))
+        .build();
+    let mut s3 = S3::new_with(Client::from_conf(config)).await;
+    s3.set_default_bucket("ogcapi");
+
+    let spawn = |mode: Option<AssetHrefs>| {
+        let s3 = s3.clone();
+        setup::spawn_app_with(move |state| {
+            let mut state = state.memory_backend(Memory::new());
+            state.s3 = s3;
+            match mode {
+                Some(mode) => state.asset_hrefs(mode),
+                None => state,
+            }
+        })
+    };
+    let assets = json!({
+        "data": { "href": "s3://ogcapi/data.bin" },
+        "data file#1": { "href": "s3://ogcapi/data.bin" },
+        "key": { "href": "data.bin" },
+        "missing": { "href": "s3://ogcapi/missing.bin" },
+        "private": { "href": "s3://private/data.bin" },
+        "relative": { "href": "./data.bin" },
+        "web": { "href": "https://example.com/data.bin" }
+    });
+
+    let collection = json!({
+        "id": "scenes",
+        "license": "proprietary",
+        "crs": ["http://www.opengis.net/def/crs/OGC/1.3/CRS84"],
+        "links": [],
+        "assets": assets
+    });
+
+    let addr = spawn(Some(AssetHrefs::Proxy)).await?;
+    let client = hyper::Client::new();
+
+    let post = |uri: String, body: serde_json::Value| {
+        client.request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(uri)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+    };
+    let res = post(format!("http://{addr}/collections"), collection.clone()).await?;
+    assert_eq!(201, res.status());
+
+    let res = post(
+        format!("http://{addr}/collections/scenes/items"),
+        json!({
+            "id": "scene",
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [7.0, 46.0] },
+            "properties": {},
+            "assets": assets
+        }),
+    )
+    .await?;
+    assert_eq!(201, res.status());
+
+    // hrefs of assets in allowed buckets are rewritten
+    for base in [
+        format!("http://{addr}/collections/scenes/"),
+        format!("http://{addr}/collections/scenes/items/scene/"),
+    ] {
+        let res = client.get(base.trim_end_matches('/').parse()?).await?;
+        let body = hyper::body::to_bytes(res.into_body()).await?;
+        let entity: serde_json::Value = serde_json::from_slice(&body)?;
+
+        for key in ["data", "key", "missing"] {
+            assert_eq!(entity["assets"][key]["href"], format!("{base}assets/{key}"));
+        }
+        for key in ["private", "relative", "web"] {
+            assert_eq!(entity["assets"][key], assets[key]);
+        }
+
+        // asset keys are percent-encoded
+        assert_eq!(
+            entity["assets"]["data file#1"]["href"],
+            format!("{base}assets/data%20file%231")
+        );
+    }
+
+    let get = |uri: String, range: Option<&str>| {
+        let mut request = Request::builder().uri(uri);
+        if let Some(range) = range {
+            request = request.header(header::RANGE, range);
+        }
+        client.request(request.body(Body::empty()).unwrap())
+    };
+
+    for base in [
+        format!("http://{addr}/collections/scenes/assets"),
+        format!("http://{addr}/collections/scenes/items/scene/assets"),
+    ] {
+        // whole objects
+        for key in ["data", "key", "data%20file%231"] {
+            let res = get(format!("{base}/{key}"), None).await?;
+            assert_eq!(200, res.status());
+            assert_eq!(res.headers()[header::ACCEPT_RANGES], "bytes");
+            assert_eq!(&hyper::body::to_bytes(res.into_body()).await?[..], DATA);
+        }
+
+        // range requests are forwarded
+        let res = get(format!("{base}/data"), Some("bytes=2-5")).await?;
+        assert_eq!(206, res.status());
+        assert_eq!(res.headers()[header::CONTENT_RANGE], "bytes 2-5/10");
+        assert_eq!(&hyper::body::to_bytes(res.into_body()).await?[..], b"2345");
+
+        let res = get(format!("{base}/data"), Some("bytes=20-")).await?;
+        assert_eq!(416, res.status());
+
+        // missing objects and assets, assets not to be served
+        for key in ["missing", "private", "relative", "web", "unknown"] {
+            let res = get(format!("{base}/{key}"), None).await?;
+            assert_eq!(404, res.status(), "{key}");
+        }
+    }
+
+    // the asset routes are only served in proxy mode
+    let addr = spawn(None).await?;
+    let res = post(format!("http://{addr}/collections"), collection.clone()).await?;
+    assert_eq!(201, res.status());
+
+    let res = get(
+        format!("http://{addr}/collections/scenes/assets/data"),
+        None,
+    )
+    .await?;
+    assert_eq!(404, res.status());
+
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use axum::http::{Request, StatusCode};
+use hyper::Body;
+
+use ogcapi_services::{Config, ConfigParser, Service, State};
+use ogcapi_types::common::{Collection, Conformance};
+
+/// Config from the arguments only, ignoring the environment
+fn config(args: &[&str]) -> Config {
+    for var in ["DATABASE_URL", "SOURCE", "BACKEND"] {
+        std::env::remove_var(var);
+    }
+    Config::parse_from(["ogcapi"].iter().chain(args))
+}
+
+#[test]
+fn backend_selection() {
+    // postgres by default, memory when serving a source
+    assert!(config(&[]).check_backends().is_err());
+    assert!(config(&["--backend", "memory"]).check_backends().is_ok());
+    assert!(config(&["--database-url", "postgresql://localhost/ogcapi"])
+        .check_backends()
+        .is_ok());
+
+    // postgres requires a database url
+    assert!(config(&["--backend", "postgres"]).check_backends().is_err());
+
+    // gpkg requires a source and supports collections and features only
+    assert!(config(&["--collections-backend", "gpkg"])
+        .check_backends()
+        .is_err());
+    assert!(config(&["--source", "data.gpkg", "--backend", "gpkg"])
+        .check_backends()
+        .is_err());
+    assert!(config(&["--source", "data.gpkg"]).check_backends().is_ok());
+
+    // features are stored with their collections
+    #[cfg(feature = "features")]
+    assert!(config(&[
+        "--collections-backend",
+        "memory",
+        "--features-backend",
+        "postgres",
+        "--database-url",
+        "postgresql://localhost/ogcapi",
+    ])
+    .check_backends()
+    .is_err());
+
+    #[cfg(feature = "stac")]
+    {
+        assert!(config(&[
+            "--collections-backend",
+            "s3",
+            "--features-backend",
+            "postgres",
+            "--database-url",
+            "postgresql://localhost/ogcapi",
+        ])
+        .check_backends()
+        .is_err());
+
+        // search runs on the features
+        assert!(config(&["--backend", "s3", "--search-backend", "memory"])
+            .check_backends()
+            .is_err());
+        assert!(config(&["--backend", "s3"]).check_backends().is_ok());
+        assert!(
+            config(&["--search-backend", "gpkg", "--source", "data.gpkg"])
+                .check_backends()
+                .is_err()
+        );
+    }
+}
+
+#[tokio::test]
+async fn memory_backend() -> anyhow::Result<()> {
+    // misconfigurations fail at startup
+    assert!(State::new_from(&config(&[])).await.is_err());
+
+    let mut config = config(&["--backend", "memory"]);
+    config.port = 0;
+
+    let state = State::new_from(&config).await?;
+
+    let collection = Collection {
+        id: "memory".to_string(),
+        ..Default::default()
+    };
+    state
+        .drivers
+        .collections
+        .create_collection(&collection)
+        .await?;
+    assert!(state
+        .drivers
+        .collections
+        .read_collection("memory")
+        .await?
+        .is_some());
+
+    let service = Service::new_with(&config, state).await;
+    let addr = service.local_addr()?;
+    tokio::spawn(async move {
+        service.serve().await;
+    });
+
+    let client = hyper::Client::new();
+
+    // operations without backend are unavailable and not advertised
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{addr}/conformance"))
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let conformance: Conformance = serde_json::from_slice(&body)?;
+    assert!(!conformance
+        .conforms_to
+        .iter()
+        .any(|class| class.contains("ogcapi-edr-1")));
+
+    #[cfg(feature = "edr")]
+    {
+        let res = client
+            .request(
+                Request::builder()
+                    .uri(format!(
+                        "http://{addr}/collections/memory/position?coords=POINT(0%200)"
+                    ))
+                    .body(Body::empty())?,
+            )
+            .await?;
+        assert_eq!(res.status(), StatusCode::NOT_IMPLEMENTED);
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "features")]
+#[tokio::test]
+async fn gpkg_source() -> anyhow::Result<()> {
+    use ogcapi_drivers::{gpkg::GeoPackage, CollectionTransactions};
+
+    let path = std::env::temp_dir().join(format!("ogcapi-source-{}.gpkg", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let collection = Collection {
+        id: "places".to_string(),
+        ..Default::default()
+    };
+    GeoPackage::open(&path)
+        .await?
+        .create_collection(&collection)
+        .await?;
+
+    let mut config = config(&["--source", path.to_str().unwrap()]);
+    config.port = 0;
+
+    let service = Service::new_with(&config, State::new_from(&config).await?).await;
+    let addr = service.local_addr()?;
+    tokio::spawn(async move {
+        service.serve().await;
+    });
+
+    let client = hyper::Client::new();
+
+    // features are served in the storage crs only
+    let res = client
+        .get(format!("http://{addr}/collections/places/items").parse()?)
+        .await?;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = client
+        .get(
+            format!(
+                "http://{addr}/collections/places/items?crs=http://www.opengis.net/def/crs/EPSG/0/3857"
+            )
+            .parse()?,
+        )
+        .await?;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    std::fs::remove_file(&path)?;
+
+    Ok(())
+}
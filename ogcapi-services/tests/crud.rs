@@ -12,7 +12,7 @@ use ogcapi_types::{
 #[tokio::test]
 async fn minimal_feature_crud() -> anyhow::Result<()> {
     // setup app
-    let (addr, _) = setup::spawn_app().await?;
+    let addr = setup::spawn_app().await?;
     let client = hyper::Client::new();
 
     let collection = Collection {
@@ -9,7 +9,7 @@ async fn edr() -> anyhow::Result<()> {
     use ogcapi::import::{self, Args};
     use ogcapi_types::{common::Crs, edr::Query, features::FeatureCollection};
 
-    let (addr, database_url) = setup::spawn_app().await?;
+    let (addr, database_url) = setup::spawn_postgres_app().await?;
 
     let client = hyper::Client::new();
 
@@ -149,5 +149,213 @@ async fn edr() -> anyhow::Result<()> {
     //     .find(|f| f.properties.as_ref().unwrap().0["NAME"].as_str() == Some("Bern"));
     // assert!(feature.is_some());
 
+    // query area with invalid coords
+    let query = Query {
+        coords: "POINT(7.5 47)".to_string(),
+        ..Default::default()
+    };
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::GET)
+                .uri(format!(
+                    "http://{}/collections/places/area?{}",
+                    addr,
+                    serde_qs::to_string(&query)?
+                ))
+                .body(hyper::Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(400, res.status());
+
+    // query corridor
+    let query = Query {
+        coords: "LINESTRING(7 46, 8 47.5)".to_string(),
+        parameter_name: Some("NAME".to_string()),
+        corridor_width: Some("100".to_string()),
+        width_units: Some("km".to_string()),
+        ..Default::default()
+    };
+    let (status, fc) = get(
+        &client,
+        format!(
+            "http://{addr}/collections/places/corridor?{}",
+            serde_qs::to_string(&query)?
+        ),
+    )
+    .await?;
+    assert_eq!(200, status);
+    assert!(fc["features"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|f| f["properties"]["NAME"] == "Bern"));
+
+    // query items, sharing the path with the features items
+    let (status, fc) = get(
+        &client,
+        format!("http://{addr}/collections/places/items?coords=6,45,9,49&parameter-name=NAME"),
+    )
+    .await?;
+    assert_eq!(200, status);
+    assert_eq!(fc["numberMatched"], 2);
+    assert_eq!(
+        fc["features"][0]["properties"].as_object().unwrap().len(),
+        1
+    );
+
+    let (status, fc) = get(
+        &client,
+        format!("http://{addr}/collections/places/items?coords=6,45,-100,9,49,100"),
+    )
+    .await?;
+    assert_eq!(200, status);
+    assert_eq!(fc["numberMatched"], 2);
+
+    // query radius around a position with height, in meters
+    let (status, fc) = get(
+        &client,
+        format!(
+            "http://{addr}/collections/places/radius?coords=POINT%20Z(7.44%2046.95%200)&within=20&within-units=km&parameter-name=NAME"
+        ),
+    )
+    .await?;
+    assert_eq!(200, status);
+    assert!(fc["features"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|f| f["properties"]["NAME"] == "Bern"));
+
+    // named locations
+    let location = serde_json::json!({
+        "type": "Feature",
+        "geometry": {
+            "type": "Polygon",
+            "coordinates": [[[7.0, 46.5], [8.0, 46.5], [8.0, 47.5], [7.0, 47.5], [7.0, 46.5]]]
+        },
+        "properties": { "name": "Bern area" }
+    });
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::PUT)
+                .uri(format!("http://{addr}/collections/places/locations/bern"))
+                .header("Content-Type", "application/json")
+                .body(hyper::Body::from(serde_json::to_vec(&location)?))?,
+        )
+        .await?;
+    assert_eq!(201, res.status());
+
+    let (status, fc) = get(
+        &client,
+        format!("http://{addr}/collections/places/locations"),
+    )
+    .await?;
+    assert_eq!(200, status);
+    assert_eq!(fc["features"][0]["id"], "bern");
+    assert_eq!(fc["features"][0]["properties"]["name"], "Bern area");
+
+    let (status, fc) = get(
+        &client,
+        format!("http://{addr}/collections/places/locations/bern?parameter-name=NAME"),
+    )
+    .await?;
+    assert_eq!(200, status);
+    assert_eq!(fc["features"][0]["properties"]["NAME"], "Bern");
+
+    let (status, _) = get(
+        &client,
+        format!("http://{addr}/collections/places/locations/missing"),
+    )
+    .await?;
+    assert_eq!(404, status);
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::DELETE)
+                .uri(format!("http://{addr}/collections/places/locations/bern"))
+                .body(hyper::Body::empty())?,
+        )
+        .await?;
+    assert_eq!(204, res.status());
+
+    // instances
+    let post = |uri: String, body: serde_json::Value| {
+        client.request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(uri)
+                .header("Content-Type", "application/json")
+                .body(hyper::Body::from(body.to_string()))
+                .unwrap(),
+        )
+    };
+    let instances = format!("http://{addr}/collections/places/instances");
+
+    for id in ["a\"b", "../a", &"a".repeat(64)] {
+        let res = post(
+            instances.clone(),
+            serde_json::json!({ "id": id, "links": [] }),
+        )
+        .await?;
+        assert_eq!(400, res.status());
+    }
+
+    let res = post(
+        instances.clone(),
+        serde_json::json!({ "id": "run-1", "links": [] }),
+    )
+    .await?;
+    assert_eq!(201, res.status());
+
+    let items = serde_json::json!({
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [7.0, 47.0] },
+                "properties": { "datetime": "2022-01-01T00:00:00Z" }
+            },
+            {
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [7.5, 47.0] },
+                "properties": { "datetime": "yesterday noon" }
+            }
+        ]
+    });
+    let res = post(format!("{instances}/run-1/items"), items).await?;
+    assert_eq!(201, res.status());
+
+    // malformed datetimes are left out of the extent
+    let (status, instance) = get(&client, format!("{instances}/run-1")).await?;
+    assert_eq!(200, status);
+    assert_eq!(
+        instance["extent"]["temporal"]["interval"][0][0],
+        instance["extent"]["temporal"]["interval"][0][1]
+    );
+
+    let (status, fc) = get(
+        &client,
+        format!("{instances}/run-1/area?coords=POLYGON((6 46,8 46,8 48,6 48,6 46))"),
+    )
+    .await?;
+    assert_eq!(200, status);
+    assert_eq!(fc["numberMatched"], 2);
+
     Ok(())
 }
+
+#[cfg(feature = "edr")]
+async fn get(
+    client: &hyper::Client<hyper::client::HttpConnector>,
+    uri: String,
+) -> anyhow::Result<(u16, serde_json::Value)> {
+    let res = client.get(uri.parse()?).await?;
+    let status = res.status().as_u16();
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    Ok((status, serde_json::from_slice(&body).unwrap_or_default()))
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "processes")]
+
+use serde_json::json;
+
+use ogcapi_services::{Buffer, Clip, OpenAPI, Processor};
+use ogcapi_types::processes::Process;
+
+#[test]
+fn process_execution_paths() -> anyhow::Result<()> {
+    let mut openapi = OpenAPI::default();
+    let point = Process::new(
+        "point",
+        "0.1.0",
+        &json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "properties": { "p": { "$ref": "#/definitions/Point" } },
+            "definitions": {
+                "Point": { "type": "array", "items": { "type": ["number", "null"] } }
+            }
+        }),
+        &json!({}),
+    );
+    openapi.processes(&[Buffer.process(), Clip.process(), point]);
+
+    // round-trip
+    let value = serde_json::to_value(&openapi.0)?;
+    let parsed: openapiv3::OpenAPI = serde_json::from_value(value.to_owned())?;
+    assert_eq!(serde_json::to_value(&parsed)?, value);
+
+    let path = &value["paths"]["/processes/clip/execution"]["post"];
+    assert_eq!(path["operationId"], "execute_clip");
+    let inputs =
+        &path["requestBody"]["content"]["application/json"]["schema"]["properties"]["inputs"];
+
+    // plain, qualified, nested process and collection input forms
+    let forms = inputs["properties"]["geometry"]["oneOf"]
+        .as_array()
+        .unwrap();
+    assert_eq!(forms.len(), 4);
+    assert_eq!(forms[1]["properties"]["value"], forms[0]);
+    assert_eq!(forms[2]["$ref"], "#/components/schemas/processInput");
+    assert_eq!(forms[3]["$ref"], "#/components/schemas/collectionInput");
+
+    // nullable types
+    assert_eq!(inputs["properties"]["output"]["oneOf"][0]["nullable"], true);
+    assert_eq!(inputs["properties"]["output"]["oneOf"][0]["type"], "string");
+
+    // JSON schema definitions are moved to the components
+    let path = &value["paths"]["/processes/point/execution"]["post"];
+    let inputs =
+        &path["requestBody"]["content"]["application/json"]["schema"]["properties"]["inputs"];
+    assert_eq!(
+        inputs["properties"]["p"]["oneOf"][0]["$ref"],
+        "#/components/schemas/point.Point"
+    );
+    let schemas = &value["components"]["schemas"];
+    assert_eq!(schemas["point.Point"]["type"], "array");
+    assert_eq!(schemas["point.Point"]["items"]["nullable"], true);
+    assert!(schemas["processInput"].is_object());
+    assert!(schemas["collectionInput"].is_object());
+    assert!(!value.to_string().contains("$schema"));
+
+    Ok(())
+}
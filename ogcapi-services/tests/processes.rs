@@ -0,0 +1,429 @@
+#![cfg(feature = "processes")]
+
+mod setup;
+
+use std::net::SocketAddr;
+
+use axum::http::{Method, Request, StatusCode};
+use hyper::{client::HttpConnector, Body, Client};
+use serde_json::{json, Value};
+
+use ogcapi_services::{CommandProcessor, Processor};
+use ogcapi_types::common::media_type::JSON;
+
+async fn request(
+    client: &Client<HttpConnector>,
+    method: Method,
+    uri: String,
+    body: Value,
+) -> anyhow::Result<(StatusCode, Value)> {
+    let res = client
+        .request(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("Content-Type", JSON)
+                .body(Body::from(serde_json::to_vec(&body)?))?,
+        )
+        .await?;
+    let status = res.status();
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    Ok((status, serde_json::from_slice(&body).unwrap_or_default()))
+}
+
+/// Inline features to process, a town at 7/47 and 8/47 and a river along 46
+fn features() -> Value {
+    json!({ "value": {
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "id": "a",
+                "geometry": { "type": "Point", "coordinates": [7.0, 47.0] },
+                "properties": { "kind": "town" }
+            },
+            {
+                "type": "Feature",
+                "id": "b",
+                "geometry": { "type": "Point", "coordinates": [8.0, 47.0] },
+                "properties": { "kind": "town" }
+            },
+            {
+                "type": "Feature",
+                "id": "c",
+                "geometry": { "type": "LineString", "coordinates": [[7.0, 46.0], [7.5, 46.001], [8.0, 46.0]] },
+                "properties": { "kind": "river" }
+            }
+        ]
+    }})
+}
+
+async fn execute(
+    client: &Client<HttpConnector>,
+    addr: &SocketAddr,
+    process: &str,
+    mut inputs: Value,
+) -> anyhow::Result<(StatusCode, Value)> {
+    // inline features unless given
+    if inputs.get("collection").is_none() {
+        inputs["collection"] = features();
+    }
+    request(
+        client,
+        Method::POST,
+        format!("http://{addr}/processes/{process}/execution"),
+        json!({ "inputs": inputs }),
+    )
+    .await
+}
+
+fn feature<'a>(fc: &'a Value, id: &str) -> &'a Value {
+    fc["features"]
+        .as_array()
+        .and_then(|features| features.iter().find(|f| f["id"] == id))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn geoprocessing() -> anyhow::Result<()> {
+    // geoprocessing requires the postgres backend
+    let (addr, _) = setup::spawn_postgres_app().await?;
+    let client = Client::new();
+
+    // buffer
+    let (status, fc) = execute(&client, &addr, "buffer", json!({ "distance": 1000 })).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fc["features"][0]["geometry"]["type"], "Polygon");
+
+    // clip
+    let inputs = json!({
+        "geometry": {
+            "type": "Polygon",
+            "coordinates": [[[6.5, 46.5], [7.5, 46.5], [7.5, 47.5], [6.5, 47.5], [6.5, 46.5]]]
+        }
+    });
+    let (status, fc) = execute(&client, &addr, "clip", inputs).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fc["features"].as_array().unwrap().len(), 1);
+    assert_eq!(fc["features"][0]["id"], "a");
+
+    // dissolve
+    let (status, fc) = execute(&client, &addr, "dissolve", json!({ "property": "kind" })).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fc["features"].as_array().unwrap().len(), 2);
+
+    // property names are not interpolated into the query
+    let inputs = json!({ "property": "kind') AS properties, NULL AS geom; --" });
+    let (status, fc) = execute(&client, &addr, "dissolve", inputs).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fc["features"].as_array().unwrap().len(), 1);
+    assert_eq!(fc["features"][0]["id"], "null");
+
+    // spatial join
+    let zones = json!({ "id": "zones", "links": [] });
+    let (status, _) = request(
+        &client,
+        Method::POST,
+        format!("http://{addr}/collections"),
+        zones,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let zone = json!({
+        "collection": "zones",
+        "type": "Feature",
+        "geometry": {
+            "type": "Polygon",
+            "coordinates": [[[7.5, 46.5], [8.5, 46.5], [8.5, 47.5], [7.5, 47.5], [7.5, 46.5]]]
+        },
+        "properties": { "zone": "east" }
+    });
+    let (status, _) = request(
+        &client,
+        Method::POST,
+        format!("http://{addr}/collections/zones/items"),
+        zone,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, fc) = execute(&client, &addr, "spatial-join", json!({ "join": "zones" })).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fc["features"].as_array().unwrap().len(), 1);
+    assert_eq!(fc["features"][0]["properties"]["zone"], "east");
+
+    // centroid
+    let (status, fc) = execute(&client, &addr, "centroid", json!({})).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(feature(&fc, "c")["geometry"]["type"], "Point");
+
+    // reproject
+    let inputs = json!({ "crs": "http://www.opengis.net/def/crs/EPSG/0/2056" });
+    let (status, fc) = execute(&client, &addr, "reproject", inputs).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fc["features"].as_array().unwrap().len(), 3);
+    let town = &feature(&fc, "a")["geometry"]["coordinates"];
+    assert!((2_560_000.0..2_575_000.0).contains(&town[0].as_f64().unwrap()));
+    assert!((1_200_000.0..1_210_000.0).contains(&town[1].as_f64().unwrap()));
+
+    // simplify
+    let (status, fc) = execute(&client, &addr, "simplify", json!({ "tolerance": 0.1 })).await?;
+    assert_eq!(status, StatusCode::OK);
+    let river = &feature(&fc, "c")["geometry"]["coordinates"];
+    assert_eq!(river.as_array().unwrap().len(), 2);
+
+    // output collections of failed processes are removed
+    let inputs = json!({
+        "geometry": { "type": "Polygon", "coordinates": [[[7.0, 47.0]]] },
+        "output": "failed"
+    });
+    let (status, _) = execute(&client, &addr, "clip", inputs).await?;
+    assert!(status.is_server_error());
+    let (status, _) = request(
+        &client,
+        Method::GET,
+        format!("http://{addr}/collections/failed"),
+        Value::Null,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn workflow() -> anyhow::Result<()> {
+    // geoprocessing requires the postgres backend
+    let (addr, _) = setup::spawn_postgres_app().await?;
+    let client = Client::new();
+
+    // centroids of the buffered features
+    let inputs = json!({
+        "collection": {
+            "process": format!("http://{addr}/processes/buffer"),
+            "inputs": { "collection": features(), "distance": 1000 }
+        }
+    });
+    let (status, fc) = execute(&client, &addr, "centroid", inputs.clone()).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fc["features"].as_array().unwrap().len(), 3);
+    assert_eq!(feature(&fc, "c")["geometry"]["type"], "Point");
+
+    // virtual collection of the workflow
+    let (status, collection) = request(
+        &client,
+        Method::POST,
+        format!("http://{addr}/processes/centroid/execution?response=collection"),
+        json!({ "inputs": inputs }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let id = collection["id"].as_str().unwrap();
+
+    let (_, collections) = request(
+        &client,
+        Method::GET,
+        format!("http://{addr}/collections"),
+        Value::Null,
+    )
+    .await?;
+    assert!(collections["collections"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|c| c["id"] == id));
+
+    // paging and bbox
+    let (status, fc) = request(
+        &client,
+        Method::GET,
+        format!("http://{addr}/collections/{id}/items?limit=1&offset=1"),
+        Value::Null,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fc["numberMatched"], 3);
+    assert_eq!(fc["features"].as_array().unwrap().len(), 1);
+
+    let (_, fc) = request(
+        &client,
+        Method::GET,
+        format!("http://{addr}/collections/{id}/items?bbox=7.5,46.5,8.5,47.5"),
+        Value::Null,
+    )
+    .await?;
+    assert_eq!(fc["numberMatched"], 1);
+    assert_eq!(fc["features"][0]["id"], "b");
+
+    // other parameters are rejected
+    let (status, _) = request(
+        &client,
+        Method::GET,
+        format!("http://{addr}/collections/{id}/items?datetime=2022-01-01T00:00:00Z"),
+        Value::Null,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // clients cannot attach workflows, a self-referencing collection is a
+    // plain collection
+    let cyclic = json!({
+        "id": "cyclic",
+        "links": [],
+        "workflow": {
+            "process": "buffer",
+            "execute": { "inputs": {
+                "collection": { "collection": format!("http://{addr}/collections/cyclic") },
+                "distance": 10
+            } }
+        }
+    });
+    let (status, _) = request(
+        &client,
+        Method::POST,
+        format!("http://{addr}/collections"),
+        cyclic,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, collection) = request(
+        &client,
+        Method::GET,
+        format!("http://{addr}/collections/cyclic"),
+        Value::Null,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert!(collection.get("workflow").is_none());
+
+    // virtual collection as collection input
+    let inputs = json!({
+        "collection": { "collection": format!("http://{addr}/collections/{id}") },
+        "distance": 10
+    });
+    let (status, fc) = execute(&client, &addr, "buffer", inputs).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fc["features"].as_array().unwrap().len(), 3);
+
+    let (status, _) = request(
+        &client,
+        Method::DELETE,
+        format!("http://{addr}/collections/{id}"),
+        Value::Null,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    Ok(())
+}
+
+fn command(definition: Value) -> Box<dyn Processor> {
+    Box::new(CommandProcessor::new(serde_json::from_value(definition).unwrap()).unwrap())
+}
+
+/// Executes a process and returns the raw response body
+async fn execute_raw(
+    client: &Client<HttpConnector>,
+    addr: &SocketAddr,
+    process: &str,
+    inputs: Value,
+) -> anyhow::Result<(StatusCode, String)> {
+    let res = client
+        .request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("http://{addr}/processes/{process}/execution"))
+                .header("Content-Type", JSON)
+                .body(Body::from(serde_json::to_vec(
+                    &json!({ "inputs": inputs }),
+                )?))?,
+        )
+        .await?;
+    let status = res.status();
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    Ok((status, String::from_utf8_lossy(&body).into_owned()))
+}
+
+#[tokio::test]
+async fn commands() -> anyhow::Result<()> {
+    std::env::set_var("OGCAPI_TEST_SECRET", "secret");
+
+    let processors = vec![
+        command(json!({
+            "id": "echo",
+            "command": "sh",
+            "args": ["-c", "printf '%s %s|%s|%s' \"$1\" \"$2\" \"${OGCAPI_TEST_SECRET:-}\" \"$(pwd)\"", "sh", "{a}", "{b}"],
+            "inputs": { "a": {}, "b": { "default": "b" } }
+        })),
+        command(json!({
+            "id": "cat",
+            "command": "cat",
+            "args": ["{text}"],
+            "inputs": { "text": { "mode": "file" } },
+            "outputs": { "text": { "media_type": "text/plain" } }
+        })),
+        command(json!({
+            "id": "print",
+            "command": "sh",
+            "args": ["-c", "printf '%s' \"$1\"", "--", "{value}"],
+            "inputs": { "value": {} }
+        })),
+        // duplicate ids are skipped
+        command(json!({
+            "id": "print",
+            "command": "false"
+        })),
+        command(json!({
+            "id": "sleep",
+            "command": "sleep",
+            "args": ["{seconds}"],
+            "inputs": { "seconds": {} },
+            "timeout": 1
+        })),
+    ];
+    let addr = setup::spawn_app_with(|state| state.processors(processors)).await?;
+    let client = Client::new();
+
+    // placeholders are substituted once, the environment is cleared and the
+    // working directory is removed afterwards
+    let (status, body) = execute_raw(&client, &addr, "echo", json!({ "a": "{b}" })).await?;
+    assert_eq!(status, StatusCode::OK);
+    let (args, rest) = body.split_once('|').unwrap();
+    let (secret, dir) = rest.split_once('|').unwrap();
+    assert_eq!(args, "{b} b");
+    assert_eq!(secret, "");
+    assert!(dir.contains("ogcapi-processes"));
+    assert!(!std::path::Path::new(dir).exists());
+
+    // values are not passed as options
+    let (status, _) = execute_raw(&client, &addr, "echo", json!({ "a": "--help" })).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // values after `--` are no options
+    let (status, body) = execute_raw(&client, &addr, "print", json!({ "value": "-5" })).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "-5");
+
+    // missing required input
+    let (status, _) = execute_raw(&client, &addr, "echo", json!({})).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // file mode, options in files are harmless
+    let (status, body) = execute_raw(&client, &addr, "cat", json!({ "text": "-n hello" })).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "-n hello");
+
+    // invalid media types fail when loading
+    let definition = json!({
+        "id": "invalid",
+        "command": "true",
+        "outputs": { "out": { "media_type": "text/plain\n" } }
+    });
+    assert!(CommandProcessor::new(serde_json::from_value(definition)?).is_err());
+
+    // timeout
+    let (status, _) = execute_raw(&client, &addr, "sleep", json!({ "seconds": "5" })).await?;
+    assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+
+    Ok(())
+}
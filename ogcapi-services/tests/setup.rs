@@ -1,22 +1,80 @@
 use std::net::SocketAddr;
 
+use anyhow::Context;
 use url::Url;
 use uuid::Uuid;
 
-use ogcapi_services::{Config, ConfigParser};
+use ogcapi_services::{Backend, Config, ConfigParser, State};
 
-pub async fn spawn_app() -> anyhow::Result<(SocketAddr, Url)> {
+/// Spawns the app on a fresh Postgres database if `DATABASE_URL` is set and
+/// on the memory backend otherwise
+#[allow(dead_code)]
+pub async fn spawn_app() -> anyhow::Result<SocketAddr> {
+    spawn_app_with(|state| state).await
+}
+
+/// Spawns the app with a state customized by `f`
+pub async fn spawn_app_with(f: impl FnOnce(State) -> State) -> anyhow::Result<SocketAddr> {
+    let mut config = config();
+    if config.database_url.is_none() {
+        config.backend = Some(Backend::Memory);
+    }
+
+    spawn(&config, f).await
+}
+
+/// Spawns the app on a fresh Postgres database, returns its url
+#[allow(dead_code)]
+pub async fn spawn_postgres_app() -> anyhow::Result<(SocketAddr, Url)> {
+    let config = config();
+    let database_url = config
+        .database_url
+        .clone()
+        .context("`DATABASE_URL` is required by the test")?;
+
+    spawn(&config, |state| state)
+        .await
+        .map(|addr| (addr, database_url))
+}
+
+/// Config from the environment, with a fresh database
+fn config() -> Config {
     dotenv::dotenv().ok();
 
     // ogcapi_services::telemetry::init();
 
     let mut config = Config::parse();
-    config.database_url.set_path(&Uuid::new_v4().to_string());
+    if let Some(database_url) = config.database_url.as_mut() {
+        database_url.set_path(&Uuid::new_v4().to_string());
+    }
     config.port = 0;
 
-    let state = ogcapi_services::State::new_from(&config).await;
+    config
+}
+
+async fn spawn(config: &Config, f: impl FnOnce(State) -> State) -> anyhow::Result<SocketAddr> {
+    let state = State::new_from(config).await?;
+
+    #[cfg(feature = "processes")]
+    let state = {
+        let mut processors: Vec<Box<dyn ogcapi_services::Processor>> =
+            vec![Box::new(ogcapi_services::Greeter)];
+        // geoprocessing requires the postgres backend
+        if state.drivers.geoprocessing.is_available() {
+            processors.extend([
+                Box::new(ogcapi_services::Buffer) as Box<dyn ogcapi_services::Processor>,
+                Box::new(ogcapi_services::Clip),
+                Box::new(ogcapi_services::Dissolve),
+                Box::new(ogcapi_services::SpatialJoin),
+                Box::new(ogcapi_services::Centroid),
+                Box::new(ogcapi_services::Reproject),
+                Box::new(ogcapi_services::Simplify),
+            ]);
+        }
+        state.processors(processors)
+    };
 
-    let service = ogcapi_services::Service::new_with(&config, state).await;
+    let service = ogcapi_services::Service::new_with(config, f(state)).await;
 
     let addr = service.local_addr()?;
 
@@ -24,5 +82,5 @@ pub async fn spawn_app() -> anyhow::Result<(SocketAddr, Url)> {
         service.serve().await;
     });
 
-    Ok((addr, config.database_url))
+    Ok(addr)
 }
@@ -0,0 +1,443 @@
+mod setup;
+
+#[cfg(feature = "stac")]
+#[tokio::test]
+async fn search_filter_and_queryables() -> anyhow::Result<()> {
+    use axum::http::{Method, Request};
+    use hyper::Body;
+    use serde_json::json;
+
+    use ogcapi_types::{
+        common::media_type::JSON,
+        features::{cql2::MAX_DEPTH, FilterLang},
+        stac::SearchParams,
+    };
+
+    // filter expressions require the postgres backend
+    let (addr, _) = setup::spawn_postgres_app().await?;
+    let client = hyper::Client::new();
+
+    let post = |uri: String, body: serde_json::Value| {
+        client.request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(uri)
+                .header("Content-Type", JSON)
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+    };
+
+    let res = post(
+        format!("http://{addr}/collections"),
+        json!({ "id": "scenes", "links": [] }),
+    )
+    .await?;
+    assert_eq!(201, res.status());
+
+    let feature = |name: &str, cloud_cover: f64| {
+        json!({
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [7.0, 46.0] },
+            "properties": { "name": name, "eo:cloud_cover": cloud_cover }
+        })
+    };
+    let res = post(
+        format!("http://{addr}/collections/scenes/items"),
+        json!({
+            "type": "FeatureCollection",
+            "features": [
+                feature("50% cloudy", 50.0),
+                feature("5000", 10.0),
+                feature("clear", 0.0)
+            ]
+        }),
+    )
+    .await?;
+    assert_eq!(201, res.status());
+
+    let search = |params: SearchParams| {
+        let uri = format!(
+            "http://{addr}/search?{}",
+            serde_qs::to_string(&params).unwrap()
+        );
+        get(&client, uri)
+    };
+
+    // cql2-text
+    let (status, fc) = search(
+        SearchParams::new()
+            .with_filter("\"eo:cloud_cover\" < 20", FilterLang::CqlText)
+            .with_collections(["scenes"].as_slice()),
+    )
+    .await?;
+    assert_eq!(200, status);
+    assert_eq!(fc["numberMatched"], 2);
+
+    // cql2-json
+    let (status, fc) = search(SearchParams::new().with_filter(
+        json!({ "op": "=", "args": [{ "property": "name" }, "clear"] }),
+        FilterLang::CqlJson,
+    ))
+    .await?;
+    assert_eq!(200, status);
+    assert_eq!(fc["features"][0]["properties"]["name"], "clear");
+
+    // wildcards of the query extension match literally
+    let (status, fc) = search(SearchParams {
+        query: Some(json!({ "name": { "startsWith": "50%" } }).to_string()),
+        ..Default::default()
+    })
+    .await?;
+    assert_eq!(200, status);
+    assert_eq!(fc["numberMatched"], 1);
+    assert_eq!(fc["features"][0]["properties"]["name"], "50% cloudy");
+
+    // invalid and too deeply nested filters
+    for filter in [
+        "name =".to_string(),
+        format!(
+            "{}name = 'clear'{}",
+            "(".repeat(MAX_DEPTH),
+            ")".repeat(MAX_DEPTH)
+        ),
+    ] {
+        let (status, _) =
+            search(SearchParams::new().with_filter(filter, FilterLang::CqlText)).await?;
+        assert_eq!(400, status);
+    }
+
+    // queryables
+    let (status, queryables) = get(&client, format!("http://{addr}/queryables")).await?;
+    assert_eq!(200, status);
+    for property in ["id", "collection", "geometry", "datetime"] {
+        assert!(queryables["properties"].get(property).is_some());
+    }
+
+    let (status, queryables) = get(
+        &client,
+        format!("http://{addr}/collections/scenes/queryables"),
+    )
+    .await?;
+    assert_eq!(200, status);
+    assert_eq!(
+        queryables["properties"]["eo:cloud_cover"],
+        json!({ "type": "number" })
+    );
+    assert_eq!(
+        queryables["properties"]["name"],
+        json!({ "type": "string" })
+    );
+
+    let (status, _) = get(
+        &client,
+        format!("http://{addr}/collections/missing/queryables"),
+    )
+    .await?;
+    assert_eq!(404, status);
+
+    Ok(())
+}
+
+#[cfg(feature = "stac")]
+async fn get(
+    client: &hyper::Client<hyper::client::HttpConnector>,
+    uri: String,
+) -> anyhow::Result<(u16, serde_json::Value)> {
+    let res = client.get(uri.parse()?).await?;
+    let status = res.status().as_u16();
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    Ok((status, serde_json::from_slice(&body).unwrap_or_default()))
+}
+
+#[cfg(feature = "stac")]
+#[tokio::test]
+async fn search_index() -> anyhow::Result<()> {
+    use axum::http::{Method, Request};
+    use hyper::Body;
+    use serde_json::json;
+
+    use ogcapi_types::{common::media_type::JSON, stac::SearchParams};
+
+    let (addr, database_url) = setup::spawn_postgres_app().await?;
+    let client = hyper::Client::new();
+
+    let request = |method: Method, uri: String, body: serde_json::Value| {
+        client.request(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("Content-Type", JSON)
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+    };
+    let feature = |id: &str, datetime: &str| {
+        json!({
+            "id": id,
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [7.0, 46.0] },
+            "properties": { "datetime": datetime }
+        })
+    };
+    let search = |params: SearchParams| {
+        let uri = format!(
+            "http://{addr}/search?{}",
+            serde_qs::to_string(&params).unwrap()
+        );
+        get(&client, uri)
+    };
+    let ids = |fc: &serde_json::Value| -> Vec<String> {
+        fc["features"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| {
+                format!(
+                    "{}/{}",
+                    f["collection"].as_str().unwrap(),
+                    f["id"].as_str().unwrap()
+                )
+            })
+            .collect()
+    };
+
+    for collection in ["b", "a"] {
+        let res = request(
+            Method::POST,
+            format!("http://{addr}/collections"),
+            json!({ "id": collection, "links": [] }),
+        )
+        .await?;
+        assert_eq!(201, res.status());
+
+        for id in ["2", "1"] {
+            let res = request(
+                Method::POST,
+                format!("http://{addr}/collections/{collection}/items"),
+                feature(id, "2022-01-01T00:00:00Z"),
+            )
+            .await?;
+            assert_eq!(201, res.status());
+        }
+    }
+
+    // insert, paged by collection and id
+    let mut params = SearchParams::new();
+    params.limit = Some(3);
+    let (status, fc) = search(params).await?;
+    assert_eq!(200, status);
+    assert_eq!(fc["numberMatched"], 4);
+    assert_eq!(ids(&fc), ["a/1", "a/2", "b/1"]);
+
+    let mut params = SearchParams::new();
+    params.limit = Some(3);
+    params.offset = Some(3);
+    let (_, fc) = search(params).await?;
+    assert_eq!(ids(&fc), ["b/2"]);
+
+    // update, malformed datetimes are indexed without datetime
+    let res = request(
+        Method::PUT,
+        format!("http://{addr}/collections/a/items/1"),
+        feature("1", "yesterday"),
+    )
+    .await?;
+    assert!(res.status().is_success());
+
+    let datetime = |d: &str| SearchParams {
+        datetime: Some(d.parse().unwrap()),
+        ..Default::default()
+    };
+    let (_, fc) = search(datetime("2022-01-01T00:00:00Z")).await?;
+    assert_eq!(fc["numberMatched"], 3);
+
+    // delete
+    let res = client
+        .request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("http://{addr}/collections/b/items/2"))
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert!(res.status().is_success());
+
+    let (_, fc) = search(SearchParams::new()).await?;
+    assert_eq!(fc["numberMatched"], 3);
+
+    // backfill of items written without index
+    let pool = sqlx::PgPool::connect(database_url.as_str()).await?;
+    sqlx::query(r#"ALTER TABLE items."b" DISABLE TRIGGER search_index"#)
+        .execute(&pool)
+        .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO items."b" (id, properties, geom)
+        VALUES ('3', '{"datetime": "not a datetime"}', ST_SetSRID(ST_MakePoint(7, 46), 4326))
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+
+    let (_, fc) = search(SearchParams::new()).await?;
+    assert_eq!(fc["numberMatched"], 3);
+
+    sqlx::query("SELECT meta.search_index('b')")
+        .execute(&pool)
+        .await?;
+
+    let (_, fc) = search(SearchParams::new()).await?;
+    assert_eq!(fc["numberMatched"], 4);
+    assert_eq!(ids(&fc).last().unwrap(), "b/3");
+
+    Ok(())
+}
+
+#[cfg(feature = "stac")]
+#[tokio::test]
+async fn extent_and_refresh() -> anyhow::Result<()> {
+    use axum::http::{Method, Request};
+    use hyper::Body;
+    use serde_json::json;
+
+    use ogcapi::refresh::{refresh, Args};
+    use ogcapi_drivers::{postgres::Db, CatalogTransactions};
+    use ogcapi_types::{common::media_type::JSON, stac::Catalog};
+
+    let (addr, database_url) = setup::spawn_postgres_app().await?;
+    let client = hyper::Client::new();
+
+    let request = |method: Method, uri: String, body: serde_json::Value| {
+        client.request(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("Content-Type", JSON)
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+    };
+    let item = |id: &str, x: f64| {
+        json!({
+            "id": id,
+            "type": "Feature",
+            "stac_version": "1.0.0",
+            "geometry": { "type": "Point", "coordinates": [x, 46.0] },
+            "properties": { "datetime": "2022-01-01T00:00:00Z" },
+            "links": [],
+            "assets": {}
+        })
+    };
+    let bbox = |collection: &serde_json::Value| collection["extent"]["spatial"]["bbox"][0].clone();
+
+    // a STAC collection and a plain collection
+    let res = request(
+        Method::POST,
+        format!("http://{addr}/collections"),
+        json!({
+            "id": "stac",
+            "type": "Collection",
+            "stac_version": "1.0.0",
+            "description": "STAC collection",
+            "license": "MIT",
+            "extent": {
+                "spatial": { "bbox": [[7.0, 46.0, 7.0, 46.0]] },
+                "temporal": { "interval": [["2022-01-01T00:00:00Z", "2022-01-01T00:00:00Z"]] }
+            },
+            "links": []
+        }),
+    )
+    .await?;
+    assert_eq!(201, res.status());
+
+    let res = request(
+        Method::POST,
+        format!("http://{addr}/collections"),
+        json!({ "id": "plain", "links": [] }),
+    )
+    .await?;
+    assert_eq!(201, res.status());
+
+    for (collection, id, x) in [("stac", "a", 6.0), ("stac", "b", 8.0), ("plain", "a", 6.0)] {
+        let res = request(
+            Method::POST,
+            format!("http://{addr}/collections/{collection}/items"),
+            item(id, x),
+        )
+        .await?;
+        assert_eq!(201, res.status());
+    }
+
+    // collections grow with their items
+    let (status, collection) = get(&client, format!("http://{addr}/collections/stac")).await?;
+    assert_eq!(200, status);
+    assert_eq!(bbox(&collection), json!([6.0, 46.0, 8.0, 46.0]));
+
+    let (_, collection) = get(&client, format!("http://{addr}/collections/plain")).await?;
+    assert_eq!(bbox(&collection), json!([6.0, 46.0, 6.0, 46.0]));
+
+    // and shrink when an item on the boundary is removed
+    let res = client
+        .request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("http://{addr}/collections/stac/items/b"))
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert!(res.status().is_success());
+
+    let (_, collection) = get(&client, format!("http://{addr}/collections/stac")).await?;
+    assert_eq!(bbox(&collection), json!([6.0, 46.0, 6.0, 46.0]));
+
+    // refresh endpoint
+    let res = request(
+        Method::POST,
+        format!("http://{addr}/collections/plain/refresh"),
+        json!({}),
+    )
+    .await?;
+    assert_eq!(200, res.status());
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let collection: serde_json::Value = serde_json::from_slice(&body)?;
+    assert_eq!(bbox(&collection), json!([6.0, 46.0, 6.0, 46.0]));
+
+    // the extent is cleared with the last item
+    let res = client
+        .request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("http://{addr}/collections/plain/items/a"))
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert!(res.status().is_success());
+
+    let (_, collection) = get(&client, format!("http://{addr}/collections/plain")).await?;
+    assert!(collection["extent"].is_null());
+
+    let res = request(
+        Method::POST,
+        format!("http://{addr}/collections/missing/refresh"),
+        json!({}),
+    )
+    .await?;
+    assert_eq!(404, res.status());
+
+    // refresh command, catalogs have no items
+    let db = Db::setup(&database_url).await?;
+    db.create_catalog(&Catalog::new("catalog", "Catalog"))
+        .await?;
+
+    let args = |collection: Option<&str>| Args {
+        collection: collection.map(ToString::to_string),
+        database_url: database_url.to_owned(),
+    };
+    refresh(args(None)).await?;
+    refresh(args(Some("stac"))).await?;
+    assert!(refresh(args(Some("catalog"))).await.is_err());
+    assert!(refresh(args(Some("missing"))).await.is_err());
+
+    Ok(())
+}
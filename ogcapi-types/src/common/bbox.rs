@@ -12,6 +12,58 @@ pub enum Bbox {
     Bbox3D(Bbox3D),
 }
 
+impl Bbox {
+    /// Horizontal corners `[xmin, ymin, xmax, ymax]` of the bbox
+    pub fn corners(&self) -> [f64; 4] {
+        match self {
+            Bbox::Bbox2D(b) => *b,
+            Bbox::Bbox3D(b) => [b[0], b[1], b[3], b[4]],
+        }
+    }
+
+    /// Whether the horizontal extents of two bboxes intersect
+    pub fn intersects(&self, other: &Bbox) -> bool {
+        let [xmin, ymin, xmax, ymax] = self.corners();
+        let [oxmin, oymin, oxmax, oymax] = other.corners();
+        xmin <= oxmax && oxmin <= xmax && ymin <= oymax && oymin <= ymax
+    }
+
+    /// Bbox of the positions of a geometry
+    pub fn from_geometry(geometry: &geojson::Geometry) -> Option<Bbox> {
+        let mut positions = Vec::new();
+        collect_positions(&geometry.value, &mut positions);
+
+        let mut iter = positions.into_iter();
+        let first = iter.next()?;
+        let mut bbox = [first[0], first[1], first[0], first[1]];
+        for p in iter {
+            bbox = [
+                bbox[0].min(p[0]),
+                bbox[1].min(p[1]),
+                bbox[2].max(p[0]),
+                bbox[3].max(p[1]),
+            ];
+        }
+        Some(Bbox::Bbox2D(bbox))
+    }
+}
+
+fn collect_positions<'a>(value: &'a geojson::Value, positions: &mut Vec<&'a Vec<f64>>) {
+    use geojson::Value::*;
+
+    match value {
+        Point(p) => positions.push(p),
+        MultiPoint(ps) | LineString(ps) => positions.extend(ps),
+        MultiLineString(ls) | Polygon(ls) => positions.extend(ls.iter().flatten()),
+        MultiPolygon(ps) => positions.extend(ps.iter().flatten().flatten()),
+        GeometryCollection(gs) => {
+            for g in gs {
+                collect_positions(&g.value, positions)
+            }
+        }
+    }
+}
+
 impl fmt::Display for Bbox {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -113,4 +165,17 @@ mod tests {
             serde_json::to_string(&bbox).unwrap()
         );
     }
+
+    #[test]
+    fn intersects() {
+        let geometry: geojson::Geometry = serde_json::from_str(
+            r#"{ "type": "LineString", "coordinates": [[0.5, -1.0], [2.0, 0.5, 10.0]] }"#,
+        )
+        .unwrap();
+        let bbox = Bbox::from_geometry(&geometry).unwrap();
+        assert_eq!(bbox, Bbox::Bbox2D([0.5, -1.0, 2.0, 0.5]));
+
+        assert!(bbox.intersects(&Bbox::Bbox3D([1.0, 0.0, -5.0, 3.0, 3.0, 5.0])));
+        assert!(!bbox.intersects(&Bbox::Bbox2D([2.5, -1.0, 3.0, 0.0])));
+    }
 }
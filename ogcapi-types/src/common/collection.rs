@@ -56,10 +56,14 @@ pub struct Collection {
         skip_serializing_if = "std::collections::HashMap::is_empty"
     )]
     pub parameter_names: std::collections::HashMap<String, crate::edr::ParameterNames>,
-    /// The STAC version the Collection implements.
+    /// The STAC version the Collection implements, `None` for plain collections.
     #[cfg(feature = "stac")]
-    #[serde(default = "crate::stac::stac_version", rename = "stac_version")]
-    pub stac_version: String,
+    #[serde(
+        default,
+        rename = "stac_version",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub stac_version: Option<String>,
     // /// A list of extension identifiers the Collection implements.
     #[cfg(feature = "stac")]
     #[serde(
@@ -71,6 +75,7 @@ pub struct Collection {
     /// Collection's license(s), either a SPDX License identifier, `various` if
     /// multiple licenses apply or `proprietary` for all other cases.
     #[cfg(feature = "stac")]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub license: String,
     /// A list of providers, which may include all organizations capturing or processing the data or the hosting provider.
     #[cfg(feature = "stac")]
@@ -117,7 +122,7 @@ impl Default for Collection {
             #[cfg(feature = "edr")]
             parameter_names: Default::default(),
             #[cfg(feature = "stac")]
-            stac_version: crate::stac::stac_version(),
+            stac_version: Default::default(),
             #[cfg(feature = "stac")]
             stac_extensions: Default::default(),
             #[cfg(feature = "stac")]
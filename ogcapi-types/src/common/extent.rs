@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use serde_with::{serde_as, DisplayFromStr};
+use serde_with::{serde_as, DisplayFromStr, Same};
 
 use crate::common::{Bbox, Crs};
 
@@ -43,7 +43,10 @@ impl Default for SpatialExtent {
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct TemporalExtent {
     #[serde(skip_serializing_if = "Vec::is_empty")]
-    #[serde_as(as = "Vec<Vec<Option<DisplayFromStr>>>")]
+    #[serde_as(
+        serialize_as = "Vec<Vec<Option<Same>>>",
+        deserialize_as = "Vec<Vec<Option<DisplayFromStr>>>"
+    )]
     pub interval: Vec<Vec<Option<DateTime<Utc>>>>,
     #[serde(default = "default_trs")]
     pub trs: String,
@@ -5,6 +5,14 @@
 
 pub const ABOUT: &str = "about";
 
+/// The target URI points to the aggregations of the context resource
+/// (STAC aggregation extension).
+pub const AGGREGATE: &str = "aggregate";
+
+/// The target URI lists the aggregations supported by the context resource
+/// (STAC aggregation extension).
+pub const AGGREGATIONS: &str = "aggregations";
+
 /// Refers to a substitute for the link’s context.
 pub const ATERNATE: &str = "alternate";
 
@@ -66,6 +74,11 @@ pub const PREV: &str = "prev";
 /// See: <http://www.opengis.net/def/rel/ogc/1.0/processes>
 pub const PROCESSES: &str = "processes";
 
+/// The target URI points to the queryables of the context resource.
+///
+/// See: <http://www.opengis.net/def/rel/ogc/1.0/queryables>
+pub const QUERYABLES: &str = "http://www.opengis.net/def/rel/ogc/1.0/queryables";
+
 pub const RELATED: &str = "related";
 
 /// The target URI points to the results of a job.
@@ -2,7 +2,7 @@ use std::{fmt, str};
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ListParam(pub Vec<String>);
 
 impl fmt::Display for ListParam {
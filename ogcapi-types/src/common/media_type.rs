@@ -6,6 +6,9 @@ pub const COVERAGE_JSON: &str = "application/prs.coverage+json";
 /// Media Type for `application/geo+json`
 pub const GEO_JSON: &str = "application/geo+json";
 
+/// Media Type for `image/tiff; application=geotiff`
+pub const GEO_TIFF: &str = "image/tiff; application=geotiff";
+
 /// Media Type for `text/html`
 pub const HTML: &str = "text/html";
 
@@ -30,5 +33,8 @@ pub const PNG: &str = "image/png";
 /// Media Type for `application/problem+json`
 pub const PROBLEM_JSON: &str = "application/problem+json";
 
+/// Media Type for `application/schema+json`
+pub const SCHEMA_JSON: &str = "application/schema+json";
+
 /// Media Type for `application/vnd.ogc.sld+xml;version=1.0`
 pub const SLD: &str = "application/vnd.ogc.sld+xml;version=1.0";
@@ -13,6 +13,7 @@ mod links;
 mod list_param;
 pub mod media_type;
 mod query;
+pub mod wkt;
 
 pub use bbox::Bbox;
 pub use collection::*;
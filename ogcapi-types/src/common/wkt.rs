@@ -0,0 +1,390 @@
+use std::{fmt, iter::Peekable, str::FromStr};
+
+/// Position of a WKT geometry, with two to four ordinates (`x y [z] [m]`)
+pub type Position = Vec<f64>;
+
+/// Ordinates of the positions of a WKT geometry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimensions {
+    Xy,
+    Xyz,
+    Xym,
+    Xyzm,
+}
+
+impl Dimensions {
+    /// Number of ordinates of a position
+    pub fn size(&self) -> usize {
+        match self {
+            Dimensions::Xy => 2,
+            Dimensions::Xyz | Dimensions::Xym => 3,
+            Dimensions::Xyzm => 4,
+        }
+    }
+
+    pub fn has_z(&self) -> bool {
+        matches!(self, Dimensions::Xyz | Dimensions::Xyzm)
+    }
+
+    pub fn has_m(&self) -> bool {
+        matches!(self, Dimensions::Xym | Dimensions::Xyzm)
+    }
+}
+
+/// Geometry of a Well Known Text (WKT) representation
+#[derive(Debug, Clone, PartialEq)]
+pub enum Geometry {
+    Point(Position),
+    LineString(Vec<Position>),
+    Polygon(Vec<Vec<Position>>),
+    MultiPoint(Vec<Position>),
+    MultiLineString(Vec<Vec<Position>>),
+    MultiPolygon(Vec<Vec<Vec<Position>>>),
+}
+
+impl Geometry {
+    /// WKT name of the geometry type
+    pub fn name(&self) -> &'static str {
+        match self {
+            Geometry::Point(_) => "POINT",
+            Geometry::LineString(_) => "LINESTRING",
+            Geometry::Polygon(_) => "POLYGON",
+            Geometry::MultiPoint(_) => "MULTIPOINT",
+            Geometry::MultiLineString(_) => "MULTILINESTRING",
+            Geometry::MultiPolygon(_) => "MULTIPOLYGON",
+        }
+    }
+}
+
+/// Geometry parsed from Well Known Text, e.g. `POINT(10 50)` or `LINESTRINGZ(10 50 2, 11 51 3)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wkt {
+    pub geometry: Geometry,
+    pub dimensions: Dimensions,
+}
+
+impl Wkt {
+    /// All positions of the geometry
+    pub fn positions(&self) -> Vec<&Position> {
+        match &self.geometry {
+            Geometry::Point(p) => vec![p],
+            Geometry::LineString(ps) | Geometry::MultiPoint(ps) => ps.iter().collect(),
+            Geometry::Polygon(rings) | Geometry::MultiLineString(rings) => {
+                rings.iter().flatten().collect()
+            }
+            Geometry::MultiPolygon(polygons) => polygons.iter().flatten().flatten().collect(),
+        }
+    }
+
+    /// Polygons (exterior ring followed by holes) of areal geometries
+    pub fn polygons(&self) -> Vec<&Vec<Vec<Position>>> {
+        match &self.geometry {
+            Geometry::Polygon(rings) => vec![rings],
+            Geometry::MultiPolygon(polygons) => polygons.iter().collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl From<Wkt> for geojson::Geometry {
+    fn from(wkt: Wkt) -> Self {
+        let value = match wkt.geometry {
+            Geometry::Point(p) => geojson::Value::Point(p),
+            Geometry::LineString(ps) => geojson::Value::LineString(ps),
+            Geometry::Polygon(rings) => geojson::Value::Polygon(rings),
+            Geometry::MultiPoint(ps) => geojson::Value::MultiPoint(ps),
+            Geometry::MultiLineString(lines) => geojson::Value::MultiLineString(lines),
+            Geometry::MultiPolygon(polygons) => geojson::Value::MultiPolygon(polygons),
+        };
+        geojson::Geometry::new(value)
+    }
+}
+
+impl FromStr for Wkt {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?.into_iter().peekable(),
+            dimensions: None,
+        };
+
+        let name = match parser.tokens.next() {
+            Some(Token::Word(word)) => word,
+            _ => return Err("Expected a WKT geometry type, e.g. `POINT`".to_string()),
+        };
+
+        // dimensions either suffixed (`POINTZ`) or separated (`POINT Z`)
+        let (name, mut suffix) = ["ZM", "Z", "M"]
+            .iter()
+            .find_map(|d| {
+                name.strip_suffix(d)
+                    .filter(|n| GEOMETRY_TYPES.contains(n))
+                    .map(|n| (n.to_owned(), d.to_string()))
+            })
+            .unwrap_or((name, String::new()));
+        if let Some(Token::Word(word)) = parser.tokens.peek() {
+            if suffix.is_empty() && ["Z", "M", "ZM"].contains(&word.as_str()) {
+                suffix = word.to_owned();
+                parser.tokens.next();
+            }
+        }
+        if parser
+            .tokens
+            .next_if_eq(&Token::Word("EMPTY".to_string()))
+            .is_some()
+        {
+            return Err("Empty geometries are not supported".to_string());
+        }
+        parser.dimensions = match suffix.as_str() {
+            "Z" => Some(Dimensions::Xyz),
+            "M" => Some(Dimensions::Xym),
+            "ZM" => Some(Dimensions::Xyzm),
+            _ => None,
+        };
+
+        let geometry = match name.as_str() {
+            "POINT" => Geometry::Point(parser.point()?),
+            "LINESTRING" => Geometry::LineString(parser.line_string()?),
+            "POLYGON" => Geometry::Polygon(parser.polygon()?),
+            "MULTIPOINT" => Geometry::MultiPoint(parser.multi_point()?),
+            "MULTILINESTRING" => Geometry::MultiLineString(parser.list(|p| p.line_string())?),
+            "MULTIPOLYGON" => Geometry::MultiPolygon(parser.list(|p| p.polygon())?),
+            name => return Err(format!("Unsupported WKT geometry type `{name}`")),
+        };
+
+        if let Some(token) = parser.tokens.next() {
+            return Err(format!("Unexpected {token} after geometry"));
+        }
+
+        Ok(Wkt {
+            geometry,
+            dimensions: parser.dimensions.unwrap_or(Dimensions::Xy),
+        })
+    }
+}
+
+impl fmt::Display for Wkt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let position = |p: &Position| {
+            p.iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let line = |ps: &Vec<Position>| {
+            format!(
+                "({})",
+                ps.iter().map(position).collect::<Vec<_>>().join(",")
+            )
+        };
+        let polygon = |rings: &Vec<Vec<Position>>| {
+            format!("({})", rings.iter().map(line).collect::<Vec<_>>().join(","))
+        };
+
+        let coordinates = match &self.geometry {
+            Geometry::Point(p) => format!("({})", position(p)),
+            Geometry::LineString(ps) | Geometry::MultiPoint(ps) => line(ps),
+            Geometry::Polygon(rings) | Geometry::MultiLineString(rings) => polygon(rings),
+            Geometry::MultiPolygon(polygons) => format!(
+                "({})",
+                polygons.iter().map(polygon).collect::<Vec<_>>().join(",")
+            ),
+        };
+
+        let dimensions = match self.dimensions {
+            Dimensions::Xy => "",
+            Dimensions::Xyz => " Z",
+            Dimensions::Xym => " M",
+            Dimensions::Xyzm => " ZM",
+        };
+
+        write!(f, "{}{}{}", self.geometry.name(), dimensions, coordinates)
+    }
+}
+
+/// Supported WKT geometry types
+pub(crate) const GEOMETRY_TYPES: [&str; 6] = [
+    "POINT",
+    "LINESTRING",
+    "POLYGON",
+    "MULTIPOINT",
+    "MULTILINESTRING",
+    "MULTIPOLYGON",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Number(f64),
+    Open,
+    Close,
+    Comma,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "`{word}`"),
+            Token::Number(number) => write!(f, "`{number}`"),
+            Token::Open => write!(f, "`(`"),
+            Token::Close => write!(f, "`)`"),
+            Token::Comma => write!(f, "`,`"),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            ',' => tokens.push(Token::Comma),
+            c if c.is_whitespace() => {}
+            c if c.is_ascii_alphabetic() => {
+                let mut word = c.to_ascii_uppercase().to_string();
+                while let Some(c) = chars.next_if(char::is_ascii_alphabetic) {
+                    word.push(c.to_ascii_uppercase());
+                }
+                tokens.push(Token::Word(word));
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+                let mut number = c.to_string();
+                while let Some(c) =
+                    chars.next_if(|c| c.is_ascii_digit() || ['.', 'e', 'E', '-', '+'].contains(c))
+                {
+                    number.push(c);
+                }
+                tokens.push(Token::Number(
+                    number
+                        .parse()
+                        .map_err(|_| format!("Invalid number `{number}`"))?,
+                ));
+            }
+            c => return Err(format!("Unexpected character `{c}`")),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<I: Iterator<Item = Token>> {
+    tokens: Peekable<I>,
+    /// Dimensions declared or inferred from the first position
+    dimensions: Option<Dimensions>,
+}
+
+impl<I: Iterator<Item = Token>> Parser<I> {
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.tokens.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("Expected {expected}, found {token}")),
+            None => Err(format!("Expected {expected}, found end of input")),
+        }
+    }
+
+    /// Comma separated list in parentheses
+    fn list<T>(&mut self, item: impl Fn(&mut Self) -> Result<T, String>) -> Result<Vec<T>, String> {
+        self.expect(Token::Open)?;
+        let mut items = vec![item(self)?];
+        while self.tokens.next_if_eq(&Token::Comma).is_some() {
+            items.push(item(self)?);
+        }
+        self.expect(Token::Close)?;
+        Ok(items)
+    }
+
+    fn position(&mut self) -> Result<Position, String> {
+        let mut position = Vec::new();
+        while let Some(Token::Number(n)) = self.tokens.peek() {
+            position.push(*n);
+            self.tokens.next();
+        }
+
+        let dimensions = match (self.dimensions, position.len()) {
+            (Some(d), n) if d.size() == n => d,
+            (None, 2) => Dimensions::Xy,
+            (None, 3) => Dimensions::Xyz,
+            (None, 4) => Dimensions::Xyzm,
+            (Some(d), n) => {
+                return Err(format!(
+                    "Expected {} ordinates per position, found {n}",
+                    d.size()
+                ))
+            }
+            (None, n) => return Err(format!("Expected 2 to 4 ordinates per position, found {n}")),
+        };
+        self.dimensions = Some(dimensions);
+
+        Ok(position)
+    }
+
+    fn point(&mut self) -> Result<Position, String> {
+        self.expect(Token::Open)?;
+        let position = self.position()?;
+        self.expect(Token::Close)?;
+        Ok(position)
+    }
+
+    fn line_string(&mut self) -> Result<Vec<Position>, String> {
+        let positions = self.list(|p| p.position())?;
+        if positions.len() < 2 {
+            return Err("Line strings require at least two positions".to_string());
+        }
+        Ok(positions)
+    }
+
+    fn polygon(&mut self) -> Result<Vec<Vec<Position>>, String> {
+        self.list(|p| {
+            let ring = p.list(|p| p.position())?;
+            if ring.len() < 4 {
+                return Err("Polygon rings require at least four positions".to_string());
+            }
+            if ring.first() != ring.last() {
+                return Err("Polygon rings must be closed".to_string());
+            }
+            Ok(ring)
+        })
+    }
+
+    fn multi_point(&mut self) -> Result<Vec<Position>, String> {
+        // positions may be enclosed in parentheses, `MULTIPOINT((1 2),(3 4))`
+        self.list(|p| match p.tokens.peek() {
+            Some(Token::Open) => p.point(),
+            _ => p.position(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dimensions, Geometry, Wkt};
+
+    #[test]
+    fn parse_wkt() {
+        let wkt: Wkt = "POINT(10 50)".parse().unwrap();
+        assert_eq!(wkt.geometry, Geometry::Point(vec![10.0, 50.0]));
+        assert_eq!(wkt.dimensions, Dimensions::Xy);
+
+        let wkt: Wkt = "LineStringZ(10 50 1, -11.5 5.1e1 2)".parse().unwrap();
+        assert_eq!(wkt.dimensions, Dimensions::Xyz);
+        assert_eq!(wkt.to_string(), "LINESTRING Z(10 50 1,-11.5 51 2)");
+
+        let wkt: Wkt = "POLYGON((0 0, 1 0, 1 1, 0 0))".parse().unwrap();
+        assert_eq!(wkt.polygons()[0][0].len(), 4);
+        assert_eq!(wkt.to_string().parse::<Wkt>().unwrap(), wkt);
+
+        let wkt: Wkt = "MULTIPOINT((1 2), (3 4))".parse().unwrap();
+        assert_eq!(wkt.positions().len(), 2);
+
+        assert!("POINT(10)".parse::<Wkt>().is_err());
+        assert!("POINT Z(10 50)".parse::<Wkt>().is_err());
+        assert!("LINESTRING(10 50 1, 11 51)".parse::<Wkt>().is_err());
+        assert!("POLYGON((0 0, 1 0, 1 1, 0 1))".parse::<Wkt>().is_err());
+        assert!("POINT(10 50); DROP TABLE".parse::<Wkt>().is_err());
+        assert!("CIRCLE(10 50)".parse::<Wkt>().is_err());
+    }
+}
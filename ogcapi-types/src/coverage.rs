@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
+use serde_with::skip_serializing_none;
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct Coverage {
@@ -11,11 +12,14 @@ pub struct Coverage {
     pub ranges: Map<String, Value>,
 }
 
+#[skip_serializing_none]
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
 pub struct Domain {
     pub r#type: String,
     pub domain_type: Option<DomainType>,
     pub axes: Map<String, Value>,
+    #[serde(default)]
     pub referencing: Vec<Value>,
 }
 
@@ -28,7 +32,7 @@ pub enum CoverageType {
     CoverageCollection,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DomainType {
     Grid,
     VerticalProfile,
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::skip_serializing_none;
+
+/// Domain set of a coverage (CIS 1.1 JSON)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DomainSet {
+    pub r#type: String,
+    pub general_grid: GeneralGrid,
+}
+
+impl DomainSet {
+    pub fn new(general_grid: GeneralGrid) -> Self {
+        DomainSet {
+            r#type: "DomainSet".to_string(),
+            general_grid,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneralGrid {
+    pub r#type: String,
+    /// Coordinate reference system of the axes
+    pub srs_name: String,
+    pub axis_labels: Vec<String>,
+    pub axis: Vec<Axis>,
+    pub grid_limits: GridLimits,
+}
+
+impl GeneralGrid {
+    pub fn new(srs_name: impl ToString, axis: Vec<Axis>, grid_limits: GridLimits) -> Self {
+        GeneralGrid {
+            r#type: "GeneralGridCoverage".to_string(),
+            srs_name: srs_name.to_string(),
+            axis_labels: axis.iter().map(|a| a.axis_label.to_owned()).collect(),
+            axis,
+            grid_limits,
+        }
+    }
+}
+
+/// Regular or irregular axis of a grid
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Axis {
+    pub r#type: String,
+    pub axis_label: String,
+    pub lower_bound: Option<Value>,
+    pub upper_bound: Option<Value>,
+    pub resolution: Option<f64>,
+    pub uom: Option<String>,
+    /// Coordinates of irregular axes
+    pub coordinate: Option<Vec<Value>>,
+}
+
+impl Axis {
+    pub fn regular(
+        label: impl ToString,
+        lower_bound: f64,
+        upper_bound: f64,
+        resolution: f64,
+        uom: impl ToString,
+    ) -> Self {
+        Axis {
+            r#type: "RegularAxis".to_string(),
+            axis_label: label.to_string(),
+            lower_bound: Some(lower_bound.into()),
+            upper_bound: Some(upper_bound.into()),
+            resolution: Some(resolution),
+            uom: Some(uom.to_string()),
+            coordinate: None,
+        }
+    }
+
+    pub fn irregular(label: impl ToString, coordinate: Vec<Value>, uom: impl ToString) -> Self {
+        Axis {
+            r#type: "IrregularAxis".to_string(),
+            axis_label: label.to_string(),
+            lower_bound: None,
+            upper_bound: None,
+            resolution: None,
+            uom: Some(uom.to_string()),
+            coordinate: Some(coordinate),
+        }
+    }
+}
+
+/// Extent of the grid in cells
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GridLimits {
+    pub r#type: String,
+    pub srs_name: String,
+    pub axis_labels: Vec<String>,
+    pub axis: Vec<IndexAxis>,
+}
+
+impl GridLimits {
+    pub fn new(axis: Vec<IndexAxis>) -> Self {
+        GridLimits {
+            r#type: "GridLimits".to_string(),
+            srs_name: format!("http://www.opengis.net/def/crs/OGC/0/Index{}D", axis.len()),
+            axis_labels: axis.iter().map(|a| a.axis_label.to_owned()).collect(),
+            axis,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexAxis {
+    pub r#type: String,
+    pub axis_label: String,
+    pub lower_bound: i64,
+    pub upper_bound: i64,
+}
+
+impl IndexAxis {
+    pub fn new(label: impl ToString, lower_bound: i64, upper_bound: i64) -> Self {
+        IndexAxis {
+            r#type: "IndexAxis".to_string(),
+            axis_label: label.to_string(),
+            lower_bound,
+            upper_bound,
+        }
+    }
+}
@@ -0,0 +1,7 @@
+mod domain_set;
+mod query;
+mod range_type;
+
+pub use domain_set::{Axis, DomainSet, GeneralGrid, GridLimits, IndexAxis};
+pub use query::{AxisSubset, AxisValues, Interval, Query, Subset};
+pub use range_type::{Field, RangeType, UnitReference};
@@ -0,0 +1,256 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none, DisplayFromStr};
+
+use crate::common::ListParam;
+
+/// Query parameters of coverage requests
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct Query {
+    /// Trim or slice of axes, e.g. `Lat(40:50),Lon(10:20),time("2022-01-01T00:00:00Z")`
+    #[serde(default)]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub subset: Option<Subset>,
+    /// Factor by which all axes are downscaled
+    pub scale_factor: Option<f64>,
+    /// Factors by which individual axes are downscaled, e.g. `Lon(2),Lat(2)`
+    #[serde(default)]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub scale_axes: Option<AxisValues>,
+    /// Number of cells of individual axes, e.g. `Lon(100),Lat(50)`
+    #[serde(default)]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub scale_size: Option<AxisValues>,
+    /// Fields to return (range subsetting)
+    #[serde(default, alias = "range-subset")]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub properties: Option<ListParam>,
+    pub f: Option<String>,
+}
+
+/// Subset of the axes of a coverage
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subset(pub Vec<AxisSubset>);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AxisSubset {
+    pub axis: String,
+    pub interval: Interval,
+}
+
+/// Slice at a coordinate or trim to an interval (`*` for open bounds)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Interval {
+    Slice(String),
+    Trim(Option<String>, Option<String>),
+}
+
+impl Interval {
+    /// Numeric bounds, open bounds are infinite
+    pub fn bounds(&self) -> Result<(f64, f64), String> {
+        let number = |v: &Option<String>, open: f64| match v {
+            Some(v) => v
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid coordinate `{v}`")),
+            None => Ok(open),
+        };
+        match self {
+            Interval::Slice(v) => {
+                let v = number(&Some(v.to_owned()), f64::NAN)?;
+                Ok((v, v))
+            }
+            Interval::Trim(low, high) => Ok((
+                number(low, f64::NEG_INFINITY)?,
+                number(high, f64::INFINITY)?,
+            )),
+        }
+    }
+}
+
+impl Subset {
+    /// Subset of an axis, axis names are case insensitive
+    pub fn axis(&self, names: &[&str]) -> Option<&Interval> {
+        self.0
+            .iter()
+            .find(|s| names.iter().any(|n| n.eq_ignore_ascii_case(&s.axis)))
+            .map(|s| &s.interval)
+    }
+}
+
+impl FromStr for Subset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let subsets = split(s)?
+            .into_iter()
+            .map(|(axis, value)| {
+                let bound = |v: &str| {
+                    let v = v.trim().trim_matches('"');
+                    (v != "*").then(|| v.to_owned())
+                };
+                let interval = match value.split_once(':') {
+                    // times contain colons, bounds are quoted
+                    Some(_) if is_quoted(value.trim()) => {
+                        Interval::Slice(value.trim().trim_matches('"').to_owned())
+                    }
+                    Some(_) if value.contains('"') => {
+                        let parts: Vec<&str> = value.split('"').collect();
+                        match parts.as_slice() {
+                            ["", low, ":", high, ""] => Interval::Trim(bound(low), bound(high)),
+                            ["", low, ":*"] => Interval::Trim(bound(low), None),
+                            ["*:", high, ""] => Interval::Trim(None, bound(high)),
+                            _ => return Err(format!("Invalid subset `{axis}({value})`")),
+                        }
+                    }
+                    Some((low, high)) => Interval::Trim(bound(low), bound(high)),
+                    None => Interval::Slice(value.trim().trim_matches('"').to_owned()),
+                };
+                Ok(AxisSubset { axis, interval })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Subset(subsets))
+    }
+}
+
+impl fmt::Display for Subset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // non numeric values (e.g. times) are quoted
+        let quote = |v: &str| match v.parse::<f64>() {
+            Ok(_) => v.to_owned(),
+            Err(_) => format!("\"{v}\""),
+        };
+        let value = |v: &Option<String>| v.as_deref().map_or("*".to_string(), quote);
+        let subsets: Vec<String> = self
+            .0
+            .iter()
+            .map(|s| match &s.interval {
+                Interval::Slice(v) => format!("{}({})", s.axis, quote(v)),
+                Interval::Trim(low, high) => {
+                    format!("{}({}:{})", s.axis, value(low), value(high))
+                }
+            })
+            .collect();
+        write!(f, "{}", subsets.join(","))
+    }
+}
+
+/// Values per axis, e.g. `Lon(100),Lat(50)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AxisValues(pub Vec<(String, f64)>);
+
+impl AxisValues {
+    /// Value of an axis, axis names are case insensitive
+    pub fn axis(&self, names: &[&str]) -> Option<f64> {
+        self.0
+            .iter()
+            .find(|(axis, _)| names.iter().any(|n| n.eq_ignore_ascii_case(axis)))
+            .map(|(_, v)| *v)
+    }
+}
+
+impl FromStr for AxisValues {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = split(s)?
+            .into_iter()
+            .map(|(axis, value)| match value.trim().parse::<f64>() {
+                Ok(v) if v > 0.0 => Ok((axis, v)),
+                _ => Err(format!("Invalid value `{axis}({value})`")),
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(AxisValues(values))
+    }
+}
+
+impl fmt::Display for AxisValues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values: Vec<String> = self
+            .0
+            .iter()
+            .map(|(axis, value)| format!("{axis}({value})"))
+            .collect();
+        write!(f, "{}", values.join(","))
+    }
+}
+
+/// Whether a value is a single quoted string
+fn is_quoted(v: &str) -> bool {
+    v.len() > 1 && v.starts_with('"') && v.ends_with('"') && v.matches('"').count() == 2
+}
+
+/// Splits `Axis(value),Axis(value)` into axis and value
+fn split(s: &str) -> Result<Vec<(String, String)>, String> {
+    let mut parts = Vec::new();
+    let mut rest = s.trim();
+
+    while !rest.is_empty() {
+        let (axis, tail) = rest
+            .split_once('(')
+            .ok_or_else(|| format!("Invalid axis expression `{rest}`"))?;
+        let (value, tail) = tail
+            .split_once(')')
+            .ok_or_else(|| format!("Missing `)` in `{rest}`"))?;
+
+        let axis = axis.trim();
+        if axis.is_empty() {
+            return Err(format!("Missing axis name in `{s}`"));
+        }
+        parts.push((axis.to_owned(), value.to_owned()));
+
+        rest = tail.trim_start().trim_start_matches(',').trim_start();
+    }
+
+    Ok(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AxisValues, Interval, Subset};
+
+    #[test]
+    fn subset() {
+        let subset: Subset = r#"Lat(40:50),Lon(10),time("2022-01-01T00:00:00Z":*)"#
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            subset.axis(&["lat"]),
+            Some(&Interval::Trim(
+                Some("40".to_string()),
+                Some("50".to_string())
+            ))
+        );
+        assert_eq!(
+            subset.axis(&["Lon"]).unwrap().bounds().unwrap(),
+            (10.0, 10.0)
+        );
+        assert_eq!(
+            subset.axis(&["time"]),
+            Some(&Interval::Trim(
+                Some("2022-01-01T00:00:00Z".to_string()),
+                None
+            ))
+        );
+        assert_eq!(
+            subset.to_string(),
+            r#"Lat(40:50),Lon(10),time("2022-01-01T00:00:00Z":*)"#
+        );
+        assert_eq!(subset.to_string().parse::<Subset>().unwrap(), subset);
+
+        assert!("Lat(40:50".parse::<Subset>().is_err());
+    }
+
+    #[test]
+    fn axis_values() {
+        let values: AxisValues = "Lon(100), Lat(50)".parse().unwrap();
+        assert_eq!(values.axis(&["lat"]), Some(50.0));
+        assert!("Lon(-1)".parse::<AxisValues>().is_err());
+    }
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+/// Range type of a coverage describing its fields (CIS 1.1 JSON)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RangeType {
+    pub r#type: String,
+    pub field: Vec<Field>,
+}
+
+impl RangeType {
+    pub fn new(field: Vec<Field>) -> Self {
+        RangeType {
+            r#type: "DataRecord".to_string(),
+            field,
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Field {
+    pub r#type: String,
+    pub id: Option<String>,
+    pub name: String,
+    pub definition: Option<String>,
+    pub description: Option<String>,
+    pub uom: Option<UnitReference>,
+}
+
+impl Field {
+    /// Quantity field
+    pub fn new(name: impl ToString) -> Self {
+        Field {
+            r#type: "Quantity".to_string(),
+            id: None,
+            name: name.to_string(),
+            definition: None,
+            description: None,
+            uom: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UnitReference {
+    pub r#type: String,
+    pub code: String,
+}
+
+impl UnitReference {
+    pub fn new(code: impl ToString) -> Self {
+        UnitReference {
+            r#type: "UnitReference".to_string(),
+            code: code.to_string(),
+        }
+    }
+}
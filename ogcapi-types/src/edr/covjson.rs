@@ -0,0 +1,413 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    common::Crs,
+    coverage::{Coverage, CoverageType, Domain, DomainType},
+    features::{Feature, FeatureCollection, Geometry},
+};
+
+use super::{ParameterNames, QueryType};
+
+/// Located observation of a feature
+struct Observation<'a> {
+    x: f64,
+    y: f64,
+    z: Option<f64>,
+    t: Option<String>,
+    properties: Option<&'a Map<String, Value>>,
+}
+
+impl Coverage {
+    /// Encodes the result of an EDR query as CoverageJSON.
+    ///
+    /// Features are located by their point geometry (or the center of their
+    /// bounding box) and their `datetime` property. Parameters are the properties
+    /// described in `parameters` or, if there are none, all numeric properties.
+    /// Numeric values of features at the same position and time are averaged,
+    /// of other values the last one is kept.
+    pub fn from_features(
+        fc: &FeatureCollection,
+        query_type: &QueryType,
+        parameters: &HashMap<String, ParameterNames>,
+        crs: &Crs,
+    ) -> Coverage {
+        let mut observations: Vec<Observation> = fc.features.iter().filter_map(locate).collect();
+
+        // parameters
+        let keys: BTreeSet<&String> = observations
+            .iter()
+            .filter_map(|o| o.properties)
+            .flat_map(|p| p.keys())
+            .filter(|k| {
+                if parameters.is_empty() {
+                    k.as_str() != "datetime"
+                        && observations
+                            .iter()
+                            .filter_map(|o| o.properties.and_then(|p| p.get(*k)))
+                            .all(|v| v.is_number() || v.is_null())
+                } else {
+                    parameters.contains_key(*k)
+                }
+            })
+            .collect();
+
+        let parameters: Map<String, Value> = keys
+            .iter()
+            .map(|k| {
+                let parameter = match parameters.get(*k) {
+                    Some(p) => serde_json::to_value(p).unwrap(),
+                    None => json!({
+                        "type": "Parameter",
+                        "observedProperty": { "label": { "en": k } }
+                    }),
+                };
+                (k.to_string(), parameter)
+            })
+            .collect();
+
+        // domain
+        let mut times: Vec<String> = observations
+            .iter()
+            .filter_map(|o| o.t.to_owned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let has_time = !times.is_empty();
+
+        let mut positions: Vec<(f64, f64)> = Vec::new();
+        for o in &observations {
+            if !positions.contains(&(o.x, o.y)) {
+                positions.push((o.x, o.y));
+            }
+        }
+
+        let xs = unique(positions.iter().map(|p| p.0));
+        let ys = unique(positions.iter().map(|p| p.1));
+
+        let domain_type = match query_type {
+            QueryType::Trajectory | QueryType::Corridor => DomainType::Trajectory,
+            QueryType::Area | QueryType::Cube | QueryType::Items
+                if xs.len() > 1 && ys.len() > 1 && xs.len() * ys.len() == positions.len() =>
+            {
+                DomainType::Grid
+            }
+            _ if positions.len() == 1 && has_time => DomainType::PointSeries,
+            _ if positions.len() == 1 => DomainType::Point,
+            _ if has_time => DomainType::MultiPointSeries,
+            _ => DomainType::MultiPoint,
+        };
+
+        let mut axes = Map::new();
+        let mut referencing = vec![json!({
+            "coordinates": ["x", "y"],
+            "system": {
+                "type": if crs.as_srid() == 4326 { "GeographicCRS" } else { "ProjectedCRS" },
+                "id": crs.to_string()
+            }
+        })];
+        if has_time {
+            referencing.push(json!({
+                "coordinates": ["t"],
+                "system": { "type": "TemporalRS", "calendar": "Gregorian" }
+            }));
+        }
+
+        let (axis_names, shape): (Vec<&str>, Vec<usize>) = match domain_type {
+            DomainType::Trajectory => {
+                observations.sort_by(|a, b| a.t.cmp(&b.t));
+                let mut coordinates = vec!["x", "y"];
+                if has_time {
+                    coordinates.insert(0, "t");
+                }
+                let has_z = observations.iter().all(|o| o.z.is_some());
+                if has_z {
+                    coordinates.push("z");
+                }
+                let values: Vec<Value> = observations
+                    .iter()
+                    .map(|o| {
+                        let mut tuple = vec![json!(o.x), json!(o.y)];
+                        if has_time {
+                            tuple.insert(0, json!(o.t));
+                        }
+                        if has_z {
+                            tuple.push(json!(o.z));
+                        }
+                        Value::Array(tuple)
+                    })
+                    .collect();
+                axes.insert(
+                    "composite".to_string(),
+                    json!({ "dataType": "tuple", "coordinates": coordinates, "values": values }),
+                );
+                // time is part of the composite axis
+                times.clear();
+                (vec!["composite"], vec![observations.len()])
+            }
+            DomainType::Grid => {
+                axes.insert("x".to_string(), json!({ "values": xs }));
+                axes.insert("y".to_string(), json!({ "values": ys }));
+                if has_time {
+                    (vec!["t", "y", "x"], vec![times.len(), ys.len(), xs.len()])
+                } else {
+                    (vec!["y", "x"], vec![ys.len(), xs.len()])
+                }
+            }
+            DomainType::PointSeries | DomainType::Point => {
+                axes.insert("x".to_string(), json!({ "values": [positions[0].0] }));
+                axes.insert("y".to_string(), json!({ "values": [positions[0].1] }));
+                if has_time {
+                    (vec!["t"], vec![times.len()])
+                } else {
+                    (vec![], vec![])
+                }
+            }
+            _ => {
+                let values: Vec<Value> = positions.iter().map(|p| json!([p.0, p.1])).collect();
+                axes.insert(
+                    "composite".to_string(),
+                    json!({ "dataType": "tuple", "coordinates": ["x", "y"], "values": values }),
+                );
+                if has_time {
+                    (vec!["t", "composite"], vec![times.len(), positions.len()])
+                } else {
+                    (vec!["composite"], vec![positions.len()])
+                }
+            }
+        };
+
+        // index of the i-th observation in the (row-major) range values
+        let index = |i: usize, o: &Observation| -> usize {
+            let t = time_index(&times, &o.t);
+            match domain_type {
+                DomainType::Trajectory => i,
+                DomainType::Grid => {
+                    let x = xs.iter().position(|x| *x == o.x).unwrap();
+                    let y = ys.iter().position(|y| *y == o.y).unwrap();
+                    (t * ys.len() + y) * xs.len() + x
+                }
+                DomainType::PointSeries | DomainType::Point => t,
+                _ => {
+                    let p = positions.iter().position(|p| *p == (o.x, o.y)).unwrap();
+                    t * positions.len() + p
+                }
+            }
+        };
+
+        if !times.is_empty() {
+            axes.insert("t".to_string(), json!({ "values": times }));
+        }
+
+        // ranges
+        let size = shape.iter().product::<usize>();
+        let ranges: Map<String, Value> = keys
+            .iter()
+            .map(|k| {
+                let mut values = vec![Value::Null; size];
+                let mut counts = vec![0; size];
+                for (i, o) in observations.iter().enumerate() {
+                    if let Some(v) = o.properties.and_then(|p| p.get(*k)) {
+                        if v.is_null() {
+                            continue;
+                        }
+                        let j = index(i, o);
+                        values[j] = aggregate(&values[j], counts[j], v);
+                        counts[j] += 1;
+                    }
+                }
+
+                let data_type = if values.iter().all(|v| v.is_i64() || v.is_null()) {
+                    "integer"
+                } else if values.iter().all(|v| v.is_number() || v.is_null()) {
+                    "float"
+                } else {
+                    "string"
+                };
+
+                let range = json!({
+                    "type": "NdArray",
+                    "dataType": data_type,
+                    "axisNames": axis_names,
+                    "shape": shape,
+                    "values": values
+                });
+                (k.to_string(), range)
+            })
+            .collect();
+
+        Coverage {
+            r#type: CoverageType::Coverage,
+            domain: Domain {
+                r#type: "Domain".to_string(),
+                domain_type: Some(domain_type),
+                axes,
+                referencing,
+            },
+            parameters,
+            ranges,
+        }
+    }
+}
+
+fn locate(feature: &Feature) -> Option<Observation<'_>> {
+    let (x, y, z) = center(&feature.geometry)?;
+
+    let t = feature
+        .properties
+        .as_ref()
+        .and_then(|p| p.get("datetime"))
+        .and_then(|t| t.as_str())
+        .map(ToString::to_string);
+
+    Some(Observation {
+        x,
+        y,
+        z,
+        t,
+        properties: feature.properties.as_ref(),
+    })
+}
+
+/// Position of a point or the center of the bounding box of other geometries
+fn center(geometry: &Geometry) -> Option<(f64, f64, Option<f64>)> {
+    let mut positions = Vec::new();
+    collect_positions(&serde_json::to_value(geometry).ok()?, &mut positions);
+
+    let dims = positions.iter().map(|p| p.len()).min()?;
+    if dims < 2 {
+        return None;
+    }
+
+    let mid = |d: usize| {
+        let values = positions.iter().map(|p| p[d]);
+        let min = values.clone().fold(f64::INFINITY, f64::min);
+        let max = values.fold(f64::NEG_INFINITY, f64::max);
+        (min + max) / 2.0
+    };
+
+    Some((mid(0), mid(1), (dims > 2).then(|| mid(2))))
+}
+
+fn collect_positions(value: &Value, positions: &mut Vec<Vec<f64>>) {
+    match value {
+        Value::Array(a) if !a.is_empty() && a.iter().all(Value::is_number) => {
+            positions.push(a.iter().filter_map(Value::as_f64).collect())
+        }
+        Value::Array(a) => a.iter().for_each(|v| collect_positions(v, positions)),
+        Value::Object(o) => o.values().for_each(|v| collect_positions(v, positions)),
+        _ => {}
+    }
+}
+
+/// Aggregates a value with the mean of `n` values, non-numeric values replace
+/// the aggregate
+fn aggregate(mean: &Value, n: usize, value: &Value) -> Value {
+    match (mean.as_f64(), value.as_f64()) {
+        (Some(mean), Some(v)) if n > 0 => json!((mean * n as f64 + v) / (n + 1) as f64),
+        _ => value.to_owned(),
+    }
+}
+
+fn unique(values: impl Iterator<Item = f64>) -> Vec<f64> {
+    let mut values: Vec<f64> = values.collect();
+    values.sort_by(|a, b| a.total_cmp(b));
+    values.dedup();
+    values
+}
+
+fn time_index(times: &[String], t: &Option<String>) -> usize {
+    t.as_ref()
+        .and_then(|t| times.iter().position(|time| time == t))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use crate::{
+        common::Crs,
+        coverage::{Coverage, DomainType},
+        edr::QueryType,
+        features::FeatureCollection,
+    };
+
+    fn point(x: f64, y: f64, t: &str, value: f64) -> serde_json::Value {
+        json!({
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [x, y] },
+            "properties": { "datetime": t, "temperature": value, "name": "a" }
+        })
+    }
+
+    #[test]
+    fn point_series() {
+        let fc: FeatureCollection = serde_json::from_value(json!({
+            "type": "FeatureCollection",
+            "features": [
+                point(7.0, 46.0, "2022-01-01T01:00:00Z", 2.5),
+                point(7.0, 46.0, "2022-01-01T00:00:00Z", 1.5),
+            ]
+        }))
+        .unwrap();
+
+        let coverage =
+            Coverage::from_features(&fc, &QueryType::Position, &HashMap::new(), &Crs::default());
+
+        assert_eq!(coverage.domain.domain_type, Some(DomainType::PointSeries));
+        assert_eq!(coverage.parameters.len(), 1);
+        assert_eq!(coverage.ranges["temperature"]["values"], json!([1.5, 2.5]));
+        assert_eq!(coverage.ranges["temperature"]["axisNames"], json!(["t"]));
+    }
+
+    #[test]
+    fn coinciding() {
+        let fc: FeatureCollection = serde_json::from_value(json!({
+            "type": "FeatureCollection",
+            "features": [
+                point(7.0, 46.0, "2022-01-01T00:00:00Z", 1.0),
+                point(7.0, 46.0, "2022-01-01T00:00:00Z", 2.0),
+                point(7.0, 46.0, "2022-01-01T00:00:00Z", 6.0),
+                point(8.0, 46.0, "2022-01-01T00:00:00Z", 4.0),
+            ]
+        }))
+        .unwrap();
+
+        let coverage =
+            Coverage::from_features(&fc, &QueryType::Area, &HashMap::new(), &Crs::default());
+
+        assert_eq!(
+            coverage.domain.domain_type,
+            Some(DomainType::MultiPointSeries)
+        );
+        assert_eq!(coverage.ranges["temperature"]["values"], json!([3.0, 4.0]));
+    }
+
+    #[test]
+    fn grid() {
+        let fc: FeatureCollection = serde_json::from_value(json!({
+            "type": "FeatureCollection",
+            "features": [
+                point(1.0, 1.0, "2022-01-01T00:00:00Z", 1.0),
+                point(2.0, 1.0, "2022-01-01T00:00:00Z", 2.0),
+                point(1.0, 2.0, "2022-01-01T00:00:00Z", 3.0),
+                point(2.0, 2.0, "2022-01-01T00:00:00Z", 4.0),
+            ]
+        }))
+        .unwrap();
+
+        let coverage =
+            Coverage::from_features(&fc, &QueryType::Area, &HashMap::new(), &Crs::default());
+
+        assert_eq!(coverage.domain.domain_type, Some(DomainType::Grid));
+        assert_eq!(coverage.ranges["temperature"]["shape"], json!([1, 2, 2]));
+        assert_eq!(
+            coverage.ranges["temperature"]["values"],
+            json!([1.0, 2.0, 3.0, 4.0])
+        );
+    }
+}
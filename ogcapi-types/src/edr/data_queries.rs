@@ -1,39 +1,90 @@
 use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
 
 use crate::common::Link;
 
 use super::QueryType;
 
 /// Detailed information relevant to individual query types
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone)]
 pub struct DataQueries {
-    pub position: Option<PositionLink>,
-    // pub radius: Option<RadiusLink>,
-    // pub area: Option<AreaLink>,
-    // pub cube: Option<CubeLink>,
-    // pub trajectory: Option<TrajectoryLink>,
-    // pub corridor: Option<CorridorLink>,
-    // pub location: Option<LocationLink>,
-    // pub item: Option<ItemLink>,
+    pub position: Option<DataQuery>,
+    pub radius: Option<DataQuery>,
+    pub area: Option<DataQuery>,
+    pub cube: Option<DataQuery>,
+    pub trajectory: Option<DataQuery>,
+    pub corridor: Option<DataQuery>,
+    pub items: Option<DataQuery>,
+    pub locations: Option<DataQuery>,
+    pub instances: Option<DataQuery>,
+}
+
+impl DataQueries {
+    /// Sets the data query of a query type
+    pub fn insert(&mut self, query_type: &QueryType, query: DataQuery) {
+        let entry = match query_type {
+            QueryType::Position => &mut self.position,
+            QueryType::Radius => &mut self.radius,
+            QueryType::Area => &mut self.area,
+            QueryType::Cube => &mut self.cube,
+            QueryType::Trajectory => &mut self.trajectory,
+            QueryType::Corridor => &mut self.corridor,
+            QueryType::Items => &mut self.items,
+            QueryType::Locations => &mut self.locations,
+        };
+        *entry = Some(query);
+    }
 }
 
+/// Link to the endpoint of a data query
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
-pub struct PositionLink {
+pub struct DataQuery {
+    pub link: DataQueryLink,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct DataQueryLink {
     #[serde(flatten)]
     pub link: Link,
-    pub variables: PositionDataQuery,
+    pub variables: Variables,
 }
 
 /// Property to contain any extra metadata information that is specific
 /// to an individual data queries
+#[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
-pub struct PositionDataQuery {
+pub struct Variables {
     pub title: Option<String>,
     pub description: Option<String>,
     pub query_type: QueryType,
+    #[serde(default)]
     pub output_formats: Vec<String>,
     pub default_output_format: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub crs_details: Vec<CrsObject>,
+    /// Distance units supported by radius queries
+    pub within_units: Option<Vec<String>>,
+    /// Width units supported by corridor queries
+    pub width_units: Option<Vec<String>>,
+    /// Height units supported by corridor queries
+    pub height_units: Option<Vec<String>>,
+}
+
+impl Variables {
+    pub fn new(query_type: QueryType) -> Self {
+        Variables {
+            title: None,
+            description: None,
+            query_type,
+            output_formats: Vec::new(),
+            default_output_format: None,
+            crs_details: Vec::new(),
+            within_units: None,
+            width_units: None,
+            height_units: None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -44,3 +95,43 @@ pub struct CrsObject {
     /// Well Known text description of the coordinate reference system
     pub wkt: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        common::Link,
+        edr::{DataQueries, DataQuery, DataQueryLink, QueryType, Variables},
+    };
+
+    #[test]
+    fn serialize_data_queries() {
+        let mut data_queries = DataQueries::default();
+        data_queries.insert(
+            &QueryType::Position,
+            DataQuery {
+                link: DataQueryLink {
+                    link: Link::new("https://example.org/collections/obs/position", "data"),
+                    variables: Variables::new(QueryType::Position),
+                },
+            },
+        );
+
+        assert_eq!(
+            serde_json::to_value(&data_queries).unwrap(),
+            json!({
+                "position": {
+                    "link": {
+                        "href": "https://example.org/collections/obs/position",
+                        "rel": "data",
+                        "variables": {
+                            "query_type": "position",
+                            "output_formats": []
+                        }
+                    }
+                }
+            })
+        );
+    }
+}
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Collection, Links};
+
+/// Instances (e.g. model runs) of a collection
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+pub struct Instances {
+    pub links: Links,
+    pub instances: Vec<Collection>,
+}
@@ -1,13 +1,18 @@
+mod covjson;
 mod data_queries;
+mod instances;
 mod observed_property;
 mod parameter_names;
 mod query;
 mod units;
 
-pub use data_queries::DataQueries;
-pub use observed_property::ObservedPropertyCollection;
-pub use parameter_names::ParameterNames;
-pub use query::{Query, QueryType};
+#[doc(inline)]
+pub use crate::common::wkt::{Dimensions, Geometry, Position, Wkt};
+pub use data_queries::{CrsObject, DataQueries, DataQuery, DataQueryLink, Variables};
+pub use instances::Instances;
+pub use observed_property::{Label, ObservedPropertyCollection};
+pub use parameter_names::{DataType, ParameterNames};
+pub use query::{Aggregation, Coords, Levels, Query, QueryType};
 pub use units::Units;
 
 use serde::{Deserialize, Serialize};
@@ -5,13 +5,13 @@ use serde::{Deserialize, Serialize};
 /// Description of the property
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 #[serde(rename_all = "camelCase")]
-
 pub struct ObservedPropertyCollection {
     /// URI linking to an external registry which contains the definitive
     /// definition of the observed property
     pub id: Option<String>,
     pub label: Label,
     pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub categories: Vec<Category>,
 }
 
@@ -25,6 +25,7 @@ pub struct Category {
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(untagged)]
 pub enum Label {
     String(String),
     Object {
@@ -1,7 +1,12 @@
+use std::{fmt, str::FromStr};
+
 use serde::{Deserialize, Serialize};
-use serde_with::{serde_as, DisplayFromStr};
+use serde_with::{serde_as, CommaSeparator, DisplayFromStr, StringWithSeparator};
 
-use crate::common::{Crs, Datetime};
+use crate::common::{
+    wkt::{Geometry, Wkt},
+    Bbox, Crs, Datetime,
+};
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 #[serde(rename_all = "lowercase")]
@@ -12,18 +17,33 @@ pub enum QueryType {
     Cube,
     Trajectory,
     Corridor,
-    // Items,
+    Items,
     Locations,
     // Instances,
 }
 
+impl fmt::Display for QueryType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryType::Position => write!(f, "position"),
+            QueryType::Radius => write!(f, "radius"),
+            QueryType::Area => write!(f, "area"),
+            QueryType::Cube => write!(f, "cube"),
+            QueryType::Trajectory => write!(f, "trajectory"),
+            QueryType::Corridor => write!(f, "corridor"),
+            QueryType::Items => write!(f, "items"),
+            QueryType::Locations => write!(f, "locations"),
+        }
+    }
+}
+
 #[serde_as]
-#[derive(Serialize, Deserialize, Default, Debug)]
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
 pub struct Query {
     /// Well Known Text (WKT) of representation geometry. The representation
     /// type will depend on the [QueryType] of the API.
-    #[serde(alias = "bbox")]
+    #[serde(default, alias = "bbox")]
     pub coords: String,
     #[serde(default)]
     #[serde_as(as = "Option<DisplayFromStr>")]
@@ -33,7 +53,10 @@ pub struct Query {
     #[serde_as(as = "DisplayFromStr")]
     pub crs: Crs,
     pub f: Option<String>,
-    pub z: Option<Vec<String>>,
+    /// Vertical level(s) to return data for
+    #[serde(default)]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub z: Option<Levels>,
     pub within: Option<String>,
     pub within_units: Option<String>,
     pub resolution_x: Option<usize>,
@@ -42,4 +65,244 @@ pub struct Query {
     pub height_units: Option<String>,
     pub corridor_width: Option<String>,
     pub width_units: Option<String>,
+    /// Time window (ISO 8601 duration, e.g. `PT1H`) to aggregate
+    /// parameter values over
+    pub interval: Option<String>,
+    /// Aggregation functions applied per time window, defaults to `mean`
+    #[serde(default)]
+    #[serde_as(as = "Option<StringWithSeparator<CommaSeparator, Aggregation>>")]
+    pub aggregate: Option<Vec<Aggregation>>,
+    /// Id of the named location for [QueryType::Locations] queries (path parameter)
+    #[serde(skip)]
+    pub location_id: Option<String>,
+    /// Id of the collection instance to query (path parameter)
+    #[serde(skip)]
+    pub instance_id: Option<String>,
+}
+
+impl Query {
+    /// Parses and validates the `coords` for a query type
+    ///
+    /// Position and radius queries require a (multi) point, area queries a
+    /// (multi) polygon, trajectory and corridor queries a line string and cube
+    /// queries a bounding box. Items queries may be restricted by a bounding box.
+    pub fn geometry(&self, query_type: &QueryType) -> Result<Coords, String> {
+        let coords = self.coords.trim();
+
+        if coords.is_empty() {
+            return match query_type {
+                QueryType::Items | QueryType::Locations => Ok(Coords::None),
+                query_type => Err(format!("Missing `coords` for {query_type} query")),
+            };
+        }
+
+        match query_type {
+            QueryType::Cube | QueryType::Items => coords
+                .parse::<Bbox>()
+                .map(Coords::Bbox)
+                .map_err(|e| format!("Invalid bbox `{coords}`: {e}")),
+            QueryType::Locations => Ok(Coords::None),
+            query_type => {
+                let wkt: Wkt = coords
+                    .parse()
+                    .map_err(|e| format!("Invalid WKT `{coords}`: {e}"))?;
+
+                let expected = match (query_type, &wkt.geometry) {
+                    (
+                        QueryType::Position | QueryType::Radius,
+                        Geometry::Point(_) | Geometry::MultiPoint(_),
+                    )
+                    | (QueryType::Area, Geometry::Polygon(_) | Geometry::MultiPolygon(_))
+                    | (QueryType::Trajectory | QueryType::Corridor, Geometry::LineString(_)) => {
+                        return Ok(Coords::Wkt(wkt))
+                    }
+                    (QueryType::Position | QueryType::Radius, _) => "POINT or MULTIPOINT",
+                    (QueryType::Area, _) => "POLYGON or MULTIPOLYGON",
+                    _ => "LINESTRING[Z|M|ZM]",
+                };
+
+                Err(format!(
+                    "Invalid geometry type `{}` for {query_type} query, expected {expected}",
+                    wkt.geometry.name()
+                ))
+            }
+        }
+    }
+}
+
+/// Parsed `coords` of a query
+#[derive(Debug, Clone, PartialEq)]
+pub enum Coords {
+    Wkt(Wkt),
+    Bbox(Bbox),
+    None,
+}
+
+/// Maximum number of recurrences of [Levels::Recurring]
+pub const MAX_RECURRENCES: usize = 1000;
+
+/// Vertical level selection of the `z` query parameter
+#[derive(Debug, Clone, PartialEq)]
+pub enum Levels {
+    /// List of levels, e.g. `850,500`
+    List(Vec<f64>),
+    /// Interval of levels, e.g. `100/500`
+    Interval(f64, f64),
+    /// Recurring levels, e.g. `R5/100/50` for five levels starting at 100 in steps of 50
+    Recurring { count: usize, start: f64, step: f64 },
+}
+
+impl Levels {
+    /// Explicit levels, `None` for intervals
+    pub fn values(&self) -> Option<Vec<f64>> {
+        match self {
+            Levels::List(values) => Some(values.to_owned()),
+            Levels::Interval(..) => None,
+            Levels::Recurring { count, start, step } => {
+                Some((0..*count).map(|i| start + i as f64 * step).collect())
+            }
+        }
+    }
+}
+
+impl FromStr for Levels {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |s: &str| {
+            s.trim()
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid level `{s}`"))
+        };
+
+        let parts: Vec<&str> = s.split('/').collect();
+        match parts.as_slice() {
+            [count, start, step] if count.starts_with('R') => {
+                let count = count[1..]
+                    .parse()
+                    .map_err(|_| format!("Invalid number of recurrences `{count}`"))?;
+                if count > MAX_RECURRENCES {
+                    return Err(format!(
+                        "Number of recurrences must not exceed {MAX_RECURRENCES}"
+                    ));
+                }
+                Ok(Levels::Recurring {
+                    count,
+                    start: number(start)?,
+                    step: number(step)?,
+                })
+            }
+            [min, max] => Ok(Levels::Interval(number(min)?, number(max)?)),
+            [list] => Ok(Levels::List(
+                list.split(',').map(number).collect::<Result<_, _>>()?,
+            )),
+            _ => Err(format!("Invalid levels `{s}`")),
+        }
+    }
+}
+
+impl fmt::Display for Levels {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Levels::List(values) => write!(
+                f,
+                "{}",
+                values
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            Levels::Interval(min, max) => write!(f, "{min}/{max}"),
+            Levels::Recurring { count, start, step } => write!(f, "R{count}/{start}/{step}"),
+        }
+    }
+}
+
+/// Aggregation of parameter values per time window
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Aggregation {
+    Min,
+    Max,
+    Mean,
+    Count,
+}
+
+impl FromStr for Aggregation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "min" => Ok(Aggregation::Min),
+            "max" => Ok(Aggregation::Max),
+            "mean" | "avg" => Ok(Aggregation::Mean),
+            "count" => Ok(Aggregation::Count),
+            _ => Err(format!("Unknown aggregation `{s}`")),
+        }
+    }
+}
+
+impl fmt::Display for Aggregation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Aggregation::Min => write!(f, "min"),
+            Aggregation::Max => write!(f, "max"),
+            Aggregation::Mean => write!(f, "mean"),
+            Aggregation::Count => write!(f, "count"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Coords, Levels, Query, QueryType};
+
+    #[test]
+    fn levels() {
+        assert_eq!(
+            "850,500".parse::<Levels>().unwrap(),
+            Levels::List(vec![850.0, 500.0])
+        );
+        assert_eq!(
+            "100/500".parse::<Levels>().unwrap(),
+            Levels::Interval(100.0, 500.0)
+        );
+
+        let levels: Levels = "R3/100/50".parse().unwrap();
+        assert_eq!(levels.values(), Some(vec![100.0, 150.0, 200.0]));
+        assert_eq!(levels.to_string(), "R3/100/50");
+
+        assert!("R1000/0/1".parse::<Levels>().is_ok());
+        assert!("R1001/0/1".parse::<Levels>().is_err());
+        assert!("R3/100".parse::<Levels>().is_err());
+        assert!("abc".parse::<Levels>().is_err());
+    }
+
+    #[test]
+    fn geometry() {
+        let query = |coords: &str| Query {
+            coords: coords.to_string(),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            query("POINT(10 50)").geometry(&QueryType::Position),
+            Ok(Coords::Wkt(_))
+        ));
+        assert!(matches!(
+            query("LINESTRINGM(10 50 1, 11 51 2)").geometry(&QueryType::Trajectory),
+            Ok(Coords::Wkt(_))
+        ));
+        assert!(matches!(
+            query("0,0,10,10").geometry(&QueryType::Cube),
+            Ok(Coords::Bbox(_))
+        ));
+        assert_eq!(query("").geometry(&QueryType::Items), Ok(Coords::None));
+
+        assert!(query("").geometry(&QueryType::Position).is_err());
+        assert!(query("POINT(10 50)").geometry(&QueryType::Area).is_err());
+        assert!(query("POINT(10 50").geometry(&QueryType::Position).is_err());
+        assert!(query("0,0,10").geometry(&QueryType::Cube).is_err());
+    }
 }
@@ -0,0 +1,566 @@
+//! Common Query Language (CQL2)
+//!
+//! Expressions are modeled after the JSON encoding (`cql2-json`), the text
+//! encoding (`cql2-text`) is parsed into the same representation.
+
+use std::{fmt, iter::Peekable, str::FromStr};
+
+use chrono::{DateTime, NaiveDate};
+use geojson::Geometry;
+use serde::{Deserialize, Serialize};
+
+use crate::common::wkt::{Wkt, GEOMETRY_TYPES};
+
+/// Operators supported in `op` of [Expr::Operation]
+pub const OPERATORS: [&str; 30] = [
+    // logical
+    "and",
+    "or",
+    "not",
+    // comparison
+    "=",
+    "<>",
+    "<",
+    "<=",
+    ">",
+    ">=",
+    "like",
+    "between",
+    "in",
+    "isNull",
+    // spatial
+    "s_intersects",
+    "s_equals",
+    "s_disjoint",
+    "s_touches",
+    "s_within",
+    "s_overlaps",
+    "s_crosses",
+    "s_contains",
+    // temporal
+    "t_after",
+    "t_before",
+    "t_contains",
+    "t_disjoint",
+    "t_during",
+    "t_equals",
+    "t_intersects",
+    // array
+    "a_contains",
+    "a_overlaps",
+];
+
+/// Maximum nesting depth of expressions
+pub const MAX_DEPTH: usize = 64;
+
+/// CQL2 expression
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Expr {
+    Operation { op: String, args: Vec<Expr> },
+    Property { property: String },
+    Timestamp { timestamp: String },
+    Date { date: String },
+    Interval { interval: Vec<Expr> },
+    BBox { bbox: Vec<f64> },
+    Geometry(Geometry),
+    Array(Vec<Expr>),
+    Bool(bool),
+    Number(f64),
+    Text(String),
+}
+
+impl Expr {
+    pub fn op(op: &str, args: Vec<Expr>) -> Self {
+        Expr::Operation {
+            op: op.to_string(),
+            args,
+        }
+    }
+
+    pub fn property(name: &str) -> Self {
+        Expr::Property {
+            property: name.to_string(),
+        }
+    }
+
+    /// Whether the expression nests operations, arrays or intervals more
+    /// than `max` levels deep
+    pub fn deeper_than(&self, max: usize) -> bool {
+        let children = match self {
+            Expr::Operation { args, .. } => args,
+            Expr::Interval { interval } => interval,
+            Expr::Array(items) => items,
+            _ => return false,
+        };
+        !children.is_empty() && (max == 0 || children.iter().any(|c| c.deeper_than(max - 1)))
+    }
+
+    /// Checks the nesting depth, operators, their number of arguments and
+    /// temporal literals
+    pub fn validate(&self) -> Result<(), String> {
+        if self.deeper_than(MAX_DEPTH) {
+            return Err(format!(
+                "Expressions must not nest more than {MAX_DEPTH} levels"
+            ));
+        }
+        self.check()
+    }
+
+    fn check(&self) -> Result<(), String> {
+        match self {
+            Expr::Operation { op, args } => {
+                let arity = match op.as_str() {
+                    "and" | "or" => 2..usize::MAX,
+                    "not" | "isNull" => 1..2,
+                    "between" => 3..4,
+                    op if OPERATORS.contains(&op) => 2..3,
+                    op => return Err(format!("Unsupported operator `{op}`")),
+                };
+                if !arity.contains(&args.len()) {
+                    return Err(format!(
+                        "Invalid number of arguments ({}) for operator `{op}`",
+                        args.len()
+                    ));
+                }
+                if op == "in" && !matches!(args[1], Expr::Array(_)) {
+                    return Err("Operator `in` requires a list".to_string());
+                }
+                args.iter().try_for_each(Expr::check)
+            }
+            Expr::Timestamp { timestamp } => DateTime::parse_from_rfc3339(timestamp)
+                .map(|_| ())
+                .map_err(|_| format!("Invalid timestamp `{timestamp}`")),
+            Expr::Date { date } => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map(|_| ())
+                .map_err(|_| format!("Invalid date `{date}`")),
+            Expr::Interval { interval } => {
+                if interval.len() != 2 {
+                    return Err("Intervals require a start and an end".to_string());
+                }
+                interval.iter().try_for_each(|bound| match bound {
+                    Expr::Text(t) if t == ".." => Ok(()),
+                    Expr::Text(t) => DateTime::parse_from_rfc3339(t)
+                        .map(|_| ())
+                        .or_else(|_| NaiveDate::parse_from_str(t, "%Y-%m-%d").map(|_| ()))
+                        .map_err(|_| format!("Invalid interval bound `{t}`")),
+                    Expr::Timestamp { .. } | Expr::Date { .. } | Expr::Property { .. } => {
+                        bound.check()
+                    }
+                    _ => Err("Invalid interval bound".to_string()),
+                })
+            }
+            Expr::BBox { bbox } => match bbox.len() {
+                4 | 6 => Ok(()),
+                _ => Err("Bounding boxes require 4 or 6 numbers".to_string()),
+            },
+            Expr::Array(items) => items.iter().try_for_each(Expr::check),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = String;
+
+    /// Parses the text encoding, e.g. `eo:cloud_cover < 10 AND S_INTERSECTS(geometry, POINT(7 46))`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?.into_iter().peekable(),
+            depth: 0,
+        };
+
+        let expr = parser.or()?;
+        if let Some(token) = parser.tokens.next() {
+            return Err(format!("Unexpected {token}"));
+        }
+        expr.validate()?;
+
+        Ok(expr)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Keyword or unquoted property name
+    Ident(String),
+    /// Double quoted property name
+    Quoted(String),
+    Text(String),
+    Number(f64),
+    Wkt(String),
+    Operator(String),
+    Open,
+    Close,
+    Comma,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(ident) | Token::Operator(ident) => write!(f, "`{ident}`"),
+            Token::Quoted(ident) => write!(f, "`\"{ident}\"`"),
+            Token::Text(text) => write!(f, "`'{text}'`"),
+            Token::Number(number) => write!(f, "`{number}`"),
+            Token::Wkt(wkt) => write!(f, "`{wkt}`"),
+            Token::Open => write!(f, "`(`"),
+            Token::Close => write!(f, "`)`"),
+            Token::Comma => write!(f, "`,`"),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            ',' => tokens.push(Token::Comma),
+            c if c.is_whitespace() => {}
+            '=' => tokens.push(Token::Operator("=".to_string())),
+            '<' | '>' => {
+                let mut op = c.to_string();
+                if let Some((_, c)) = chars.next_if(|(_, n)| *n == '=' || (c == '<' && *n == '>')) {
+                    op.push(c);
+                }
+                tokens.push(Token::Operator(op));
+            }
+            '\'' | '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        // quotes are escaped by doubling
+                        Some((_, q)) if q == c => match chars.next_if(|(_, n)| *n == c) {
+                            Some(_) => text.push(c),
+                            None => break,
+                        },
+                        Some((_, n)) => text.push(n),
+                        None => return Err(format!("Unterminated literal `{c}{text}`")),
+                    }
+                }
+                tokens.push(if c == '\'' {
+                    Token::Text(text)
+                } else {
+                    Token::Quoted(text)
+                });
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+                let mut end = start + c.len_utf8();
+                while let Some((i, n)) = chars
+                    .next_if(|(_, n)| n.is_ascii_digit() || ['.', 'e', 'E', '-', '+'].contains(n))
+                {
+                    end = i + n.len_utf8();
+                }
+                let number = &s[start..end];
+                tokens.push(Token::Number(
+                    number
+                        .parse()
+                        .map_err(|_| format!("Invalid number `{number}`"))?,
+                ));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = start + c.len_utf8();
+                while let Some((i, n)) =
+                    chars.next_if(|(_, n)| n.is_alphanumeric() || ['_', ':', '.'].contains(n))
+                {
+                    end = i + n.len_utf8();
+                }
+                let ident = &s[start..end];
+
+                // geometry literals are captured up to the matching parenthesis
+                let upper = ident.to_uppercase();
+                let is_geometry = GEOMETRY_TYPES.iter().any(|t| {
+                    matches!(upper.strip_prefix(t), Some(d) if ["", "Z", "M", "ZM"].contains(&d))
+                });
+                if is_geometry {
+                    let mut depth = 0;
+                    let mut end = end;
+                    for (i, n) in chars.by_ref() {
+                        end = i + n.len_utf8();
+                        match n {
+                            '(' => depth += 1,
+                            ')' => depth -= 1,
+                            _ => {}
+                        }
+                        if depth == 0 && n == ')' {
+                            break;
+                        }
+                    }
+                    tokens.push(Token::Wkt(s[start..end].to_string()));
+                } else {
+                    tokens.push(Token::Ident(ident.to_string()));
+                }
+            }
+            c => return Err(format!("Unexpected character `{c}`")),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<I: Iterator<Item = Token>> {
+    tokens: Peekable<I>,
+    /// Current nesting depth
+    depth: usize,
+}
+
+impl<I: Iterator<Item = Token>> Parser<I> {
+    /// Consumes the next token if it is the (case insensitive) keyword
+    fn keyword(&mut self, keyword: &str) -> bool {
+        self.tokens
+            .next_if(|t| matches!(t, Token::Ident(i) if i.eq_ignore_ascii_case(keyword)))
+            .is_some()
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.tokens.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("Expected {expected}, found {token}")),
+            None => Err(format!("Expected {expected}, found end of input")),
+        }
+    }
+
+    /// Comma separated scalars in parentheses
+    fn list(&mut self) -> Result<Vec<Expr>, String> {
+        self.expect(Token::Open)?;
+        let mut items = vec![self.scalar()?];
+        while self.tokens.next_if_eq(&Token::Comma).is_some() {
+            items.push(self.scalar()?);
+        }
+        self.expect(Token::Close)?;
+        Ok(items)
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut args = vec![self.and()?];
+        while self.keyword("OR") {
+            args.push(self.and()?);
+        }
+        Ok(match args.len() {
+            1 => args.remove(0),
+            _ => Expr::op("or", args),
+        })
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut args = vec![self.not()?];
+        while self.keyword("AND") {
+            args.push(self.not()?);
+        }
+        Ok(match args.len() {
+            1 => args.remove(0),
+            _ => Expr::op("and", args),
+        })
+    }
+
+    /// Negations and parentheses are the only (unbounded) recursion
+    fn not(&mut self) -> Result<Expr, String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(format!(
+                "Expressions must not nest more than {MAX_DEPTH} levels"
+            ));
+        }
+        let expr = if self.keyword("NOT") {
+            Expr::op("not", vec![self.not()?])
+        } else {
+            self.predicate()?
+        };
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    fn predicate(&mut self) -> Result<Expr, String> {
+        if self.tokens.next_if_eq(&Token::Open).is_some() {
+            let expr = self.or()?;
+            self.expect(Token::Close)?;
+            return Ok(expr);
+        }
+
+        // spatial, temporal and array functions
+        if let Some(Token::Ident(ident)) = self.tokens.peek() {
+            let op = ident.to_lowercase();
+            if ["s_", "t_", "a_"].iter().any(|p| op.starts_with(p)) {
+                self.tokens.next();
+                return Ok(Expr::op(&op, self.list()?));
+            }
+        }
+
+        let left = self.scalar()?;
+
+        let negated = self.keyword("NOT");
+        let expr = if let Some(Token::Operator(op)) = self.tokens.peek() {
+            let op = op.to_owned();
+            self.tokens.next();
+            Expr::op(&op, vec![left, self.scalar()?])
+        } else if self.keyword("LIKE") {
+            Expr::op("like", vec![left, self.scalar()?])
+        } else if self.keyword("BETWEEN") {
+            let low = self.scalar()?;
+            if !self.keyword("AND") {
+                return Err("Expected `AND` in `BETWEEN` predicate".to_string());
+            }
+            Expr::op("between", vec![left, low, self.scalar()?])
+        } else if self.keyword("IN") {
+            Expr::op("in", vec![left, Expr::Array(self.list()?)])
+        } else if self.keyword("IS") {
+            let negated = self.keyword("NOT");
+            if !self.keyword("NULL") {
+                return Err("Expected `NULL` after `IS`".to_string());
+            }
+            let expr = Expr::op("isNull", vec![left]);
+            if negated {
+                Expr::op("not", vec![expr])
+            } else {
+                expr
+            }
+        } else if negated {
+            return Err("Expected `LIKE`, `BETWEEN` or `IN` after `NOT`".to_string());
+        } else {
+            // boolean literal or property
+            return Ok(left);
+        };
+
+        Ok(if negated {
+            Expr::op("not", vec![expr])
+        } else {
+            expr
+        })
+    }
+
+    fn scalar(&mut self) -> Result<Expr, String> {
+        match self.tokens.next() {
+            Some(Token::Text(text)) => Ok(Expr::Text(text)),
+            Some(Token::Number(number)) => Ok(Expr::Number(number)),
+            Some(Token::Quoted(property)) => Ok(Expr::Property { property }),
+            Some(Token::Wkt(wkt)) => {
+                let wkt: Wkt = wkt.parse().map_err(|e| format!("Invalid geometry: {e}"))?;
+                Ok(Expr::Geometry(wkt.into()))
+            }
+            Some(Token::Ident(ident)) => match ident.to_uppercase().as_str() {
+                "TRUE" => Ok(Expr::Bool(true)),
+                "FALSE" => Ok(Expr::Bool(false)),
+                "TIMESTAMP" => Ok(Expr::Timestamp {
+                    timestamp: self.text_argument()?,
+                }),
+                "DATE" => Ok(Expr::Date {
+                    date: self.text_argument()?,
+                }),
+                "INTERVAL" => Ok(Expr::Interval {
+                    interval: self.list()?,
+                }),
+                "BBOX" => {
+                    let bbox = self
+                        .list()?
+                        .into_iter()
+                        .map(|n| match n {
+                            Expr::Number(n) => Ok(n),
+                            _ => Err("Bounding boxes require numbers".to_string()),
+                        })
+                        .collect::<Result<_, _>>()?;
+                    Ok(Expr::BBox { bbox })
+                }
+                _ => Ok(Expr::Property { property: ident }),
+            },
+            Some(token) => Err(format!("Unexpected {token}")),
+            None => Err("Unexpected end of input".to_string()),
+        }
+    }
+
+    /// Single text argument in parentheses, e.g. of `TIMESTAMP('...')`
+    fn text_argument(&mut self) -> Result<String, String> {
+        self.expect(Token::Open)?;
+        let text = match self.tokens.next() {
+            Some(Token::Text(text)) => text,
+            _ => return Err("Expected a quoted text argument".to_string()),
+        };
+        self.expect(Token::Close)?;
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{Expr, MAX_DEPTH};
+
+    #[test]
+    fn parse_text() {
+        let expr: Expr =
+            "eo:cloud_cover < 10 AND (platform = 'sentinel-2a' OR platform IN ('l8', 'l9'))"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            serde_json::to_value(&expr).unwrap(),
+            json!({
+                "op": "and",
+                "args": [
+                    { "op": "<", "args": [{ "property": "eo:cloud_cover" }, 10.0] },
+                    {
+                        "op": "or",
+                        "args": [
+                            { "op": "=", "args": [{ "property": "platform" }, "sentinel-2a"] },
+                            { "op": "in", "args": [{ "property": "platform" }, ["l8", "l9"]] }
+                        ]
+                    }
+                ]
+            })
+        );
+
+        let expr: Expr = "S_INTERSECTS(geometry, POINT(7 46)) AND \
+            T_INTERSECTS(datetime, INTERVAL('2022-01-01T00:00:00Z', '..')) AND \
+            name NOT LIKE 'B%' AND \"id\" IS NOT NULL"
+            .parse()
+            .unwrap();
+        match expr {
+            Expr::Operation { op, args } => {
+                assert_eq!(op, "and");
+                assert_eq!(args.len(), 4);
+                assert!(matches!(&args[0], Expr::Operation { op, .. } if op == "s_intersects"));
+            }
+            _ => panic!("expected an operation"),
+        }
+
+        assert!("a = ".parse::<Expr>().is_err());
+        assert!("a == 1".parse::<Expr>().is_err());
+        assert!("x_unknown(a, b) = 1".parse::<Expr>().is_err());
+        assert!("datetime > TIMESTAMP('yesterday')".parse::<Expr>().is_err());
+        assert!("name = 'unterminated".parse::<Expr>().is_err());
+
+        let nested = |n: usize| format!("{}a = 1{}", "(".repeat(n), ")".repeat(n));
+        assert!(nested(MAX_DEPTH - 1).parse::<Expr>().is_ok());
+        assert!(nested(MAX_DEPTH).parse::<Expr>().is_err());
+        assert!(nested(100_000).parse::<Expr>().is_err());
+        assert!(format!("{}a = 1", "NOT ".repeat(MAX_DEPTH))
+            .parse::<Expr>()
+            .is_err());
+    }
+
+    #[test]
+    fn parse_json() {
+        let expr: Expr = serde_json::from_value(json!({
+            "op": "s_intersects",
+            "args": [
+                { "property": "geometry" },
+                { "type": "Point", "coordinates": [7.0, 46.0] }
+            ]
+        }))
+        .unwrap();
+        assert!(expr.validate().is_ok());
+
+        let expr: Expr =
+            serde_json::from_value(json!({ "op": "unknown", "args": [1, 2] })).unwrap();
+        assert!(expr.validate().is_err());
+
+        let mut expr = Expr::op("=", vec![Expr::property("a"), Expr::Number(1.0)]);
+        for _ in 0..MAX_DEPTH {
+            expr = Expr::op("not", vec![expr]);
+        }
+        assert!(expr.deeper_than(MAX_DEPTH));
+        assert!(expr.validate().is_err());
+    }
+}
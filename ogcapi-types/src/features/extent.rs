@@ -0,0 +1,350 @@
+#[cfg(feature = "stac")]
+use serde_json::{json, Value};
+
+use crate::common::{Bbox, Collection, Extent, SpatialExtent, TemporalExtent};
+
+use super::Feature;
+
+/// Includes an item in the extent and summaries of a collection
+///
+/// The overall bbox and interval of the extent grow to cover the item. Of the
+/// summaries (with the `stac` feature), ranges are widened and value sets
+/// extended by the respective property of the item, other summaries are left
+/// as they are.
+pub fn include_item(collection: &mut Collection, item: &Feature) {
+    let bbox = bbox(item);
+    let (start, end) = item.interval();
+
+    match collection.extent.as_mut() {
+        Some(extent) => {
+            if let Some(bbox) = bbox {
+                let spatial = extent.spatial.get_or_insert_with(|| SpatialExtent {
+                    bbox: vec![],
+                    crs: Default::default(),
+                });
+                match spatial.bbox.first_mut() {
+                    Some(overall) => *overall = union(overall, &bbox),
+                    None => spatial.bbox.push(bbox),
+                }
+            }
+            if start.is_some() || end.is_some() {
+                let temporal = extent.temporal.get_or_insert_with(|| TemporalExtent {
+                    interval: vec![],
+                    ..Default::default()
+                });
+                match temporal.interval.first_mut() {
+                    Some(overall) if overall.len() == 2 => {
+                        // open bounds stay open
+                        if let (Some(a), Some(b)) = (overall[0], start) {
+                            overall[0] = Some(a.min(b));
+                        }
+                        if let (Some(a), Some(b)) = (overall[1], end) {
+                            overall[1] = Some(a.max(b));
+                        }
+                    }
+                    _ => temporal.interval.insert(0, vec![start, end]),
+                }
+            }
+        }
+        None => {
+            collection.extent = Some(Extent {
+                spatial: bbox.map(|bbox| SpatialExtent {
+                    bbox: vec![bbox],
+                    crs: Default::default(),
+                }),
+                temporal: Some(TemporalExtent {
+                    interval: vec![vec![start, end]],
+                    ..Default::default()
+                }),
+            })
+        }
+    }
+
+    #[cfg(feature = "stac")]
+    if let Some(properties) = &item.properties {
+        for (key, summary) in collection.summaries.iter_mut() {
+            if let Some(value) = properties.get(key) {
+                summarize(summary, value);
+            }
+        }
+    }
+}
+
+/// Whether an item lies on the boundary of the extent or summaries of a
+/// collection, i.e. whether removing it may shrink them
+///
+/// Value sets are always affected by the items contributing to them.
+pub fn on_boundary(collection: &Collection, item: &Feature) -> bool {
+    if let Some(extent) = &collection.extent {
+        let bbox = bbox(item);
+        if let (Some(overall), Some(bbox)) =
+            (extent.spatial.as_ref().and_then(|s| s.bbox.first()), bbox)
+        {
+            let [xmin, ymin, xmax, ymax] = overall.corners();
+            let [bxmin, bymin, bxmax, bymax] = bbox.corners();
+            if bxmin <= xmin || bymin <= ymin || bxmax >= xmax || bymax >= ymax {
+                return true;
+            }
+        }
+
+        let (start, end) = item.interval();
+        if let Some([overall_start, overall_end]) = extent
+            .temporal
+            .as_ref()
+            .and_then(|t| t.interval.first())
+            .and_then(|i| <[_; 2]>::try_from(i.as_slice()).ok())
+        {
+            if matches!((overall_start, start), (Some(a), Some(b)) if b <= a)
+                || matches!((overall_end, end), (Some(a), Some(b)) if b >= a)
+            {
+                return true;
+            }
+        }
+    }
+
+    #[cfg(feature = "stac")]
+    if let Some(properties) = &item.properties {
+        return collection.summaries.iter().any(|(key, summary)| {
+            match (summary, properties.get(key)) {
+                (_, None | Some(Value::Null)) => false,
+                (Value::Array(_), Some(_)) => true,
+                (Value::Object(range), Some(value)) => {
+                    range.get("minimum") == Some(value) || range.get("maximum") == Some(value)
+                }
+                _ => false,
+            }
+        });
+    }
+
+    false
+}
+
+/// Extends a summary by a value
+#[cfg(feature = "stac")]
+fn summarize(summary: &mut Value, value: &Value) {
+    match summary {
+        Value::Array(values) => {
+            let items = match value {
+                Value::Array(items) => items.iter().collect(),
+                value => vec![value],
+            };
+            for item in items {
+                if !item.is_null() && !values.contains(item) {
+                    values.push(item.to_owned());
+                }
+            }
+        }
+        Value::Object(range) if range.contains_key("minimum") && range.contains_key("maximum") => {
+            let (minimum, maximum) = (&range["minimum"], &range["maximum"]);
+            match (minimum, maximum, value) {
+                (Value::Number(min), Value::Number(max), Value::Number(v)) => {
+                    let (min, max, v) = (
+                        min.as_f64().unwrap_or(f64::NAN),
+                        max.as_f64().unwrap_or(f64::NAN),
+                        v.as_f64().unwrap_or(f64::NAN),
+                    );
+                    if v < min {
+                        range.insert("minimum".to_string(), value.to_owned());
+                    }
+                    if v > max {
+                        range.insert("maximum".to_string(), value.to_owned());
+                    }
+                }
+                // RFC 3339 datetimes compare lexicographically when in UTC
+                (Value::String(min), Value::String(max), Value::String(v)) => {
+                    if v < min {
+                        range.insert("minimum".to_string(), json!(v));
+                    } else if v > max {
+                        range.insert("maximum".to_string(), json!(v));
+                    }
+                }
+                _ => {}
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Bbox of an item, derived from its geometry if it has none
+fn bbox(item: &Feature) -> Option<Bbox> {
+    #[cfg(feature = "stac")]
+    let bbox = item
+        .bbox
+        .clone()
+        .or_else(|| Bbox::from_geometry(&item.geometry));
+    #[cfg(not(feature = "stac"))]
+    let bbox = Bbox::from_geometry(&item.geometry);
+
+    bbox
+}
+
+/// Union of two bboxes, retaining the dimensions of the first
+fn union(a: &Bbox, b: &Bbox) -> Bbox {
+    let [bxmin, bymin, bxmax, bymax] = b.corners();
+    match (a, b) {
+        (Bbox::Bbox3D(a), Bbox::Bbox3D(b)) => Bbox::Bbox3D([
+            a[0].min(b[0]),
+            a[1].min(b[1]),
+            a[2].min(b[2]),
+            a[3].max(b[3]),
+            a[4].max(b[4]),
+            a[5].max(b[5]),
+        ]),
+        (Bbox::Bbox3D(a), _) => Bbox::Bbox3D([
+            a[0].min(bxmin),
+            a[1].min(bymin),
+            a[2],
+            a[3].max(bxmax),
+            a[4].max(bymax),
+            a[5],
+        ]),
+        (Bbox::Bbox2D(a), _) => Bbox::Bbox2D([
+            a[0].min(bxmin),
+            a[1].min(bymin),
+            a[2].max(bxmax),
+            a[3].max(bymax),
+        ]),
+    }
+}
+
+#[cfg(all(test, feature = "stac"))]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        common::{Bbox, Collection},
+        stac::Item,
+    };
+
+    use super::{include_item, on_boundary};
+
+    #[test]
+    fn include() {
+        let mut collection: Collection = serde_json::from_value(json!({
+            "id": "collection",
+            "license": "MIT",
+            "extent": {
+                "spatial": { "bbox": [[0.0, 0.0, 1.0, 1.0]] },
+                "temporal": { "interval": [["2022-01-01T00:00:00Z", "2022-01-02T00:00:00Z"]] }
+            },
+            "summaries": {
+                "platform": ["sentinel-2a"],
+                "eo:cloud_cover": { "minimum": 10, "maximum": 20 }
+            }
+        }))
+        .unwrap();
+
+        let item: Item = serde_json::from_value(json!({
+            "id": "item",
+            "geometry": { "type": "LineString", "coordinates": [[0.5, -1.0], [2.0, 0.5]] },
+            "properties": {
+                "datetime": "2022-01-03T00:00:00Z",
+                "platform": "sentinel-2b",
+                "eo:cloud_cover": 5
+            }
+        }))
+        .unwrap();
+
+        include_item(&mut collection, &item);
+
+        let inner: Item = serde_json::from_value(json!({
+            "id": "inner",
+            "geometry": { "type": "Point", "coordinates": [0.5, 0.5] },
+            "properties": { "datetime": "2022-01-02T00:00:00Z", "eo:cloud_cover": 10 }
+        }))
+        .unwrap();
+        assert!(on_boundary(&collection, &item));
+        assert!(!on_boundary(&collection, &inner));
+
+        let extent = collection.extent.unwrap();
+        assert_eq!(
+            extent.spatial.unwrap().bbox[0],
+            Bbox::Bbox2D([0.0, -1.0, 2.0, 1.0])
+        );
+        assert_eq!(
+            extent.temporal.unwrap().interval[0][1]
+                .unwrap()
+                .to_rfc3339(),
+            "2022-01-03T00:00:00+00:00"
+        );
+        assert_eq!(
+            collection.summaries["platform"],
+            json!(["sentinel-2a", "sentinel-2b"])
+        );
+        assert_eq!(
+            collection.summaries["eo:cloud_cover"],
+            json!({ "minimum": 5, "maximum": 20 })
+        );
+    }
+
+    #[test]
+    fn boundary() {
+        let mut collection: Collection = serde_json::from_value(json!({
+            "id": "collection",
+            "license": "MIT",
+            "summaries": {
+                "platform": ["sentinel-2a"],
+                "eo:cloud_cover": { "minimum": 10, "maximum": 20 },
+                "gsd": [10]
+            }
+        }))
+        .unwrap();
+        let item = |x: f64, datetime: &str, cloud_cover: u64| -> Item {
+            serde_json::from_value(json!({
+                "id": "item",
+                "geometry": { "type": "Point", "coordinates": [x, 0.5] },
+                "properties": { "datetime": datetime, "eo:cloud_cover": cloud_cover }
+            }))
+            .unwrap()
+        };
+
+        // without extent, the first item defines it
+        include_item(&mut collection, &item(0.0, "2022-01-01T00:00:00Z", 15));
+        include_item(&mut collection, &item(1.0, "2022-01-03T00:00:00Z", 15));
+        assert_eq!(
+            collection
+                .extent
+                .as_ref()
+                .unwrap()
+                .spatial
+                .as_ref()
+                .unwrap()
+                .bbox[0],
+            Bbox::Bbox2D([0.0, 0.5, 1.0, 0.5])
+        );
+
+        // spatial, temporal and range boundaries
+        assert!(on_boundary(
+            &collection,
+            &item(0.0, "2022-01-02T00:00:00Z", 15)
+        ));
+        assert!(on_boundary(
+            &collection,
+            &item(0.5, "2022-01-01T00:00:00Z", 15)
+        ));
+        assert!(on_boundary(
+            &collection,
+            &item(0.5, "2022-01-03T00:00:00Z", 15)
+        ));
+        assert!(on_boundary(
+            &collection,
+            &item(0.5, "2022-01-02T00:00:00Z", 20)
+        ));
+
+        // summaries of properties the item does not have are not affected
+        let mut inner = item(0.5, "2022-01-02T00:00:00Z", 15);
+        inner.bbox = Some(Bbox::Bbox2D([0.25, 0.25, 0.75, 0.75]));
+        let mut extent = collection.extent.clone().unwrap();
+        extent.spatial.as_mut().unwrap().bbox[0] = Bbox::Bbox2D([0.0, 0.0, 1.0, 1.0]);
+        collection.extent = Some(extent);
+        assert!(!on_boundary(&collection, &inner));
+
+        // value sets are affected by any item contributing to them
+        inner
+            .properties
+            .as_mut()
+            .unwrap()
+            .insert("platform".to_string(), json!("sentinel-2a"));
+        assert!(on_boundary(&collection, &inner));
+    }
+}
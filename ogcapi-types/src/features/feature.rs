@@ -3,6 +3,7 @@ use std::collections::HashMap;
 
 #[cfg(feature = "stac")]
 use crate::common::Bbox;
+use chrono::{DateTime, Utc};
 use geojson::Geometry;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
@@ -21,6 +22,7 @@ impl Default for Type {
 }
 
 /// Abstraction of real world phenomena (ISO 19101-1:2014)
+#[serde_with::serde_as]
 #[serde_with::skip_serializing_none]
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct Feature {
@@ -33,13 +35,13 @@ pub struct Feature {
     pub geometry: Geometry,
     #[serde(default)]
     pub links: Links,
-    /// The STAC version the Item implements.
+    /// The STAC version the Item implements, `None` for plain features.
     #[cfg(feature = "stac")]
-    #[serde(default = "crate::stac::stac_version")]
-    pub stac_version: String,
+    pub stac_version: Option<String>,
     /// A list of extensions the Item implements.
     #[cfg(feature = "stac")]
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde_as(as = "serde_with::DefaultOnNull")]
     pub stac_extensions: Vec<String>,
     /// Dictionary of asset objects that can be downloaded, each with a unique key.
     #[cfg(feature = "stac")]
@@ -58,4 +60,23 @@ impl Feature {
             self.properties = Some(other);
         }
     }
+
+    /// Start and end of the feature from its `start_datetime` and
+    /// `end_datetime` or `datetime` property
+    pub fn interval(&self) -> (Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
+        let get = |key: &str| {
+            self.properties
+                .as_ref()
+                .and_then(|p| p.get(key))
+                .and_then(|v| v.as_str())
+                .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                .map(|d| d.with_timezone(&Utc))
+        };
+
+        let datetime = get("datetime");
+        (
+            get("start_datetime").or(datetime),
+            get("end_datetime").or(datetime),
+        )
+    }
 }
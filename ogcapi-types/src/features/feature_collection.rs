@@ -18,7 +18,7 @@ impl Default for Type {
 
 /// A set of Features from a dataset
 #[serde_with::skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct FeatureCollection {
     #[serde(default)]
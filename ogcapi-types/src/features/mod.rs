@@ -1,9 +1,14 @@
+pub mod cql2;
+mod extent;
 mod feature;
 mod feature_collection;
 mod query;
+mod queryables;
 
+pub use extent::{include_item, on_boundary};
 pub use feature::Feature;
 pub use feature_collection::FeatureCollection;
-pub use query::Query;
+pub use query::{FilterLang, Query};
+pub use queryables::Queryables;
 
 pub use geojson::Geometry;
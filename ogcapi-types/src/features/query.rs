@@ -6,7 +6,7 @@ use serde_with::{serde_as, DisplayFromStr};
 use crate::common::{Bbox, Crs, Datetime};
 
 #[serde_as]
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
 #[serde(rename_all = "kebab-case")]
 pub struct Query {
     pub limit: Option<usize>,
@@ -34,10 +34,12 @@ pub struct Query {
     pub additional_parameters: HashMap<String, String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(rename_all = "kebab-case")]
+/// Encoding of a CQL2 filter expression
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FilterLang {
+    #[serde(rename = "cql2-text", alias = "cql-text")]
     CqlText,
+    #[serde(rename = "cql2-json", alias = "cql-json")]
     CqlJson,
 }
 
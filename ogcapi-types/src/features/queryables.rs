@@ -1,16 +1,35 @@
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use serde_with::skip_serializing_none;
 
-#[derive(Serialize, Deserialize, Debug)]
-struct Queryables {
-    queryables: Vec<Queryable>
+/// Properties that can be used in filter expressions, described as JSON Schema
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Queryables {
+    #[serde(rename = "$schema")]
+    pub schema: String,
+    #[serde(rename = "$id")]
+    pub id: Option<String>,
+    pub r#type: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub properties: Map<String, Value>,
+    #[serde(default)]
+    pub additional_properties: bool,
+}
+
+impl Default for Queryables {
+    fn default() -> Self {
+        Queryables {
+            schema: "https://json-schema.org/draft/2019-09/schema".to_string(),
+            id: None,
+            r#type: "object".to_string(),
+            title: Some("Queryables".to_string()),
+            description: None,
+            properties: Map::new(),
+            additional_properties: true,
+        }
+    }
 }
-#[derive(Serialize, Deserialize, Debug)]
-struct Queryable {
-    queryable: String,
-    title: Option<String>,
-    description: Option<String>,
-    language: Option<String>, // default en
-    r#type: String,
-    #[serde(rename = "type-ref")]
-    type_ref: String,
-}
\ No newline at end of file
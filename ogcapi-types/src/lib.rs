@@ -2,6 +2,10 @@
 
 /// Types specified in the `OGC API - Common` standard.
 pub mod common;
+/// Types of the `CoverageJSON` format.
+pub mod coverage;
+/// Types specified in the `OGC API - Coverages` standard.
+pub mod coverages;
 /// Types specified in the `OGC API - Environmental Data Retrieval` standard.
 pub mod edr;
 /// Types specified in the `OGC API - Features` standard.
@@ -15,5 +19,3 @@ pub mod stac;
 pub mod styles;
 /// Types specified in the `OGC API - Tiles` standard.
 pub mod tiles;
-
-mod coverage;
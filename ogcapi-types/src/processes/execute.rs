@@ -5,7 +5,7 @@ use serde_json::{Map, Value};
 
 use crate::common::{Bbox, Link};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Execute {
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub inputs: HashMap<String, Input>,
@@ -16,22 +16,24 @@ pub struct Execute {
     pub subscriber: Option<Subscriber>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum Input {
     InlineOrRefData(InlineOrRefData),
     InlineOrRefDataArray(Vec<InlineOrRefData>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum InlineOrRefData {
     InputValueNoObject(InputValueNoObject),
     QualifiedInputValue(QualifiedInputValue),
     Link(Link),
+    ProcessInput(ProcessInput),
+    CollectionInput(CollectionInput),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum InputValueNoObject {
     String(String),
@@ -44,27 +46,45 @@ pub enum InputValueNoObject {
     // Bbox(BoundingBox), // Bbox is actually an object
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Output of a nested process execution, see `OGC API - Processes - Part 3`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProcessInput {
+    /// URI of the process to execute
+    pub process: String,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub inputs: HashMap<String, Input>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub outputs: HashMap<String, Output>,
+}
+
+/// Reference to a collection, see `OGC API - Processes - Part 3`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CollectionInput {
+    /// URI of the collection
+    pub collection: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BoundingBox {
     pub bbox: Bbox,
     pub crs: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct QualifiedInputValue {
     pub value: InputValue,
     #[serde(flatten)]
     pub format: Format,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum InputValue {
     InputValueNoObject(InputValueNoObject),
     Object(Map<String, Value>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Output {
     pub format: Option<Format>,
@@ -72,7 +92,7 @@ pub struct Output {
     pub transmission_mode: TransmissionMode,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Format {
     pub media_type: Option<String>,
@@ -80,14 +100,14 @@ pub struct Format {
     pub schema: Option<Schema>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum Schema {
     String(String),
     Object(Map<String, Value>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum TransmissionMode {
     Value,
@@ -100,7 +120,7 @@ impl Default for TransmissionMode {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum Response {
     Raw,
@@ -113,10 +133,43 @@ impl Default for Response {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Subscriber {
     pub success_uri: String,
     pub in_progress_uri: Option<String>,
     pub failed_uri: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_process() {
+        let execute: Execute = serde_json::from_str(
+            r#"{
+                "inputs": {
+                    "collection": {
+                        "process": "http://localhost:8484/processes/clip",
+                        "inputs": {
+                            "collection": { "collection": "http://localhost:8484/collections/countries" }
+                        }
+                    },
+                    "distance": 1000
+                }
+            }"#,
+        )
+        .unwrap();
+
+        match &execute.inputs["collection"] {
+            Input::InlineOrRefData(InlineOrRefData::ProcessInput(nested)) => {
+                assert!(matches!(
+                    nested.inputs["collection"],
+                    Input::InlineOrRefData(InlineOrRefData::CollectionInput(_))
+                ))
+            }
+            _ => panic!("expected nested process input"),
+        }
+    }
+}
@@ -7,7 +7,7 @@ use crate::common::Links;
 
 use super::execute::InlineOrRefData;
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct StatusInfo {
     #[serde(rename = "processID")]
     pub process_id: Option<String>,
@@ -23,7 +23,7 @@ pub struct StatusInfo {
     pub links: Links,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum StatusCode {
     Accepted,
@@ -39,6 +39,14 @@ impl Default for StatusCode {
     }
 }
 
+/// List of jobs
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct JobList {
+    pub jobs: Vec<StatusInfo>,
+    #[serde(default)]
+    pub links: Links,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Results {
     #[serde(flatten)]
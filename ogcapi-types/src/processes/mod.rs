@@ -14,4 +14,4 @@ pub use job::*;
 pub use output_description::OutputDescription;
 pub use process::{Process, ProcessList};
 pub use process_summary::ProcessSummary;
-pub use query::ProcessQuery;
+pub use query::{JobQuery, ProcessQuery};
@@ -1,7 +1,71 @@
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+
+use crate::common::{Datetime, IntervalDatetime, ListParam};
+
+use super::StatusInfo;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct ProcessQuery {
     pub limit: Option<usize>,
     pub offset: Option<usize>,
 }
+
+/// Query parameters of the job list
+#[serde_as]
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct JobQuery {
+    /// Filter by process ids
+    #[serde(default, rename = "processID")]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub process_id: Option<ListParam>,
+    /// Filter by job status, e.g. `running,successful`
+    #[serde(default)]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub status: Option<ListParam>,
+    /// Filter by creation time
+    #[serde(default)]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub datetime: Option<Datetime>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+impl JobQuery {
+    /// Whether a job passes the process id, status and datetime filters
+    pub fn matches(&self, job: &StatusInfo) -> bool {
+        let process = match (&self.process_id, &job.process_id) {
+            (Some(ids), Some(id)) => ids.0.contains(id),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+
+        let status = match &self.status {
+            Some(status) => serde_json::to_value(job.status)
+                .ok()
+                .and_then(|s| s.as_str().map(|s| status.0.iter().any(|q| q == s)))
+                .unwrap_or(false),
+            None => true,
+        };
+
+        let created = match (&self.datetime, job.created) {
+            (Some(Datetime::Datetime(datetime)), Some(created)) => created == *datetime,
+            (Some(Datetime::Interval { from, to }), Some(created)) => {
+                let after = match from {
+                    IntervalDatetime::Datetime(from) => created >= *from,
+                    IntervalDatetime::Open => true,
+                };
+                let before = match to {
+                    IntervalDatetime::Datetime(to) => created <= *to,
+                    IntervalDatetime::Open => created <= Utc::now(),
+                };
+                after && before
+            }
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+
+        process && status && created
+    }
+}
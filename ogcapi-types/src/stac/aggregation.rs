@@ -0,0 +1,186 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::{serde_as, DisplayFromStr};
+
+use crate::common::{Links, ListParam};
+
+/// Aggregations supported by the aggregation extension with their data type
+pub const AGGREGATIONS: [(&str, &str); 6] = [
+    ("total_count", "integer"),
+    ("datetime_min", "datetime"),
+    ("datetime_max", "datetime"),
+    ("collection_frequency", "frequency_distribution"),
+    ("datetime_frequency", "frequency_distribution"),
+    ("geohash_frequency", "frequency_distribution"),
+];
+
+/// Aggregation parameters accompanying the search parameters of an
+/// aggregation request.
+#[serde_as]
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct AggregationParams {
+    /// Names of the aggregations to compute, defaults to all
+    #[serde(default)]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub aggregations: Option<ListParam>,
+    /// Geohash precision of the `geohash_frequency` buckets, from 1 to 12
+    pub geohash_frequency_precision: Option<u8>,
+    /// Interval of the `datetime_frequency` buckets
+    pub datetime_frequency_interval: Option<DatetimeInterval>,
+}
+
+impl AggregationParams {
+    /// Names of the requested aggregations
+    pub fn names(&self) -> Result<Vec<&str>, String> {
+        match &self.aggregations {
+            Some(aggregations) => aggregations
+                .0
+                .iter()
+                .map(|name| {
+                    AGGREGATIONS
+                        .iter()
+                        .find(|(n, _)| n == name)
+                        .map(|(n, _)| *n)
+                        .ok_or_else(|| format!("Unknown aggregation `{name}`"))
+                })
+                .collect(),
+            None => Ok(AGGREGATIONS.iter().map(|(n, _)| *n).collect()),
+        }
+    }
+
+    /// Geohash precision of the `geohash_frequency` buckets, defaults to 1
+    pub fn geohash_precision(&self) -> Result<u8, String> {
+        match self.geohash_frequency_precision {
+            None => Ok(1),
+            Some(precision) if (1..=12).contains(&precision) => Ok(precision),
+            Some(precision) => Err(format!(
+                "Geohash precision `{precision}` not in range 1 to 12"
+            )),
+        }
+    }
+}
+
+/// Aggregation body accompanying the search body of an aggregation request.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct AggregationBody {
+    pub aggregations: Option<ListParam>,
+    pub geohash_frequency_precision: Option<u8>,
+    pub datetime_frequency_interval: Option<DatetimeInterval>,
+}
+
+impl From<AggregationBody> for AggregationParams {
+    fn from(body: AggregationBody) -> Self {
+        AggregationParams {
+            aggregations: body.aggregations,
+            geohash_frequency_precision: body.geohash_frequency_precision,
+            datetime_frequency_interval: body.datetime_frequency_interval,
+        }
+    }
+}
+
+/// Interval of datetime buckets
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DatetimeInterval {
+    Year,
+    #[default]
+    Month,
+    Day,
+    Hour,
+    Minute,
+}
+
+impl fmt::Display for DatetimeInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatetimeInterval::Year => write!(f, "year"),
+            DatetimeInterval::Month => write!(f, "month"),
+            DatetimeInterval::Day => write!(f, "day"),
+            DatetimeInterval::Hour => write!(f, "hour"),
+            DatetimeInterval::Minute => write!(f, "minute"),
+        }
+    }
+}
+
+/// Result of an aggregation request.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct AggregationCollection {
+    #[serde(default = "aggregation_collection")]
+    pub r#type: String,
+    pub aggregations: Vec<Aggregation>,
+    #[serde(default)]
+    pub links: Links,
+}
+
+impl AggregationCollection {
+    pub fn new(aggregations: Vec<Aggregation>) -> Self {
+        AggregationCollection {
+            r#type: aggregation_collection(),
+            aggregations,
+            links: Default::default(),
+        }
+    }
+}
+
+fn aggregation_collection() -> String {
+    "AggregationCollection".to_string()
+}
+
+/// A single valued aggregation or a frequency distribution.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Aggregation {
+    pub name: String,
+    pub data_type: String,
+    /// Value of single valued aggregations
+    pub value: Option<Value>,
+    /// Buckets of frequency distributions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub buckets: Vec<Bucket>,
+}
+
+/// Bucket of a frequency distribution.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Bucket {
+    pub key: String,
+    pub data_type: String,
+    pub frequency: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{AggregationBody, AggregationParams, DatetimeInterval};
+
+    #[test]
+    fn params() {
+        let params: AggregationParams = serde_json::from_value(json!({
+            "aggregations": "total_count,geohash_frequency",
+            "geohash_frequency_precision": 3,
+            "datetime_frequency_interval": "day"
+        }))
+        .unwrap();
+        assert_eq!(
+            params.names().unwrap(),
+            vec!["total_count", "geohash_frequency"]
+        );
+        assert_eq!(params.geohash_precision().unwrap(), 3);
+        assert_eq!(
+            params.datetime_frequency_interval,
+            Some(DatetimeInterval::Day)
+        );
+
+        let body: AggregationBody = serde_json::from_value(json!({
+            "aggregations": ["datetime_max", "unknown"],
+            "geohash_frequency_precision": 13
+        }))
+        .unwrap();
+        let params = AggregationParams::from(body);
+        assert!(params.names().is_err());
+        assert!(params.geohash_precision().is_err());
+        assert_eq!(AggregationParams::default().names().unwrap().len(), 6);
+    }
+}
@@ -1,14 +1,23 @@
+mod aggregation;
 mod asset;
 mod catalog;
 mod entity;
 mod provider;
+mod schema;
 mod search;
+mod validation;
 
+pub use aggregation::{
+    Aggregation, AggregationBody, AggregationCollection, AggregationParams, Bucket,
+    DatetimeInterval, AGGREGATIONS,
+};
 pub use asset::Asset;
 pub use catalog::Catalog;
 pub use entity::StacEntity;
 pub use provider::{Provider, ProviderRole};
+pub use schema::{Validator, EXTENSIONS};
 pub use search::{SearchBody, SearchParams};
+pub use validation::{validate_collection, validate_item, STAC_VERSIONS};
 
 #[doc(inline)]
 pub use crate::common::Collection;
@@ -16,6 +25,9 @@ pub use crate::common::Collection;
 #[doc(inline)]
 pub use crate::features::Feature as Item;
 
+#[doc(inline)]
+pub use crate::features::{include_item, on_boundary};
+
 /// Default stac version
 pub(crate) const STAC_VERSION: &str = "1.0.0";
 
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use jsonschema::{Draft, JSONSchema};
+use serde_json::Value;
+
+const ITEM: &str = "https://schemas.stacspec.org/v1.0.0/item-spec/json-schema/item.json";
+const COLLECTION: &str =
+    "https://schemas.stacspec.org/v1.0.0/collection-spec/json-schema/collection.json";
+const CATALOG: &str = "https://schemas.stacspec.org/v1.0.0/catalog-spec/json-schema/catalog.json";
+
+/// Extensions with a bundled schema
+pub const EXTENSIONS: [&str; 4] = [
+    "https://stac-extensions.github.io/eo/v1.0.0/schema.json",
+    "https://stac-extensions.github.io/projection/v1.0.0/schema.json",
+    "https://stac-extensions.github.io/view/v1.0.0/schema.json",
+    "https://stac-extensions.github.io/sat/v1.0.0/schema.json",
+];
+
+/// Bundled schemas by their URL
+const SCHEMAS: [(&str, &str); 14] = [
+    (
+        ITEM,
+        include_str!("schemas/v1.0.0/item-spec/json-schema/item.json"),
+    ),
+    (
+        "https://schemas.stacspec.org/v1.0.0/item-spec/json-schema/basics.json",
+        include_str!("schemas/v1.0.0/item-spec/json-schema/basics.json"),
+    ),
+    (
+        "https://schemas.stacspec.org/v1.0.0/item-spec/json-schema/datetime.json",
+        include_str!("schemas/v1.0.0/item-spec/json-schema/datetime.json"),
+    ),
+    (
+        "https://schemas.stacspec.org/v1.0.0/item-spec/json-schema/instrument.json",
+        include_str!("schemas/v1.0.0/item-spec/json-schema/instrument.json"),
+    ),
+    (
+        "https://schemas.stacspec.org/v1.0.0/item-spec/json-schema/licensing.json",
+        include_str!("schemas/v1.0.0/item-spec/json-schema/licensing.json"),
+    ),
+    (
+        "https://schemas.stacspec.org/v1.0.0/item-spec/json-schema/provider.json",
+        include_str!("schemas/v1.0.0/item-spec/json-schema/provider.json"),
+    ),
+    (
+        COLLECTION,
+        include_str!("schemas/v1.0.0/collection-spec/json-schema/collection.json"),
+    ),
+    (
+        CATALOG,
+        include_str!("schemas/v1.0.0/catalog-spec/json-schema/catalog.json"),
+    ),
+    (
+        "https://geojson.org/schema/Feature.json",
+        include_str!("schemas/geojson/Feature.json"),
+    ),
+    (
+        "https://geojson.org/schema/Geometry.json",
+        include_str!("schemas/geojson/Geometry.json"),
+    ),
+    (
+        EXTENSIONS[0],
+        include_str!("schemas/extensions/eo/v1.0.0/schema.json"),
+    ),
+    (
+        EXTENSIONS[1],
+        include_str!("schemas/extensions/projection/v1.0.0/schema.json"),
+    ),
+    (
+        EXTENSIONS[2],
+        include_str!("schemas/extensions/view/v1.0.0/schema.json"),
+    ),
+    (
+        EXTENSIONS[3],
+        include_str!("schemas/extensions/sat/v1.0.0/schema.json"),
+    ),
+];
+
+/// Offline validator of STAC entities against bundled JSON schemas
+///
+/// Bundles the STAC `1.0.0` core schemas and the schemas of the [EXTENSIONS],
+/// references are resolved without network access.
+pub struct Validator {
+    schemas: HashMap<&'static str, JSONSchema>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        let documents: Vec<(&str, Value)> = SCHEMAS
+            .iter()
+            .map(|(url, schema)| (*url, serde_json::from_str(schema).expect("valid schema")))
+            .collect();
+
+        let schemas = [ITEM, COLLECTION, CATALOG]
+            .into_iter()
+            .chain(EXTENSIONS)
+            .map(|url| {
+                let mut options = JSONSchema::options();
+                options.with_draft(Draft::Draft7);
+                for (id, document) in &documents {
+                    options.with_document(id.to_string(), document.to_owned());
+                }
+                let schema = &documents.iter().find(|(id, _)| *id == url).unwrap().1;
+                let compiled = options.compile(schema).expect("compilable schema");
+                (url, compiled)
+            })
+            .collect();
+
+        Validator { schemas }
+    }
+
+    /// Validates a STAC Item, Collection or Catalog against the core schema
+    /// and the bundled schemas of its `stac_extensions`
+    ///
+    /// Extensions without a bundled schema are not validated.
+    pub fn validate(&self, value: &Value) -> Result<(), Vec<String>> {
+        let core = match value.get("type").and_then(Value::as_str) {
+            Some("Feature") => ITEM,
+            Some("Collection") => COLLECTION,
+            Some("Catalog") => CATALOG,
+            _ => return Err(vec!["Not a STAC Item, Collection or Catalog".to_string()]),
+        };
+
+        let extensions = value
+            .get("stac_extensions")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str);
+
+        let mut errors = Vec::new();
+        for url in std::iter::once(core).chain(extensions) {
+            if let Some(Err(e)) = self.schemas.get(url).map(|schema| schema.validate(value)) {
+                errors.extend(e.map(|e| {
+                    let path = e.instance_path.to_string();
+                    if path.is_empty() {
+                        e.to_string()
+                    } else {
+                        format!("{e} at `{path}`")
+                    }
+                }));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Default for Validator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::Validator;
+
+    #[test]
+    fn validate() {
+        let validator = Validator::new();
+
+        let mut item = json!({
+            "type": "Feature",
+            "stac_version": "1.0.0",
+            "stac_extensions": [
+                "https://stac-extensions.github.io/eo/v1.0.0/schema.json",
+                "https://stac-extensions.github.io/example/v1.0.0/schema.json"
+            ],
+            "id": "item",
+            "geometry": { "type": "Point", "coordinates": [7.4, 46.9] },
+            "bbox": [7.4, 46.9, 7.4, 46.9],
+            "properties": { "datetime": "2022-09-01T10:00:00Z", "eo:cloud_cover": 12.5 },
+            "links": [{ "rel": "self", "href": "./item.json" }],
+            "assets": { "data": { "href": "./data.tif", "roles": ["data"] } }
+        });
+        assert!(validator.validate(&item).is_ok());
+
+        item["properties"]["eo:cloud_cover"] = json!(120);
+        item["properties"]["datetime"] = json!(null);
+        let errors = validator.validate(&item).unwrap_err();
+        assert!(errors.iter().any(|e| e.contains("eo:cloud_cover")));
+
+        let collection = json!({
+            "type": "Collection",
+            "stac_version": "1.0.0",
+            "id": "collection",
+            "description": "A collection",
+            "license": "CC-BY-4.0",
+            "extent": {
+                "spatial": { "bbox": [[-180, -90, 180, 90]] },
+                "temporal": { "interval": [["2022-01-01T00:00:00Z", null]] }
+            },
+            "links": []
+        });
+        assert!(validator.validate(&collection).is_ok());
+    }
+}
@@ -1,8 +1,12 @@
 use geojson::Geometry;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use serde_with::{serde_as, DisplayFromStr};
 
-use crate::common::{Bbox, Datetime, ListParam};
+use crate::{
+    common::{Bbox, Datetime, ListParam},
+    features::{cql2::Expr, FilterLang},
+};
 
 /// Search parameters for searching a SpatioTemporal Asset Catalog.
 #[serde_as]
@@ -25,6 +29,13 @@ pub struct SearchParams {
     #[serde(default)]
     #[serde_as(as = "Option<DisplayFromStr>")]
     pub collections: Option<ListParam>,
+    /// CQL2 filter expression (filter extension)
+    pub filter: Option<String>,
+    #[serde(rename = "filter-lang")]
+    pub filter_lang: Option<FilterLang>,
+    /// JSON encoded property constraints (query extension),
+    /// e.g. `{"eo:cloud_cover":{"lt":10}}`
+    pub query: Option<String>,
 }
 
 impl SearchParams {
@@ -74,6 +85,103 @@ impl SearchParams {
         self.collections = Some(collections.into());
         self
     }
+
+    /// Set the `filter` property with its language
+    pub fn with_filter(mut self, filter: impl ToString, filter_lang: FilterLang) -> Self {
+        self.filter = Some(filter.to_string());
+        self.filter_lang = Some(filter_lang);
+        self
+    }
+
+    /// Filter expression combining the `filter` and `query` properties
+    pub fn filter_expr(&self) -> Result<Option<Expr>, String> {
+        let mut args = Vec::new();
+
+        if let Some(filter) = &self.filter {
+            let expr: Expr = match self.filter_lang.unwrap_or_default() {
+                FilterLang::CqlText => filter.parse()?,
+                FilterLang::CqlJson => {
+                    serde_json::from_str(filter).map_err(|e| format!("Invalid CQL2 JSON: {e}"))?
+                }
+            };
+            expr.validate()?;
+            args.push(expr);
+        }
+
+        if let Some(query) = &self.query {
+            let query: Map<String, Value> =
+                serde_json::from_str(query).map_err(|e| format!("Invalid query: {e}"))?;
+            args.extend(query_expr(&query)?);
+        }
+
+        Ok(match args.len() {
+            0 => None,
+            1 => args.pop(),
+            _ => Some(Expr::op("and", args)),
+        })
+    }
+}
+
+/// Translates the query extension into CQL2 predicates
+fn query_expr(query: &Map<String, Value>) -> Result<Vec<Expr>, String> {
+    let literal = |value: &Value| match value {
+        Value::Bool(b) => Ok(Expr::Bool(*b)),
+        Value::Number(n) => Ok(Expr::Number(n.as_f64().unwrap_or_default())),
+        Value::String(s) => Ok(Expr::Text(s.to_owned())),
+        value => Err(format!("Invalid query value `{value}`")),
+    };
+    let text = |value: &Value| match value {
+        // escape wildcards of `like` patterns
+        Value::String(s) => Ok(s
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")),
+        value => Err(format!("Expected a string, found `{value}`")),
+    };
+
+    let mut predicates = Vec::new();
+    for (property, operators) in query {
+        let operators = operators
+            .as_object()
+            .ok_or_else(|| format!("Invalid query for property `{property}`"))?;
+
+        for (operator, value) in operators {
+            let property = Expr::property(property);
+            let predicate = match operator.as_str() {
+                "eq" => Expr::op("=", vec![property, literal(value)?]),
+                "neq" => Expr::op("<>", vec![property, literal(value)?]),
+                "lt" => Expr::op("<", vec![property, literal(value)?]),
+                "lte" => Expr::op("<=", vec![property, literal(value)?]),
+                "gt" => Expr::op(">", vec![property, literal(value)?]),
+                "gte" => Expr::op(">=", vec![property, literal(value)?]),
+                "startsWith" => Expr::op(
+                    "like",
+                    vec![property, Expr::Text(format!("{}%", text(value)?))],
+                ),
+                "endsWith" => Expr::op(
+                    "like",
+                    vec![property, Expr::Text(format!("%{}", text(value)?))],
+                ),
+                "contains" => Expr::op(
+                    "like",
+                    vec![property, Expr::Text(format!("%{}%", text(value)?))],
+                ),
+                "in" => {
+                    let values = value
+                        .as_array()
+                        .ok_or_else(|| "Operator `in` requires an array".to_string())?
+                        .iter()
+                        .map(literal)
+                        .collect::<Result<_, _>>()?;
+                    Expr::op("in", vec![property, Expr::Array(values)])
+                }
+                operator => return Err(format!("Unknown query operator `{operator}`")),
+            };
+            predicates.push(predicate);
+        }
+    }
+
+    Ok(predicates)
 }
 
 /// Search body for searching a SpatioTemporal Asset Catalog.
@@ -90,10 +198,25 @@ pub struct SearchBody {
     pub intersects: Option<Geometry>,
     pub ids: Option<ListParam>,
     pub collections: Option<ListParam>,
+    /// CQL2 filter expression, a JSON object or text
+    pub filter: Option<Value>,
+    #[serde(rename = "filter-lang")]
+    pub filter_lang: Option<FilterLang>,
+    pub query: Option<Map<String, Value>>,
 }
 
 impl From<SearchBody> for SearchParams {
     fn from(body: SearchBody) -> Self {
+        // objects are `cql2-json`, text defaults to `cql2-text`
+        let (filter, filter_lang) = match body.filter {
+            Some(Value::String(filter)) => (Some(filter), body.filter_lang),
+            Some(filter) => (
+                Some(filter.to_string()),
+                Some(body.filter_lang.unwrap_or(FilterLang::CqlJson)),
+            ),
+            None => (None, body.filter_lang),
+        };
+
         SearchParams {
             limit: body.limit,
             offset: body.offset,
@@ -102,6 +225,56 @@ impl From<SearchBody> for SearchParams {
             intersects: body.intersects,
             ids: body.ids,
             collections: body.collections,
+            filter,
+            filter_lang,
+            query: body.query.map(|query| Value::Object(query).to_string()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::features::FilterLang;
+
+    use super::{SearchBody, SearchParams};
+
+    #[test]
+    fn filter_expr() {
+        let body: SearchBody = serde_json::from_value(json!({
+            "filter": { "op": "=", "args": [{ "property": "platform" }, "sentinel-2a"] },
+            "query": { "eo:cloud_cover": { "lt": 10 } }
+        }))
+        .unwrap();
+        let params = SearchParams::from(body);
+
+        let expr = params.filter_expr().unwrap().unwrap();
+        assert_eq!(
+            serde_json::to_value(&expr).unwrap(),
+            json!({
+                "op": "and",
+                "args": [
+                    { "op": "=", "args": [{ "property": "platform" }, "sentinel-2a"] },
+                    { "op": "<", "args": [{ "property": "eo:cloud_cover" }, 10.0] }
+                ]
+            })
+        );
+
+        let params = SearchParams::new().with_filter("eo:cloud_cover < 10", FilterLang::CqlText);
+        assert!(params.filter_expr().unwrap().is_some());
+
+        let params = SearchParams::new().with_filter("eo:cloud_cover <", FilterLang::CqlText);
+        assert!(params.filter_expr().is_err());
+
+        // wildcards of the query extension match literally
+        let params = SearchParams {
+            query: Some(json!({ "name": { "startsWith": "50%_" } }).to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(params.filter_expr().unwrap().unwrap()).unwrap(),
+            json!({ "op": "like", "args": [{ "property": "name" }, r"50\%\_%"] })
+        );
+    }
+}
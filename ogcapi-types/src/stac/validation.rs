@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset};
+use serde_json::{Map, Value};
+use url::Url;
+
+use crate::common::{Bbox, Collection};
+
+use super::{Asset, Item};
+
+/// STAC versions accepted on ingest, the versions of the bundled schemas
+pub const STAC_VERSIONS: [&str; 1] = ["1.0.0"];
+
+/// Checks the structure of a STAC Item
+///
+/// Requires an `id`, a supported `stac_version`, schema URLs as
+/// `stac_extensions`, a `datetime` or a `start_datetime` and `end_datetime`
+/// property and well formed assets.
+pub fn validate_item(item: &Item) -> Result<(), String> {
+    match item.id.as_deref() {
+        Some(id) if !id.is_empty() => {}
+        _ => return Err("Item requires an `id`".to_string()),
+    }
+
+    validate_version(
+        item.stac_version
+            .as_deref()
+            .ok_or_else(|| "Item requires a `stac_version`".to_string())?,
+    )?;
+    validate_extensions(&item.stac_extensions)?;
+
+    let properties = item
+        .properties
+        .as_ref()
+        .ok_or_else(|| "Item requires `properties`".to_string())?;
+    validate_datetime(properties)?;
+
+    if let Some(bbox) = &item.bbox {
+        validate_bbox(bbox)?;
+    }
+
+    validate_assets(&item.assets)
+}
+
+/// Checks the structure of a STAC Collection
+///
+/// Requires an `id`, a `description`, a `license`, a spatial and temporal
+/// `extent`, a supported `stac_version`, schema URLs as `stac_extensions`
+/// and well formed assets.
+pub fn validate_collection(collection: &Collection) -> Result<(), String> {
+    if collection.id.is_empty() {
+        return Err("Collection requires an `id`".to_string());
+    }
+
+    validate_version(
+        collection
+            .stac_version
+            .as_deref()
+            .ok_or_else(|| "Collection requires a `stac_version`".to_string())?,
+    )?;
+    validate_extensions(&collection.stac_extensions)?;
+
+    if collection
+        .description
+        .as_deref()
+        .unwrap_or_default()
+        .is_empty()
+    {
+        return Err("Collection requires a `description`".to_string());
+    }
+
+    if collection.license.is_empty() {
+        return Err("Collection requires a `license`".to_string());
+    }
+
+    let extent = collection
+        .extent
+        .as_ref()
+        .ok_or_else(|| "Collection requires an `extent`".to_string())?;
+
+    let spatial = extent
+        .spatial
+        .as_ref()
+        .ok_or_else(|| "Collection requires a spatial extent".to_string())?;
+    if spatial.bbox.is_empty() {
+        return Err("Spatial extent requires at least one `bbox`".to_string());
+    }
+    for bbox in &spatial.bbox {
+        validate_bbox(bbox)?;
+    }
+
+    let temporal = extent
+        .temporal
+        .as_ref()
+        .ok_or_else(|| "Collection requires a temporal extent".to_string())?;
+    if temporal.interval.is_empty() {
+        return Err("Temporal extent requires at least one `interval`".to_string());
+    }
+    for interval in &temporal.interval {
+        match interval[..] {
+            [Some(start), Some(end)] if start > end => {
+                return Err(format!("Temporal interval starts after its end `{end}`"))
+            }
+            [_, _] => {}
+            _ => return Err("Temporal intervals require a start and an end".to_string()),
+        }
+    }
+
+    validate_assets(&collection.assets)
+}
+
+fn validate_version(version: &str) -> Result<(), String> {
+    if STAC_VERSIONS.contains(&version) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unsupported `stac_version` `{version}`, expected one of `{}`",
+            STAC_VERSIONS.join("`, `")
+        ))
+    }
+}
+
+fn validate_extensions(extensions: &[String]) -> Result<(), String> {
+    for extension in extensions {
+        match Url::parse(extension) {
+            Ok(url) if url.path().ends_with(".json") => {}
+            _ => {
+                return Err(format!(
+                    "Extension `{extension}` is not the URL of a JSON schema"
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate_datetime(properties: &Map<String, Value>) -> Result<(), String> {
+    let parse = |key: &str| -> Result<Option<DateTime<FixedOffset>>, String> {
+        match properties.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(s)) => DateTime::parse_from_rfc3339(s)
+                .map(Some)
+                .map_err(|_| format!("Property `{key}` is not a RFC 3339 datetime")),
+            Some(_) => Err(format!("Property `{key}` must be a string")),
+        }
+    };
+
+    let datetime = parse("datetime")?;
+    let start = parse("start_datetime")?;
+    let end = parse("end_datetime")?;
+
+    if !properties.contains_key("datetime") {
+        return Err("Item requires a `datetime` property".to_string());
+    }
+
+    match (datetime, start, end) {
+        (_, Some(start), Some(end)) if start > end => {
+            Err("Property `start_datetime` is after `end_datetime`".to_string())
+        }
+        (None, None, _) | (None, _, None) => {
+            Err("Items without `datetime` require `start_datetime` and `end_datetime`".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_bbox(bbox: &Bbox) -> Result<(), String> {
+    let valid = match bbox {
+        Bbox::Bbox2D(b) => b[1] <= b[3],
+        Bbox::Bbox3D(b) => b[1] <= b[4] && b[2] <= b[5],
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid bbox `{bbox}`"))
+    }
+}
+
+fn validate_assets(assets: &HashMap<String, Asset>) -> Result<(), String> {
+    for (key, asset) in assets {
+        if asset.href.trim().is_empty() {
+            return Err(format!("Asset `{key}` requires a `href`"));
+        }
+        if asset.roles.iter().any(|role| role.is_empty()) {
+            return Err(format!("Asset `{key}` has an empty role"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::stac::Item;
+
+    use super::validate_item;
+
+    #[test]
+    fn item() {
+        let mut item: Item = serde_json::from_value(json!({
+            "id": "item",
+            "type": "Feature",
+            "stac_version": "1.0.0",
+            "stac_extensions": ["https://stac-extensions.github.io/eo/v1.0.0/schema.json"],
+            "geometry": { "type": "Point", "coordinates": [7.4, 46.9] },
+            "properties": { "datetime": "2022-09-01T10:00:00Z" },
+            "assets": { "data": { "href": "data.tif", "roles": ["data"] } }
+        }))
+        .unwrap();
+        assert!(validate_item(&item).is_ok());
+
+        let properties = item.properties.as_mut().unwrap();
+        properties.insert("datetime".to_string(), json!(null));
+        assert!(validate_item(&item).is_err());
+
+        let properties = item.properties.as_mut().unwrap();
+        properties.insert("start_datetime".to_string(), json!("2022-09-01T00:00:00Z"));
+        properties.insert("end_datetime".to_string(), json!("2022-09-02T00:00:00Z"));
+        assert!(validate_item(&item).is_ok());
+
+        item.stac_extensions = vec!["eo".to_string()];
+        assert!(validate_item(&item).is_err());
+
+        // plain features are no items
+        let feature: Item = serde_json::from_value(json!({
+            "id": "feature",
+            "type": "Feature",
+            "stac_extensions": null,
+            "geometry": { "type": "Point", "coordinates": [7.4, 46.9] },
+            "properties": { "datetime": "2022-09-01T10:00:00Z" }
+        }))
+        .unwrap();
+        assert_eq!(feature.stac_version, None);
+        assert!(feature.stac_extensions.is_empty());
+        assert!(validate_item(&feature).is_err());
+    }
+}
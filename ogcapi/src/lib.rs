@@ -1,6 +1,12 @@
 #[cfg(feature = "import")]
 pub mod import;
 
+pub mod refresh;
+#[cfg(feature = "stac")]
+pub mod stac;
+#[cfg(feature = "stac")]
+pub mod validate;
+
 pub mod types {
     pub use ogcapi_types::*;
 }
@@ -12,9 +12,18 @@ pub enum Command {
     /// Import geodata into the database
     #[cfg(feature = "import")]
     Import(ogcapi::import::Args),
+    /// Recompute the extent and summaries of collections
+    Refresh(ogcapi::refresh::Args),
     /// Start the ogcapi services
     #[cfg(feature = "serve")]
     Serve(ogcapi_services::Config),
+    /// Import or export static STAC catalogs
+    #[cfg(feature = "stac")]
+    #[clap(subcommand)]
+    Stac(ogcapi::stac::Args),
+    /// Validate a local STAC catalog against the bundled schemas
+    #[cfg(feature = "stac")]
+    Validate(ogcapi::validate::Args),
 }
 
 #[tokio::main]
@@ -43,12 +52,37 @@ async fn main() -> anyhow::Result<()> {
                 }
             }
         }
+        Command::Refresh(args) => ogcapi::refresh::refresh(args).await?,
         #[cfg(feature = "serve")]
         Command::Serve(config) => {
             // Application state
-            let state = ogcapi_services::State::new_from(&config)
-                .await
-                .processors(vec![Box::new(ogcapi_services::Greeter)]);
+            let state = ogcapi_services::State::new_from(&config).await?;
+
+            // Built-in processes, command processes must not replace them
+            let mut processors: Vec<Box<dyn ogcapi_services::Processor>> =
+                vec![Box::new(ogcapi_services::Greeter)];
+            // geoprocessing requires the postgres backend
+            if state.drivers.geoprocessing.is_available() {
+                processors.extend([
+                    Box::new(ogcapi_services::Buffer) as Box<dyn ogcapi_services::Processor>,
+                    Box::new(ogcapi_services::Clip),
+                    Box::new(ogcapi_services::Dissolve),
+                    Box::new(ogcapi_services::SpatialJoin),
+                    Box::new(ogcapi_services::Centroid),
+                    Box::new(ogcapi_services::Reproject),
+                    Box::new(ogcapi_services::Simplify),
+                ]);
+            }
+            if let Some(p) = processors
+                .iter()
+                .find(|p| state.processors.contains_key(&p.id()))
+            {
+                anyhow::bail!(
+                    "Command process `{}` collides with the built-in process of the same id",
+                    p.id()
+                );
+            }
+            let state = state.processors(processors);
 
             // Build & run with hyper
             ogcapi_services::Service::new_with(&config, state)
@@ -56,6 +90,13 @@ async fn main() -> anyhow::Result<()> {
                 .serve()
                 .await;
         }
+        #[cfg(feature = "stac")]
+        Command::Stac(args) => match args {
+            ogcapi::stac::Args::Import(args) => ogcapi::stac::load(args).await?,
+            ogcapi::stac::Args::Export(args) => ogcapi::stac::export(args).await?,
+        },
+        #[cfg(feature = "stac")]
+        Command::Validate(args) => ogcapi::validate::validate(args)?,
     }
 
     Ok(())
@@ -0,0 +1,39 @@
+use ogcapi_drivers::{postgres::Db, CollectionMaintenance, CollectionTransactions};
+use ogcapi_types::common::Query;
+
+#[derive(clap::Parser, Debug)]
+pub struct Args {
+    /// Collection to refresh, refreshes all collections if not present
+    #[clap(long)]
+    pub collection: Option<String>,
+
+    /// Postgres database url
+    #[clap(long, env, hide_env_values = true, parse(try_from_str))]
+    pub database_url: url::Url,
+}
+
+/// Recomputes the extent and summaries of collections from their items
+pub async fn refresh(args: Args) -> anyhow::Result<()> {
+    // Setup driver
+    let db = Db::setup(&args.database_url).await?;
+
+    match args.collection {
+        Some(id) => match db.refresh_collection(&id).await? {
+            Some(_) => tracing::info!("Refreshed collection `{id}`"),
+            None => anyhow::bail!("Collection `{id}` not found"),
+        },
+        None => {
+            let collections = db.list_collections(&Query::default()).await?.collections;
+            for collection in collections {
+                let id = collection.id;
+                // collections without items (e.g. catalogs) are skipped
+                match db.refresh_collection(&id).await? {
+                    Some(_) => tracing::info!("Refreshed collection `{id}`"),
+                    None => tracing::warn!("Skipped collection `{id}` without items"),
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
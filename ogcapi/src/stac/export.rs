@@ -0,0 +1,203 @@
+use std::path::PathBuf;
+
+use ogcapi_drivers::{
+    postgres::Db, s3::S3, CatalogTransactions, CollectionTransactions, FeatureTransactions,
+};
+use ogcapi_types::{
+    common::{
+        link_rel::{CHILD, COLLECTION, ITEM, PARENT, ROOT},
+        media_type::{GEO_JSON, JSON},
+        Link, Query as CollectionQuery,
+    },
+    features::Query,
+    stac::Catalog,
+};
+use serde::Serialize;
+
+use super::strip_structural_links;
+
+/// Items fetched per request
+const PAGE_SIZE: usize = 1000;
+
+#[derive(clap::Parser, Debug)]
+pub struct ExportArgs {
+    /// Output directory of the static catalog
+    #[clap(long, parse(from_os_str))]
+    pub output: PathBuf,
+
+    /// Id of the root catalog
+    #[clap(long, default_value = "catalog")]
+    pub id: String,
+
+    /// Description of the root catalog
+    #[clap(long, default_value = "Static catalog exported by ogcapi")]
+    pub description: String,
+
+    /// Upload the static catalog to this S3 bucket as well
+    #[clap(long)]
+    pub bucket: Option<String>,
+
+    /// Key prefix of the uploaded static catalog
+    #[clap(long, default_value = "")]
+    pub prefix: String,
+
+    /// Postgres database url
+    #[clap(long, env, hide_env_values = true, parse(try_from_str))]
+    pub database_url: url::Url,
+}
+
+/// Writes the catalogs, collections and items of the database as a
+/// self-contained static catalog with relative links
+///
+/// Catalogs and collections become children of a new root catalog, items are
+/// written next to their collection as `{collection}/{item}/{item}.json`.
+pub async fn export(args: ExportArgs) -> anyhow::Result<()> {
+    // Setup drivers
+    let db = Db::setup(&args.database_url).await?;
+
+    let s3 = match &args.bucket {
+        Some(bucket) => {
+            let mut s3 = S3::new().await;
+            s3.set_default_bucket(bucket);
+            Some(s3)
+        }
+        None => None,
+    };
+
+    let writer = Writer {
+        output: args.output,
+        prefix: args.prefix,
+        s3,
+    };
+
+    let mut root = Catalog::new(&args.id, &args.description);
+    root.links
+        .push(Link::new("./catalog.json", ROOT).mediatype(JSON));
+
+    // catalogs
+    for mut catalog in db.list_catalogs().await? {
+        strip_structural_links(&mut catalog.links);
+        catalog.links.extend([
+            Link::new("../catalog.json", ROOT).mediatype(JSON),
+            Link::new("../catalog.json", PARENT).mediatype(JSON),
+        ]);
+
+        let path = format!("{}/catalog.json", segment(&catalog.id)?);
+        writer.write(&path, &catalog).await?;
+
+        root.links
+            .push(Link::new(format!("./{path}"), CHILD).mediatype(JSON));
+    }
+
+    // collections
+    let mut count = 0;
+    for mut collection in db
+        .list_collections(&CollectionQuery::default())
+        .await?
+        .collections
+    {
+        segment(&collection.id)?;
+
+        strip_structural_links(&mut collection.links);
+        collection.links.extend([
+            Link::new("../catalog.json", ROOT).mediatype(JSON),
+            Link::new("../catalog.json", PARENT).mediatype(JSON),
+        ]);
+
+        // items
+        let mut query = Query {
+            limit: Some(PAGE_SIZE),
+            offset: Some(0),
+            ..Default::default()
+        };
+        loop {
+            let items = db.list_items(&collection.id, &query).await?.features;
+            let returned = items.len();
+
+            for mut item in items {
+                let id = item.id.clone().unwrap_or_default();
+                segment(&id)?;
+
+                strip_structural_links(&mut item.links);
+                item.links.extend([
+                    Link::new("../../catalog.json", ROOT).mediatype(JSON),
+                    Link::new("../collection.json", PARENT).mediatype(JSON),
+                    Link::new("../collection.json", COLLECTION).mediatype(JSON),
+                ]);
+                item.collection = Some(collection.id.to_owned());
+
+                writer
+                    .write(&format!("{}/{id}/{id}.json", collection.id), &item)
+                    .await?;
+
+                collection
+                    .links
+                    .push(Link::new(format!("./{id}/{id}.json"), ITEM).mediatype(GEO_JSON));
+
+                count += 1;
+            }
+
+            // items are ordered by id, the offset is stable
+            if returned < PAGE_SIZE {
+                break;
+            }
+            query.offset = query.offset.map(|offset| offset + PAGE_SIZE);
+        }
+
+        let path = format!("{}/collection.json", collection.id);
+        writer.write(&path, &collection).await?;
+
+        root.links
+            .push(Link::new(format!("./{path}"), CHILD).mediatype(JSON));
+    }
+
+    writer.write("catalog.json", &root).await?;
+
+    tracing::info!("Exported {count} items to {}", writer.output.display());
+
+    Ok(())
+}
+
+/// Checks that an id is usable as a path segment of files and S3 keys
+fn segment(id: &str) -> anyhow::Result<&str> {
+    if id.is_empty() || id == "." || id.contains(['/', '\\']) || id.contains("..") {
+        anyhow::bail!("Id `{id}` is not a valid path segment");
+    }
+    Ok(id)
+}
+
+/// Writes the files of the static catalog to disk and optionally to S3
+struct Writer {
+    output: PathBuf,
+    prefix: String,
+    s3: Option<S3>,
+}
+
+impl Writer {
+    async fn write(&self, path: &str, value: &impl Serialize) -> anyhow::Result<()> {
+        let data = serde_json::to_vec_pretty(value)?;
+
+        let file = self.output.join(path);
+        if let Some(dir) = file.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(&file, &data).await?;
+
+        if let Some(s3) = &self.s3 {
+            let key = if self.prefix.is_empty() {
+                path.to_owned()
+            } else {
+                format!("{}/{path}", self.prefix.trim_end_matches('/'))
+            };
+            s3.put_object(
+                s3.bucket.clone().unwrap_or_default(),
+                key,
+                data,
+                Some(JSON.to_string()),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+}
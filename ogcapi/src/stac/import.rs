@@ -0,0 +1,116 @@
+use anyhow::Context;
+
+use ogcapi_client::Client;
+use ogcapi_drivers::{
+    postgres::Db, CatalogTransactions, CollectionTransactions, FeatureTransactions,
+};
+use ogcapi_types::{common::Crs, stac::StacEntity};
+
+use super::strip_structural_links;
+
+#[derive(clap::Parser, Debug)]
+pub struct ImportArgs {
+    /// Path or URL of the root `catalog.json` of a static catalog
+    #[clap(long)]
+    pub input: String,
+
+    /// Postgres database url
+    #[clap(long, env, hide_env_values = true, parse(try_from_str))]
+    pub database_url: url::Url,
+}
+
+/// Loads the catalogs, collections and items of a static catalog
+///
+/// Existing entities are replaced. Relative asset hrefs are resolved against
+/// the location of their entity, the structure of the catalog is not retained.
+pub async fn load(args: ImportArgs) -> anyhow::Result<()> {
+    let endpoint = match url::Url::parse(&args.input) {
+        Ok(url) => url,
+        Err(_) => {
+            let path = std::fs::canonicalize(&args.input)
+                .with_context(|| format!("Unable to resolve `{}`", args.input))?;
+            url::Url::from_file_path(path).expect("absolute path")
+        }
+    };
+
+    // Setup driver
+    let db = Db::setup(&args.database_url).await?;
+
+    // the client blocks, walk the catalog off the runtime and stream the entities
+    let (tx, mut rx) = tokio::sync::mpsc::channel(64);
+    let walker = tokio::task::spawn_blocking(move || -> anyhow::Result<()> {
+        let client = Client::new(endpoint.as_str())?;
+        for entity in client.walk()? {
+            if tx.blocking_send(entity).is_err() {
+                // loading failed, stop walking
+                break;
+            }
+        }
+        Ok(())
+    });
+
+    let now = std::time::Instant::now();
+    let mut count = 0;
+
+    // items without a `collection` belong to the collection walked last
+    let mut current = None;
+
+    while let Some(entity) = rx.recv().await {
+        match entity? {
+            StacEntity::Catalog(mut catalog) => {
+                strip_structural_links(&mut catalog.links);
+
+                if db.read_catalog(&catalog.id).await?.is_some() {
+                    db.update_catalog(&catalog).await?;
+                } else {
+                    db.create_catalog(&catalog).await?;
+                }
+            }
+            StacEntity::Collection(mut collection) => {
+                strip_structural_links(&mut collection.links);
+
+                if db.read_collection(&collection.id).await?.is_some() {
+                    db.update_collection(&collection).await?;
+                } else {
+                    db.create_collection(&collection).await?;
+                }
+
+                current = Some(collection.id);
+            }
+            StacEntity::Item(mut item) => {
+                strip_structural_links(&mut item.links);
+
+                let collection = item
+                    .collection
+                    .clone()
+                    .or_else(|| current.clone())
+                    .with_context(|| format!("Item `{:?}` without a collection", item.id))?;
+                item.collection = Some(collection.to_owned());
+
+                let id = item.id.clone().context("Item without an `id`")?;
+                if db
+                    .read_feature(&collection, &id, &Crs::default())
+                    .await?
+                    .is_some()
+                {
+                    db.update_feature(&item).await?;
+                } else {
+                    db.create_feature(&item).await?;
+                }
+
+                count += 1;
+            }
+        }
+    }
+
+    walker.await??;
+
+    // stats
+    let elapsed = now.elapsed().as_millis() as f64 / 1000.0;
+    tracing::info!(
+        "Loaded {count} items in {elapsed} seconds ({:.2}/s)",
+        count as f64 / elapsed
+    );
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+//! Static STAC catalogs
+mod export;
+mod import;
+
+pub use export::{export, ExportArgs};
+pub use import::{load, ImportArgs};
+
+use ogcapi_types::common::{
+    link_rel::{CHILD, COLLECTION, ITEM, PARENT, ROOT, SELF},
+    Links,
+};
+
+#[derive(clap::Parser, Debug)]
+pub enum Args {
+    /// Import a static catalog into the database
+    Import(ImportArgs),
+    /// Export the database as a static catalog
+    Export(ExportArgs),
+}
+
+/// Links describing the structure of a catalog, derived on export
+const STRUCTURAL: [&str; 6] = [SELF, ROOT, PARENT, CHILD, ITEM, COLLECTION];
+
+/// Removes the links describing the structure of a catalog
+fn strip_structural_links(links: &mut Links) {
+    links.retain(|l| !STRUCTURAL.contains(&l.rel.as_str()));
+}
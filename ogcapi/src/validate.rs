@@ -0,0 +1,79 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use serde_json::Value;
+
+use ogcapi_types::stac::Validator;
+
+#[derive(clap::Parser, Debug)]
+pub struct Args {
+    /// Root catalog, collection or item of a local STAC catalog
+    #[clap(long, parse(from_os_str))]
+    pub input: PathBuf,
+}
+
+/// Validates a local STAC catalog against the bundled schemas
+///
+/// Follows relative `child` and `item` links from the input, remote links
+/// are not traversed.
+pub fn validate(args: Args) -> anyhow::Result<()> {
+    let validator = Validator::new();
+
+    let mut visited = HashSet::new();
+    let mut queue = vec![args.input];
+    let mut invalid = 0;
+
+    while let Some(path) = queue.pop() {
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("Unable to resolve `{}`", path.display()))?;
+        if !visited.insert(path.clone()) {
+            continue;
+        }
+
+        let file = std::fs::File::open(&path)
+            .with_context(|| format!("Unable to open `{}`", path.display()))?;
+        let value: Value = serde_json::from_reader(file)
+            .with_context(|| format!("Unable to parse `{}`", path.display()))?;
+
+        match validator.validate(&value) {
+            Ok(_) => tracing::debug!("{} is valid", path.display()),
+            Err(errors) => {
+                invalid += 1;
+                println!("{}", path.display());
+                for error in errors {
+                    println!("  {error}");
+                }
+            }
+        }
+
+        queue.extend(children(&path, &value));
+    }
+
+    if invalid > 0 {
+        anyhow::bail!("{invalid} of {} STAC entities are invalid", visited.len());
+    }
+
+    println!("{} STAC entities are valid", visited.len());
+
+    Ok(())
+}
+
+/// Local paths of the `child` and `item` links of an entity
+fn children(path: &Path, value: &Value) -> Vec<PathBuf> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+
+    value
+        .get("links")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|link| matches!(link["rel"].as_str(), Some("child" | "item")))
+        .filter_map(|link| link["href"].as_str())
+        .filter(|href| url::Url::parse(href).is_err())
+        .map(|href| dir.join(href))
+        .collect()
+}